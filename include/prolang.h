@@ -0,0 +1,123 @@
+/* Generated from src/ffi/ffi.rs by tests/c_api.rs; do not edit. */
+#ifndef PROLANG_H
+#define PROLANG_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * An engine along with the outcome of the last program it ran, which C
+ * reads through the `prolang_result_` functions.
+ */
+typedef struct ProlangEngine ProlangEngine;
+
+/*
+ * A value passed to or returned from a C callback. A callback may instead
+ * return an error made by `prolang_value_error`, failing the call.
+ */
+typedef struct ProlangValue ProlangValue;
+
+/*
+ * Called with the `user_data` it was registered with and the arguments of
+ * the call, which it borrows. It returns a value it made, which prolang
+ * takes over.
+ */
+typedef ProlangValue *(*ProlangCallback)(void *user_data, const ProlangValue *const *args, size_t argc);
+
+/*
+ * Creates an engine, which grants programs no capabilities. Free it with
+ * `prolang_engine_free`.
+ */
+ProlangEngine *prolang_engine_new(void);
+
+/*
+ * Frees an engine and the strings it returned.
+ */
+void prolang_engine_free(ProlangEngine *engine);
+
+/*
+ * Runs `source` on the engine, returning 0 if it succeeded and -1 if it
+ * failed, including when `source` is not valid UTF-8 or prolang panicked.
+ * The error is then also the last error.
+ */
+int prolang_eval(ProlangEngine *engine, const char *source);
+
+/*
+ * The value the last program returned as it prints, or the error that
+ * stopped it. The string lives until the next `prolang_eval`. Returns
+ * null if `engine` is null.
+ */
+const char *prolang_result_string(const ProlangEngine *engine);
+
+/*
+ * Stores the integer the last program returned in `out`, returning 0, or
+ * returns -1 if it returned something else or failed.
+ */
+int prolang_result_int(const ProlangEngine *engine, int64_t *out);
+
+/*
+ * Makes `callback` callable from programs as `name` with `arity`
+ * arguments, like a builtin. Returns 0, or -1 if `name` is not valid
+ * UTF-8 or `callback` is null.
+ */
+int prolang_register_fn(ProlangEngine *engine, const char *name, size_t arity, ProlangCallback callback, void *user_data);
+
+/*
+ * Makes an integer value.
+ */
+ProlangValue *prolang_value_int(int64_t value);
+
+/*
+ * Makes a string value, copying `value`. Returns null if it is not valid
+ * UTF-8.
+ */
+ProlangValue *prolang_value_string(const char *value);
+
+/*
+ * Makes `null`.
+ */
+ProlangValue *prolang_value_null(void);
+
+/*
+ * Makes an error for a callback to return, which fails the call with
+ * `message`.
+ */
+ProlangValue *prolang_value_error(const char *message);
+
+/*
+ * Stores the integer `value` holds in `out`, returning 0, or returns -1
+ * if it holds something else.
+ */
+int prolang_value_as_int(const ProlangValue *value, int64_t *out);
+
+/*
+ * Renders `value` as it prints. Free the string with
+ * `prolang_string_free`. Returns null if `value` is null.
+ */
+char *prolang_value_to_string(const ProlangValue *value);
+
+/*
+ * Frees a value made and not handed back to prolang.
+ */
+void prolang_value_free(ProlangValue *value);
+
+/*
+ * Frees a string returned by `prolang_value_to_string`.
+ */
+void prolang_string_free(char *string);
+
+/*
+ * The message of the last call on this thread that failed, or null if
+ * none has. The string lives until the next call on the thread fails.
+ */
+const char *prolang_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* PROLANG_H */
@@ -0,0 +1,274 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    lexer::token::Token,
+    module::loader::MAIN_FILE,
+    parser::{
+        ast::{
+            ArrayLiteral, AssignExpression, BlockStatement, CallExpression,
+            ConditionalIteratorExpression, EnumLiteral, Expression, ExpressionStatement,
+            ForLoopCondition, ForLoopExpression, FunctionLiteral, HashLiteral, IfExpression,
+            ImplStatement, IndexExpression, InfixExpression, IteratorLiteral, LetStatement,
+            MatchExpression, MemberExpression, Pattern, PrefixExpression, Program, ReturnStatemnt,
+            Statement, StructExpression, ThrowStatement, TryExpression,
+        },
+        get_of_type, grow_stack,
+    },
+};
+
+/// Static checks that run between parsing and evaluation. Findings are
+/// warnings: they never stop a program from running.
+#[derive(Default)]
+pub struct Checker<'a> {
+    enums: HashMap<String, Vec<String>>,
+    matches: Vec<&'a MatchExpression>,
+    warnings: Vec<String>,
+    /// The file being checked, as warnings name it.
+    file: Option<String>,
+}
+
+impl<'a> Checker<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names the file being checked in warnings, in place of `main.pr`.
+    pub fn set_file(&mut self, file: impl Into<String>) {
+        self.file = Some(file.into());
+    }
+
+    pub fn check_program(&mut self, program: &'a Program) {
+        for stmt in &program.statements {
+            self.visit_statement(stmt.as_ref());
+        }
+        // enums may be declared after the functions matching on them
+        for exp in std::mem::take(&mut self.matches) {
+            self.check_match(exp);
+        }
+    }
+
+    pub fn warnings(&self) -> &Vec<String> {
+        &self.warnings
+    }
+
+    fn visit_statement(&mut self, stmt: &'a dyn Statement) {
+        grow_stack(|| self.visit_statement_node(stmt))
+    }
+
+    fn visit_statement_node(&mut self, stmt: &'a dyn Statement) {
+        let any = stmt.get_as_any();
+        if let Some(stmt) = get_of_type::<ExpressionStatement>(any) {
+            self.visit_optional(&stmt.expression);
+        } else if let Some(stmt) = get_of_type::<LetStatement>(any) {
+            if let Some(value) = &stmt.value {
+                if let Some(lit) = get_of_type::<EnumLiteral>(value.get_as_any()) {
+                    let variants = lit.variants.iter().map(|v| v.name.value.clone()).collect();
+                    self.enums.insert(stmt.name.value.clone(), variants);
+                }
+            }
+            self.visit_optional(&stmt.value);
+        } else if let Some(stmt) = get_of_type::<ReturnStatemnt>(any) {
+            self.visit_optional(&stmt.return_value);
+        } else if let Some(stmt) = get_of_type::<ThrowStatement>(any) {
+            self.visit_optional(&stmt.value);
+        } else if let Some(stmt) = get_of_type::<ImplStatement>(any) {
+            for (_, method) in &stmt.methods {
+                self.visit_block(method.body.as_deref());
+            }
+        } else if let Some(stmt) = get_of_type::<ForLoopExpression>(any) {
+            match &stmt.condition {
+                Some(ForLoopCondition::For(exp)) | Some(ForLoopCondition::ForIn(exp)) => {
+                    self.visit_expression(exp.as_ref())
+                }
+                _ => (),
+            }
+            self.visit_block(stmt.body.as_ref());
+        }
+    }
+
+    fn visit_block(&mut self, block: Option<&'a BlockStatement>) {
+        if let Some(block) = block {
+            for stmt in &block.statements {
+                self.visit_statement(stmt.as_ref());
+            }
+        }
+    }
+
+    fn visit_optional(&mut self, exp: &'a Option<Box<dyn Expression>>) {
+        if let Some(exp) = exp {
+            self.visit_expression(exp.as_ref());
+        }
+    }
+
+    fn visit_expression(&mut self, exp: &'a dyn Expression) {
+        grow_stack(|| self.visit_expression_node(exp))
+    }
+
+    fn visit_expression_node(&mut self, exp: &'a dyn Expression) {
+        let any = exp.get_as_any();
+        if let Some(exp) = get_of_type::<PrefixExpression>(any) {
+            self.visit_optional(&exp.right);
+        } else if let Some(exp) = get_of_type::<InfixExpression>(any) {
+            self.visit_expression(exp.left.as_ref());
+            self.visit_optional(&exp.right);
+        } else if let Some(exp) = get_of_type::<AssignExpression>(any) {
+            self.visit_expression(exp.target.as_ref());
+            self.visit_optional(&exp.value);
+        } else if let Some(exp) = get_of_type::<IfExpression>(any) {
+            self.visit_optional(&exp.condition);
+            self.visit_block(exp.consequence.as_ref());
+            for (condition, consequence) in &exp.else_ifs {
+                self.visit_expression(condition.as_ref());
+                self.visit_block(Some(consequence));
+            }
+            self.visit_block(exp.alternative.as_ref());
+        } else if let Some(exp) = get_of_type::<TryExpression>(any) {
+            self.visit_block(Some(&exp.body));
+            self.visit_block(Some(&exp.handler));
+        } else if let Some(lit) = get_of_type::<FunctionLiteral>(any) {
+            self.visit_block(lit.body.as_deref());
+        } else if let Some(exp) = get_of_type::<CallExpression>(any) {
+            self.visit_expression(exp.function.as_ref());
+            for arg in &exp.arguments {
+                self.visit_expression(arg.as_ref());
+            }
+        } else if let Some(lit) = get_of_type::<ArrayLiteral>(any) {
+            for element in &lit.elements {
+                self.visit_expression(element.as_ref());
+            }
+        } else if let Some(lit) = get_of_type::<HashLiteral>(any) {
+            for (key, value) in &lit.pairs {
+                self.visit_expression(key.as_ref());
+                self.visit_expression(value.as_ref());
+            }
+        } else if let Some(exp) = get_of_type::<IndexExpression>(any) {
+            self.visit_expression(exp.left.as_ref());
+            self.visit_optional(&exp.index);
+        } else if let Some(exp) = get_of_type::<MemberExpression>(any) {
+            self.visit_expression(exp.object.as_ref());
+        } else if let Some(lit) = get_of_type::<IteratorLiteral>(any) {
+            self.visit_expression(lit.start.as_ref());
+            self.visit_optional(&lit.end);
+        } else if let Some(exp) = get_of_type::<ConditionalIteratorExpression>(any) {
+            self.visit_optional(&exp.r#in);
+        } else if let Some(exp) = get_of_type::<StructExpression>(any) {
+            for (_, value) in &exp.fields {
+                self.visit_expression(value.as_ref());
+            }
+        } else if let Some(exp) = get_of_type::<MatchExpression>(any) {
+            self.visit_optional(&exp.subject);
+            for arm in &exp.arms {
+                self.visit_optional(&arm.guard);
+                self.visit_block(Some(&arm.body));
+            }
+            self.matches.push(exp);
+        }
+    }
+
+    fn check_match(&mut self, exp: &MatchExpression) {
+        let mut catch_all = false;
+        let mut matched_enum: Option<&str> = None;
+        let mut covered: HashSet<&str> = HashSet::new();
+        let mut literals: Vec<String> = vec![];
+
+        for arm in &exp.arms {
+            let pattern = &arm.pattern;
+            if catch_all {
+                self.unreachable_arm(pattern);
+                continue;
+            }
+
+            match pattern {
+                Pattern::Wildcard(_) | Pattern::Binding(_) => catch_all = arm.guard.is_none(),
+                Pattern::EnumVariant {
+                    enum_name,
+                    variant,
+                    payload,
+                } => {
+                    let Some(variants) = self.enums.get(&enum_name.value) else {
+                        continue;
+                    };
+                    if !variants.contains(&variant.value) {
+                        self.warn(
+                            &variant.token,
+                            "unknown variant",
+                            &format!(
+                                "enum {} has no variant `{}`",
+                                enum_name.value, variant.value
+                            ),
+                        );
+                        continue;
+                    }
+                    matched_enum = Some(&enum_name.value);
+                    if covered.contains(variant.value.as_str()) {
+                        self.unreachable_arm(pattern);
+                        continue;
+                    }
+                    let whole_variant = payload
+                        .as_ref()
+                        .is_none_or(|p| p.iter().all(Pattern::is_irrefutable));
+                    if whole_variant && arm.guard.is_none() {
+                        covered.insert(&variant.value);
+                    }
+                }
+                Pattern::Literal { .. } => {
+                    if arm.guard.is_some() {
+                        continue;
+                    }
+                    let literal = pattern.string();
+                    if literals.contains(&literal) {
+                        self.unreachable_arm(pattern);
+                    } else {
+                        literals.push(literal);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if catch_all {
+            return;
+        }
+        if let Some(enum_name) = matched_enum {
+            let missing: Vec<String> = self.enums[enum_name]
+                .iter()
+                .filter(|v| !covered.contains(v.as_str()))
+                .map(|v| format!("`{}`", v))
+                .collect();
+            if !missing.is_empty() {
+                self.warn(
+                    &exp.token,
+                    "non-exhaustive match",
+                    &format!(
+                        "enum {} variant(s) not covered: {}",
+                        enum_name,
+                        missing.join(", ")
+                    ),
+                );
+            }
+        }
+    }
+
+    fn unreachable_arm(&mut self, pattern: &Pattern) {
+        self.warn(
+            pattern.token(),
+            "unreachable match arm",
+            &format!(
+                "`{}` can never match, an earlier arm already covers it",
+                pattern.string()
+            ),
+        );
+    }
+
+    fn warn(&mut self, token: &Token, kind: &str, reason: &str) {
+        let msg = format!(
+            "{}:{}:{} \n {} -> {}",
+            self.file.as_deref().unwrap_or(MAIN_FILE),
+            token.position.0,
+            token.position.1,
+            kind,
+            reason,
+        );
+        self.warnings.push(msg)
+    }
+}
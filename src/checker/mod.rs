@@ -0,0 +1,5 @@
+#[allow(clippy::module_inception)]
+pub mod checker;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,158 @@
+use crate::{checker::checker::Checker, lexer::lexer::Lexer, parser::parser::Parser};
+
+#[test]
+fn test_match_exhaustiveness_warnings() {
+    struct TestCase {
+        input: String,
+        expected_warnings: Vec<&'static str>,
+    }
+
+    let prelude = "let Option = enum(some(int), none);\n";
+    let tests = [
+        TestCase {
+            input: String::from("match o { Option.some(v) => v, Option.none => 0 }"),
+            expected_warnings: vec![],
+        },
+        TestCase {
+            input: String::from("match o { Option.some(v) => v }"),
+            expected_warnings: vec![
+                "non-exhaustive match -> enum Option variant(s) not covered: `none`",
+            ],
+        },
+        TestCase {
+            input: String::from("match o { Option.some(1) => 1, Option.none => 0 }"),
+            expected_warnings: vec![
+                "non-exhaustive match -> enum Option variant(s) not covered: `some`",
+            ],
+        },
+        TestCase {
+            input: String::from("match o { Option.some(v) if v > 1 => v, Option.none => 0 }"),
+            expected_warnings: vec![
+                "non-exhaustive match -> enum Option variant(s) not covered: `some`",
+            ],
+        },
+        TestCase {
+            input: String::from("match o { Option.some(v) => v, other => 0 }"),
+            expected_warnings: vec![],
+        },
+        TestCase {
+            input: String::from("fn(o) { match o { Option.none => 0 } }"),
+            expected_warnings: vec![
+                "non-exhaustive match -> enum Option variant(s) not covered: `some`",
+            ],
+        },
+        TestCase {
+            input: String::from("match o { Option.gone => 0, _ => 1 }"),
+            expected_warnings: vec!["unknown variant -> enum Option has no variant `gone`"],
+        },
+        TestCase {
+            input: String::from("match 1 { 1 => 1 }"),
+            expected_warnings: vec![],
+        },
+    ];
+
+    for tt in tests {
+        let warnings = check(&format!("{}{}", prelude, tt.input));
+        assert_warnings(&warnings, &tt.expected_warnings);
+    }
+}
+
+#[test]
+fn test_unreachable_arm_warnings() {
+    struct TestCase {
+        input: String,
+        expected_warnings: Vec<&'static str>,
+    }
+
+    let prelude = "let Option = enum(some(int), none);\n";
+    let tests = [
+        TestCase {
+            input: String::from("match o { _ => 0, Option.none => 1 }"),
+            expected_warnings: vec![
+                "unreachable match arm -> `Option.none` can never match, an earlier arm already covers it",
+            ],
+        },
+        TestCase {
+            input: String::from("match o { x if x => 0, Option.none => 1, y => 2 }"),
+            expected_warnings: vec![],
+        },
+        TestCase {
+            input: String::from("match o { Option.some(_) => 0, Option.some(1) => 1, _ => 2 }"),
+            expected_warnings: vec![
+                "unreachable match arm -> `Option.some(1)` can never match, an earlier arm already covers it",
+            ],
+        },
+        TestCase {
+            input: String::from("match n { 1 => 0, 2 => 1, 1 => 2, _ => 3 }"),
+            expected_warnings: vec![
+                "unreachable match arm -> `1` can never match, an earlier arm already covers it",
+            ],
+        },
+        TestCase {
+            input: String::from("match n { a => 0, _ => 1, 2 => 2 }"),
+            expected_warnings: vec![
+                "unreachable match arm -> `_` can never match, an earlier arm already covers it",
+                "unreachable match arm -> `2` can never match, an earlier arm already covers it",
+            ],
+        },
+    ];
+
+    for tt in tests {
+        let warnings = check(&format!("{}{}", prelude, tt.input));
+        assert_warnings(&warnings, &tt.expected_warnings);
+    }
+}
+
+#[test]
+fn test_warnings_name_the_file() {
+    let input = "let Option = enum(some(int), none);\nmatch o { Option.none => 0 }";
+    let mut p = Parser::new(Lexer::new(input.to_string()));
+    let program = p.parse_program().expect("parse_program() return some");
+
+    let mut checker = Checker::new();
+    checker.check_program(&program);
+    assert!(checker.warnings()[0].starts_with("main.pr:2:5 \n"));
+
+    let mut checker = Checker::new();
+    checker.set_file("lib/options.pr");
+    checker.check_program(&program);
+    assert_eq!(
+        checker.warnings(),
+        &["lib/options.pr:2:5 \n non-exhaustive match -> enum Option variant(s) not covered: `some`"]
+    );
+}
+
+//utils
+fn check(input: &str) -> Vec<String> {
+    let l = Lexer::new(input.to_string());
+    let mut p = Parser::new(l);
+    let program = p.parse_program().expect("parse_program() return some");
+    assert!(
+        p.errors().is_empty(),
+        "parser has errors for {}: {:?}",
+        input,
+        p.errors()
+    );
+
+    let mut checker = Checker::new();
+    checker.check_program(&program);
+    checker.warnings().clone()
+}
+
+fn assert_warnings(warnings: &[String], expected: &[&str]) {
+    assert_eq!(
+        warnings.len(),
+        expected.len(),
+        "wrong number of warnings. expected={:?}, got={:?}",
+        expected,
+        warnings
+    );
+    for (warning, expected) in warnings.iter().zip(expected) {
+        assert!(
+            warning.ends_with(expected),
+            "wrong warning. expected={}, got={}",
+            expected,
+            warning
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use std::{fmt, path::PathBuf, rc::Rc};
+
+use crate::evaluator::value::Value;
+
+use super::code::Instructions;
+
+/// The output of the compiler: the top level code, wrapped in a function so
+/// the vm can run it like any other call frame, and the tables it refers to.
+pub struct Bytecode {
+    pub main: Rc<CompiledFunction>,
+    pub constants: Vec<Constant>,
+    /// Names of the global slots, indexed by the operands of global opcodes.
+    pub globals: Vec<String>,
+    /// The modules compiled in for imports, with the hashes of their
+    /// sources, so a cache can tell when one of them changed.
+    pub imports: Vec<(PathBuf, u64)>,
+}
+
+pub enum Constant {
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Function(Rc<CompiledFunction>),
+    /// An enum, struct or interface declaration. `OpNewType` copies it, so
+    /// every evaluation of the declaration creates a distinct type.
+    Type(Value),
+    /// Field names of a struct expression, or the names a pattern refers to.
+    Names(Vec<String>),
+}
+
+pub struct CompiledFunction {
+    /// The name a function literal was bound to by `let` or `impl`.
+    pub name: Option<String>,
+    pub parameters: Vec<String>,
+    pub instructions: Instructions,
+    /// Slots reserved at the bottom of the frame for parameters and locals.
+    pub num_locals: usize,
+    pub upvalues: Vec<UpvalueRef>,
+    /// `(offset, (line, column))` pairs: the instructions from each offset up
+    /// to the next entry were compiled from the expression at that position.
+    pub positions: Vec<(usize, (usize, usize))>,
+    /// `(offset, file)` pairs like `positions`, naming the file the
+    /// instructions were compiled from as errors name it. The top level code
+    /// changes file where it runs a module.
+    pub files: Vec<(usize, String)>,
+}
+
+impl CompiledFunction {
+    /// The source `(line, column)` of the instruction at `offset`.
+    pub fn position_at(&self, offset: usize) -> (usize, usize) {
+        let entry = self
+            .positions
+            .partition_point(|&(start, _)| start <= offset);
+        entry.checked_sub(1).map_or((0, 0), |i| self.positions[i].1)
+    }
+
+    /// The file the instruction at `offset` was compiled from.
+    pub fn file_at(&self, offset: usize) -> &str {
+        let entry = self.files.partition_point(|(start, _)| *start <= offset);
+        entry.checked_sub(1).map_or("", |i| &self.files[i].1)
+    }
+
+    /// The source line of the instruction at `offset`.
+    pub fn line_at(&self, offset: usize) -> usize {
+        self.position_at(offset).0
+    }
+}
+
+/// Where a closure finds a captured variable when it is created: a local slot
+/// of the enclosing frame, or an upvalue of the enclosing closure.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UpvalueRef {
+    pub is_local: bool,
+    pub index: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompileError {
+    pub message: String,
+}
+
+impl CompileError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
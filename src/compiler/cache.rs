@@ -0,0 +1,736 @@
+use std::{cell::RefCell, collections::HashMap, fmt, path::PathBuf, rc::Rc};
+
+use crate::evaluator::{
+    stdlib,
+    value::{EnumType, InterfaceType, StructType, Value},
+};
+
+use super::{
+    bytecode::{Bytecode, CompiledFunction, Constant, UpvalueRef},
+    code::{read_operands, Opcode},
+};
+
+/// Bumped whenever the layout of a `.prc` file or the instruction set changes,
+/// so caches written by another version are rebuilt instead of misread.
+pub const FORMAT_VERSION: u16 = 5;
+
+const MAGIC: &[u8; 4] = b"PRC\0";
+
+/// Local slots a frame may reserve: every slot an operand can name, and the
+/// one after the last that `OpIterInit` also uses.
+const MAX_LOCALS: usize = u16::MAX as usize + 2;
+
+/// A `.prc` file: a compiled module and the hash of the source it was
+/// compiled from.
+///
+/// The file starts with the magic bytes, the format version and the source
+/// hash, followed by the imported modules with their source hashes, the
+/// global names, the function table (the top level code first) with each
+/// function's position and file tables, and the constant pool, which refers
+/// to functions by their index in the table. Numbers are big-endian.
+pub struct CachedModule {
+    pub source_hash: u64,
+    pub bytecode: Bytecode,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheError {
+    pub message: String,
+}
+
+impl CacheError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+type CacheResult<T = ()> = Result<T, CacheError>;
+
+/// FNV-1a, which unlike the std hashers is stable across builds.
+pub fn source_hash(source: &str) -> u64 {
+    source.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+pub fn encode(bytecode: &Bytecode, source_hash: u64) -> Vec<u8> {
+    let mut w = Writer::default();
+    w.bytes(MAGIC);
+    w.u16(FORMAT_VERSION);
+    w.u64(source_hash);
+
+    w.len(bytecode.imports.len());
+    for (path, hash) in &bytecode.imports {
+        w.str(&path.to_string_lossy());
+        w.u64(*hash);
+    }
+
+    w.len(bytecode.globals.len());
+    for name in &bytecode.globals {
+        w.str(name);
+    }
+
+    let functions: Vec<&CompiledFunction> = std::iter::once(bytecode.main.as_ref())
+        .chain(bytecode.constants.iter().filter_map(|c| match c {
+            Constant::Function(function) => Some(function.as_ref()),
+            _ => None,
+        }))
+        .collect();
+    w.len(functions.len());
+    for function in functions {
+        w.function(function);
+    }
+
+    // functions are numbered in pool order, after the top level code
+    let mut next_function = 1;
+    w.len(bytecode.constants.len());
+    for constant in &bytecode.constants {
+        match constant {
+            Constant::Int(i) => {
+                w.u8(0);
+                w.u64(*i as u64);
+            }
+            Constant::Str(s) => {
+                w.u8(1);
+                w.str(s);
+            }
+            Constant::Function(_) => {
+                w.u8(2);
+                w.len(next_function);
+                next_function += 1;
+            }
+            Constant::Type(value) => {
+                w.u8(3);
+                w.type_descriptor(value);
+            }
+            Constant::Names(names) => {
+                w.u8(4);
+                w.len(names.len());
+                for name in names {
+                    w.str(name);
+                }
+            }
+            Constant::Float(x) => {
+                w.u8(5);
+                w.u64(x.to_bits());
+            }
+        }
+    }
+
+    w.out
+}
+
+/// Reads a `.prc` file and validates its bytecode, so the vm never runs
+/// instructions that refer outside of the module.
+pub fn decode(bytes: &[u8]) -> CacheResult<CachedModule> {
+    let mut r = Reader { bytes, offset: 0 };
+    if r.take(MAGIC.len())? != MAGIC {
+        return Err(CacheError::new("not a prolang bytecode file"));
+    }
+    let version = r.u16()?;
+    if version != FORMAT_VERSION {
+        return Err(CacheError::new(format!(
+            "unsupported bytecode version {}, expected {}",
+            version, FORMAT_VERSION
+        )));
+    }
+    let source_hash = r.u64()?;
+
+    let imports = (0..r.count()?)
+        .map(|_| Ok((PathBuf::from(r.str()?), r.u64()?)))
+        .collect::<CacheResult<_>>()?;
+    let globals = (0..r.count()?)
+        .map(|_| r.str())
+        .collect::<CacheResult<_>>()?;
+    let mut functions = (0..r.count()?)
+        .map(|_| r.function().map(Rc::new))
+        .collect::<CacheResult<Vec<_>>>()?;
+    if functions.is_empty() {
+        return Err(CacheError::new("missing top level code"));
+    }
+    let main = functions.remove(0);
+
+    let mut constants = vec![];
+    for _ in 0..r.count()? {
+        let constant = match r.u8()? {
+            0 => Constant::Int(r.u64()? as i64),
+            1 => Constant::Str(r.str()?),
+            2 => {
+                let index = r.len()?;
+                let function = index
+                    .checked_sub(1)
+                    .and_then(|i| functions.get(i))
+                    .ok_or_else(|| CacheError::new(format!("unknown function {}", index)))?;
+                Constant::Function(Rc::clone(function))
+            }
+            3 => Constant::Type(r.type_descriptor()?),
+            4 => Constant::Names(
+                (0..r.count()?)
+                    .map(|_| r.str())
+                    .collect::<CacheResult<_>>()?,
+            ),
+            5 => match f64::from_bits(r.u64()?) {
+                x if x.is_finite() => Constant::Float(x),
+                x => {
+                    return Err(CacheError::new(format!(
+                        "float constant {} is not finite",
+                        x
+                    )))
+                }
+            },
+            tag => return Err(CacheError::new(format!("unknown constant tag {}", tag))),
+        };
+        constants.push(constant);
+    }
+    if r.offset != bytes.len() {
+        return Err(CacheError::new("trailing bytes after the constant pool"));
+    }
+
+    let bytecode = Bytecode {
+        main,
+        constants,
+        globals,
+        imports,
+    };
+    validate(&bytecode)?;
+
+    Ok(CachedModule {
+        source_hash,
+        bytecode,
+    })
+}
+
+/// Checks that every instruction decodes, that jumps land on instructions,
+/// that operands index existing constants of the right kind, globals, local
+/// slots and upvalues, and that no instruction pops more than its frame
+/// pushed.
+fn validate(bytecode: &Bytecode) -> CacheResult {
+    if !bytecode.main.parameters.is_empty() || !bytecode.main.upvalues.is_empty() {
+        return Err(CacheError::new("top level code cannot take parameters"));
+    }
+    validate_function(bytecode, &bytecode.main, "<main>")?;
+    for constant in &bytecode.constants {
+        if let Constant::Function(function) = constant {
+            let name = function.name.as_deref().unwrap_or("<anonymous fn>");
+            validate_function(bytecode, function, name)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_function(bytecode: &Bytecode, function: &CompiledFunction, name: &str) -> CacheResult {
+    let invalid = |offset: usize, message: String| {
+        CacheError::new(format!(
+            "invalid bytecode in {} at {:04}: {}",
+            name, offset, message
+        ))
+    };
+    if function.num_locals < function.parameters.len() {
+        return Err(invalid(0, "fewer local slots than parameters".to_owned()));
+    }
+    if function.num_locals > MAX_LOCALS {
+        return Err(invalid(
+            0,
+            format!("{} local slots exceed {}", function.num_locals, MAX_LOCALS),
+        ));
+    }
+
+    let ins = &function.instructions;
+    // the instructions by offset, with the operands of each
+    let mut decoded = vec![];
+    let mut starts = HashMap::new();
+    let mut jumps = vec![];
+    let mut last = None;
+    let mut offset = 0;
+    while offset < ins.len() {
+        let op = Opcode::from_byte(ins[offset])
+            .ok_or_else(|| invalid(offset, format!("unknown opcode {}", ins[offset])))?;
+        let def = op.definition();
+        let width: usize = def.operand_widths.iter().sum();
+        if offset + 1 + width > ins.len() {
+            return Err(invalid(offset, format!("truncated {}", op)));
+        }
+        let (operands, _) = read_operands(&def, &ins[offset + 1..]);
+
+        let constant = |index: usize| {
+            bytecode.constants.get(index).ok_or_else(|| {
+                invalid(
+                    offset,
+                    format!("{} refers to missing constant {}", op, index),
+                )
+            })
+        };
+        let string = |index: usize| match constant(index)? {
+            Constant::Str(_) => Ok(()),
+            _ => Err(invalid(offset, format!("{} expects a string constant", op))),
+        };
+        let slot = |slot: usize| match slot < function.num_locals {
+            true => Ok(()),
+            false => Err(invalid(
+                offset,
+                format!("{} uses missing local slot {}", op, slot),
+            )),
+        };
+        let upvalue = |index: usize| match index < function.upvalues.len() {
+            true => Ok(()),
+            false => Err(invalid(
+                offset,
+                format!("{} uses missing upvalue {}", op, index),
+            )),
+        };
+        let global = |index: usize| match index < bytecode.globals.len() {
+            true => Ok(()),
+            false => Err(invalid(
+                offset,
+                format!("{} uses missing global {}", op, index),
+            )),
+        };
+        let expect = |valid: bool, kind: &str| match valid {
+            true => Ok(()),
+            false => Err(invalid(offset, format!("{} expects {}", op, kind))),
+        };
+
+        match op {
+            Opcode::Constant => match constant(operands[0])? {
+                Constant::Int(_) | Constant::Float(_) | Constant::Str(_) => {}
+                _ => return Err(invalid(offset, format!("{} expects a value constant", op))),
+            },
+            Opcode::GetMember
+            | Opcode::SetMember
+            | Opcode::Impl
+            | Opcode::Method
+            | Opcode::MatchStruct
+            | Opcode::StructField => string(operands[0])?,
+            Opcode::MakeStruct => {
+                string(operands[0])?;
+                let names = constant(operands[1])?;
+                expect(matches!(names, Constant::Names(_)), "field names")?;
+            }
+            Opcode::Module => {
+                string(operands[0])?;
+                let names = constant(operands[1])?;
+                expect(matches!(names, Constant::Names(_)), "export names")?;
+            }
+            Opcode::StdModule => {
+                let Constant::Str(import) = constant(operands[0])? else {
+                    return Err(invalid(offset, format!("{} expects a string constant", op)));
+                };
+                expect(
+                    matches!(stdlib::lookup(import), Some(Ok(_))),
+                    "a standard library module",
+                )?;
+            }
+            Opcode::MatchVariant => match constant(operands[0])? {
+                Constant::Names(names) if names.len() == 3 => {}
+                _ => return Err(invalid(offset, format!("{} expects pattern names", op))),
+            },
+            Opcode::NewType => {
+                let declaration = constant(operands[0])?;
+                expect(matches!(declaration, Constant::Type(_)), "a type constant")?;
+            }
+            Opcode::Closure => {
+                let Constant::Function(closure) = constant(operands[0])? else {
+                    return Err(invalid(
+                        offset,
+                        format!("{} expects a function constant", op),
+                    ));
+                };
+                for captured in &closure.upvalues {
+                    match captured.is_local {
+                        true => slot(captured.index)?,
+                        false => upvalue(captured.index)?,
+                    }
+                }
+            }
+            Opcode::GetGlobal
+            | Opcode::GetGlobalOpt
+            | Opcode::SetGlobal
+            | Opcode::AssignGlobal
+            | Opcode::CheckGlobal => global(operands[0])?,
+            Opcode::GetLocal | Opcode::SetLocal => slot(operands[0])?,
+            // closes the slots from the operand up, which may be all of them
+            Opcode::CloseUpvalues => slot(operands[0].saturating_sub(1))?,
+            Opcode::GetUpvalue | Opcode::SetUpvalue => upvalue(operands[0])?,
+            Opcode::IterInit => slot(operands[0] + 1)?,
+            Opcode::IterNext => {
+                slot(operands[0] + 1)?;
+                jumps.push((offset, operands[1]));
+            }
+            Opcode::Jump | Opcode::JumpIfFalse | Opcode::Try => jumps.push((offset, operands[0])),
+            _ => {}
+        }
+
+        starts.insert(offset, decoded.len());
+        decoded.push((offset, op, operands));
+        last = Some(op);
+        offset += 1 + width;
+    }
+
+    if last != Some(Opcode::Return) {
+        return Err(invalid(offset, "missing final OpReturn".to_owned()));
+    }
+    for (offset, target) in jumps {
+        if !starts.contains_key(&target) {
+            return Err(invalid(
+                offset,
+                format!("jump to {:04} is not an instruction", target),
+            ));
+        }
+    }
+
+    // follow every path from the start with the depth of the operand stack
+    // above the local slots, which paths meeting at an instruction must
+    // agree on
+    let mut depths: Vec<Option<usize>> = vec![None; decoded.len()];
+    let mut pending = vec![(0, 0)];
+    while let Some((i, depth)) = pending.pop() {
+        let (offset, op, operands) = &decoded[i];
+        match depths[i] {
+            Some(known) if known == depth => continue,
+            Some(known) => {
+                return Err(invalid(
+                    *offset,
+                    format!(
+                        "stack depth {} differs from {} on another path",
+                        depth, known
+                    ),
+                ))
+            }
+            None => depths[i] = Some(depth),
+        }
+
+        let (pops, pushes) = stack_effect(bytecode, *op, operands);
+        if pops > depth {
+            return Err(invalid(
+                *offset,
+                format!("{} pops {} values from a stack of {}", op, pops, depth),
+            ));
+        }
+        let after = depth - pops + pushes;
+        let target = |operand: usize| starts[&operand];
+        match op {
+            Opcode::Jump => pending.push((target(operands[0]), after)),
+            Opcode::Return | Opcode::TailCall | Opcode::Throw | Opcode::NoMatch => {}
+            // the loop exits without pushing an item
+            Opcode::IterNext => pending.extend([(i + 1, after), (target(operands[1]), depth)]),
+            // the catch block starts with the error pushed
+            Opcode::Try => pending.extend([(i + 1, after), (target(operands[0]), after + 1)]),
+            Opcode::JumpIfFalse => pending.extend([(i + 1, after), (target(operands[0]), after)]),
+            // the last instruction is an OpReturn, so another one follows
+            _ => pending.push((i + 1, after)),
+        }
+    }
+
+    Ok(())
+}
+
+/// The values an instruction needs on the stack, and the values it leaves
+/// in their place when execution continues past it.
+fn stack_effect(bytecode: &Bytecode, op: Opcode, operands: &[usize]) -> (usize, usize) {
+    let names = |index: usize| match &bytecode.constants[index] {
+        Constant::Names(names) => names.len(),
+        _ => unreachable!("names constant validated"),
+    };
+    match op {
+        Opcode::Jump
+        | Opcode::CheckGlobal
+        | Opcode::CloseUpvalues
+        | Opcode::Try
+        | Opcode::EndTry => (0, 0),
+        Opcode::Constant
+        | Opcode::Null
+        | Opcode::True
+        | Opcode::False
+        | Opcode::GetGlobal
+        | Opcode::GetGlobalOpt
+        | Opcode::GetLocal
+        | Opcode::GetUpvalue
+        | Opcode::Closure
+        | Opcode::NewType
+        | Opcode::StdModule
+        | Opcode::IterNext => (0, 1),
+        Opcode::Pop
+        | Opcode::JumpIfFalse
+        | Opcode::SetGlobal
+        | Opcode::AssignGlobal
+        | Opcode::SetLocal
+        | Opcode::SetUpvalue
+        | Opcode::IterInit
+        | Opcode::Return
+        | Opcode::Throw
+        | Opcode::NoMatch => (1, 0),
+        Opcode::Minus
+        | Opcode::Bang
+        | Opcode::ToBool
+        | Opcode::GetMember
+        | Opcode::Impl
+        | Opcode::VariantField
+        | Opcode::MatchArray
+        | Opcode::ArrayRest
+        | Opcode::StructField => (1, 1),
+        Opcode::Dup => (1, 2),
+        Opcode::Dup2 => (2, 4),
+        Opcode::Add
+        | Opcode::Sub
+        | Opcode::Mul
+        | Opcode::Div
+        | Opcode::Mod
+        | Opcode::Equal
+        | Opcode::NotEqual
+        | Opcode::Less
+        | Opcode::Greater
+        | Opcode::LessEqual
+        | Opcode::GreaterEqual
+        | Opcode::Range
+        | Opcode::Index
+        | Opcode::SetMember
+        | Opcode::Method
+        | Opcode::CheckType
+        | Opcode::MatchVariant
+        | Opcode::MatchStruct => (2, 1),
+        Opcode::SetIndex => (3, 1),
+        Opcode::Array => (operands[0], 1),
+        Opcode::Hash => (operands[0] * 2, 1),
+        // the callee below its arguments
+        Opcode::Call | Opcode::TailCall => (operands[0] + 1, 1),
+        // the struct type below the field values
+        Opcode::MakeStruct => (names(operands[1]) + 1, 1),
+        Opcode::Module => (names(operands[1]), 1),
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    out: Vec<u8>,
+}
+
+impl Writer {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.out.extend_from_slice(bytes);
+    }
+
+    fn u8(&mut self, n: u8) {
+        self.out.push(n);
+    }
+
+    fn u16(&mut self, n: u16) {
+        self.bytes(&n.to_be_bytes());
+    }
+
+    fn u32(&mut self, n: u32) {
+        self.bytes(&n.to_be_bytes());
+    }
+
+    fn u64(&mut self, n: u64) {
+        self.bytes(&n.to_be_bytes());
+    }
+
+    fn len(&mut self, n: usize) {
+        self.u32(n as u32);
+    }
+
+    fn str(&mut self, s: &str) {
+        self.len(s.len());
+        self.bytes(s.as_bytes());
+    }
+
+    fn function(&mut self, function: &CompiledFunction) {
+        match &function.name {
+            Some(name) => {
+                self.u8(1);
+                self.str(name);
+            }
+            None => self.u8(0),
+        }
+        self.len(function.parameters.len());
+        for parameter in &function.parameters {
+            self.str(parameter);
+        }
+        self.len(function.num_locals);
+        self.len(function.upvalues.len());
+        for upvalue in &function.upvalues {
+            self.u8(upvalue.is_local as u8);
+            self.len(upvalue.index);
+        }
+        self.len(function.instructions.len());
+        self.bytes(&function.instructions);
+        self.len(function.positions.len());
+        for &(offset, (line, column)) in &function.positions {
+            self.len(offset);
+            self.len(line);
+            self.len(column);
+        }
+        self.len(function.files.len());
+        for (offset, file) in &function.files {
+            self.len(*offset);
+            self.str(file);
+        }
+    }
+
+    fn type_descriptor(&mut self, value: &Value) {
+        let (tag, name, members): (u8, &str, Vec<(&str, usize)>) = match value {
+            Value::Enum(enum_type) => (
+                0,
+                &enum_type.name,
+                enum_type
+                    .variants
+                    .iter()
+                    .map(|(name, arity)| (name.as_str(), *arity))
+                    .collect(),
+            ),
+            Value::StructType(struct_type) => (
+                1,
+                &struct_type.name,
+                struct_type.fields.iter().map(|f| (f.as_str(), 0)).collect(),
+            ),
+            Value::Interface(interface) => (
+                2,
+                &interface.name,
+                interface
+                    .methods
+                    .iter()
+                    .map(|(name, arity)| (name.as_str(), *arity))
+                    .collect(),
+            ),
+            other => unreachable!("{} is not a type declaration", other.type_name()),
+        };
+        self.u8(tag);
+        self.str(name);
+        self.len(members.len());
+        for (member, arity) in members {
+            self.str(member);
+            self.len(arity);
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> CacheResult<&'a [u8]> {
+        let end = self
+            .offset
+            .checked_add(n)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| CacheError::new("unexpected end of file"))?;
+        let bytes = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> CacheResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> CacheResult<u16> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> CacheResult<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes(bytes.try_into().expect("took 4 bytes")))
+    }
+
+    fn u64(&mut self) -> CacheResult<u64> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_be_bytes(bytes.try_into().expect("took 8 bytes")))
+    }
+
+    fn len(&mut self) -> CacheResult<usize> {
+        Ok(self.u32()? as usize)
+    }
+
+    /// The number of items of a list, each taking at least a byte, so a
+    /// corrupt count fails before anything is allocated for it.
+    fn count(&mut self) -> CacheResult<usize> {
+        let count = self.len()?;
+        if count > self.bytes.len() - self.offset {
+            return Err(CacheError::new(format!(
+                "count {} exceeds the rest of the file",
+                count
+            )));
+        }
+        Ok(count)
+    }
+
+    fn str(&mut self) -> CacheResult<String> {
+        let len = self.len()?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| CacheError::new("invalid utf-8 in string"))
+    }
+
+    fn function(&mut self) -> CacheResult<CompiledFunction> {
+        let name = match self.u8()? {
+            0 => None,
+            _ => Some(self.str()?),
+        };
+        let parameters = (0..self.count()?)
+            .map(|_| self.str())
+            .collect::<CacheResult<_>>()?;
+        let num_locals = self.len()?;
+        let upvalues = (0..self.count()?)
+            .map(|_| {
+                Ok(UpvalueRef {
+                    is_local: self.u8()? != 0,
+                    index: self.len()?,
+                })
+            })
+            .collect::<CacheResult<_>>()?;
+        let len = self.len()?;
+        let instructions = self.take(len)?.to_vec();
+        let positions = (0..self.count()?)
+            .map(|_| Ok((self.len()?, (self.len()?, self.len()?))))
+            .collect::<CacheResult<_>>()?;
+        let files = (0..self.count()?)
+            .map(|_| Ok((self.len()?, self.str()?)))
+            .collect::<CacheResult<_>>()?;
+
+        Ok(CompiledFunction {
+            name,
+            parameters,
+            instructions,
+            num_locals,
+            upvalues,
+            positions,
+            files,
+        })
+    }
+
+    fn type_descriptor(&mut self) -> CacheResult<Value> {
+        let tag = self.u8()?;
+        let name = self.str()?;
+        let members: Vec<(String, usize)> = (0..self.count()?)
+            .map(|_| Ok((self.str()?, self.len()?)))
+            .collect::<CacheResult<_>>()?;
+
+        Ok(match tag {
+            0 => Value::Enum(Rc::new(EnumType {
+                name,
+                variants: members,
+            })),
+            1 => Value::StructType(Rc::new(StructType {
+                name,
+                fields: members.into_iter().map(|(field, _)| field).collect(),
+                methods: RefCell::default(),
+            })),
+            2 => Value::Interface(Rc::new(InterfaceType {
+                name,
+                methods: members,
+            })),
+            tag => return Err(CacheError::new(format!("unknown type tag {}", tag))),
+        })
+    }
+}
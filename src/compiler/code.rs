@@ -0,0 +1,307 @@
+use std::fmt;
+
+pub type Instructions = Vec<u8>;
+
+/// A single bytecode instruction. Operands follow the opcode byte, big-endian,
+/// with the widths given by its `Definition`.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Constant,
+    Null,
+    True,
+    False,
+    Pop,
+    Dup,
+    Dup2,
+
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Equal,
+    NotEqual,
+    Less,
+    Greater,
+    LessEqual,
+    GreaterEqual,
+    Minus,
+    Bang,
+    ToBool,
+
+    Jump,
+    JumpIfFalse,
+
+    GetGlobal,
+    GetGlobalOpt,
+    SetGlobal,
+    AssignGlobal,
+    CheckGlobal,
+    GetLocal,
+    SetLocal,
+    GetUpvalue,
+    SetUpvalue,
+    CloseUpvalues,
+
+    Array,
+    Hash,
+    Range,
+    Index,
+    SetIndex,
+    GetMember,
+    SetMember,
+
+    Call,
+    Return,
+    Closure,
+
+    NewType,
+    MakeStruct,
+    Impl,
+    Method,
+    CheckType,
+
+    IterInit,
+    IterNext,
+
+    MatchVariant,
+    VariantField,
+    MatchArray,
+    ArrayRest,
+    MatchStruct,
+    StructField,
+    NoMatch,
+
+    Try,
+    EndTry,
+    Throw,
+
+    TailCall,
+
+    Module,
+    StdModule,
+}
+
+const OPCODES: &[Opcode] = &[
+    Opcode::Constant,
+    Opcode::Null,
+    Opcode::True,
+    Opcode::False,
+    Opcode::Pop,
+    Opcode::Dup,
+    Opcode::Dup2,
+    Opcode::Add,
+    Opcode::Sub,
+    Opcode::Mul,
+    Opcode::Div,
+    Opcode::Mod,
+    Opcode::Equal,
+    Opcode::NotEqual,
+    Opcode::Less,
+    Opcode::Greater,
+    Opcode::LessEqual,
+    Opcode::GreaterEqual,
+    Opcode::Minus,
+    Opcode::Bang,
+    Opcode::ToBool,
+    Opcode::Jump,
+    Opcode::JumpIfFalse,
+    Opcode::GetGlobal,
+    Opcode::GetGlobalOpt,
+    Opcode::SetGlobal,
+    Opcode::AssignGlobal,
+    Opcode::CheckGlobal,
+    Opcode::GetLocal,
+    Opcode::SetLocal,
+    Opcode::GetUpvalue,
+    Opcode::SetUpvalue,
+    Opcode::CloseUpvalues,
+    Opcode::Array,
+    Opcode::Hash,
+    Opcode::Range,
+    Opcode::Index,
+    Opcode::SetIndex,
+    Opcode::GetMember,
+    Opcode::SetMember,
+    Opcode::Call,
+    Opcode::Return,
+    Opcode::Closure,
+    Opcode::NewType,
+    Opcode::MakeStruct,
+    Opcode::Impl,
+    Opcode::Method,
+    Opcode::CheckType,
+    Opcode::IterInit,
+    Opcode::IterNext,
+    Opcode::MatchVariant,
+    Opcode::VariantField,
+    Opcode::MatchArray,
+    Opcode::ArrayRest,
+    Opcode::MatchStruct,
+    Opcode::StructField,
+    Opcode::NoMatch,
+    Opcode::Try,
+    Opcode::EndTry,
+    Opcode::Throw,
+    Opcode::TailCall,
+    Opcode::Module,
+    Opcode::StdModule,
+];
+
+pub struct Definition {
+    pub name: &'static str,
+    pub operand_widths: &'static [usize],
+}
+
+impl Opcode {
+    pub fn from_byte(byte: u8) -> Option<Opcode> {
+        OPCODES.get(byte as usize).copied()
+    }
+
+    pub fn definition(self) -> Definition {
+        let (name, operand_widths): (&'static str, &'static [usize]) = match self {
+            Opcode::Constant => ("OpConstant", &[2]),
+            Opcode::Null => ("OpNull", &[]),
+            Opcode::True => ("OpTrue", &[]),
+            Opcode::False => ("OpFalse", &[]),
+            Opcode::Pop => ("OpPop", &[]),
+            Opcode::Dup => ("OpDup", &[]),
+            Opcode::Dup2 => ("OpDup2", &[]),
+            Opcode::Add => ("OpAdd", &[]),
+            Opcode::Sub => ("OpSub", &[]),
+            Opcode::Mul => ("OpMul", &[]),
+            Opcode::Div => ("OpDiv", &[]),
+            Opcode::Mod => ("OpMod", &[]),
+            Opcode::Equal => ("OpEqual", &[]),
+            Opcode::NotEqual => ("OpNotEqual", &[]),
+            Opcode::Less => ("OpLess", &[]),
+            Opcode::Greater => ("OpGreater", &[]),
+            Opcode::LessEqual => ("OpLessEqual", &[]),
+            Opcode::GreaterEqual => ("OpGreaterEqual", &[]),
+            Opcode::Minus => ("OpMinus", &[]),
+            Opcode::Bang => ("OpBang", &[]),
+            Opcode::ToBool => ("OpToBool", &[]),
+            // absolute instruction offset
+            Opcode::Jump => ("OpJump", &[2]),
+            Opcode::JumpIfFalse => ("OpJumpIfFalse", &[2]),
+            // global index
+            Opcode::GetGlobal => ("OpGetGlobal", &[2]),
+            Opcode::GetGlobalOpt => ("OpGetGlobalOpt", &[2]),
+            Opcode::SetGlobal => ("OpSetGlobal", &[2]),
+            Opcode::AssignGlobal => ("OpAssignGlobal", &[2]),
+            Opcode::CheckGlobal => ("OpCheckGlobal", &[2]),
+            // local slot or upvalue index
+            Opcode::GetLocal => ("OpGetLocal", &[2]),
+            Opcode::SetLocal => ("OpSetLocal", &[2]),
+            Opcode::GetUpvalue => ("OpGetUpvalue", &[2]),
+            Opcode::SetUpvalue => ("OpSetUpvalue", &[2]),
+            Opcode::CloseUpvalues => ("OpCloseUpvalues", &[2]),
+            // element count
+            Opcode::Array => ("OpArray", &[2]),
+            Opcode::Hash => ("OpHash", &[2]),
+            Opcode::Range => ("OpRange", &[]),
+            Opcode::Index => ("OpIndex", &[]),
+            Opcode::SetIndex => ("OpSetIndex", &[]),
+            // property name constant
+            Opcode::GetMember => ("OpGetMember", &[2]),
+            Opcode::SetMember => ("OpSetMember", &[2]),
+            // argument count
+            Opcode::Call => ("OpCall", &[1]),
+            Opcode::Return => ("OpReturn", &[]),
+            // function constant
+            Opcode::Closure => ("OpClosure", &[2]),
+            // type descriptor constant
+            Opcode::NewType => ("OpNewType", &[2]),
+            // struct name constant, field names constant
+            Opcode::MakeStruct => ("OpMakeStruct", &[2, 2]),
+            // target name constant
+            Opcode::Impl => ("OpImpl", &[2]),
+            // method name constant
+            Opcode::Method => ("OpMethod", &[2]),
+            Opcode::CheckType => ("OpCheckType", &[]),
+            // first of two hidden local slots: the iterable and the position
+            Opcode::IterInit => ("OpIterInit", &[2]),
+            // hidden local slot, exit offset
+            Opcode::IterNext => ("OpIterNext", &[2, 2]),
+            // pattern names constant, payload length (255 without a payload)
+            Opcode::MatchVariant => ("OpMatchVariant", &[2, 1]),
+            Opcode::VariantField => ("OpVariantField", &[1]),
+            // element count, whether a rest pattern follows
+            Opcode::MatchArray => ("OpMatchArray", &[2, 1]),
+            Opcode::ArrayRest => ("OpArrayRest", &[2]),
+            // struct name constant
+            Opcode::MatchStruct => ("OpMatchStruct", &[2]),
+            // field name constant
+            Opcode::StructField => ("OpStructField", &[2]),
+            Opcode::NoMatch => ("OpNoMatch", &[]),
+            // offset of the catch block
+            Opcode::Try => ("OpTry", &[2]),
+            Opcode::EndTry => ("OpEndTry", &[]),
+            Opcode::Throw => ("OpThrow", &[]),
+            Opcode::TailCall => ("OpTailCall", &[1]),
+            // module name constant, export names constant
+            Opcode::Module => ("OpModule", &[2, 2]),
+            // import path constant of a standard library module
+            Opcode::StdModule => ("OpStdModule", &[2]),
+        };
+
+        Definition {
+            name,
+            operand_widths,
+        }
+    }
+}
+
+impl fmt::Display for Opcode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.definition().name)
+    }
+}
+
+/// The first operand too large for the width `make` encodes it in, with
+/// the largest value that width holds.
+pub fn oversized_operand(op: Opcode, operands: &[usize]) -> Option<(usize, usize)> {
+    operands
+        .iter()
+        .zip(op.definition().operand_widths)
+        .map(|(&operand, &width)| (operand, (1 << (8 * width)) - 1))
+        .find(|&(operand, max)| operand > max)
+}
+
+/// Encodes an instruction. Operands are truncated to their widths, so
+/// callers check them with `oversized_operand` first.
+pub fn make(op: Opcode, operands: &[usize]) -> Instructions {
+    let def = op.definition();
+    let mut instruction = vec![op as u8];
+    for (operand, width) in operands.iter().zip(def.operand_widths) {
+        match width {
+            2 => instruction.extend_from_slice(&(*operand as u16).to_be_bytes()),
+            1 => instruction.push(*operand as u8),
+            _ => unreachable!("unsupported operand width {}", width),
+        }
+    }
+
+    instruction
+}
+
+/// Decodes the operands of an instruction, returning them with the number of
+/// bytes read.
+pub fn read_operands(def: &Definition, ins: &[u8]) -> (Vec<usize>, usize) {
+    let mut operands = Vec::with_capacity(def.operand_widths.len());
+    let mut offset = 0;
+    for width in def.operand_widths {
+        match width {
+            2 => operands.push(read_u16(&ins[offset..]) as usize),
+            1 => operands.push(ins[offset] as usize),
+            _ => unreachable!("unsupported operand width {}", width),
+        }
+        offset += width;
+    }
+
+    (operands, offset)
+}
+
+pub fn read_u16(ins: &[u8]) -> u16 {
+    u16::from_be_bytes([ins[0], ins[1]])
+}
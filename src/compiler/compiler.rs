@@ -0,0 +1,1216 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    evaluator::{
+        capabilities::Capabilities,
+        evaluator::{eval_enum_literal, eval_interface_literal, eval_struct_literal},
+        stdlib,
+        value::Value,
+    },
+    module::loader::{self, Loader, Source, MAIN_FILE},
+    parser::{
+        ast::{
+            ArrayLiteral, AssignExpression, BlockStatement, BooleanLiteral, CallExpression,
+            ConditionalIteratorExpression, EnumLiteral, Expression, ExpressionStatement,
+            FloatLiteral, ForLoopCondition, ForLoopExpression, FunctionLiteral, HashLiteral,
+            Identifier, IfExpression, ImplStatement, ImportStatement, IndexExpression,
+            InfixExpression, IntegerLiteral, InterfaceLiteral, IteratorLiteral, LetStatement,
+            MatchExpression, MemberExpression, Node, Pattern, PrefixExpression, Program,
+            ReturnStatemnt, Statement, StringLiteral, StructExpression, StructLiteral,
+            ThrowStatement, TryExpression, TypeExpr,
+        },
+        get_of_type, grow_stack,
+    },
+};
+
+use super::{
+    bytecode::{Bytecode, CompileError, CompiledFunction, Constant, UpvalueRef},
+    cache::source_hash,
+    code::{make, oversized_operand, read_operands, Instructions, Opcode},
+};
+
+type CompileResult = Result<(), CompileError>;
+
+/// Placeholder operand of a jump that is patched once its target is known.
+const UNPATCHED: usize = 9999;
+
+/// The payload operand of `OpMatchVariant` for patterns without a payload.
+pub const NO_PAYLOAD: usize = 255;
+
+struct Local {
+    name: String,
+    slot: usize,
+    depth: usize,
+    captured: bool,
+    /// Checked against every value assigned to the local.
+    annotation: Option<TypeExpr>,
+}
+
+/// The state of the function being compiled. Block scopes only exist at
+/// compile time: every local gets its own slot in the frame.
+#[derive(Default)]
+struct CompilationScope {
+    instructions: Instructions,
+    locals: Vec<Local>,
+    depth: usize,
+    next_slot: usize,
+    num_slots: usize,
+    upvalues: Vec<UpvalueRef>,
+    positions: Vec<(usize, (usize, usize))>,
+    files: Vec<(usize, String)>,
+    /// `try` blocks of the function being compiled around the current
+    /// instruction. A call in one of them must not replace the frame, as its
+    /// errors are caught there.
+    try_depth: usize,
+}
+
+enum Symbol {
+    Global(usize),
+    Local(usize),
+    Upvalue(usize),
+}
+
+/// Lowers a program to bytecode for the vm. Top level `let` bindings become
+/// global slots, every other binding a local slot of its function's frame.
+pub struct Compiler {
+    constants: Vec<Constant>,
+    globals: Vec<String>,
+    global_indexes: HashMap<String, usize>,
+    /// The annotations of the globals declared with one, by global index.
+    global_annotations: HashMap<usize, TypeExpr>,
+    scopes: Vec<CompilationScope>,
+    /// Source `(line, column)` the emitted instructions are attributed to.
+    position: (usize, usize),
+    /// The file the emitted instructions are compiled from, as errors name
+    /// it.
+    file: String,
+    /// Whether the next statement or expression compiled is in tail
+    /// position, where a call compiles to `OpTailCall`. Every statement and
+    /// expression takes the flag when it starts, like in the interpreter.
+    tail: bool,
+    /// Reads the files programs import, which they may not without one.
+    loader: Option<Loader>,
+    /// What the imports compiled may read.
+    capabilities: Capabilities,
+    /// The global slot holding each module imported so far, by path.
+    modules: HashMap<PathBuf, usize>,
+    /// Prepended to the names of the globals of the module being compiled,
+    /// which are kept apart from those of the program and other modules.
+    prefix: String,
+    imports: Vec<(PathBuf, u64)>,
+    /// The first operand that did not fit its instruction, reported once
+    /// compiling ends instead of emitting a truncated operand.
+    error: Option<CompileError>,
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Self {
+            constants: vec![],
+            globals: vec![],
+            global_indexes: HashMap::new(),
+            global_annotations: HashMap::new(),
+            scopes: vec![CompilationScope::default()],
+            position: (1, 1),
+            file: MAIN_FILE.to_string(),
+            tail: false,
+            loader: None,
+            capabilities: Capabilities::default(),
+            modules: HashMap::new(),
+            prefix: String::new(),
+            imports: vec![],
+            error: None,
+        }
+    }
+}
+
+impl Compiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves the imports of the program against the file at `path`, which
+    /// it was read from.
+    pub fn set_path(&mut self, path: &Path) {
+        self.loader.get_or_insert_with(Loader::new).set_main(path);
+    }
+
+    /// Loads the modules programs import with `loader`, which may search
+    /// more than the directory of the importing file. Without a loader,
+    /// programs may import the standard library only.
+    pub fn set_loader(&mut self, loader: Loader) {
+        self.loader = Some(loader);
+    }
+
+    /// Lets imports read the files `capabilities` grant reading, in place of
+    /// none, like the programs run with them.
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities;
+    }
+
+    pub fn compile(mut self, program: &Program) -> Result<Bytecode, CompileError> {
+        self.file = loader::main_file(self.loader.as_ref());
+        self.declare_globals(program);
+        self.compile_statements(&program.statements)?;
+        self.emit(Opcode::Return, &[]);
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+
+        let scope = self.scopes.pop().expect("the main scope is never left");
+        Ok(Bytecode {
+            main: Rc::new(CompiledFunction {
+                name: None,
+                parameters: vec![],
+                instructions: scope.instructions,
+                num_locals: scope.num_slots,
+                upvalues: vec![],
+                positions: scope.positions,
+                files: scope.files,
+            }),
+            constants: self.constants,
+            globals: self.globals,
+            imports: self.imports,
+        })
+    }
+
+    /// Globals may be referenced, and assigned, by functions declared
+    /// before them.
+    fn declare_globals(&mut self, program: &Program) {
+        for stmt in &program.statements {
+            if let Some(stmt) = get_of_type::<LetStatement>(stmt.get_as_any()) {
+                let index = self.global_index(&stmt.name.value);
+                self.annotate_global(index, &stmt.type_annotation);
+            }
+        }
+    }
+
+    /// Compiles statements so that they leave the value of the last one on
+    /// the stack, or null when there are none.
+    fn compile_statements(&mut self, stmts: &[Box<dyn Statement>]) -> CompileResult {
+        let tail = std::mem::take(&mut self.tail);
+        if stmts.is_empty() {
+            self.emit(Opcode::Null, &[]);
+        }
+        for (i, stmt) in stmts.iter().enumerate() {
+            self.tail = tail && i + 1 == stmts.len();
+            self.compile_statement(stmt.as_ref())?;
+            if i + 1 < stmts.len() {
+                self.emit(Opcode::Pop, &[]);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn compile_statement(&mut self, stmt: &dyn Statement) -> CompileResult {
+        grow_stack(|| self.compile_statement_node(stmt))
+    }
+
+    fn compile_statement_node(&mut self, stmt: &dyn Statement) -> CompileResult {
+        let tail = std::mem::take(&mut self.tail);
+        self.position = stmt.position();
+        let any = stmt.get_as_any();
+        if let Some(stmt) = get_of_type::<ExpressionStatement>(any) {
+            match &stmt.expression {
+                Some(exp) => {
+                    self.tail = tail;
+                    self.compile_expression(exp.as_ref())?
+                }
+                None => {
+                    self.emit(Opcode::Null, &[]);
+                }
+            }
+        } else if let Some(stmt) = get_of_type::<LetStatement>(any) {
+            self.compile_let_statement(stmt)?;
+        } else if let Some(stmt) = get_of_type::<ReturnStatemnt>(any) {
+            match &stmt.return_value {
+                Some(exp) => {
+                    self.tail = self.scopes.len() > 1 && self.scope().try_depth == 0;
+                    self.compile_expression(exp.as_ref())?
+                }
+                None => {
+                    self.emit(Opcode::Null, &[]);
+                }
+            }
+            self.emit(Opcode::Return, &[]);
+        } else if let Some(stmt) = get_of_type::<ThrowStatement>(any) {
+            self.compile_expression(expect_expression(&stmt.value)?)?;
+            self.emit(Opcode::Throw, &[]);
+        } else if let Some(stmt) = get_of_type::<ForLoopExpression>(any) {
+            self.compile_for_expression(stmt)?;
+        } else if let Some(stmt) = get_of_type::<ImplStatement>(any) {
+            self.compile_impl_statement(stmt)?;
+        } else if let Some(stmt) = get_of_type::<ImportStatement>(any) {
+            self.compile_import_statement(stmt)?;
+        } else {
+            return Err(CompileError::new(format!(
+                "unknown statement: {}",
+                stmt.string()
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn compile_block(&mut self, block: &BlockStatement) -> CompileResult {
+        self.begin_scope();
+        self.compile_statements(&block.statements)?;
+        self.end_scope();
+
+        Ok(())
+    }
+
+    fn compile_let_statement(&mut self, stmt: &LetStatement) -> CompileResult {
+        let exp = expect_expression(&stmt.value)?;
+        let name = &stmt.name.value;
+
+        if self.scopes.len() == 1 && self.scope().depth == 0 {
+            self.compile_named_value(exp, name)?;
+            self.compile_type_check(&stmt.type_annotation);
+            let index = self.global_index(name);
+            self.annotate_global(index, &stmt.type_annotation);
+            self.emit(Opcode::SetGlobal, &[index]);
+        } else {
+            // a function can only refer to itself if its slot exists first
+            let slot = if get_of_type::<FunctionLiteral>(exp.get_as_any()).is_some() {
+                let slot = self.declare_local(name);
+                self.compile_named_value(exp, name)?;
+                slot
+            } else {
+                self.compile_named_value(exp, name)?;
+                self.declare_local(name)
+            };
+            self.compile_type_check(&stmt.type_annotation);
+            self.annotate_local(slot, &stmt.type_annotation);
+            self.emit(Opcode::SetLocal, &[slot]);
+        }
+        self.emit(Opcode::Null, &[]);
+
+        Ok(())
+    }
+
+    /// Binds the module to its alias like a `let`, compiling the module
+    /// into the program where it is first imported.
+    fn compile_import_statement(&mut self, stmt: &ImportStatement) -> CompileResult {
+        let module = match stdlib::lookup(&stmt.path) {
+            Some(module) => {
+                module.map_err(CompileError::new)?;
+                self.compile_std_module(&stmt.path)
+            }
+            None => self.compile_file_import(stmt)?,
+        };
+        self.emit(Opcode::GetGlobal, &[module]);
+        let alias = self.global_index(&stmt.alias.value);
+        self.emit(Opcode::SetGlobal, &[alias]);
+        self.emit(Opcode::Null, &[]);
+
+        Ok(())
+    }
+
+    /// Leaves a module of the standard library in a global slot of its own,
+    /// the first time it is imported, returning the slot.
+    fn compile_std_module(&mut self, import: &str) -> usize {
+        let path = PathBuf::from(import);
+        if let Some(module) = self.modules.get(&path) {
+            return *module;
+        }
+        let name = self.add_constant(Constant::Str(import.to_owned()));
+        self.emit(Opcode::StdModule, &[name]);
+        let module = self.global_slot(format!("{}::", import));
+        self.emit(Opcode::SetGlobal, &[module]);
+        self.modules.insert(path, module);
+        module
+    }
+
+    fn compile_file_import(&mut self, stmt: &ImportStatement) -> Result<usize, CompileError> {
+        let Some(loader) = &mut self.loader else {
+            return Err(CompileError::new(loader::no_loader(&stmt.path).message));
+        };
+        let path = loader.resolve(&stmt.path).map_err(CompileError::new)?;
+        Ok(match self.modules.get(&path) {
+            Some(module) => *module,
+            None => {
+                let source = loader
+                    .enter(path, &self.capabilities)
+                    .map_err(|err| CompileError::new(err.message))?;
+                let file = loader.display(&source.path);
+                let module = self.compile_module(&source, file);
+                if let Some(loader) = &mut self.loader {
+                    loader.leave();
+                }
+                module?
+            }
+        })
+    }
+
+    /// Compiles the top level code of a module, read from the file errors
+    /// name `file`, which leaves the module in a global slot of its own,
+    /// returning the slot.
+    fn compile_module(&mut self, source: &Source, file: String) -> Result<usize, CompileError> {
+        let prefix = format!("{}::", source.path.display());
+        let outer = std::mem::replace(&mut self.prefix, prefix);
+        let position = self.position;
+        let importer = std::mem::replace(&mut self.file, file);
+
+        self.declare_globals(&source.program);
+        for stmt in &source.program.statements {
+            if get_of_type::<ReturnStatemnt>(stmt.get_as_any()).is_some() {
+                return Err(CompileError::new(format!(
+                    "module {} returns outside of a function",
+                    source.name
+                )));
+            }
+            self.compile_statement(stmt.as_ref())?;
+            self.emit(Opcode::Pop, &[]);
+        }
+        let exports: Vec<String> = source
+            .program
+            .statements
+            .iter()
+            .filter_map(|stmt| get_of_type::<LetStatement>(stmt.get_as_any()))
+            .filter(|stmt| stmt.exported)
+            .map(|stmt| stmt.name.value.clone())
+            .collect();
+        for name in &exports {
+            let index = self.global_index(name);
+            self.emit(Opcode::GetGlobal, &[index]);
+        }
+
+        self.prefix = outer;
+        self.position = position;
+        self.file = importer;
+        let name = self.add_constant(Constant::Str(source.name.clone()));
+        let exports = self.add_constant(Constant::Names(exports));
+        self.emit(Opcode::Module, &[name, exports]);
+        let module = self.global_slot(format!("{}::", source.path.display()));
+        self.emit(Opcode::SetGlobal, &[module]);
+
+        self.modules.insert(source.path.clone(), module);
+        self.imports
+            .push((source.path.clone(), source_hash(&source.text)));
+
+        Ok(module)
+    }
+
+    /// Compiles the value of a `let`, which names declarations and functions.
+    fn compile_named_value(&mut self, exp: &dyn Expression, name: &str) -> CompileResult {
+        let any = exp.get_as_any();
+        if let Some(lit) = get_of_type::<EnumLiteral>(any) {
+            self.emit_type(eval_enum_literal(lit, name));
+        } else if let Some(lit) = get_of_type::<StructLiteral>(any) {
+            self.emit_type(eval_struct_literal(lit, name));
+        } else if let Some(lit) = get_of_type::<InterfaceLiteral>(any) {
+            self.emit_type(eval_interface_literal(lit, name));
+        } else if let Some(lit) = get_of_type::<FunctionLiteral>(any) {
+            self.compile_function(lit, Some(name))?;
+        } else {
+            self.compile_expression(exp)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks the value on top of the stack against an annotation naming an
+    /// interface, leaving the value in place.
+    fn compile_type_check(&mut self, type_annotation: &Option<TypeExpr>) {
+        let Some(TypeExpr::Named(name)) = type_annotation else {
+            return;
+        };
+        match self.resolve(&name.value) {
+            Symbol::Global(index) => self.emit(Opcode::GetGlobalOpt, &[index]),
+            Symbol::Local(slot) => self.emit(Opcode::GetLocal, &[slot]),
+            Symbol::Upvalue(index) => self.emit(Opcode::GetUpvalue, &[index]),
+        };
+        self.emit(Opcode::CheckType, &[]);
+    }
+
+    /// Compiles an expression, attributing its instructions to its position.
+    /// The enclosing position is restored for what the caller emits after it.
+    fn compile_expression(&mut self, exp: &dyn Expression) -> CompileResult {
+        let position = std::mem::replace(&mut self.position, exp.position());
+        grow_stack(|| self.compile_expression_node(exp))?;
+        self.position = position;
+
+        Ok(())
+    }
+
+    fn compile_expression_node(&mut self, exp: &dyn Expression) -> CompileResult {
+        let tail = std::mem::take(&mut self.tail);
+        let any = exp.get_as_any();
+        if let Some(lit) = get_of_type::<IntegerLiteral>(any) {
+            let index = self.add_constant(Constant::Int(lit.value));
+            self.emit(Opcode::Constant, &[index]);
+        } else if let Some(lit) = get_of_type::<FloatLiteral>(any) {
+            let index = self.add_constant(Constant::Float(lit.value));
+            self.emit(Opcode::Constant, &[index]);
+        } else if let Some(lit) = get_of_type::<BooleanLiteral>(any) {
+            self.emit(
+                if lit.value {
+                    Opcode::True
+                } else {
+                    Opcode::False
+                },
+                &[],
+            );
+        } else if let Some(lit) = get_of_type::<StringLiteral>(any) {
+            let index = self.add_constant(Constant::Str(lit.value.clone()));
+            self.emit(Opcode::Constant, &[index]);
+        } else if let Some(ident) = get_of_type::<Identifier>(any) {
+            self.load(&ident.value);
+        } else if let Some(exp) = get_of_type::<PrefixExpression>(any) {
+            self.compile_expression(expect_expression(&exp.right)?)?;
+            match exp.operator.as_str() {
+                "-" => self.emit(Opcode::Minus, &[]),
+                "!" => self.emit(Opcode::Bang, &[]),
+                operator => {
+                    return Err(CompileError::new(format!("unknown operator: {}", operator)))
+                }
+            };
+        } else if let Some(exp) = get_of_type::<InfixExpression>(any) {
+            self.compile_infix_expression(exp)?;
+        } else if let Some(exp) = get_of_type::<AssignExpression>(any) {
+            self.compile_assign_expression(exp)?;
+        } else if let Some(exp) = get_of_type::<IfExpression>(any) {
+            self.compile_if_expression(exp, tail)?;
+        } else if let Some(exp) = get_of_type::<TryExpression>(any) {
+            self.compile_try_expression(exp)?;
+        } else if let Some(lit) = get_of_type::<FunctionLiteral>(any) {
+            self.compile_function(lit, None)?;
+        } else if let Some(exp) = get_of_type::<CallExpression>(any) {
+            self.compile_expression(exp.function.as_ref())?;
+            for arg in &exp.arguments {
+                self.compile_expression(arg.as_ref())?;
+            }
+            let call = if tail { Opcode::TailCall } else { Opcode::Call };
+            self.emit(call, &[exp.arguments.len()]);
+        } else if let Some(lit) = get_of_type::<ArrayLiteral>(any) {
+            for element in &lit.elements {
+                self.compile_expression(element.as_ref())?;
+            }
+            self.emit(Opcode::Array, &[lit.elements.len()]);
+        } else if let Some(lit) = get_of_type::<HashLiteral>(any) {
+            for (key, value) in &lit.pairs {
+                self.compile_expression(key.as_ref())?;
+                self.compile_expression(value.as_ref())?;
+            }
+            self.emit(Opcode::Hash, &[lit.pairs.len()]);
+        } else if let Some(exp) = get_of_type::<IndexExpression>(any) {
+            self.compile_expression(exp.left.as_ref())?;
+            self.compile_expression(expect_expression(&exp.index)?)?;
+            self.emit(Opcode::Index, &[]);
+        } else if let Some(exp) = get_of_type::<MemberExpression>(any) {
+            self.compile_expression(exp.object.as_ref())?;
+            let name = self.add_constant(Constant::Str(exp.property.value.clone()));
+            self.emit(Opcode::GetMember, &[name]);
+        } else if let Some(lit) = get_of_type::<IteratorLiteral>(any) {
+            self.compile_expression(lit.start.as_ref())?;
+            self.compile_expression(expect_expression(&lit.end)?)?;
+            self.emit(Opcode::Range, &[]);
+        } else if let Some(lit) = get_of_type::<EnumLiteral>(any) {
+            self.emit_type(eval_enum_literal(lit, "<anonymous enum>"));
+        } else if let Some(lit) = get_of_type::<StructLiteral>(any) {
+            self.emit_type(eval_struct_literal(lit, "<anonymous struct>"));
+        } else if let Some(lit) = get_of_type::<InterfaceLiteral>(any) {
+            self.emit_type(eval_interface_literal(lit, "<anonymous interface>"));
+        } else if let Some(exp) = get_of_type::<StructExpression>(any) {
+            self.load(&exp.name.value);
+            let mut fields = vec![];
+            for (field, value) in &exp.fields {
+                self.compile_expression(value.as_ref())?;
+                fields.push(field.value.clone());
+            }
+            let name = self.add_constant(Constant::Str(exp.name.value.clone()));
+            let fields = self.add_constant(Constant::Names(fields));
+            self.emit(Opcode::MakeStruct, &[name, fields]);
+        } else if let Some(exp) = get_of_type::<MatchExpression>(any) {
+            self.compile_match_expression(exp)?;
+        } else if get_of_type::<ConditionalIteratorExpression>(any).is_some() {
+            return Err(CompileError::new(format!(
+                "`{}` is only valid as a for-loop condition",
+                exp.string()
+            )));
+        } else {
+            return Err(CompileError::new(format!(
+                "unknown expression: {}",
+                exp.string()
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn compile_infix_expression(&mut self, exp: &InfixExpression) -> CompileResult {
+        let right = expect_expression(&exp.right)?;
+        self.compile_expression(exp.left.as_ref())?;
+
+        match exp.operator.as_str() {
+            "&&" => {
+                let jump_false = self.emit(Opcode::JumpIfFalse, &[UNPATCHED]);
+                self.compile_expression(right)?;
+                self.emit(Opcode::ToBool, &[]);
+                let jump_end = self.emit(Opcode::Jump, &[UNPATCHED]);
+                self.patch_jump(jump_false);
+                self.emit(Opcode::False, &[]);
+                self.patch_jump(jump_end);
+            }
+            "||" => {
+                let jump_right = self.emit(Opcode::JumpIfFalse, &[UNPATCHED]);
+                self.emit(Opcode::True, &[]);
+                let jump_end = self.emit(Opcode::Jump, &[UNPATCHED]);
+                self.patch_jump(jump_right);
+                self.compile_expression(right)?;
+                self.emit(Opcode::ToBool, &[]);
+                self.patch_jump(jump_end);
+            }
+            operator => {
+                self.compile_expression(right)?;
+                self.emit(binary_opcode(operator)?, &[]);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn compile_assign_expression(&mut self, exp: &AssignExpression) -> CompileResult {
+        let value = expect_expression(&exp.value)?;
+        let operator = exp.binary_operator().map(binary_opcode).transpose()?;
+        let any = exp.target.get_as_any();
+
+        if let Some(ident) = get_of_type::<Identifier>(any) {
+            let symbol = self.resolve(&ident.value);
+            if let Symbol::Global(index) = symbol {
+                self.emit(Opcode::CheckGlobal, &[index]);
+            }
+            if let Some(operator) = operator {
+                self.load_symbol(&symbol);
+                self.compile_expression(value)?;
+                self.emit(operator, &[]);
+            } else {
+                self.compile_expression(value)?;
+            }
+            let annotation = self.annotation(&ident.value);
+            self.compile_type_check(&annotation);
+            self.emit(Opcode::Dup, &[]);
+            match symbol {
+                Symbol::Global(index) => self.emit(Opcode::AssignGlobal, &[index]),
+                Symbol::Local(slot) => self.emit(Opcode::SetLocal, &[slot]),
+                Symbol::Upvalue(index) => self.emit(Opcode::SetUpvalue, &[index]),
+            };
+        } else if let Some(target) = get_of_type::<IndexExpression>(any) {
+            self.compile_expression(target.left.as_ref())?;
+            self.compile_expression(expect_expression(&target.index)?)?;
+            if let Some(operator) = operator {
+                self.emit(Opcode::Dup2, &[]);
+                self.emit(Opcode::Index, &[]);
+                self.compile_expression(value)?;
+                self.emit(operator, &[]);
+            } else {
+                self.compile_expression(value)?;
+            }
+            self.emit(Opcode::SetIndex, &[]);
+        } else if let Some(target) = get_of_type::<MemberExpression>(any) {
+            self.compile_expression(target.object.as_ref())?;
+            let name = self.add_constant(Constant::Str(target.property.value.clone()));
+            if let Some(operator) = operator {
+                self.emit(Opcode::Dup, &[]);
+                self.emit(Opcode::GetMember, &[name]);
+                self.compile_expression(value)?;
+                self.emit(operator, &[]);
+            } else {
+                self.compile_expression(value)?;
+            }
+            self.emit(Opcode::SetMember, &[name]);
+        } else {
+            return Err(CompileError::new(format!(
+                "cannot assign to `{}`",
+                exp.target.string()
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn compile_if_expression(&mut self, exp: &IfExpression, tail: bool) -> CompileResult {
+        let consequence = exp
+            .consequence
+            .as_ref()
+            .ok_or_else(|| CompileError::new("if expression without a body"))?;
+
+        let mut end_jumps = vec![];
+        self.compile_expression(expect_expression(&exp.condition)?)?;
+        let mut next_branch = self.emit(Opcode::JumpIfFalse, &[UNPATCHED]);
+        self.tail = tail;
+        self.compile_block(consequence)?;
+        end_jumps.push(self.emit(Opcode::Jump, &[UNPATCHED]));
+
+        for (condition, consequence) in &exp.else_ifs {
+            self.patch_jump(next_branch);
+            self.compile_expression(condition.as_ref())?;
+            next_branch = self.emit(Opcode::JumpIfFalse, &[UNPATCHED]);
+            self.tail = tail;
+            self.compile_block(consequence)?;
+            end_jumps.push(self.emit(Opcode::Jump, &[UNPATCHED]));
+        }
+
+        self.patch_jump(next_branch);
+        match &exp.alternative {
+            Some(alternative) => {
+                self.tail = tail;
+                self.compile_block(alternative)?
+            }
+            None => {
+                self.emit(Opcode::Null, &[]);
+            }
+        }
+        for jump in end_jumps {
+            self.patch_jump(jump);
+        }
+
+        Ok(())
+    }
+
+    /// Registers a handler for the `try` block. When an error unwinds to it,
+    /// the vm resumes at the catch block with the error on the stack.
+    fn compile_try_expression(&mut self, exp: &TryExpression) -> CompileResult {
+        let handler = self.emit(Opcode::Try, &[UNPATCHED]);
+        self.scope().try_depth += 1;
+        self.compile_block(&exp.body)?;
+        self.scope().try_depth -= 1;
+        self.emit(Opcode::EndTry, &[]);
+        let jump_end = self.emit(Opcode::Jump, &[UNPATCHED]);
+
+        self.patch_jump(handler);
+        self.begin_scope();
+        match &exp.parameter {
+            Some(parameter) => {
+                let slot = self.declare_local(&parameter.value);
+                self.emit(Opcode::SetLocal, &[slot]);
+            }
+            None => {
+                self.emit(Opcode::Pop, &[]);
+            }
+        }
+        self.compile_statements(&exp.handler.statements)?;
+        self.end_scope();
+        self.patch_jump(jump_end);
+
+        Ok(())
+    }
+
+    fn compile_for_expression(&mut self, exp: &ForLoopExpression) -> CompileResult {
+        let body = exp
+            .body
+            .as_ref()
+            .ok_or_else(|| CompileError::new("for loop without a body"))?;
+
+        match &exp.condition {
+            Some(ForLoopCondition::Loop) => {
+                let start = self.current_offset();
+                self.compile_block(body)?;
+                self.emit(Opcode::Pop, &[]);
+                self.emit(Opcode::Jump, &[start]);
+            }
+            Some(ForLoopCondition::For(condition)) => {
+                let start = self.current_offset();
+                self.compile_expression(condition.as_ref())?;
+                let exit = self.emit(Opcode::JumpIfFalse, &[UNPATCHED]);
+                self.compile_block(body)?;
+                self.emit(Opcode::Pop, &[]);
+                self.emit(Opcode::Jump, &[start]);
+                self.patch_jump(exit);
+            }
+            Some(ForLoopCondition::ForIn(condition)) => {
+                let condition =
+                    get_of_type::<ConditionalIteratorExpression>(condition.get_as_any())
+                        .ok_or_else(|| CompileError::new("invalid for-in condition"))?;
+
+                self.begin_scope();
+                self.compile_expression(expect_expression(&condition.r#in)?)?;
+                let iterator = self.declare_local("@iterable");
+                self.declare_local("@position");
+                self.emit(Opcode::IterInit, &[iterator]);
+
+                let start = self.current_offset();
+                let exit = self.emit(Opcode::IterNext, &[iterator, UNPATCHED]);
+                // a fresh scope per iteration, so closures capture each item
+                self.begin_scope();
+                let item = self.declare_local(&condition.variable.value);
+                self.emit(Opcode::SetLocal, &[item]);
+                self.compile_statements(&body.statements)?;
+                self.end_scope();
+                self.emit(Opcode::Pop, &[]);
+                self.emit(Opcode::Jump, &[start]);
+
+                let end = self.current_offset();
+                self.change_operand(exit, 1, end);
+                self.end_scope();
+            }
+            None => return Err(CompileError::new("for loop without a condition")),
+        }
+        self.emit(Opcode::Null, &[]);
+
+        Ok(())
+    }
+
+    fn compile_impl_statement(&mut self, stmt: &ImplStatement) -> CompileResult {
+        self.load(&stmt.target.value);
+        let target = self.add_constant(Constant::Str(stmt.target.value.clone()));
+        self.emit(Opcode::Impl, &[target]);
+        for (name, lit) in &stmt.methods {
+            self.compile_function(lit, Some(&name.value))?;
+            let name = self.add_constant(Constant::Str(name.value.clone()));
+            self.emit(Opcode::Method, &[name]);
+        }
+        self.emit(Opcode::Pop, &[]);
+        self.emit(Opcode::Null, &[]);
+
+        Ok(())
+    }
+
+    fn compile_function(&mut self, lit: &FunctionLiteral, name: Option<&str>) -> CompileResult {
+        let body = lit
+            .body
+            .as_ref()
+            .ok_or_else(|| CompileError::new("function literal without a body"))?;
+
+        // parameter type checks fail at the function body, like in the interpreter
+        let position = std::mem::replace(&mut self.position, body.position());
+        self.scopes.push(CompilationScope {
+            depth: 1,
+            ..Default::default()
+        });
+        for param in &lit.parameters {
+            self.declare_fresh_local(&param.value);
+        }
+        for (slot, param_type) in lit.parameter_types.iter().enumerate() {
+            if param_type.is_some() {
+                self.emit(Opcode::GetLocal, &[slot]);
+                self.compile_type_check(param_type);
+                self.emit(Opcode::Pop, &[]);
+                self.annotate_local(slot, param_type);
+            }
+        }
+        self.tail = true;
+        self.compile_statements(&body.statements)?;
+        self.emit(Opcode::Return, &[]);
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+
+        let scope = self.scopes.pop().expect("function scope was pushed");
+        self.position = position;
+        let function = CompiledFunction {
+            name: name.map(str::to_owned),
+            parameters: lit.parameters.iter().map(|p| p.value.clone()).collect(),
+            instructions: scope.instructions,
+            num_locals: scope.num_slots,
+            upvalues: scope.upvalues,
+            positions: scope.positions,
+            files: scope.files,
+        };
+        let index = self.add_constant(Constant::Function(Rc::new(function)));
+        self.emit(Opcode::Closure, &[index]);
+
+        Ok(())
+    }
+
+    fn compile_match_expression(&mut self, exp: &MatchExpression) -> CompileResult {
+        self.begin_scope();
+        self.compile_expression(expect_expression(&exp.subject)?)?;
+        let subject = self.declare_local("@subject");
+        self.emit(Opcode::SetLocal, &[subject]);
+
+        let mut end_jumps = vec![];
+        for arm in &exp.arms {
+            let mut fail_jumps = vec![];
+            self.begin_scope();
+            let first_slot = self.scope().next_slot;
+            self.compile_pattern(&arm.pattern, subject, &mut fail_jumps)?;
+            if let Some(guard) = &arm.guard {
+                self.compile_expression(guard.as_ref())?;
+                fail_jumps.push(self.emit(Opcode::JumpIfFalse, &[UNPATCHED]));
+            }
+            self.compile_statements(&arm.body.statements)?;
+            let captured = self.end_scope();
+            end_jumps.push(self.emit(Opcode::Jump, &[UNPATCHED]));
+
+            for jump in fail_jumps {
+                self.patch_jump(jump);
+            }
+            if captured {
+                self.emit(Opcode::CloseUpvalues, &[first_slot]);
+            }
+        }
+        // the arms moved the position to their bodies
+        self.position = exp.position();
+        self.emit(Opcode::GetLocal, &[subject]);
+        self.emit(Opcode::NoMatch, &[]);
+
+        for jump in end_jumps {
+            self.patch_jump(jump);
+        }
+        self.end_scope();
+
+        Ok(())
+    }
+
+    /// Tests the value in `slot` against `pattern`, binding its names to
+    /// locals. Emits jumps to be patched to the next arm when it fails.
+    fn compile_pattern(
+        &mut self,
+        pattern: &Pattern,
+        slot: usize,
+        fail_jumps: &mut Vec<usize>,
+    ) -> CompileResult {
+        match pattern {
+            Pattern::Wildcard(_) => (),
+            Pattern::Binding(ident) => {
+                self.emit(Opcode::GetLocal, &[slot]);
+                let binding = self.declare_local(&ident.value);
+                self.emit(Opcode::SetLocal, &[binding]);
+            }
+            Pattern::Literal { value, .. } => {
+                self.emit(Opcode::GetLocal, &[slot]);
+                self.compile_expression(value.as_ref())?;
+                self.emit(Opcode::Equal, &[]);
+                fail_jumps.push(self.emit(Opcode::JumpIfFalse, &[UNPATCHED]));
+            }
+            Pattern::EnumVariant {
+                enum_name,
+                variant,
+                payload,
+            } => {
+                self.emit(Opcode::GetLocal, &[slot]);
+                self.load(&enum_name.value);
+                let names = self.add_constant(Constant::Names(vec![
+                    enum_name.value.clone(),
+                    variant.value.clone(),
+                    pattern.string(),
+                ]));
+                let payload_len = payload.as_ref().map_or(NO_PAYLOAD, Vec::len);
+                self.emit(Opcode::MatchVariant, &[names, payload_len]);
+                fail_jumps.push(self.emit(Opcode::JumpIfFalse, &[UNPATCHED]));
+                for (i, pattern) in payload.iter().flatten().enumerate() {
+                    self.emit(Opcode::GetLocal, &[slot]);
+                    self.emit(Opcode::VariantField, &[i]);
+                    self.compile_subpattern(pattern, fail_jumps)?;
+                }
+            }
+            Pattern::Array { elements, rest, .. } => {
+                self.emit(Opcode::GetLocal, &[slot]);
+                self.emit(
+                    Opcode::MatchArray,
+                    &[elements.len(), rest.is_some() as usize],
+                );
+                fail_jumps.push(self.emit(Opcode::JumpIfFalse, &[UNPATCHED]));
+                for (i, pattern) in elements.iter().enumerate() {
+                    self.emit(Opcode::GetLocal, &[slot]);
+                    let index = self.add_constant(Constant::Int(i as i64));
+                    self.emit(Opcode::Constant, &[index]);
+                    self.emit(Opcode::Index, &[]);
+                    self.compile_subpattern(pattern, fail_jumps)?;
+                }
+                if let Some(rest) = rest {
+                    self.emit(Opcode::GetLocal, &[slot]);
+                    self.emit(Opcode::ArrayRest, &[elements.len()]);
+                    self.compile_subpattern(rest, fail_jumps)?;
+                }
+            }
+            Pattern::Struct { name, fields } => {
+                self.emit(Opcode::GetLocal, &[slot]);
+                self.load(&name.value);
+                let struct_name = self.add_constant(Constant::Str(name.value.clone()));
+                self.emit(Opcode::MatchStruct, &[struct_name]);
+                fail_jumps.push(self.emit(Opcode::JumpIfFalse, &[UNPATCHED]));
+                for (field, pattern) in fields {
+                    self.emit(Opcode::GetLocal, &[slot]);
+                    let field = self.add_constant(Constant::Str(field.value.clone()));
+                    self.emit(Opcode::StructField, &[field]);
+                    self.compile_subpattern(pattern, fail_jumps)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stores the value on top of the stack in a hidden local and matches it.
+    fn compile_subpattern(
+        &mut self,
+        pattern: &Pattern,
+        fail_jumps: &mut Vec<usize>,
+    ) -> CompileResult {
+        let slot = self.declare_fresh_local("@element");
+        self.emit(Opcode::SetLocal, &[slot]);
+        grow_stack(|| self.compile_pattern(pattern, slot, fail_jumps))
+    }
+}
+
+// symbol resolution
+impl Compiler {
+    fn scope(&mut self) -> &mut CompilationScope {
+        self.scopes.last_mut().expect("there is always a scope")
+    }
+
+    fn begin_scope(&mut self) {
+        self.scope().depth += 1;
+    }
+
+    /// Leaves a block scope, freeing its slots. Returns whether a closure
+    /// captured one of them, in which case `OpCloseUpvalues` has been emitted.
+    fn end_scope(&mut self) -> bool {
+        let scope = self.scope();
+        scope.depth -= 1;
+        let depth = scope.depth;
+        let first = scope.locals.iter().position(|l| l.depth > depth);
+        let Some(first) = first else {
+            return false;
+        };
+
+        let locals = scope.locals.split_off(first);
+        let first_slot = locals[0].slot;
+        scope.next_slot = first_slot;
+        let captured = locals.iter().any(|l| l.captured);
+        if captured {
+            self.emit(Opcode::CloseUpvalues, &[first_slot]);
+        }
+
+        captured
+    }
+
+    /// Declares a local in the current block, reusing the slot of a binding
+    /// with the same name in the same block, as `let` rebinding does.
+    fn declare_local(&mut self, name: &str) -> usize {
+        let scope = self.scope();
+        let existing = scope
+            .locals
+            .iter()
+            .rev()
+            .take_while(|l| l.depth == scope.depth)
+            .find(|l| l.name == name);
+        match existing {
+            Some(local) => local.slot,
+            None => self.declare_fresh_local(name),
+        }
+    }
+
+    fn declare_fresh_local(&mut self, name: &str) -> usize {
+        let scope = self.scope();
+        let slot = scope.next_slot;
+        scope.locals.push(Local {
+            name: name.to_owned(),
+            slot,
+            depth: scope.depth,
+            captured: false,
+            annotation: None,
+        });
+        scope.next_slot += 1;
+        scope.num_slots = scope.num_slots.max(scope.next_slot);
+
+        slot
+    }
+
+    fn annotate_local(&mut self, slot: usize, annotation: &Option<TypeExpr>) {
+        let local = self
+            .scope()
+            .locals
+            .iter_mut()
+            .rev()
+            .find(|l| l.slot == slot);
+        if let Some(local) = local {
+            local.annotation = annotation.clone();
+        }
+    }
+
+    fn annotate_global(&mut self, index: usize, annotation: &Option<TypeExpr>) {
+        match annotation {
+            Some(annotation) => self.global_annotations.insert(index, annotation.clone()),
+            None => self.global_annotations.remove(&index),
+        };
+    }
+
+    /// The annotation of the binding `name` resolves to, if it has one.
+    fn annotation(&self, name: &str) -> Option<TypeExpr> {
+        for scope in self.scopes.iter().rev() {
+            if let Some(local) = scope.locals.iter().rev().find(|l| l.name == name) {
+                return local.annotation.clone();
+            }
+        }
+        let index = self
+            .global_indexes
+            .get(&format!("{}{}", self.prefix, name))?;
+        self.global_annotations.get(index).cloned()
+    }
+
+    fn global_index(&mut self, name: &str) -> usize {
+        self.global_slot(format!("{}{}", self.prefix, name))
+    }
+
+    fn global_slot(&mut self, name: String) -> usize {
+        if let Some(index) = self.global_indexes.get(&name) {
+            return *index;
+        }
+        let index = self.globals.len();
+        self.globals.push(name.clone());
+        self.global_indexes.insert(name, index);
+
+        index
+    }
+
+    /// Resolves a name to a local of the current function, a variable
+    /// captured from an enclosing one, or a global. Names that are never
+    /// declared become globals that stay unset, so using them is a runtime
+    /// error just like in the interpreter, unless they name a builtin.
+    fn resolve(&mut self, name: &str) -> Symbol {
+        let current = self.scopes.len() - 1;
+        if let Some(local) = self.resolve_local(current, name) {
+            return Symbol::Local(self.scopes[current].locals[local].slot);
+        }
+        if let Some(index) = self.resolve_upvalue(current, name) {
+            return Symbol::Upvalue(index);
+        }
+
+        Symbol::Global(self.global_index(name))
+    }
+
+    fn resolve_local(&self, scope: usize, name: &str) -> Option<usize> {
+        self.scopes[scope]
+            .locals
+            .iter()
+            .rposition(|l| l.name == name)
+    }
+
+    fn resolve_upvalue(&mut self, scope: usize, name: &str) -> Option<usize> {
+        if scope == 0 {
+            return None;
+        }
+        let upvalue = match self.resolve_local(scope - 1, name) {
+            Some(local) => {
+                let local = &mut self.scopes[scope - 1].locals[local];
+                local.captured = true;
+                UpvalueRef {
+                    is_local: true,
+                    index: local.slot,
+                }
+            }
+            None => UpvalueRef {
+                is_local: false,
+                index: self.resolve_upvalue(scope - 1, name)?,
+            },
+        };
+
+        let upvalues = &mut self.scopes[scope].upvalues;
+        if let Some(index) = upvalues.iter().position(|u| *u == upvalue) {
+            return Some(index);
+        }
+        upvalues.push(upvalue);
+
+        Some(upvalues.len() - 1)
+    }
+
+    fn load(&mut self, name: &str) {
+        let symbol = self.resolve(name);
+        self.load_symbol(&symbol);
+    }
+
+    fn load_symbol(&mut self, symbol: &Symbol) {
+        match symbol {
+            Symbol::Global(index) => self.emit(Opcode::GetGlobal, &[*index]),
+            Symbol::Local(slot) => self.emit(Opcode::GetLocal, &[*slot]),
+            Symbol::Upvalue(index) => self.emit(Opcode::GetUpvalue, &[*index]),
+        };
+    }
+}
+
+// emitting
+impl Compiler {
+    fn add_constant(&mut self, constant: Constant) -> usize {
+        self.constants.push(constant);
+        self.constants.len() - 1
+    }
+
+    fn emit_type(&mut self, declaration: Value) {
+        let index = self.add_constant(Constant::Type(declaration));
+        self.emit(Opcode::NewType, &[index]);
+    }
+
+    /// Appends an instruction, returning its offset.
+    fn emit(&mut self, op: Opcode, operands: &[usize]) -> usize {
+        self.check_operands(op, operands);
+        let position = self.position;
+        let file = &self.file;
+        let scope = self.scopes.last_mut().expect("there is always a scope");
+        let offset = scope.instructions.len();
+        if scope.positions.last().map(|&(_, p)| p) != Some(position) {
+            scope.positions.push((offset, position));
+        }
+        if scope.files.last().map(|(_, f)| f) != Some(file) {
+            scope.files.push((offset, file.clone()));
+        }
+        scope.instructions.extend(make(op, operands));
+
+        offset
+    }
+
+    fn current_offset(&mut self) -> usize {
+        self.scope().instructions.len()
+    }
+
+    fn patch_jump(&mut self, offset: usize) {
+        let target = self.current_offset();
+        self.change_operand(offset, 0, target);
+    }
+
+    /// Records an error for an operand that would be truncated, such as the
+    /// index of a constant past the 65535th or a jump past 64 KB of code.
+    fn check_operands(&mut self, op: Opcode, operands: &[usize]) {
+        if let Some((operand, max)) = oversized_operand(op, operands) {
+            self.error.get_or_insert_with(|| {
+                CompileError::new(format!(
+                    "program too large to compile: {} operand {} exceeds {}",
+                    op, operand, max
+                ))
+            });
+        }
+    }
+
+    /// Rewrites operand `n` of the instruction at `offset`.
+    fn change_operand(&mut self, offset: usize, n: usize, operand: usize) {
+        let instructions = &mut self.scope().instructions;
+        let op = Opcode::from_byte(instructions[offset]).expect("patching a valid instruction");
+        let (mut operands, _) = read_operands(&op.definition(), &instructions[offset + 1..]);
+        operands[n] = operand;
+        self.check_operands(op, &operands);
+
+        let instructions = &mut self.scope().instructions;
+        let ins = make(op, &operands);
+        instructions[offset..offset + ins.len()].copy_from_slice(&ins);
+    }
+}
+
+fn expect_expression(exp: &Option<Box<dyn Expression>>) -> Result<&dyn Expression, CompileError> {
+    exp.as_deref()
+        .ok_or_else(|| CompileError::new("missing expression"))
+}
+
+fn binary_opcode(operator: &str) -> Result<Opcode, CompileError> {
+    match operator {
+        "+" => Ok(Opcode::Add),
+        "-" => Ok(Opcode::Sub),
+        "*" => Ok(Opcode::Mul),
+        "/" => Ok(Opcode::Div),
+        "%" => Ok(Opcode::Mod),
+        "==" => Ok(Opcode::Equal),
+        "!=" => Ok(Opcode::NotEqual),
+        "<" => Ok(Opcode::Less),
+        ">" => Ok(Opcode::Greater),
+        "<=" => Ok(Opcode::LessEqual),
+        ">=" => Ok(Opcode::GreaterEqual),
+        _ => Err(CompileError::new(format!("unknown operator: {}", operator))),
+    }
+}
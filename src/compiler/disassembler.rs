@@ -0,0 +1,153 @@
+use std::fmt::Write;
+
+use super::{
+    bytecode::{Bytecode, CompiledFunction, Constant},
+    code::{read_operands, Opcode},
+};
+
+/// Renders compiled bytecode as text: one line per instruction with its
+/// offset, source line, opcode and operands, where operands referring to the
+/// constant pool or to globals are followed by the value they name.
+///
+/// ```text
+/// == <main> ==
+/// 0000    1 OpConstant 0 (1)
+/// 0003    | OpSetGlobal 0 (x)
+/// ```
+pub struct Disassembler<'a> {
+    bytecode: &'a Bytecode,
+}
+
+impl<'a> Disassembler<'a> {
+    pub fn new(bytecode: &'a Bytecode) -> Self {
+        Self { bytecode }
+    }
+
+    /// Disassembles the top level code followed by every function in the
+    /// constant pool, in pool order.
+    pub fn disassemble(&self) -> String {
+        let mut out = self.function(&self.bytecode.main);
+        for constant in &self.bytecode.constants {
+            if let Constant::Function(function) = constant {
+                out.push('\n');
+                out.push_str(&self.function(function));
+            }
+        }
+
+        out
+    }
+
+    pub fn function(&self, function: &CompiledFunction) -> String {
+        let mut out = String::new();
+        if std::ptr::eq(function, self.bytecode.main.as_ref()) {
+            out.push_str("== <main> ==\n");
+        } else {
+            let _ = writeln!(
+                out,
+                "== {}({}) ==",
+                function_name(function),
+                function.parameters.join(", ")
+            );
+        }
+        if !function.upvalues.is_empty() {
+            let captures: Vec<String> = function
+                .upvalues
+                .iter()
+                .map(|upvalue| match upvalue.is_local {
+                    true => format!("local {}", upvalue.index),
+                    false => format!("upvalue {}", upvalue.index),
+                })
+                .collect();
+            let _ = writeln!(out, "captures: {}", captures.join(", "));
+        }
+
+        let mut offset = 0;
+        let mut previous_line = None;
+        while offset < function.instructions.len() {
+            let line = function.line_at(offset);
+            let line_column = match previous_line == Some(line) {
+                true => "|".to_owned(),
+                false => line.to_string(),
+            };
+            previous_line = Some(line);
+
+            let (instruction, width) = self.instruction(function, offset);
+            let _ = writeln!(out, "{:04} {:>4} {}", offset, line_column, instruction);
+            offset += width;
+        }
+
+        out
+    }
+
+    /// Renders the instruction at `offset`, returning it with its width in
+    /// bytes.
+    pub fn instruction(&self, function: &CompiledFunction, offset: usize) -> (String, usize) {
+        let ins = &function.instructions;
+        let Some(op) = Opcode::from_byte(ins[offset]) else {
+            return (format!("ERROR: unknown opcode {}", ins[offset]), 1);
+        };
+
+        let def = op.definition();
+        let (operands, read) = read_operands(&def, &ins[offset + 1..]);
+        let mut out = def.name.to_owned();
+        for (n, operand) in operands.iter().enumerate() {
+            let _ = write!(out, " {}", operand);
+            if let Some(annotation) = self.annotation(op, n, *operand) {
+                let _ = write!(out, " ({})", annotation);
+            }
+        }
+
+        (out, 1 + read)
+    }
+
+    fn annotation(&self, op: Opcode, n: usize, operand: usize) -> Option<String> {
+        match (op, n) {
+            (
+                Opcode::Constant
+                | Opcode::GetMember
+                | Opcode::SetMember
+                | Opcode::Closure
+                | Opcode::NewType
+                | Opcode::MakeStruct
+                | Opcode::Impl
+                | Opcode::Method
+                | Opcode::MatchVariant
+                | Opcode::MatchStruct
+                | Opcode::StructField
+                | Opcode::Module
+                | Opcode::StdModule,
+                0,
+            )
+            | (Opcode::MakeStruct | Opcode::Module, 1) => {
+                self.bytecode.constants.get(operand).map(constant_string)
+            }
+            (
+                Opcode::GetGlobal
+                | Opcode::GetGlobalOpt
+                | Opcode::SetGlobal
+                | Opcode::AssignGlobal
+                | Opcode::CheckGlobal,
+                0,
+            ) => self.bytecode.globals.get(operand).cloned(),
+            _ => None,
+        }
+    }
+}
+
+fn function_name(function: &CompiledFunction) -> &str {
+    function.name.as_deref().unwrap_or("<anonymous fn>")
+}
+
+fn constant_string(constant: &Constant) -> String {
+    match constant {
+        Constant::Int(i) => i.to_string(),
+        Constant::Float(x) => format!("{:?}", x),
+        Constant::Str(s) => format!("{:?}", s),
+        Constant::Function(function) => match &function.name {
+            Some(name) => format!("<fn {}>", name),
+            None => "<anonymous fn>".to_owned(),
+        },
+        Constant::Type(value) => value.to_string(),
+        Constant::Names(names) => format!("[{}]", names.join(", ")),
+    }
+}
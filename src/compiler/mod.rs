@@ -0,0 +1,9 @@
+pub mod bytecode;
+pub mod cache;
+pub mod code;
+#[allow(clippy::module_inception)]
+pub mod compiler;
+pub mod disassembler;
+
+#[cfg(test)]
+mod test;
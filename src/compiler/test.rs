@@ -0,0 +1,514 @@
+use crate::{
+    compiler::{
+        bytecode::{Bytecode, Constant},
+        cache::{self, FORMAT_VERSION},
+        code::{make, read_operands, Opcode},
+        compiler::Compiler,
+        disassembler::Disassembler,
+    },
+    evaluator::value::Value,
+    lexer::lexer::Lexer,
+    parser::parser::Parser,
+    test_utils::{parse, run_both},
+    vm::vm::Vm,
+};
+
+#[test]
+fn test_make() {
+    let tests = [
+        (
+            Opcode::Constant,
+            vec![65534],
+            vec![Opcode::Constant as u8, 255, 254],
+        ),
+        (Opcode::Add, vec![], vec![Opcode::Add as u8]),
+        (Opcode::Call, vec![3], vec![Opcode::Call as u8, 3]),
+        (
+            Opcode::MatchVariant,
+            vec![258, 255],
+            vec![Opcode::MatchVariant as u8, 1, 2, 255],
+        ),
+    ];
+
+    for (op, operands, expected) in tests {
+        let instruction = make(op, &operands);
+        assert_eq!(instruction, expected, "wrong encoding for {}", op);
+
+        let (read, n) = read_operands(&op.definition(), &instruction[1..]);
+        assert_eq!(n, instruction.len() - 1, "wrong operand width for {}", op);
+        assert_eq!(read, operands, "wrong operands for {}", op);
+        assert_eq!(Opcode::from_byte(instruction[0]), Some(op));
+    }
+}
+
+#[test]
+fn test_compile_expressions() {
+    let tests = [
+        (
+            "1 + 2",
+            vec![
+                make(Opcode::Constant, &[0]),
+                make(Opcode::Constant, &[1]),
+                make(Opcode::Add, &[]),
+                make(Opcode::Return, &[]),
+            ],
+        ),
+        (
+            "1; -2",
+            vec![
+                make(Opcode::Constant, &[0]),
+                make(Opcode::Pop, &[]),
+                make(Opcode::Constant, &[1]),
+                make(Opcode::Minus, &[]),
+                make(Opcode::Return, &[]),
+            ],
+        ),
+        (
+            "let x = 1; x",
+            vec![
+                make(Opcode::Constant, &[0]),
+                make(Opcode::SetGlobal, &[0]),
+                make(Opcode::Null, &[]),
+                make(Opcode::Pop, &[]),
+                make(Opcode::GetGlobal, &[0]),
+                make(Opcode::Return, &[]),
+            ],
+        ),
+        (
+            "if true { 10 } else { 20 }",
+            vec![
+                // 0000
+                make(Opcode::True, &[]),
+                // 0001
+                make(Opcode::JumpIfFalse, &[10]),
+                // 0004
+                make(Opcode::Constant, &[0]),
+                // 0007
+                make(Opcode::Jump, &[13]),
+                // 0010
+                make(Opcode::Constant, &[1]),
+                // 0013
+                make(Opcode::Return, &[]),
+            ],
+        ),
+        (
+            "[1, 2][0]",
+            vec![
+                make(Opcode::Constant, &[0]),
+                make(Opcode::Constant, &[1]),
+                make(Opcode::Array, &[2]),
+                make(Opcode::Constant, &[2]),
+                make(Opcode::Index, &[]),
+                make(Opcode::Return, &[]),
+            ],
+        ),
+        (
+            "try { 1 } catch (e) { e }",
+            vec![
+                // 0000
+                make(Opcode::Try, &[10]),
+                // 0003
+                make(Opcode::Constant, &[0]),
+                // 0006
+                make(Opcode::EndTry, &[]),
+                // 0007
+                make(Opcode::Jump, &[16]),
+                // 0010: the error is bound to a local
+                make(Opcode::SetLocal, &[0]),
+                // 0013
+                make(Opcode::GetLocal, &[0]),
+                // 0016
+                make(Opcode::Return, &[]),
+            ],
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let bytecode = test_compile(input);
+        assert_eq!(
+            bytecode.main.instructions,
+            expected.concat(),
+            "wrong instructions for {}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_compile_closures() {
+    let bytecode = test_compile("fn(a) { fn(b) { a + b } }");
+
+    let functions: Vec<_> = bytecode
+        .constants
+        .iter()
+        .filter_map(|constant| match constant {
+            Constant::Function(function) => Some(function),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(functions.len(), 2);
+
+    let (inner, outer) = (functions[0], functions[1]);
+    assert_eq!(inner.parameters, vec!["b"]);
+    assert_eq!(inner.upvalues.len(), 1);
+    assert!(inner.upvalues[0].is_local);
+    assert_eq!(inner.upvalues[0].index, 0);
+    assert_eq!(
+        inner.instructions,
+        [
+            make(Opcode::GetUpvalue, &[0]),
+            make(Opcode::GetLocal, &[0]),
+            make(Opcode::Add, &[]),
+            make(Opcode::Return, &[]),
+        ]
+        .concat()
+    );
+
+    assert_eq!(outer.num_locals, 1);
+    assert!(outer.upvalues.is_empty());
+    assert_eq!(
+        outer.instructions,
+        [make(Opcode::Closure, &[0]), make(Opcode::Return, &[])].concat()
+    );
+}
+
+#[test]
+fn test_compile_tail_calls() {
+    let tests = [
+        (
+            "fn(f) { f() }",
+            vec![
+                make(Opcode::GetLocal, &[0]),
+                make(Opcode::TailCall, &[0]),
+                make(Opcode::Return, &[]),
+            ],
+        ),
+        (
+            "fn(f) { f() + 1 }",
+            vec![
+                make(Opcode::GetLocal, &[0]),
+                make(Opcode::Call, &[0]),
+                make(Opcode::Constant, &[0]),
+                make(Opcode::Add, &[]),
+                make(Opcode::Return, &[]),
+            ],
+        ),
+        (
+            "fn(f) { if (f) { f() } else { 1 } }",
+            vec![
+                // 0000
+                make(Opcode::GetLocal, &[0]),
+                // 0003
+                make(Opcode::JumpIfFalse, &[14]),
+                // 0006
+                make(Opcode::GetLocal, &[0]),
+                // 0009
+                make(Opcode::TailCall, &[0]),
+                // 0011
+                make(Opcode::Jump, &[17]),
+                // 0014
+                make(Opcode::Constant, &[0]),
+                // 0017
+                make(Opcode::Return, &[]),
+            ],
+        ),
+        (
+            "fn(f) { try { return f(); } catch { return f(); } }",
+            vec![
+                // 0000
+                make(Opcode::Try, &[13]),
+                // 0003
+                make(Opcode::GetLocal, &[0]),
+                // 0006: errors of the call are caught
+                make(Opcode::Call, &[0]),
+                // 0008
+                make(Opcode::Return, &[]),
+                // 0009
+                make(Opcode::EndTry, &[]),
+                // 0010
+                make(Opcode::Jump, &[20]),
+                // 0013
+                make(Opcode::Pop, &[]),
+                // 0014: the handler was left when the error was caught
+                make(Opcode::GetLocal, &[0]),
+                // 0017
+                make(Opcode::TailCall, &[0]),
+                // 0019
+                make(Opcode::Return, &[]),
+                // 0020
+                make(Opcode::Return, &[]),
+            ],
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let bytecode = test_compile(input);
+        let Some(Constant::Function(function)) = bytecode.constants.last() else {
+            panic!("no function compiled for {}", input);
+        };
+        assert_eq!(
+            function.instructions,
+            expected.concat(),
+            "wrong instructions for {}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_disassemble() {
+    let input = "let add = fn(a, b) {
+    a + b
+};
+let n = 0;
+n += add(1, 2);
+let counter = fn() { fn() { n } };";
+    let expected = "
+        == <main> ==
+        0000    1 OpClosure 0 (<fn add>)
+        0003    | OpSetGlobal 0 (add)
+        0006    | OpNull
+        0007    | OpPop
+        0008    4 OpConstant 1 (0)
+        0011    | OpSetGlobal 1 (n)
+        0014    | OpNull
+        0015    | OpPop
+        0016    5 OpCheckGlobal 1 (n)
+        0019    | OpGetGlobal 1 (n)
+        0022    | OpGetGlobal 0 (add)
+        0025    | OpConstant 2 (1)
+        0028    | OpConstant 3 (2)
+        0031    | OpCall 2
+        0033    | OpAdd
+        0034    | OpDup
+        0035    | OpAssignGlobal 1 (n)
+        0038    | OpPop
+        0039    6 OpClosure 5 (<fn counter>)
+        0042    | OpSetGlobal 2 (counter)
+        0045    | OpNull
+        0046    | OpReturn
+
+        == add(a, b) ==
+        0000    2 OpGetLocal 0
+        0003    | OpGetLocal 1
+        0006    | OpAdd
+        0007    | OpReturn
+
+        == <anonymous fn>() ==
+        0000    6 OpGetGlobal 1 (n)
+        0003    | OpReturn
+
+        == counter() ==
+        0000    6 OpClosure 4 (<anonymous fn>)
+        0003    | OpReturn
+";
+
+    let bytecode = test_compile(input);
+    let disassembly = Disassembler::new(&bytecode).disassemble();
+    assert_eq!(
+        disassembly,
+        unindent(expected),
+        "wrong disassembly. got=\n{}",
+        disassembly
+    );
+}
+
+#[test]
+fn test_line_table() {
+    let bytecode = test_compile("let x = 1;\n\nlet y = x +\n  2;\ny");
+
+    let main = &bytecode.main;
+    let lines: Vec<usize> = (0..main.instructions.len())
+        .map(|offset| main.line_at(offset))
+        .collect();
+    // one entry per byte: `let x` on line 1, `let y` on line 3 except for
+    // the `2` continuing it on line 4, then `y` and the return on line 5
+    let mut expected = vec![1; 8];
+    expected.extend([3; 3]);
+    expected.extend([4; 3]);
+    expected.extend([3; 6]);
+    expected.extend([5; 4]);
+    assert_eq!(lines, expected);
+
+    // columns point at the expression each instruction belongs to
+    assert_eq!(main.position_at(8), (3, 9), "position of `x`");
+    assert_eq!(main.position_at(11), (4, 3), "position of `2`");
+    assert_eq!(main.position_at(14), (3, 11), "position of `+`");
+}
+
+#[test]
+fn test_cache_round_trip() {
+    let input = "
+        let Option = enum(some(int), none);
+        let Shape = interface { area(): int };
+        let Rect = struct { w, h };
+        impl Rect { fn area(self) { self.w * self.h } }
+        let s: Shape = Rect { w: 2, h: 3 };
+        let counter = fn() { let n = 0; fn() { n += 1 } };
+        let next = counter();
+        let ratio = 0.5;
+        next();
+        match Option.some(s.area()) { Option.some(v) => v + next(), _ => 0 }";
+    let bytecode = test_compile(input);
+    let bytes = cache::encode(&bytecode, cache::source_hash(input));
+
+    let module = match cache::decode(&bytes) {
+        Ok(module) => module,
+        Err(err) => panic!("cache did not load: {}", err),
+    };
+    assert_eq!(module.source_hash, cache::source_hash(input));
+    assert_eq!(
+        Disassembler::new(&module.bytecode).disassemble(),
+        Disassembler::new(&bytecode).disassemble()
+    );
+    match Vm::new(module.bytecode).run() {
+        Ok(Value::Int(8)) => {}
+        Ok(value) => panic!("wrong result from cached bytecode. got={}", value),
+        Err(err) => panic!("runtime error from cached bytecode: {}", err),
+    }
+}
+
+#[test]
+fn test_cache_rejects_invalid_files() {
+    let bytecode = test_compile("let x = 1; if x > 0 { x } else { 0 }");
+    let bytes = cache::encode(&bytecode, 0);
+    // the top level code is the first function: after the header, the
+    // import count, the one global `x`, the function count, the name flag,
+    // and the parameter, local and upvalue counts, its instruction length
+    // and instructions
+    let instructions = 4 + 2 + 8 + 4 + (4 + 4 + 1) + 4 + 1 + 4 + 4 + 4 + 4;
+    assert_eq!(bytes[instructions - 4..instructions], [0, 0, 0, 28]);
+
+    let corrupt = |offset: usize, byte: u8| {
+        let mut bytes = bytes.clone();
+        bytes[offset] = byte;
+        bytes
+    };
+    let mut newer = bytes.clone();
+    newer[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_be_bytes());
+
+    let tests = [
+        (
+            b"#!/bin/sh".to_vec(),
+            "not a prolang bytecode file".to_owned(),
+        ),
+        (
+            newer,
+            format!(
+                "unsupported bytecode version {}, expected {}",
+                FORMAT_VERSION + 1,
+                FORMAT_VERSION
+            ),
+        ),
+        (
+            bytes[..bytes.len() - 1].to_vec(),
+            "unexpected end of file".to_owned(),
+        ),
+        (
+            [bytes.as_slice(), &[0]].concat(),
+            "trailing bytes after the constant pool".to_owned(),
+        ),
+        (
+            corrupt(instructions, 250),
+            "invalid bytecode in <main> at 0000: unknown opcode 250".to_owned(),
+        ),
+        (
+            corrupt(instructions + 2, 9),
+            "invalid bytecode in <main> at 0000: OpConstant refers to missing constant 9"
+                .to_owned(),
+        ),
+        (
+            corrupt(instructions + 5, 1),
+            "invalid bytecode in <main> at 0003: OpSetGlobal uses missing global 1".to_owned(),
+        ),
+        (
+            // the operand of the OpJumpIfFalse after `x > 0`
+            corrupt(instructions + 17, 5),
+            "invalid bytecode in <main> at 0015: jump to 0005 is not an instruction".to_owned(),
+        ),
+        (
+            // the OpConstant setting `x` becomes an OpSetGlobal with nothing to set
+            corrupt(instructions, Opcode::SetGlobal as u8),
+            "invalid bytecode in <main> at 0000: OpSetGlobal pops 1 values from a stack of 0"
+                .to_owned(),
+        ),
+        (
+            // the OpJump after the consequence lands on the alternative,
+            // which starts without the consequence's value on the stack
+            corrupt(instructions + 23, 24),
+            "invalid bytecode in <main> at 0024: stack depth 1 differs from 0 on another path"
+                .to_owned(),
+        ),
+        (
+            // the local slot count, just before the upvalue count
+            corrupt(instructions - 12, 0xff),
+            "invalid bytecode in <main> at 0000: 4278190080 local slots exceed 65537".to_owned(),
+        ),
+        (
+            // the import count, right after the header
+            corrupt(14, 0xff),
+            "count 4278190080 exceeds the rest of the file".to_owned(),
+        ),
+    ];
+
+    for (bytes, expected) in tests {
+        match cache::decode(&bytes) {
+            Err(err) => assert_eq!(err.message, expected),
+            Ok(_) => panic!("invalid cache loaded, expected error: {}", expected),
+        }
+    }
+}
+
+#[test]
+fn test_operand_limits() {
+    let constants: String = (0..70_000).map(|i| format!("{};\n", i)).collect();
+    // each `1;` is an OpConstant and an OpPop, four bytes, so the jump over
+    // the consequence lands past 64 KB
+    let long_body = format!("if (true) {{ {} 2 }} else {{ 3 }}", "1; ".repeat(17_000));
+    let tests = [
+        (
+            constants,
+            "69999",
+            "program too large to compile: OpConstant operand 65536 exceeds 65535",
+        ),
+        (
+            long_body,
+            "2",
+            "program too large to compile: OpJumpIfFalse operand 68010 exceeds 65535",
+        ),
+    ];
+
+    for (input, interpreted, compiled) in tests {
+        let [(_, interpreter), (_, vm)] = run_both(&parse(&input), None);
+        assert_eq!(interpreter, Ok(interpreted.to_owned()));
+        assert_eq!(vm, Err(compiled.to_owned()));
+    }
+}
+
+//utils
+fn test_compile(input: &str) -> Bytecode {
+    let l = Lexer::new(input.to_string());
+    let mut p = Parser::new(l);
+    let program = p.parse_program().expect("parse_program() return some");
+    assert!(
+        p.errors().is_empty(),
+        "parser has errors for {}: {:?}",
+        input,
+        p.errors()
+    );
+
+    match Compiler::new().compile(&program) {
+        Ok(bytecode) => bytecode,
+        Err(err) => panic!("compile error for {}: {}", input, err),
+    }
+}
+
+/// Strips the leading newline and indentation of an expected disassembly.
+fn unindent(expected: &str) -> String {
+    expected
+        .trim_start_matches('\n')
+        .lines()
+        .map(|line| line.strip_prefix("        ").unwrap_or(line))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
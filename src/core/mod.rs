@@ -1,4 +0,0 @@
-pub mod lexer;
-pub mod parser;
-pub mod runner;
-pub mod utils;
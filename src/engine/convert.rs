@@ -0,0 +1,260 @@
+use std::{
+    any::Any,
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
+
+use crate::evaluator::{
+    builtins::{check_arity, unsupported},
+    host::HostObject,
+    value::{HashKey, RuntimeError, Value},
+};
+
+/// A Rust type a prolang value can be read as, to pass it to a function the
+/// host registered.
+pub trait FromValue: Sized {
+    /// Reads `value`, or returns `None` if it is of another type.
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+/// A Rust type that can be handed to scripts as a prolang value.
+pub trait IntoValue {
+    fn into_value(self) -> Value;
+}
+
+/// What a registered function may return: a value, or a `Result` failing
+/// the call with a runtime error.
+pub trait IntoResult {
+    fn into_result(self) -> Result<Value, RuntimeError>;
+}
+
+impl<T: IntoValue> IntoResult for T {
+    fn into_result(self) -> Result<Value, RuntimeError> {
+        Ok(self.into_value())
+    }
+}
+
+impl<T: IntoValue, E: Into<RuntimeError>> IntoResult for Result<T, E> {
+    fn into_result(self) -> Result<Value, RuntimeError> {
+        self.map(IntoValue::into_value).map_err(Into::into)
+    }
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value) -> Option<Self> {
+        Some(value.clone())
+    }
+}
+
+impl IntoValue for Value {
+    fn into_value(self) -> Value {
+        self
+    }
+}
+
+impl IntoValue for () {
+    fn into_value(self) -> Value {
+        Value::Null
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+}
+
+impl IntoValue for i64 {
+    fn into_value(self) -> Value {
+        Value::Int(self)
+    }
+}
+
+/// Integers read as floats too, the way arithmetic mixing the two converts
+/// them.
+impl FromValue for f64 {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Float(x) => Some(*x),
+            Value::Int(i) => Some(*i as f64),
+            _ => None,
+        }
+    }
+}
+
+impl IntoValue for f64 {
+    fn into_value(self) -> Value {
+        Value::Float(self)
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
+
+impl IntoValue for bool {
+    fn into_value(self) -> Value {
+        Value::Bool(self)
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Str(s) => Some(s.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl IntoValue for String {
+    fn into_value(self) -> Value {
+        Value::Str(self)
+    }
+}
+
+impl IntoValue for &str {
+    fn into_value(self) -> Value {
+        Value::Str(self.to_owned())
+    }
+}
+
+/// `null` reads as `None`, and `None` is handed over as `null`.
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Null => Some(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
+
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self) -> Value {
+        self.map_or(Value::Null, IntoValue::into_value)
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Array(elements) => elements.borrow().iter().map(T::from_value).collect(),
+            _ => None,
+        }
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value(self) -> Value {
+        Value::array(self.into_iter().map(IntoValue::into_value).collect())
+    }
+}
+
+/// Reads the pairs of a hash whose keys are all strings.
+fn string_pairs<T: FromValue>(value: &Value) -> Option<Vec<(String, T)>> {
+    match value {
+        Value::Hash(pairs) => pairs
+            .borrow()
+            .iter()
+            .map(|(key, value)| match key {
+                HashKey::Str(key) => Some((key.clone(), T::from_value(value)?)),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+fn hash_of<T: IntoValue>(pairs: impl Iterator<Item = (String, T)>) -> Value {
+    Value::hash(
+        pairs
+            .map(|(key, value)| (HashKey::Str(key), value.into_value()))
+            .collect(),
+    )
+}
+
+impl<T: FromValue> FromValue for BTreeMap<String, T> {
+    fn from_value(value: &Value) -> Option<Self> {
+        string_pairs(value).map(|pairs| pairs.into_iter().collect())
+    }
+}
+
+impl<T: IntoValue> IntoValue for BTreeMap<String, T> {
+    fn into_value(self) -> Value {
+        hash_of(self.into_iter())
+    }
+}
+
+impl<T: FromValue> FromValue for HashMap<String, T> {
+    fn from_value(value: &Value) -> Option<Self> {
+        string_pairs(value).map(|pairs| pairs.into_iter().collect())
+    }
+}
+
+impl<T: IntoValue> IntoValue for HashMap<String, T> {
+    fn into_value(self) -> Value {
+        hash_of(self.into_iter())
+    }
+}
+
+/// Reads a host object back as the Rust type it was made from.
+impl<T: HostObject> FromValue for Rc<T> {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Host(object) => (Rc::clone(object) as Rc<dyn Any>).downcast().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl<T: HostObject> IntoValue for Rc<T> {
+    fn into_value(self) -> Value {
+        Value::Host(self)
+    }
+}
+
+/// A Rust function or closure the host can register under a name, taking
+/// `Args` converted from the values it is called with.
+pub trait NativeFn<Args> {
+    /// Calls the function as the builtin `name`, failing like builtins do
+    /// when the arguments do not fit its parameters.
+    fn call(&self, name: &str, args: &[Value]) -> Result<Value, RuntimeError>;
+}
+
+macro_rules! impl_native_fn {
+    ($count:expr $(, $arg:ident)*) => {
+        impl<F, R, $($arg),*> NativeFn<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> R,
+            R: IntoResult,
+            $($arg: FromValue,)*
+        {
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn call(&self, name: &str, args: &[Value]) -> Result<Value, RuntimeError> {
+                check_arity(name, args, $count)?;
+                let mut args = args.iter();
+                $(
+                    let arg = args.next().expect("the arity was checked");
+                    let $arg = $arg::from_value(arg).ok_or_else(|| unsupported(name, arg))?;
+                )*
+                self($($arg),*).into_result()
+            }
+        }
+    };
+}
+
+impl_native_fn!(0);
+impl_native_fn!(1, A);
+impl_native_fn!(2, A, B);
+impl_native_fn!(3, A, B, C);
+impl_native_fn!(4, A, B, C, D);
+impl_native_fn!(5, A, B, C, D, E);
+impl_native_fn!(6, A, B, C, D, E, G);
@@ -0,0 +1,272 @@
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use super::convert::{FromValue, NativeFn};
+use crate::{
+    evaluator::{
+        builtins,
+        capabilities::Capabilities,
+        environment::{Env, Environment},
+        evaluator::Evaluator,
+        limits::Limits,
+        value::{ErrorKind, NativeFunction, RuntimeError, Value},
+    },
+    lexer::lexer::Lexer,
+    module::loader::Loader,
+    parser::parser::Parser,
+};
+
+/// Why `Engine::eval` failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// The source did not parse, for the reasons listed.
+    Parse(Vec<String>),
+    /// The program stopped with an uncaught error.
+    Runtime(RuntimeError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse(errors) => write!(f, "parser errors: {}", errors.join("; ")),
+            Error::Runtime(err) => write!(f, "runtime error: {}", err.report()),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<RuntimeError> for Error {
+    fn from(err: RuntimeError) -> Self {
+        Error::Runtime(err)
+    }
+}
+
+/// Lets registered functions fail with the error of a program they ran.
+impl From<Error> for RuntimeError {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Parse(errors) => {
+                RuntimeError::new(format!("parser errors: {}", errors.join("; ")))
+            }
+            Error::Runtime(err) => err,
+        }
+    }
+}
+
+thread_local! {
+    /// The evaluator of the engine running a program or a callable, which
+    /// functions passed from it to a registered function are called on.
+    static RUNNING: RefCell<Option<Evaluator>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with `evaluator` as the one callables read from arguments run
+/// on, so they keep the limits and capabilities of the engine.
+fn running<T>(evaluator: Evaluator, f: impl FnOnce() -> T) -> T {
+    let _previous = Restore(RUNNING.replace(Some(evaluator)));
+    f()
+}
+
+/// Puts back the evaluator `running` replaced when dropped, so it is put
+/// back even when a registered function panics.
+struct Restore(Option<Evaluator>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        RUNNING.set(self.0.take());
+    }
+}
+
+/// Whether the host can call `value` through a `Callable`.
+fn is_callable(value: &Value) -> bool {
+    matches!(
+        value,
+        Value::Function(_) | Value::Builtin(_) | Value::Native(_) | Value::BoundMethod(_)
+    )
+}
+
+/// Runs programs on the interpreter for a host. Each program sees what the
+/// ones before it defined, so a host can load a script once and then call
+/// into it.
+///
+/// An engine grants programs no capabilities until `set_capabilities` is
+/// called, so they cannot print, read files or see the environment. Nor may
+/// they import files other than the standard library until `set_loader` is
+/// called, and then only the ones the capabilities grant reading.
+pub struct Engine {
+    evaluator: Evaluator,
+    env: Env,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Self {
+            evaluator: Evaluator::new(),
+            env: Environment::new(),
+        }
+    }
+
+    /// Bounds the resources each program may use.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.evaluator.set_limits(limits);
+    }
+
+    /// Grants builtins the capabilities given, in place of none.
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.evaluator.set_capabilities(capabilities);
+    }
+
+    /// Loads the modules programs import with `loader`.
+    pub fn set_loader(&mut self, loader: Loader) {
+        self.evaluator.set_loader(loader);
+    }
+
+    /// Makes `func` callable from scripts as `name`, like a builtin. Its
+    /// arguments are converted with `FromValue`, and it may return anything
+    /// `IntoValue` converts, or a `Result` failing the call:
+    ///
+    /// ```
+    /// use prolang::{Engine, RuntimeError, Value};
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.register_fn("half", |n: i64| {
+    ///     if n % 2 != 0 {
+    ///         return Err(RuntimeError::new(format!("{} is odd", n)));
+    ///     }
+    ///     Ok(n / 2)
+    /// });
+    /// assert_eq!(engine.eval("half(42)").unwrap(), Value::Int(21));
+    /// assert!(engine.eval("half(1)").is_err());
+    /// ```
+    pub fn register_fn<Args>(&mut self, name: &str, func: impl NativeFn<Args> + 'static) {
+        self.register_native(NativeFunction {
+            name: name.to_owned(),
+            func: Box::new({
+                let name = name.to_owned();
+                move |args| func.call(&name, args)
+            }),
+        });
+    }
+
+    /// Binds `native` under its name, for hosts converting arguments
+    /// themselves.
+    pub(crate) fn register_native(&mut self, native: NativeFunction) {
+        let name = native.name.clone();
+        self.env
+            .borrow_mut()
+            .set(&name, Value::Native(Rc::new(native)));
+    }
+
+    /// Looks up a function a program defined, for the host to call back.
+    /// Builtins and registered functions can be looked up too.
+    pub fn get_fn(&self, name: &str) -> Result<Callable, Error> {
+        let function = self.env.borrow().get(name);
+        let function = function.or_else(|| builtins::lookup(name).map(Value::Builtin));
+        let function = function.ok_or_else(|| {
+            RuntimeError::with_kind(
+                ErrorKind::UnknownIdentifier,
+                format!("identifier not found: {}", name),
+            )
+        })?;
+        if !is_callable(&function) {
+            return Err(RuntimeError::with_kind(
+                ErrorKind::TypeMismatch,
+                format!("not a function: {}", function.type_name()),
+            )
+            .into());
+        }
+        Ok(Callable {
+            name: name.to_owned(),
+            function,
+            evaluator: self.evaluator.clone(),
+        })
+    }
+
+    /// Runs `source`, returning the value of its last statement.
+    pub fn eval(&mut self, source: &str) -> Result<Value, Error> {
+        let mut parser = Parser::new(Lexer::new(source.to_string()));
+        let program = parser
+            .parse_program()
+            .expect("parse_program returns a program");
+        if !parser.errors().is_empty() {
+            return Err(Error::Parse(parser.errors().clone()));
+        }
+
+        let evaluator = self.evaluator.clone();
+        Ok(running(evaluator, || {
+            self.evaluator.eval_program(&program, &self.env)
+        })?)
+    }
+}
+
+/// A function of a program the host holds on to, along with the variables
+/// it closes over, to call it when it likes. Calls run with the limits and
+/// capabilities the engine had when the function was looked up.
+#[derive(Clone)]
+pub struct Callable {
+    name: String,
+    function: Value,
+    evaluator: Evaluator,
+}
+
+impl Callable {
+    /// Calls the function with `args`, converting what it returns to `T`,
+    /// which may be `Value` to take it as it is.
+    ///
+    /// ```
+    /// use prolang::{Engine, Value};
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.eval("let handler = fn(x) { x * 2 };").unwrap();
+    /// let handler = engine.get_fn("handler").unwrap();
+    /// assert_eq!(handler.call::<i64>(&[Value::Int(21)]).unwrap(), 42);
+    /// ```
+    pub fn call<T: FromValue>(&self, args: &[Value]) -> Result<T, Error> {
+        let mut evaluator = self.evaluator.clone();
+        let value = running(self.evaluator.clone(), || {
+            evaluator.call(self.function.clone(), args.to_vec())
+        })?;
+        T::from_value(&value).ok_or_else(|| {
+            Error::Runtime(RuntimeError::with_kind(
+                ErrorKind::TypeMismatch,
+                format!(
+                    "result of `{}` not supported, got {}",
+                    self.name,
+                    value.type_name()
+                ),
+            ))
+        })
+    }
+}
+
+/// Reads a function a program passes to a registered function, which the
+/// host may call while it runs or keep to call later, with the limits and
+/// capabilities of the engine that passed it:
+///
+/// ```
+/// use prolang::{Callable, Engine, Error, Value};
+///
+/// let mut engine = Engine::new();
+/// engine.register_fn("twice", |f: Callable, x: i64| -> Result<i64, Error> {
+///     let once = f.call::<i64>(&[Value::Int(x)])?;
+///     f.call(&[Value::Int(once)])
+/// });
+/// assert_eq!(engine.eval("twice(fn(x) { x * 3 }, 2)").unwrap(), Value::Int(18));
+/// ```
+impl FromValue for Callable {
+    fn from_value(value: &Value) -> Option<Self> {
+        if !is_callable(value) {
+            return None;
+        }
+        Some(Callable {
+            name: value.to_string(),
+            function: value.clone(),
+            evaluator: RUNNING.with_borrow(Clone::clone).unwrap_or_default(),
+        })
+    }
+}
@@ -0,0 +1,6 @@
+pub mod convert;
+#[allow(clippy::module_inception)]
+pub mod engine;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,511 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap},
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+};
+
+use crate::{
+    evaluator::{
+        capabilities::{Capabilities, Paths},
+        host::HostObject,
+        limits::Limits,
+        value::{ErrorKind, RuntimeError, Value},
+    },
+    gc::gc,
+    module::loader::Loader,
+};
+
+use super::{
+    convert::FromValue,
+    engine::{Callable, Engine, Error},
+};
+
+#[test]
+fn test_engine_keeps_definitions() {
+    let mut engine = Engine::new();
+    let tests = [
+        ("let double = fn(x) { x * 2 };", Value::Null),
+        ("let n = double(4);", Value::Null),
+        ("double(n) + 1", Value::Int(17)),
+        ("n = n + 1; n", Value::Int(9)),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(engine.eval(input), Ok(expected), "{}", input);
+    }
+}
+
+#[test]
+fn test_engine_errors() {
+    let mut engine = Engine::new();
+
+    let Err(Error::Parse(errors)) = engine.eval("let = 5;") else {
+        panic!("expected parser errors");
+    };
+    assert!(!errors.is_empty());
+
+    let Err(Error::Runtime(err)) = engine.eval("1 / 0") else {
+        panic!("expected a runtime error");
+    };
+    assert_eq!(err.kind, ErrorKind::DivisionByZero);
+    assert_eq!(
+        Error::Runtime(err).to_string(),
+        "runtime error: DivisionByZero: division by zero\n    at main.pr:1:3"
+    );
+
+    // the engine is still usable after a failed program
+    assert_eq!(engine.eval("1 + 1"), Ok(Value::Int(2)));
+}
+
+#[test]
+fn test_engine_is_sandboxed() {
+    let mut engine = Engine::new();
+    let Err(Error::Runtime(err)) = engine.eval("clock()") else {
+        panic!("the engine grants no capabilities");
+    };
+    assert_eq!(err.kind, ErrorKind::PermissionDenied);
+
+    engine.set_capabilities(Capabilities {
+        clock: true,
+        ..Capabilities::default()
+    });
+    assert!(engine.eval("clock() > 0") == Ok(Value::Bool(true)));
+
+    engine.set_limits(Limits {
+        max_steps: Some(1000),
+        ..Limits::default()
+    });
+    let Err(Error::Runtime(err)) = engine.eval("for { }") else {
+        panic!("the loop runs out of steps");
+    };
+    assert_eq!(err.kind, ErrorKind::StepLimit);
+}
+
+#[test]
+fn test_engine_survives_panicking_functions() {
+    let mut engine = Engine::new();
+    engine.set_capabilities(Capabilities {
+        clock: true,
+        ..Capabilities::default()
+    });
+    engine.register_fn("explode", || -> i64 { panic!("boom") });
+    let result = panic::catch_unwind(AssertUnwindSafe(|| engine.eval("explode()")));
+    assert!(result.is_err(), "the panic reaches the host");
+
+    // functions read outside of a run have no capabilities, rather than
+    // those of the run that panicked
+    let clock = Callable::from_value(&engine.eval("clock").unwrap()).unwrap();
+    let Err(Error::Runtime(err)) = clock.call::<Value>(&[]) else {
+        panic!("clock is called without capabilities");
+    };
+    assert_eq!(err.kind, ErrorKind::PermissionDenied);
+}
+
+#[test]
+fn test_engine_rejects_deeply_nested_source() {
+    let mut engine = Engine::new();
+    engine.set_limits(Limits {
+        max_steps: Some(1000),
+        ..Limits::default()
+    });
+    let source = format!("len({}1{})", "[".repeat(20000), "]".repeat(20000));
+    let Err(Error::Parse(errors)) = engine.eval(&source) else {
+        panic!("the source is nested too deeply to run");
+    };
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("nested too deeply"), "{:?}", errors);
+}
+
+#[test]
+fn test_engine_imports_no_files() {
+    let dir = std::env::temp_dir().join(format!("prolang-engine-import-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let keys = dir.join("keys.pr");
+    std::fs::write(&keys, "export let token = \"s3cr3t\";").unwrap();
+    let input = format!("import {:?} as k; k.token", keys.display().to_string());
+
+    // with no loader, and then with one reading nothing
+    let mut engine = Engine::new();
+    for _ in 0..2 {
+        let Err(Error::Runtime(err)) = engine.eval(&input) else {
+            panic!("the engine imports no files");
+        };
+        assert_eq!(err.kind, ErrorKind::PermissionDenied);
+        engine.set_loader(Loader::new());
+    }
+
+    engine.set_capabilities(Capabilities {
+        read: Paths::Under(vec![dir.clone()]),
+        ..Capabilities::default()
+    });
+    assert_eq!(engine.eval(&input), Ok(Value::Str("s3cr3t".to_string())));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_engine_register_fn() {
+    let mut engine = Engine::new();
+    engine.register_fn("answer", || 42);
+    engine.register_fn("fetch_user", |id: i64| -> Result<Value, Error> {
+        match id {
+            1 => Ok(Value::Str("ada".to_string())),
+            _ => Err(RuntimeError::new(format!("no user {}", id)).into()),
+        }
+    });
+    engine.register_fn("shout", |s: String, times: i64| {
+        s.to_uppercase().repeat(times as usize)
+    });
+    engine.register_fn("all", |flags: Vec<bool>| flags.into_iter().all(|flag| flag));
+    engine.register_fn("keys", |pairs: HashMap<String, i64>| {
+        let mut keys: Vec<String> = pairs.into_keys().collect();
+        keys.sort();
+        keys
+    });
+    engine.register_fn("scores", || {
+        BTreeMap::from([("a".to_string(), 1), ("b".to_string(), 2)])
+    });
+    engine.register_fn("or_zero", |n: Option<i64>| n.unwrap_or(0));
+    engine.register_fn("describe", |v: Value| v.type_name());
+    engine.register_fn("log", |_: String| ());
+    engine.register_fn("half", |x: f64| x / 2.0);
+
+    let tests = [
+        ("answer()", Ok("42")),
+        ("fetch_user(1)", Ok("ada")),
+        ("shout(\"hi\", 2)", Ok("HIHI")),
+        (
+            "[all([true, true]), all([true, false])]",
+            Ok("[true, false]"),
+        ),
+        ("keys({\"b\": 2, \"a\": 1})", Ok("[\"a\", \"b\"]")),
+        ("scores()", Ok("{\"a\": 1, \"b\": 2}")),
+        ("[or_zero(log(\"x\")), or_zero(5)]", Ok("[0, 5]")),
+        ("describe(fn(x) { x })", Ok("FUNCTION")),
+        ("log(\"x\")", Ok("null")),
+        ("[half(3), half(0.5)]", Ok("[1.5, 0.25]")),
+        ("fetch_user", Ok("builtin fetch_user")),
+        ("let f = fetch_user; f(1)", Ok("ada")),
+        ("fetch_user(2)", Err((ErrorKind::Error, "no user 2"))),
+        // the same errors as a builtin called wrong
+        (
+            "fetch_user(1, 2)",
+            Err((
+                ErrorKind::WrongArguments,
+                "wrong number of arguments to `fetch_user`. got=2, want=1",
+            )),
+        ),
+        (
+            "fetch_user(\"1\")",
+            Err((
+                ErrorKind::TypeMismatch,
+                "argument to `fetch_user` not supported, got STRING",
+            )),
+        ),
+        (
+            "all([true, 1])",
+            Err((
+                ErrorKind::TypeMismatch,
+                "argument to `all` not supported, got ARRAY",
+            )),
+        ),
+        (
+            "try { fetch_user(3) } catch (e) { e.message }",
+            Ok("no user 3"),
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let result = engine
+            .eval(input)
+            .map(|value| value.to_string())
+            .map_err(|err| match err {
+                Error::Runtime(err) => (err.kind, err.message),
+                Error::Parse(errors) => panic!("parser errors for {}: {:?}", input, errors),
+            });
+        assert_eq!(
+            result,
+            expected
+                .map(str::to_string)
+                .map_err(|(kind, message)| (kind, message.to_string())),
+            "{}",
+            input
+        );
+    }
+
+    let Err(Error::Runtime(builtin)) = engine.eval("len(1, 2)") else {
+        panic!("len takes one argument");
+    };
+    assert_eq!(
+        builtin.message,
+        "wrong number of arguments to `len`. got=2, want=1"
+    );
+}
+
+#[test]
+fn test_engine_get_fn() {
+    let mut engine = Engine::new();
+    engine
+        .eval(
+            "let handler = fn(x) { x * 2 };
+             let make_adder = fn(n) { fn(x) { x + n } };
+             let add_ten = make_adder(10);
+             let fail = fn(x) { [1][x] };
+             let greet = fn(name) { \"hi \" + name };
+             let total = 0;",
+        )
+        .unwrap();
+
+    let handler = engine.get_fn("handler").unwrap();
+    assert_eq!(handler.call::<Value>(&[Value::Int(21)]), Ok(Value::Int(42)));
+    assert_eq!(handler.call::<i64>(&[Value::Int(4)]), Ok(8));
+
+    // the closure keeps what it captured after the script lets go of it
+    let add_ten = engine.get_fn("add_ten").unwrap();
+    engine.eval("add_ten = 0; make_adder = 0;").unwrap();
+    gc::collect();
+    assert_eq!(add_ten.call::<i64>(&[Value::Int(5)]), Ok(15));
+
+    let greet = engine.get_fn("greet").unwrap();
+    assert_eq!(
+        greet.call::<String>(&[Value::Str("ada".to_string())]),
+        Ok("hi ada".to_string())
+    );
+
+    let len = engine.get_fn("len").unwrap();
+    assert_eq!(len.call::<i64>(&[Value::Str("four".to_string())]), Ok(4));
+
+    let errors = [
+        (
+            handler.call::<String>(&[Value::Int(1)]).map(Value::Str),
+            ErrorKind::TypeMismatch,
+            "result of `handler` not supported, got INT",
+        ),
+        (
+            handler.call::<Value>(&[]),
+            ErrorKind::WrongArguments,
+            "wrong number of arguments. got=0, want=1",
+        ),
+        (
+            engine
+                .get_fn("fail")
+                .unwrap()
+                .call::<Value>(&[Value::Int(3)]),
+            ErrorKind::IndexOutOfBounds,
+            "index out of bounds: the length is 1 but the index is 3",
+        ),
+        (
+            engine.get_fn("total").map(|_| Value::Null),
+            ErrorKind::TypeMismatch,
+            "not a function: INT",
+        ),
+        (
+            engine.get_fn("missing").map(|_| Value::Null),
+            ErrorKind::UnknownIdentifier,
+            "identifier not found: missing",
+        ),
+    ];
+    for (result, kind, message) in errors {
+        let Err(Error::Runtime(err)) = result else {
+            panic!("expected {}", message);
+        };
+        assert_eq!((err.kind, err.message.as_str()), (kind, message));
+        // the host made the call, so it has no frame
+        assert!(err.trace.is_empty(), "{:?}", err.trace);
+    }
+}
+
+#[test]
+fn test_engine_register_fn_takes_functions() {
+    let mut engine = Engine::new();
+    engine.set_limits(Limits {
+        max_steps: Some(10_000),
+        ..Limits::default()
+    });
+    engine.register_fn("twice", |f: Callable, x: i64| -> Result<i64, Error> {
+        let once = f.call::<i64>(&[Value::Int(x)])?;
+        f.call(&[Value::Int(once)])
+    });
+    let kept = Rc::new(RefCell::new(None));
+    engine.register_fn("keep", {
+        let kept = Rc::clone(&kept);
+        move |f: Callable| *kept.borrow_mut() = Some(f)
+    });
+
+    let tests = [
+        ("let n = 1; twice(fn(x) { x * 3 + n }, 2)", Ok("22")),
+        (
+            "twice(twice, 1)",
+            Err((
+                ErrorKind::WrongArguments,
+                "wrong number of arguments to `twice`. got=1, want=2",
+            )),
+        ),
+        (
+            "twice(\"f\", 1)",
+            Err((
+                ErrorKind::TypeMismatch,
+                "argument to `twice` not supported, got STRING",
+            )),
+        ),
+        (
+            "twice(fn(x) { [x][1] }, 0)",
+            Err((
+                ErrorKind::IndexOutOfBounds,
+                "index out of bounds: the length is 1 but the index is 1",
+            )),
+        ),
+        (
+            "twice(fn(x) { \"x\" }, 0)",
+            Err((
+                ErrorKind::TypeMismatch,
+                "result of `fn(x)` not supported, got STRING",
+            )),
+        ),
+        // the function runs with the limits and capabilities of the engine
+        (
+            "twice(fn(x) { clock() }, 0)",
+            Err((ErrorKind::PermissionDenied, "`clock` is not allowed")),
+        ),
+        (
+            "twice(fn(x) { for { } }, 0)",
+            Err((ErrorKind::StepLimit, "step limit of 10000 exceeded")),
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let result = engine
+            .eval(input)
+            .map(|value| value.to_string())
+            .map_err(|err| match err {
+                Error::Runtime(err) => (err.kind, err.message),
+                Error::Parse(errors) => panic!("parser errors for {}: {:?}", input, errors),
+            });
+        assert_eq!(
+            result,
+            expected
+                .map(str::to_string)
+                .map_err(|(kind, message)| (kind, message.to_string())),
+            "{}",
+            input
+        );
+    }
+
+    // a function kept by the host can be called after the program ends
+    engine.eval("let m = 10; keep(fn(x) { x + m });").unwrap();
+    engine.eval("m = 20;").unwrap();
+    gc::collect();
+    let kept = kept.borrow_mut().take().expect("keep was called");
+    assert_eq!(kept.call::<i64>(&[Value::Int(1)]), Ok(21));
+}
+
+struct Counter {
+    count: Cell<i64>,
+    dropped: Rc<Cell<bool>>,
+}
+
+impl HostObject for Counter {
+    fn type_name(&self) -> &str {
+        "Counter"
+    }
+
+    fn has_method(&self, name: &str) -> bool {
+        matches!(name, "add" | "get")
+    }
+
+    fn call_method(&self, name: &str, args: &[Value]) -> Result<Value, RuntimeError> {
+        match (name, args) {
+            ("add", [Value::Int(n)]) => {
+                self.count.set(self.count.get() + n);
+                Ok(Value::Null)
+            }
+            ("get", []) => Ok(Value::Int(self.count.get())),
+            _ => Err(RuntimeError::with_kind(
+                ErrorKind::WrongArguments,
+                format!("bad arguments to Counter.{}", name),
+            )),
+        }
+    }
+
+    fn equals(&self, other: &dyn HostObject) -> bool {
+        other
+            .downcast_ref::<Counter>()
+            .is_some_and(|other| other.count == self.count)
+    }
+
+    fn display(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Counter({})", self.count.get())
+    }
+}
+
+impl Drop for Counter {
+    fn drop(&mut self) {
+        self.dropped.set(true);
+    }
+}
+
+#[test]
+fn test_engine_host_objects() {
+    let dropped = Rc::new(Cell::new(false));
+    let mut engine = Engine::new();
+    let counter = |dropped: Rc<Cell<bool>>| {
+        move || {
+            Value::host(Counter {
+                count: Cell::new(0),
+                dropped: Rc::clone(&dropped),
+            })
+        }
+    };
+    engine.register_fn("counter", counter(Rc::new(Cell::new(false))));
+    engine.register_fn("tracked_counter", counter(Rc::clone(&dropped)));
+    engine.register_fn("peek", |counter: Rc<Counter>| counter.count.get());
+
+    let tests = [
+        (
+            "let c = tracked_counter(); c.add(2); c.add(3); c.get()",
+            Ok("5"),
+        ),
+        ("let add = c.add; add(1); peek(c)", Ok("6")),
+        ("c", Ok("Counter(6)")),
+        ("[c]", Ok("[Counter(6)]")),
+        (
+            "[c == c, c == counter(), counter() == counter()]",
+            Ok("[true, false, true]"),
+        ),
+        ("c.reset()", Err("Counter has no method `reset`")),
+        ("c.add(\"x\")", Err("bad arguments to Counter.add")),
+        ("peek(1)", Err("argument to `peek` not supported, got INT")),
+        (
+            "try { c.get(1) } catch (e) { e.message }",
+            Ok("bad arguments to Counter.get"),
+        ),
+    ];
+    for (input, expected) in tests {
+        let result = engine
+            .eval(input)
+            .map(|value| value.to_string())
+            .map_err(|err| match err {
+                Error::Runtime(err) => err.message,
+                Error::Parse(errors) => panic!("parser errors for {}: {:?}", input, errors),
+            });
+        assert_eq!(
+            result,
+            expected.map(str::to_string).map_err(str::to_string),
+            "{}",
+            input
+        );
+    }
+
+    gc::collect();
+    assert!(!dropped.get(), "the counter is still bound to `c`");
+
+    // held only by a cycle the collector frees
+    engine
+        .eval("let a = [c, 0]; a[1] = a; c = 0; add = 0; a = 0;")
+        .unwrap();
+    assert!(!dropped.get(), "the cycle still holds the counter");
+    gc::collect();
+    assert!(dropped.get(), "the counter is dropped with the cycle");
+}
@@ -0,0 +1,316 @@
+use std::{
+    cell::Cell,
+    collections::{hash_map::RandomState, BTreeMap},
+    fs,
+    hash::{BuildHasher, Hasher},
+    io::{self, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::gc::gc;
+
+use super::{
+    capabilities::Capabilities,
+    value::{ErrorKind, HashKey, RuntimeError, Value},
+};
+
+/// A builtin, given what it may do on behalf of the program calling it.
+pub type BuiltinFn = fn(&Capabilities, &[Value]) -> Result<Value, RuntimeError>;
+
+pub struct Builtin {
+    pub name: &'static str,
+    pub func: BuiltinFn,
+}
+
+pub static BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "len",
+        func: len,
+    },
+    Builtin {
+        name: "push",
+        func: push,
+    },
+    Builtin {
+        name: "first",
+        func: first,
+    },
+    Builtin {
+        name: "last",
+        func: last,
+    },
+    Builtin {
+        name: "rest",
+        func: rest,
+    },
+    Builtin {
+        name: "print",
+        func: print,
+    },
+    Builtin {
+        name: "println",
+        func: println,
+    },
+    Builtin {
+        name: "gc_stats",
+        func: gc_stats,
+    },
+    Builtin {
+        name: "input",
+        func: input,
+    },
+    Builtin {
+        name: "read_file",
+        func: read_file,
+    },
+    Builtin {
+        name: "write_file",
+        func: write_file,
+    },
+    Builtin {
+        name: "env",
+        func: env,
+    },
+    Builtin {
+        name: "clock",
+        func: clock,
+    },
+    Builtin {
+        name: "random",
+        func: random,
+    },
+];
+
+pub fn lookup(name: &str) -> Option<&'static Builtin> {
+    BUILTINS.iter().find(|b| b.name == name)
+}
+
+pub(crate) fn check_arity(name: &str, args: &[Value], want: usize) -> Result<(), RuntimeError> {
+    if args.len() != want {
+        return Err(RuntimeError::with_kind(
+            ErrorKind::WrongArguments,
+            format!(
+                "wrong number of arguments to `{}`. got={}, want={}",
+                name,
+                args.len(),
+                want
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Like `check_arity`, for builtins whose trailing arguments are optional.
+pub(crate) fn check_arity_between(
+    name: &str,
+    args: &[Value],
+    min: usize,
+    max: usize,
+) -> Result<(), RuntimeError> {
+    if args.len() < min || args.len() > max {
+        return Err(RuntimeError::with_kind(
+            ErrorKind::WrongArguments,
+            format!(
+                "wrong number of arguments to `{}`. got={}, want={} to {}",
+                name,
+                args.len(),
+                min,
+                max
+            ),
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) fn unsupported(name: &str, arg: &Value) -> RuntimeError {
+    RuntimeError::with_kind(
+        ErrorKind::TypeMismatch,
+        format!(
+            "argument to `{}` not supported, got {}",
+            name,
+            arg.type_name()
+        ),
+    )
+}
+
+fn len(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("len", args, 1)?;
+    match &args[0] {
+        Value::Str(s) => Ok(Value::Int(s.chars().count() as i64)),
+        Value::Array(elements) => Ok(Value::Int(elements.borrow().len() as i64)),
+        Value::Hash(pairs) => Ok(Value::Int(pairs.borrow().len() as i64)),
+        other => Err(unsupported("len", other)),
+    }
+}
+
+fn push(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("push", args, 2)?;
+    match &args[0] {
+        Value::Array(elements) => {
+            let mut elements = elements.borrow().clone();
+            elements.push(args[1].clone());
+            Ok(Value::array(elements))
+        }
+        other => Err(unsupported("push", other)),
+    }
+}
+
+fn first(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("first", args, 1)?;
+    match &args[0] {
+        Value::Array(elements) => Ok(elements.borrow().first().cloned().unwrap_or(Value::Null)),
+        other => Err(unsupported("first", other)),
+    }
+}
+
+fn last(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("last", args, 1)?;
+    match &args[0] {
+        Value::Array(elements) => Ok(elements.borrow().last().cloned().unwrap_or(Value::Null)),
+        other => Err(unsupported("last", other)),
+    }
+}
+
+fn rest(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("rest", args, 1)?;
+    match &args[0] {
+        Value::Array(elements) => {
+            let elements = elements.borrow();
+            if elements.is_empty() {
+                return Ok(Value::Null);
+            }
+            Ok(Value::array(elements[1..].to_vec()))
+        }
+        other => Err(unsupported("rest", other)),
+    }
+}
+
+fn print(caps: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    Capabilities::check("print", caps.stdout)?;
+    let out: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    print!("{}", out.join(" "));
+    Ok(Value::Null)
+}
+
+fn println(caps: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    Capabilities::check("println", caps.stdout)?;
+    let out: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    println!("{}", out.join(" "));
+    Ok(Value::Null)
+}
+
+fn gc_stats(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("gc_stats", args, 0)?;
+    let stats = gc::stats();
+    let pairs: BTreeMap<HashKey, Value> = [
+        ("collections", stats.collections),
+        ("allocated", stats.allocated),
+        ("freed", stats.freed),
+        ("live", stats.live),
+    ]
+    .into_iter()
+    .map(|(name, count)| (HashKey::Str(name.to_owned()), Value::Int(count as i64)))
+    .collect();
+
+    Ok(Value::hash(pairs))
+}
+
+fn io_error(action: &str, path: &str, err: io::Error) -> RuntimeError {
+    RuntimeError::new(format!("could not {} {}: {}", action, path, err))
+}
+
+/// Reads a line from the standard input, without its line ending, or null
+/// once the input ended.
+fn input(caps: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("input", args, 0)?;
+    Capabilities::check("input", caps.stdin)?;
+    // a prompt printed before has to show
+    let _ = io::stdout().flush();
+    let mut line = String::new();
+    match io::stdin().read_line(&mut line) {
+        Ok(0) => Ok(Value::Null),
+        Ok(_) => {
+            let len = line.trim_end_matches(['\n', '\r']).len();
+            line.truncate(len);
+            Ok(Value::Str(line))
+        }
+        Err(err) => Err(io_error("read", "the standard input", err)),
+    }
+}
+
+fn read_file(caps: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("read_file", args, 1)?;
+    match &args[0] {
+        Value::Str(path) => {
+            caps.check_read("read_file", path)?;
+            fs::read_to_string(path)
+                .map(Value::Str)
+                .map_err(|err| io_error("read", path, err))
+        }
+        other => Err(unsupported("read_file", other)),
+    }
+}
+
+/// Writes a string to a file, replacing what it held.
+fn write_file(caps: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("write_file", args, 2)?;
+    match (&args[0], &args[1]) {
+        (Value::Str(path), Value::Str(contents)) => {
+            caps.check_write("write_file", path)?;
+            fs::write(path, contents)
+                .map(|_| Value::Null)
+                .map_err(|err| io_error("write", path, err))
+        }
+        (Value::Str(_), other) | (other, _) => Err(unsupported("write_file", other)),
+    }
+}
+
+/// The value of an environment variable, or null if it is not set.
+fn env(caps: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("env", args, 1)?;
+    Capabilities::check("env", caps.env)?;
+    match &args[0] {
+        Value::Str(name) => Ok(std::env::var(name).map_or(Value::Null, Value::Str)),
+        other => Err(unsupported("env", other)),
+    }
+}
+
+/// Milliseconds since the Unix epoch.
+fn clock(caps: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("clock", args, 0)?;
+    Capabilities::check("clock", caps.clock)?;
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Ok(Value::Int(since_epoch.as_millis() as i64))
+}
+
+thread_local! {
+    static RANDOM_STATE: Cell<u64> = Cell::new(RandomState::new().build_hasher().finish() | 1);
+}
+
+/// A random integer from 0 up to, but not including, its argument.
+fn random(caps: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("random", args, 1)?;
+    Capabilities::check("random", caps.random)?;
+    let bound = match &args[0] {
+        Value::Int(bound) if *bound > 0 => *bound as u64,
+        Value::Int(bound) => {
+            return Err(RuntimeError::with_kind(
+                ErrorKind::WrongArguments,
+                format!("argument to `random` must be positive, got {}", bound),
+            ))
+        }
+        other => return Err(unsupported("random", other)),
+    };
+    // xorshift64*, which is plenty for programs that are not cryptography
+    let next = RANDOM_STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        state.set(x);
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    });
+    Ok(Value::Int((next % bound) as i64))
+}
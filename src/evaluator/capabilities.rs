@@ -0,0 +1,114 @@
+use std::path::{Path, PathBuf};
+
+use super::value::{ErrorKind, RuntimeError};
+
+/// The files a program may open.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Paths {
+    /// Any file.
+    All,
+    /// Files under the directories listed, so none if there are none.
+    Under(Vec<PathBuf>),
+}
+
+impl Default for Paths {
+    fn default() -> Self {
+        Paths::Under(vec![])
+    }
+}
+
+impl Paths {
+    fn contains(&self, path: &Path) -> bool {
+        match self {
+            Paths::All => true,
+            Paths::Under(dirs) => {
+                // `..` and links cannot reach outside the directories
+                let Some(path) = resolve(path) else {
+                    return false;
+                };
+                dirs.iter()
+                    .filter_map(|dir| dir.canonicalize().ok())
+                    .any(|dir| path.starts_with(dir))
+            }
+        }
+    }
+}
+
+/// Resolves `path`, which may not exist yet, against the directory it is in.
+fn resolve(path: &Path) -> Option<PathBuf> {
+    if let Ok(path) = path.canonicalize() {
+        return Some(path);
+    }
+    // a link to a file that does not exist yet, which writing through it
+    // would create wherever the link points
+    if path.symlink_metadata().is_ok() {
+        return None;
+    }
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    Some(parent.canonicalize().ok()?.join(path.file_name()?))
+}
+
+/// What the builtins may do outside the program on its behalf. Every
+/// capability is denied by default.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Capabilities {
+    /// Writing to the standard output, with `print` and `println`.
+    pub stdout: bool,
+    /// Reading the standard input, with `input`.
+    pub stdin: bool,
+    /// Files `read_file` may read.
+    pub read: Paths,
+    /// Files `write_file` may write.
+    pub write: Paths,
+    /// Reading environment variables, with `env`.
+    pub env: bool,
+    /// Reading the time, with `clock`.
+    pub clock: bool,
+    /// Drawing random numbers, with `random`.
+    pub random: bool,
+}
+
+impl Capabilities {
+    /// Grants every capability.
+    pub fn all() -> Self {
+        Self {
+            stdout: true,
+            stdin: true,
+            read: Paths::All,
+            write: Paths::All,
+            env: true,
+            clock: true,
+            random: true,
+        }
+    }
+
+    /// Fails the builtin `name` unless it was `granted` the capability it
+    /// needs.
+    pub fn check(name: &str, granted: bool) -> Result<(), RuntimeError> {
+        if !granted {
+            return Err(denied(format!("`{}` is not allowed", name)));
+        }
+        Ok(())
+    }
+
+    pub fn check_read(&self, name: &str, path: &str) -> Result<(), RuntimeError> {
+        if !self.read.contains(Path::new(path)) {
+            return Err(denied(format!("`{}` may not read {}", name, path)));
+        }
+        Ok(())
+    }
+
+    pub fn check_write(&self, name: &str, path: &str) -> Result<(), RuntimeError> {
+        if !self.write.contains(Path::new(path)) {
+            return Err(denied(format!("`{}` may not write {}", name, path)));
+        }
+        Ok(())
+    }
+}
+
+fn denied(message: String) -> RuntimeError {
+    RuntimeError::with_kind(ErrorKind::PermissionDenied, message)
+}
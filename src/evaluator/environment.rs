@@ -0,0 +1,90 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{
+    gc::gc::{self, HeapId, Trace},
+    parser::ast::TypeExpr,
+};
+
+use super::value::Value;
+
+pub type Env = Rc<RefCell<Environment>>;
+
+#[derive(Default)]
+pub struct Environment {
+    store: HashMap<String, Value>,
+    /// The annotations of the bindings declared with one, which every
+    /// assignment to them is checked against.
+    annotations: HashMap<String, TypeExpr>,
+    outer: Option<Env>,
+}
+
+impl Environment {
+    pub fn new() -> Env {
+        gc::track(Rc::new(RefCell::new(Self::default())))
+    }
+
+    pub fn new_enclosed(outer: &Env) -> Env {
+        gc::track(Rc::new(RefCell::new(Self {
+            outer: Some(Rc::clone(outer)),
+            ..Self::default()
+        })))
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        match self.store.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.outer.as_ref()?.borrow().get(name),
+        }
+    }
+
+    /// Binds `name` in this scope, shadowing any outer binding.
+    pub fn set(&mut self, name: &str, value: Value) {
+        self.store.insert(name.to_owned(), value);
+        self.annotations.remove(name);
+    }
+
+    /// Binds `name` like `set`, keeping the annotation it was declared with.
+    pub fn set_annotated(&mut self, name: &str, value: Value, annotation: TypeExpr) {
+        self.set(name, value);
+        self.annotations.insert(name.to_owned(), annotation);
+    }
+
+    /// The annotation of the binding `name` refers to, if it has one.
+    pub fn annotation(&self, name: &str) -> Option<TypeExpr> {
+        if self.store.contains_key(name) {
+            return self.annotations.get(name).cloned();
+        }
+        self.outer.as_ref()?.borrow().annotation(name)
+    }
+
+    /// Rebinds an existing `name` in the nearest scope that declares it.
+    /// Returns false when no scope does.
+    pub fn assign(&mut self, name: &str, value: Value) -> bool {
+        if let Some(slot) = self.store.get_mut(name) {
+            *slot = value;
+            return true;
+        }
+        match &self.outer {
+            Some(outer) => outer.borrow_mut().assign(name, value),
+            None => false,
+        }
+    }
+}
+
+impl Trace for RefCell<Environment> {
+    fn trace(&self, visit: &mut dyn FnMut(HeapId)) {
+        if let Ok(env) = self.try_borrow() {
+            env.store.values().for_each(|value| value.trace(visit));
+            if let Some(outer) = &env.outer {
+                visit(gc::id(outer));
+            }
+        }
+    }
+
+    fn clear(&self) {
+        let env = self
+            .try_borrow_mut()
+            .map(|mut env| std::mem::take(&mut *env));
+        drop(env);
+    }
+}
@@ -0,0 +1,1537 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    gc::gc,
+    module::loader::{self, Loader, Source},
+    parser::{
+        ast::{
+            ArrayLiteral, AssignExpression, BlockStatement, BooleanLiteral, CallExpression,
+            ConditionalIteratorExpression, EnumLiteral, Expression, ExpressionStatement,
+            FloatLiteral, ForLoopCondition, ForLoopExpression, FunctionLiteral, HashLiteral,
+            Identifier, IfExpression, ImplStatement, ImportStatement, IndexExpression,
+            InfixExpression, IntegerLiteral, InterfaceLiteral, IteratorLiteral, LetStatement,
+            MatchExpression, MemberExpression, Node, Pattern, PrefixExpression, Program,
+            ReturnStatemnt, Statement, StringLiteral, StructExpression, StructLiteral,
+            ThrowStatement, TryExpression, TypeExpr,
+        },
+        get_of_type, grow_stack,
+    },
+};
+
+use super::{
+    builtins,
+    capabilities::Capabilities,
+    environment::{Env, Environment},
+    limits::{Budget, Limits},
+    stdlib,
+    value::{
+        BoundMethod, EnumType, EnumValue, ErrorKind, Function, InterfaceType, NativeFunction,
+        RuntimeError, StructType, StructValue, Value,
+    },
+};
+
+type EvalResult = Result<Value, RuntimeError>;
+
+/// What evaluation unwinds with, rather than a value, to the code that
+/// handles it.
+enum Unwind {
+    Error(RuntimeError),
+    /// The value of a `return`, on its way to the enclosing function.
+    Return(Value),
+    /// A call in tail position, left for the enclosing function to make in
+    /// place of its own call.
+    TailCall(TailCall),
+}
+
+struct TailCall {
+    function: Value,
+    args: Vec<Value>,
+    /// `(line, column)` of the call expression.
+    position: (usize, usize),
+}
+
+type Evaluated = Result<Value, Unwind>;
+
+impl Unwind {
+    /// Attributes an error to `position` in `file`, if it has no position yet.
+    fn at(self, file: &str, position: (usize, usize)) -> Self {
+        match self {
+            Unwind::Error(err) => Unwind::Error(err.at(file, position)),
+            unwind => unwind,
+        }
+    }
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(err: RuntimeError) -> Self {
+        Unwind::Error(err)
+    }
+}
+
+/// Stack left when a call grows the Rust stack, which has to hold what the
+/// function body evaluates before its next call.
+const STACK_RED_ZONE: usize = 1024 * 1024;
+/// Stack allocated each time the Rust stack is grown.
+const STACK_GROWTH: usize = 16 * 1024 * 1024;
+
+#[derive(Default, Clone)]
+pub struct Evaluator {
+    limits: Limits,
+    /// What the program being evaluated has used of `limits`.
+    budget: Budget,
+    capabilities: Capabilities,
+    /// Function calls running.
+    depth: usize,
+    /// Whether the next statement or expression evaluated is in tail
+    /// position, where a call is left as an `Unwind::TailCall` for the
+    /// function around it to make. Every statement and expression takes the
+    /// flag when it starts, so it only reaches the ones it is set for.
+    tail: bool,
+    /// Reads the files programs import, which they may not without one.
+    loader: Option<Loader>,
+    /// The modules imported so far by path, each evaluated once.
+    modules: HashMap<PathBuf, Value>,
+    /// The file the code being evaluated is in, as errors name it.
+    file: Rc<str>,
+}
+
+impl Evaluator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Grants builtins the capabilities given, in place of none.
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities;
+    }
+
+    /// Resolves the imports of programs against the file at `path`, which
+    /// they were read from.
+    pub fn set_path(&mut self, path: &Path) {
+        self.loader.get_or_insert_with(Loader::new).set_main(path);
+    }
+
+    /// Loads the modules programs import with `loader`, which may search
+    /// more than the directory of the importing file. Without a loader,
+    /// programs may import the standard library only.
+    pub fn set_loader(&mut self, loader: Loader) {
+        self.loader = Some(loader);
+    }
+
+    pub fn eval_program(&mut self, program: &Program, env: &Env) -> EvalResult {
+        self.budget = Budget::start(self.limits);
+        self.file = loader::main_file(self.loader.as_ref()).into();
+        let mut result = Value::Null;
+        for stmt in &program.statements {
+            result = match self.eval_statement(stmt.as_ref(), env) {
+                Ok(value) => value,
+                Err(Unwind::Error(err)) => return Err(err),
+                Err(Unwind::Return(value)) => return Ok(value),
+                Err(Unwind::TailCall(call)) => return self.make_tail_call(call),
+            };
+        }
+
+        Ok(result)
+    }
+
+    /// Calls `function` for the host, which is limited like a program of
+    /// its own.
+    pub fn call(&mut self, function: Value, args: Vec<Value>) -> EvalResult {
+        self.budget = Budget::start(self.limits);
+        // the call is not made from the program, so has no frame in traces
+        let framed = matches!(&function, Value::Function(_) | Value::BoundMethod(_));
+        self.apply_function(function, args, (0, 0))
+            .map_err(|mut err| {
+                if framed {
+                    err.trace.pop();
+                }
+                err
+            })
+    }
+
+    fn eval_statement(&mut self, stmt: &dyn Statement, env: &Env) -> Evaluated {
+        grow_stack(|| self.eval_statement_node(stmt, env))
+            .map_err(|err| err.at(&self.file, stmt.position()))
+    }
+
+    fn eval_statement_node(&mut self, stmt: &dyn Statement, env: &Env) -> Evaluated {
+        let tail = std::mem::take(&mut self.tail);
+        self.budget.step()?;
+        let any = stmt.get_as_any();
+        if let Some(stmt) = get_of_type::<ExpressionStatement>(any) {
+            match &stmt.expression {
+                Some(exp) => {
+                    self.tail = tail;
+                    self.eval_expression(exp.as_ref(), env)
+                }
+                None => Ok(Value::Null),
+            }
+        } else if let Some(stmt) = get_of_type::<LetStatement>(any) {
+            self.eval_let_statement(stmt, env)
+        } else if let Some(stmt) = get_of_type::<ReturnStatemnt>(any) {
+            // the call is made where the `return` lands: by the function
+            // around it, or by a `try` block or the program it is in
+            let value = match &stmt.return_value {
+                Some(exp) => {
+                    self.tail = true;
+                    self.eval_expression(exp.as_ref(), env)?
+                }
+                None => Value::Null,
+            };
+            Err(Unwind::Return(value))
+        } else if let Some(stmt) = get_of_type::<ThrowStatement>(any) {
+            let value = self.eval_expression(expect_expression(&stmt.value)?, env)?;
+            Err(thrown_error(value).into())
+        } else if let Some(stmt) = get_of_type::<ForLoopExpression>(any) {
+            self.eval_for_expression(stmt, env)
+        } else if let Some(stmt) = get_of_type::<ImplStatement>(any) {
+            Ok(self.eval_impl_statement(stmt, env)?)
+        } else if let Some(stmt) = get_of_type::<ImportStatement>(any) {
+            Ok(self.eval_import_statement(stmt, env)?)
+        } else {
+            Err(RuntimeError::new(format!("unknown statement: {}", stmt.string())).into())
+        }
+    }
+
+    fn eval_block_statement(&mut self, block: &BlockStatement, env: &Env) -> Evaluated {
+        let tail = std::mem::take(&mut self.tail);
+        self.budget.step()?;
+        let mut result = Value::Null;
+        for (i, stmt) in block.statements.iter().enumerate() {
+            self.tail = tail && i + 1 == block.statements.len();
+            result = self.eval_statement(stmt.as_ref(), env)?;
+        }
+
+        Ok(result)
+    }
+
+    fn eval_let_statement(&mut self, stmt: &LetStatement, env: &Env) -> Evaluated {
+        let exp = expect_expression(&stmt.value)?;
+        let name = &stmt.name.value;
+        let value = if let Some(lit) = get_of_type::<EnumLiteral>(exp.get_as_any()) {
+            eval_enum_literal(lit, name)
+        } else if let Some(lit) = get_of_type::<StructLiteral>(exp.get_as_any()) {
+            eval_struct_literal(lit, name)
+        } else if let Some(lit) = get_of_type::<InterfaceLiteral>(exp.get_as_any()) {
+            eval_interface_literal(lit, name)
+        } else if let Some(lit) = get_of_type::<FunctionLiteral>(exp.get_as_any()) {
+            self.eval_function_literal(lit, Some(name), env)?
+        } else {
+            self.eval_expression(exp, env)?
+        };
+        match &stmt.type_annotation {
+            Some(type_annotation) => {
+                check_type_annotation(type_annotation, &value, env)?;
+                env.borrow_mut()
+                    .set_annotated(name, value, type_annotation.clone());
+            }
+            None => env.borrow_mut().set(name, value),
+        }
+
+        Ok(Value::Null)
+    }
+
+    fn eval_import_statement(&mut self, stmt: &ImportStatement, env: &Env) -> EvalResult {
+        if let Some(module) = stdlib::lookup(&stmt.path) {
+            let module = module.map_err(RuntimeError::new)?;
+            let module = self
+                .modules
+                .entry(PathBuf::from(&stmt.path))
+                .or_insert_with(|| module.to_value())
+                .clone();
+            env.borrow_mut().set(&stmt.alias.value, module);
+            return Ok(Value::Null);
+        }
+
+        let Some(loader) = &mut self.loader else {
+            return Err(loader::no_loader(&stmt.path));
+        };
+        let path = loader.resolve(&stmt.path).map_err(RuntimeError::new)?;
+        let module = match self.modules.get(&path) {
+            Some(module) => module.clone(),
+            None => {
+                let source = loader.enter(path.clone(), &self.capabilities)?;
+                let file = loader.display(&source.path).into();
+                let importer = std::mem::replace(&mut self.file, file);
+                let module = self.eval_module(source);
+                self.file = importer;
+                if let Some(loader) = &mut self.loader {
+                    loader.leave();
+                }
+                let module = module.map_err(|err| {
+                    let import = format!("import {:?}", stmt.path);
+                    err.unwound(Some(&import), &self.file, stmt.position())
+                })?;
+                self.modules.insert(path, module.clone());
+                module
+            }
+        };
+        env.borrow_mut().set(&stmt.alias.value, module);
+
+        Ok(Value::Null)
+    }
+
+    /// Runs a module in an environment of its own, returning what it
+    /// exported.
+    fn eval_module(&mut self, source: Source) -> EvalResult {
+        let env = Environment::new();
+        for stmt in &source.program.statements {
+            match self.eval_statement(stmt.as_ref(), &env) {
+                Ok(_) => (),
+                Err(Unwind::Error(err)) => return Err(err),
+                Err(Unwind::Return(_) | Unwind::TailCall(_)) => {
+                    return Err(RuntimeError::new(format!(
+                        "module {} returns outside of a function",
+                        source.name
+                    )))
+                }
+            }
+        }
+
+        let exports = source
+            .program
+            .statements
+            .iter()
+            .filter_map(|stmt| get_of_type::<LetStatement>(stmt.get_as_any()))
+            .filter(|stmt| stmt.exported)
+            .map(|stmt| {
+                let name = stmt.name.value.clone();
+                let value = env.borrow().get(&name).unwrap_or(Value::Null);
+                (name, value)
+            })
+            .collect();
+
+        Ok(Value::module(source.name, exports))
+    }
+
+    /// Evaluates an expression, attributing errors raised by it, rather than
+    /// by one of its operands, to its position.
+    fn eval_expression(&mut self, exp: &dyn Expression, env: &Env) -> Evaluated {
+        grow_stack(|| self.eval_expression_node(exp, env))
+            .map_err(|err| err.at(&self.file, exp.position()))
+    }
+
+    fn eval_expression_node(&mut self, exp: &dyn Expression, env: &Env) -> Evaluated {
+        let tail = std::mem::take(&mut self.tail);
+        self.budget.step()?;
+        let any = exp.get_as_any();
+        if let Some(lit) = get_of_type::<IntegerLiteral>(any) {
+            Ok(Value::Int(lit.value))
+        } else if let Some(lit) = get_of_type::<FloatLiteral>(any) {
+            Ok(Value::Float(lit.value))
+        } else if let Some(lit) = get_of_type::<BooleanLiteral>(any) {
+            Ok(Value::Bool(lit.value))
+        } else if let Some(lit) = get_of_type::<StringLiteral>(any) {
+            Ok(Value::Str(lit.value.clone()))
+        } else if let Some(ident) = get_of_type::<Identifier>(any) {
+            Ok(eval_identifier(ident, env)?)
+        } else if let Some(exp) = get_of_type::<PrefixExpression>(any) {
+            let right = self.eval_expression(expect_expression(&exp.right)?, env)?;
+            Ok(eval_prefix_expression(&exp.operator, right)?)
+        } else if let Some(exp) = get_of_type::<InfixExpression>(any) {
+            let left = self.eval_expression(exp.left.as_ref(), env)?;
+            // the right operand of `&&` and `||` only runs when it decides the result
+            match exp.operator.as_str() {
+                "&&" if !left.is_truthy() => return Ok(Value::Bool(false)),
+                "||" if left.is_truthy() => return Ok(Value::Bool(true)),
+                _ => (),
+            }
+            let right = self.eval_expression(expect_expression(&exp.right)?, env)?;
+            Ok(eval_infix_expression(&exp.operator, left, right)?)
+        } else if let Some(exp) = get_of_type::<AssignExpression>(any) {
+            self.eval_assign_expression(exp, env)
+        } else if let Some(exp) = get_of_type::<IfExpression>(any) {
+            self.eval_if_expression(exp, env, tail)
+        } else if let Some(exp) = get_of_type::<TryExpression>(any) {
+            self.eval_try_expression(exp, env)
+        } else if let Some(lit) = get_of_type::<FunctionLiteral>(any) {
+            Ok(self.eval_function_literal(lit, None, env)?)
+        } else if let Some(exp) = get_of_type::<CallExpression>(any) {
+            let function = self.eval_expression(exp.function.as_ref(), env)?;
+            let args = self.eval_expressions(&exp.arguments, env)?;
+            if tail {
+                return Err(Unwind::TailCall(TailCall {
+                    function,
+                    args,
+                    position: exp.position(),
+                }));
+            }
+            Ok(self.apply_function(function, args, exp.position())?)
+        } else if let Some(lit) = get_of_type::<ArrayLiteral>(any) {
+            let elements = self.eval_expressions(&lit.elements, env)?;
+            Ok(Value::array(elements))
+        } else if let Some(lit) = get_of_type::<HashLiteral>(any) {
+            self.eval_hash_literal(lit, env)
+        } else if let Some(exp) = get_of_type::<IndexExpression>(any) {
+            let left = self.eval_expression(exp.left.as_ref(), env)?;
+            let index = self.eval_expression(expect_expression(&exp.index)?, env)?;
+            Ok(eval_index_expression(left, index)?)
+        } else if let Some(exp) = get_of_type::<MemberExpression>(any) {
+            let object = self.eval_expression(exp.object.as_ref(), env)?;
+            Ok(eval_member_expression(object, &exp.property.value)?)
+        } else if let Some(lit) = get_of_type::<IteratorLiteral>(any) {
+            let start = self.eval_expression(lit.start.as_ref(), env)?;
+            let end = self.eval_expression(expect_expression(&lit.end)?, env)?;
+            match (start, end) {
+                (Value::Int(start), Value::Int(end)) => Ok(Value::Range(start, end)),
+                (start, end) => Err(RuntimeError::with_kind(
+                    ErrorKind::TypeMismatch,
+                    format!(
+                        "range bounds must be INT, got {}..{}",
+                        start.type_name(),
+                        end.type_name()
+                    ),
+                )
+                .into()),
+            }
+        } else if let Some(lit) = get_of_type::<EnumLiteral>(any) {
+            Ok(eval_enum_literal(lit, "<anonymous enum>"))
+        } else if let Some(lit) = get_of_type::<StructLiteral>(any) {
+            Ok(eval_struct_literal(lit, "<anonymous struct>"))
+        } else if let Some(lit) = get_of_type::<InterfaceLiteral>(any) {
+            Ok(eval_interface_literal(lit, "<anonymous interface>"))
+        } else if let Some(exp) = get_of_type::<StructExpression>(any) {
+            self.eval_struct_expression(exp, env)
+        } else if let Some(exp) = get_of_type::<MatchExpression>(any) {
+            self.eval_match_expression(exp, env)
+        } else if get_of_type::<ConditionalIteratorExpression>(any).is_some() {
+            Err(RuntimeError::new(format!(
+                "`{}` is only valid as a for-loop condition",
+                exp.string()
+            ))
+            .into())
+        } else {
+            Err(RuntimeError::new(format!("unknown expression: {}", exp.string())).into())
+        }
+    }
+
+    fn eval_expressions(
+        &mut self,
+        exps: &[Box<dyn Expression>],
+        env: &Env,
+    ) -> Result<Vec<Value>, Unwind> {
+        exps.iter()
+            .map(|exp| self.eval_expression(exp.as_ref(), env))
+            .collect()
+    }
+
+    fn eval_assign_expression(&mut self, exp: &AssignExpression, env: &Env) -> Evaluated {
+        let compound = exp.binary_operator().is_some();
+        let any = exp.target.get_as_any();
+        if let Some(ident) = get_of_type::<Identifier>(any) {
+            let current = env.borrow().get(&ident.value).ok_or_else(|| {
+                RuntimeError::with_kind(
+                    ErrorKind::UnknownIdentifier,
+                    format!("cannot assign to undeclared variable `{}`", ident.value),
+                )
+            })?;
+            let value = self.eval_assigned_value(exp, Some(current), env)?;
+            let annotation = env.borrow().annotation(&ident.value);
+            if let Some(type_annotation) = annotation {
+                check_type_annotation(&type_annotation, &value, env)?;
+            }
+            env.borrow_mut().assign(&ident.value, value.clone());
+            Ok(value)
+        } else if let Some(target) = get_of_type::<IndexExpression>(any) {
+            let left = self.eval_expression(target.left.as_ref(), env)?;
+            let index = self.eval_expression(expect_expression(&target.index)?, env)?;
+            let current = if compound {
+                Some(eval_index_expression(left.clone(), index.clone())?)
+            } else {
+                None
+            };
+            let value = self.eval_assigned_value(exp, current, env)?;
+            assign_index(&left, index, value.clone())?;
+            Ok(value)
+        } else if let Some(target) = get_of_type::<MemberExpression>(any) {
+            let object = self.eval_expression(target.object.as_ref(), env)?;
+            let property = &target.property.value;
+            let current = if compound {
+                Some(eval_member_expression(object.clone(), property)?)
+            } else {
+                None
+            };
+            let value = self.eval_assigned_value(exp, current, env)?;
+            assign_member(&object, property, value.clone())?;
+            Ok(value)
+        } else {
+            Err(RuntimeError::new(format!("cannot assign to `{}`", exp.target.string())).into())
+        }
+    }
+
+    /// Evaluates the right hand side of an assignment, combining it with the
+    /// target's `current` value for compound operators such as `+=`.
+    fn eval_assigned_value(
+        &mut self,
+        exp: &AssignExpression,
+        current: Option<Value>,
+        env: &Env,
+    ) -> Evaluated {
+        let value = self.eval_expression(expect_expression(&exp.value)?, env)?;
+        match (exp.binary_operator(), current) {
+            (Some(operator), Some(current)) => Ok(eval_infix_expression(operator, current, value)?),
+            _ => Ok(value),
+        }
+    }
+
+    fn eval_if_expression(&mut self, exp: &IfExpression, env: &Env, tail: bool) -> Evaluated {
+        let condition = self.eval_expression(expect_expression(&exp.condition)?, env)?;
+        let mut block = exp.alternative.as_ref();
+        if condition.is_truthy() {
+            block = exp.consequence.as_ref();
+        } else {
+            for (condition, consequence) in &exp.else_ifs {
+                if self.eval_expression(condition.as_ref(), env)?.is_truthy() {
+                    block = Some(consequence);
+                    break;
+                }
+            }
+        }
+
+        match block {
+            Some(block) => {
+                self.tail = tail;
+                self.eval_block_statement(block, &Environment::new_enclosed(env))
+            }
+            None => Ok(Value::Null),
+        }
+    }
+
+    fn eval_try_expression(&mut self, exp: &TryExpression, env: &Env) -> Evaluated {
+        let err = match self.eval_block_statement(&exp.body, &Environment::new_enclosed(env)) {
+            Err(Unwind::Error(err)) => err,
+            // a call returned from the block is made in it, so its errors are caught
+            Err(Unwind::TailCall(call)) => match self.make_tail_call(call) {
+                Ok(value) => return Err(Unwind::Return(value)),
+                Err(err) => err,
+            },
+            result => return result,
+        };
+        if !err.kind.is_catchable() {
+            return Err(err.into());
+        }
+
+        let handler_env = Environment::new_enclosed(env);
+        if let Some(parameter) = &exp.parameter {
+            handler_env
+                .borrow_mut()
+                .set(&parameter.value, err.to_value());
+        }
+        self.eval_block_statement(&exp.handler, &handler_env)
+    }
+
+    fn eval_for_expression(&mut self, exp: &ForLoopExpression, env: &Env) -> Evaluated {
+        let body = exp
+            .body
+            .as_ref()
+            .ok_or_else(|| RuntimeError::new("for loop without a body"))?;
+
+        match &exp.condition {
+            Some(ForLoopCondition::Loop) => loop {
+                self.eval_block_statement(body, &Environment::new_enclosed(env))?;
+            },
+            Some(ForLoopCondition::For(condition)) => {
+                while self.eval_expression(condition.as_ref(), env)?.is_truthy() {
+                    self.eval_block_statement(body, &Environment::new_enclosed(env))?;
+                }
+            }
+            Some(ForLoopCondition::ForIn(condition)) => {
+                let condition =
+                    get_of_type::<ConditionalIteratorExpression>(condition.get_as_any())
+                        .ok_or_else(|| RuntimeError::new("invalid for-in condition"))?;
+                let iterable = self.eval_expression(expect_expression(&condition.r#in)?, env)?;
+                let items: Box<dyn Iterator<Item = Value>> = match &iterable {
+                    Value::Range(start, end) => Box::new((*start..*end).map(Value::Int)),
+                    Value::Array(elements) => Box::new(elements.borrow().clone().into_iter()),
+                    Value::Str(s) => Box::new(
+                        s.chars()
+                            .map(|c| Value::Str(c.to_string()))
+                            .collect::<Vec<_>>()
+                            .into_iter(),
+                    ),
+                    other => {
+                        return Err(RuntimeError::with_kind(
+                            ErrorKind::TypeMismatch,
+                            format!("{} is not iterable", other.type_name()),
+                        )
+                        .into())
+                    }
+                };
+                for item in items {
+                    let loop_env = Environment::new_enclosed(env);
+                    loop_env.borrow_mut().set(&condition.variable.value, item);
+                    self.eval_block_statement(body, &loop_env)?;
+                }
+            }
+            None => return Err(RuntimeError::new("for loop without a condition").into()),
+        }
+
+        Ok(Value::Null)
+    }
+
+    fn eval_impl_statement(&mut self, stmt: &ImplStatement, env: &Env) -> EvalResult {
+        let struct_type = match &eval_identifier(&stmt.target, env)? {
+            Value::StructType(struct_type) => Rc::clone(struct_type),
+            other => {
+                return Err(RuntimeError::with_kind(
+                    ErrorKind::TypeMismatch,
+                    format!(
+                        "cannot impl methods on `{}`: expected a struct, got {}",
+                        stmt.target.value,
+                        other.type_name()
+                    ),
+                ))
+            }
+        };
+
+        for (name, lit) in &stmt.methods {
+            let body = lit
+                .body
+                .as_ref()
+                .ok_or_else(|| RuntimeError::new("method without a body"))?;
+            if lit.parameters.is_empty() {
+                return Err(RuntimeError::new(format!(
+                    "method `{}` of {} must take the receiver as its first parameter",
+                    name.value, struct_type.name
+                )));
+            }
+            struct_type.methods.borrow_mut().insert(
+                name.value.clone(),
+                Value::Function(new_function(lit, Some(&name.value), body, env, &self.file)),
+            );
+        }
+
+        Ok(Value::Null)
+    }
+
+    fn eval_hash_literal(&mut self, lit: &HashLiteral, env: &Env) -> Evaluated {
+        let mut pairs = BTreeMap::new();
+        for (key, value) in &lit.pairs {
+            let key = self.eval_expression(key.as_ref(), env)?;
+            let hash_key = key.hash_key().ok_or_else(|| {
+                RuntimeError::with_kind(
+                    ErrorKind::TypeMismatch,
+                    format!("unusable as hash key: {}", key.type_name()),
+                )
+            })?;
+            let value = self.eval_expression(value.as_ref(), env)?;
+            pairs.insert(hash_key, value);
+        }
+
+        Ok(Value::hash(pairs))
+    }
+
+    fn eval_struct_expression(&mut self, exp: &StructExpression, env: &Env) -> Evaluated {
+        let struct_type = match &eval_identifier(&exp.name, env)? {
+            Value::StructType(struct_type) => Rc::clone(struct_type),
+            other => {
+                return Err(RuntimeError::with_kind(
+                    ErrorKind::TypeMismatch,
+                    format!(
+                        "`{}` is not a struct, got {}",
+                        exp.name.value,
+                        other.type_name()
+                    ),
+                )
+                .into())
+            }
+        };
+
+        let mut provided = vec![];
+        for (field, value) in &exp.fields {
+            let value = self.eval_expression(value.as_ref(), env)?;
+            provided.push((field.value.clone(), value));
+        }
+
+        Ok(new_struct(struct_type, provided)?)
+    }
+
+    fn eval_match_expression(&mut self, exp: &MatchExpression, env: &Env) -> Evaluated {
+        let subject = self.eval_expression(expect_expression(&exp.subject)?, env)?;
+
+        for arm in &exp.arms {
+            let mut bindings = vec![];
+            if !self.match_pattern(&arm.pattern, &subject, env, &mut bindings)? {
+                continue;
+            }
+            let arm_env = Environment::new_enclosed(env);
+            for (name, value) in bindings {
+                arm_env.borrow_mut().set(&name, value);
+            }
+            if let Some(guard) = &arm.guard {
+                if !self.eval_expression(guard.as_ref(), &arm_env)?.is_truthy() {
+                    continue;
+                }
+            }
+            return self.eval_block_statement(&arm.body, &arm_env);
+        }
+
+        Err(RuntimeError::with_kind(
+            ErrorKind::NoMatch,
+            format!("no match arm matched value: {}", subject.inspect()),
+        )
+        .into())
+    }
+
+    /// Tests `value` against `pattern`, collecting the names it binds on success.
+    fn match_pattern(
+        &mut self,
+        pattern: &Pattern,
+        value: &Value,
+        env: &Env,
+        bindings: &mut Vec<(String, Value)>,
+    ) -> Result<bool, Unwind> {
+        grow_stack(|| self.match_pattern_node(pattern, value, env, bindings))
+    }
+
+    fn match_pattern_node(
+        &mut self,
+        pattern: &Pattern,
+        value: &Value,
+        env: &Env,
+        bindings: &mut Vec<(String, Value)>,
+    ) -> Result<bool, Unwind> {
+        match pattern {
+            Pattern::Wildcard(_) => Ok(true),
+            Pattern::Binding(ident) => {
+                bindings.push((ident.value.clone(), value.clone()));
+                Ok(true)
+            }
+            Pattern::Literal { value: exp, .. } => {
+                Ok(self.eval_expression(exp.as_ref(), env)? == *value)
+            }
+            Pattern::EnumVariant {
+                enum_name,
+                variant,
+                payload,
+            } => {
+                let enum_type = match &eval_identifier(enum_name, env)? {
+                    Value::Enum(enum_type) => Rc::clone(enum_type),
+                    other => {
+                        return Err(RuntimeError::with_kind(
+                            ErrorKind::TypeMismatch,
+                            format!(
+                                "`{}` is not an enum, got {}",
+                                enum_name.value,
+                                other.type_name()
+                            ),
+                        )
+                        .into())
+                    }
+                };
+                let index = variant_index(&enum_type, &variant.value)?;
+                let value = match value {
+                    Value::EnumVariant(value)
+                        if Rc::ptr_eq(&value.enum_type, &enum_type) && value.variant == index =>
+                    {
+                        value
+                    }
+                    _ => return Ok(false),
+                };
+                let Some(payload) = payload else {
+                    return Ok(true);
+                };
+                if payload.len() != value.payload.len() {
+                    return Err(RuntimeError::new(format!(
+                        "pattern {} expects {} field(s), but the variant has {}",
+                        pattern.string(),
+                        payload.len(),
+                        value.payload.len()
+                    ))
+                    .into());
+                }
+                for (pattern, value) in payload.iter().zip(&value.payload) {
+                    if !self.match_pattern(pattern, value, env, bindings)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            Pattern::Array { elements, rest, .. } => {
+                let Value::Array(values) = value else {
+                    return Ok(false);
+                };
+                let values = values.borrow().clone();
+                if values.len() < elements.len()
+                    || (rest.is_none() && values.len() != elements.len())
+                {
+                    return Ok(false);
+                }
+                for (pattern, value) in elements.iter().zip(&values) {
+                    if !self.match_pattern(pattern, value, env, bindings)? {
+                        return Ok(false);
+                    }
+                }
+                match rest {
+                    Some(rest) => {
+                        let rest_value = Value::array(values[elements.len()..].to_vec());
+                        self.match_pattern(rest, &rest_value, env, bindings)
+                    }
+                    None => Ok(true),
+                }
+            }
+            Pattern::Struct { name, fields } => {
+                let struct_type = match &eval_identifier(name, env)? {
+                    Value::StructType(struct_type) => Rc::clone(struct_type),
+                    other => {
+                        return Err(RuntimeError::with_kind(
+                            ErrorKind::TypeMismatch,
+                            format!(
+                                "`{}` is not a struct, got {}",
+                                name.value,
+                                other.type_name()
+                            ),
+                        )
+                        .into())
+                    }
+                };
+                let Value::Struct(value) = value else {
+                    return Ok(false);
+                };
+                let value = value.borrow();
+                if !Rc::ptr_eq(&value.struct_type, &struct_type) {
+                    return Ok(false);
+                }
+                for (field, pattern) in fields {
+                    let field_value = value.get(&field.value).ok_or_else(|| {
+                        RuntimeError::new(format!(
+                            "struct {} has no field `{}`",
+                            struct_type.name, field.value
+                        ))
+                    })?;
+                    if !self.match_pattern(pattern, field_value, env, bindings)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+        }
+    }
+
+    fn eval_function_literal(
+        &mut self,
+        lit: &FunctionLiteral,
+        name: Option<&str>,
+        env: &Env,
+    ) -> EvalResult {
+        let body = lit
+            .body
+            .as_ref()
+            .ok_or_else(|| RuntimeError::new("function literal without a body"))?;
+
+        Ok(Value::Function(new_function(
+            lit, name, body, env, &self.file,
+        )))
+    }
+
+    /// Calls `function`; errors unwinding out of its body record the call
+    /// made at `position` in their trace.
+    pub(crate) fn apply_function(
+        &mut self,
+        function: Value,
+        args: Vec<Value>,
+        position: (usize, usize),
+    ) -> EvalResult {
+        match &function {
+            Value::Function(func) => {
+                check_arity(func, &args)?;
+                self.call_function(Rc::clone(func), args, position)
+            }
+            Value::Builtin(builtin) => (builtin.func)(&self.capabilities, &args),
+            Value::Native(native) => (native.func)(&args),
+            Value::BoundMethod(method) => {
+                let mut method_args = Vec::with_capacity(args.len() + 1);
+                method_args.push(method.receiver.clone());
+                method_args.extend(args);
+                self.apply_function(method.function.clone(), method_args, position)
+            }
+            Value::VariantConstructor(enum_type, variant) => {
+                construct_variant(Rc::clone(enum_type), *variant, args)
+            }
+            other => Err(RuntimeError::with_kind(
+                ErrorKind::TypeMismatch,
+                format!("not a function: {}", other.type_name()),
+            )),
+        }
+    }
+
+    /// Runs `func` as a new call, on a Rust stack grown as needed so that
+    /// only the call depth limits recursion.
+    fn call_function(
+        &mut self,
+        func: Rc<Function>,
+        args: Vec<Value>,
+        position: (usize, usize),
+    ) -> EvalResult {
+        self.limits.check_depth(self.depth)?;
+        self.depth += 1;
+        let caller = Rc::clone(&self.file);
+        let result = stacker::maybe_grow(STACK_RED_ZONE, STACK_GROWTH, || {
+            self.run_function(func, args, &caller, position)
+        });
+        self.file = caller;
+        self.depth -= 1;
+
+        result
+    }
+
+    /// Runs `func`, then each call it leaves in tail position in its place,
+    /// so tail recursion does not grow the Rust stack. Errors unwinding out
+    /// of the function running record the call made at `position` in the
+    /// file of the caller.
+    fn run_function(
+        &mut self,
+        mut func: Rc<Function>,
+        mut args: Vec<Value>,
+        caller: &str,
+        position: (usize, usize),
+    ) -> EvalResult {
+        loop {
+            self.file = Rc::clone(&func.file);
+            let call = match self.eval_function_body(&func, args) {
+                Ok(value) | Err(Unwind::Return(value)) => return Ok(value),
+                Err(Unwind::TailCall(call)) => call,
+                Err(Unwind::Error(err)) => {
+                    return Err(err.unwound(func.name.as_deref(), caller, position))
+                }
+            };
+
+            let (function, call_args) = match call.function {
+                Value::BoundMethod(ref method) => {
+                    let mut method_args = Vec::with_capacity(call.args.len() + 1);
+                    method_args.push(method.receiver.clone());
+                    method_args.extend(call.args);
+                    (method.function.clone(), method_args)
+                }
+                function => (function, call.args),
+            };
+            match function {
+                Value::Function(ref next) if check_arity(next, &call_args).is_ok() => {
+                    func = Rc::clone(next);
+                    args = call_args;
+                }
+                // other calls fail or complete without growing the stack,
+                // as part of the function making them
+                function => {
+                    return self
+                        .apply_function(function, call_args, call.position)
+                        .map_err(|err| {
+                            err.at(&func.file, call.position).unwound(
+                                func.name.as_deref(),
+                                caller,
+                                position,
+                            )
+                        })
+                }
+            }
+        }
+    }
+
+    fn eval_function_body(&mut self, func: &Function, args: Vec<Value>) -> Evaluated {
+        let env = Environment::new_enclosed(&func.env);
+        for ((param, param_type), arg) in
+            func.parameters.iter().zip(&func.parameter_types).zip(args)
+        {
+            match param_type {
+                Some(param_type) => {
+                    check_type_annotation(param_type, &arg, &func.env)
+                        .map_err(|err| err.at(&func.file, func.body.position()))?;
+                    env.borrow_mut()
+                        .set_annotated(&param.value, arg, param_type.clone());
+                }
+                None => env.borrow_mut().set(&param.value, arg),
+            }
+        }
+
+        self.tail = true;
+        self.eval_block_statement(&func.body, &env)
+    }
+
+    /// Makes a call left in tail position of a `return` that did not reach a
+    /// function: at the top level, or out of a `try` block.
+    fn make_tail_call(&mut self, call: TailCall) -> EvalResult {
+        self.apply_function(call.function, call.args, call.position)
+            .map_err(|err| err.at(&self.file, call.position))
+    }
+}
+
+fn check_arity(func: &Function, args: &[Value]) -> Result<(), RuntimeError> {
+    if func.parameters.len() != args.len() {
+        return Err(RuntimeError::with_kind(
+            ErrorKind::WrongArguments,
+            format!(
+                "wrong number of arguments. got={}, want={}",
+                args.len(),
+                func.parameters.len()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Builds an instance from `provided` field values, which must name every
+/// field of `struct_type` exactly once.
+pub(crate) fn new_struct(
+    struct_type: Rc<StructType>,
+    mut provided: Vec<(String, Value)>,
+) -> EvalResult {
+    if let Some((field, _)) = provided
+        .iter()
+        .find(|(field, _)| !struct_type.fields.contains(field))
+    {
+        return Err(RuntimeError::new(format!(
+            "struct {} has no field `{}`",
+            struct_type.name, field
+        )));
+    }
+
+    let mut fields = vec![];
+    for name in &struct_type.fields {
+        match provided.iter().position(|(k, _)| k == name) {
+            Some(i) => fields.push(provided.swap_remove(i)),
+            None => {
+                return Err(RuntimeError::new(format!(
+                    "missing field `{}` in initializer of struct {}",
+                    name, struct_type.name
+                )))
+            }
+        }
+    }
+    if let Some((name, _)) = provided.first() {
+        return Err(RuntimeError::new(format!(
+            "field `{}` specified more than once",
+            name
+        )));
+    }
+
+    Ok(Value::Struct(gc::track(Rc::new(RefCell::new(
+        StructValue {
+            struct_type,
+            fields,
+        },
+    )))))
+}
+
+/// Calls a variant constructor such as `Option.some`.
+pub(crate) fn construct_variant(
+    enum_type: Rc<EnumType>,
+    variant: usize,
+    args: Vec<Value>,
+) -> EvalResult {
+    let (name, arity) = &enum_type.variants[variant];
+    if *arity != args.len() {
+        return Err(RuntimeError::with_kind(
+            ErrorKind::WrongArguments,
+            format!(
+                "wrong number of arguments to {}.{}. got={}, want={}",
+                enum_type.name,
+                name,
+                args.len(),
+                arity
+            ),
+        ));
+    }
+    Ok(Value::EnumVariant(gc::track(Rc::new(EnumValue {
+        enum_type,
+        variant,
+        payload: args,
+    }))))
+}
+
+/// The error a `throw` raises: errors caught earlier are raised again as
+/// they were, any other value becomes the message of a new error.
+pub(crate) fn thrown_error(value: Value) -> RuntimeError {
+    match &value {
+        Value::Error(err) => (**err).clone(),
+        Value::Str(message) => RuntimeError::new(message.clone()),
+        other => RuntimeError::new(other.to_string()),
+    }
+}
+
+fn expect_expression(exp: &Option<Box<dyn Expression>>) -> Result<&dyn Expression, RuntimeError> {
+    exp.as_deref()
+        .ok_or_else(|| RuntimeError::new("missing expression"))
+}
+
+fn eval_identifier(ident: &Identifier, env: &Env) -> EvalResult {
+    if let Some(value) = env.borrow().get(&ident.value) {
+        return Ok(value);
+    }
+    if let Some(builtin) = builtins::lookup(&ident.value) {
+        return Ok(Value::Builtin(builtin));
+    }
+
+    Err(RuntimeError::with_kind(
+        ErrorKind::UnknownIdentifier,
+        format!("identifier not found: {}", ident.value),
+    ))
+}
+
+pub(crate) fn eval_enum_literal(lit: &EnumLiteral, name: &str) -> Value {
+    Value::Enum(Rc::new(EnumType {
+        name: name.to_owned(),
+        variants: lit
+            .variants
+            .iter()
+            .map(|v| (v.name.value.clone(), v.fields.len()))
+            .collect(),
+    }))
+}
+
+pub(crate) fn eval_struct_literal(lit: &StructLiteral, name: &str) -> Value {
+    Value::StructType(gc::track(Rc::new(StructType {
+        name: name.to_owned(),
+        fields: lit.fields.iter().map(|f| f.value.clone()).collect(),
+        methods: RefCell::default(),
+    })))
+}
+
+pub(crate) fn eval_interface_literal(lit: &InterfaceLiteral, name: &str) -> Value {
+    Value::Interface(Rc::new(InterfaceType {
+        name: name.to_owned(),
+        methods: lit
+            .methods
+            .iter()
+            .map(|m| (m.name.value.clone(), m.parameters.len()))
+            .collect(),
+    }))
+}
+
+fn new_function(
+    lit: &FunctionLiteral,
+    name: Option<&str>,
+    body: &Rc<BlockStatement>,
+    env: &Env,
+    file: &Rc<str>,
+) -> Rc<Function> {
+    gc::track(Rc::new(Function {
+        name: name.map(str::to_owned),
+        parameters: lit.parameters.clone(),
+        parameter_types: lit.parameter_types.clone(),
+        body: Rc::clone(body),
+        env: Rc::clone(env),
+        file: Rc::clone(file),
+    }))
+}
+
+/// Enforces annotations naming an interface in scope. Other annotations are
+/// not checked at runtime yet.
+fn check_type_annotation(
+    type_annotation: &TypeExpr,
+    value: &Value,
+    env: &Env,
+) -> Result<(), RuntimeError> {
+    let TypeExpr::Named(name) = type_annotation else {
+        return Ok(());
+    };
+    match env.borrow().get(&name.value) {
+        Some(type_value) => check_type(value, &type_value),
+        None => Ok(()),
+    }
+}
+
+/// Checks `value` against the value a type annotation resolved to.
+pub(crate) fn check_type(value: &Value, type_value: &Value) -> Result<(), RuntimeError> {
+    match type_value {
+        Value::Interface(interface) => check_conformance(value, interface),
+        _ => Ok(()),
+    }
+}
+
+fn check_conformance(value: &Value, interface: &InterfaceType) -> Result<(), RuntimeError> {
+    let methods = match value {
+        Value::Struct(value) => value.borrow().struct_type.methods.borrow().clone(),
+        _ => Default::default(),
+    };
+
+    let mut missing = vec![];
+    let mut mismatched = vec![];
+    for (name, arity) in &interface.methods {
+        // methods always take the receiver as their first parameter
+        match methods.get(name).and_then(Value::arity) {
+            None => missing.push(format!("`{}`", name)),
+            Some(params) if params - 1 != *arity => mismatched.push(format!(
+                "method `{}` takes {} argument(s) but the interface expects {}",
+                name,
+                params - 1,
+                arity
+            )),
+            Some(_) => (),
+        }
+    }
+    if missing.is_empty() && mismatched.is_empty() {
+        return Ok(());
+    }
+
+    let mut problems = vec![];
+    if !missing.is_empty() {
+        problems.push(format!("missing method(s) {}", missing.join(", ")));
+    }
+    problems.extend(mismatched);
+    Err(RuntimeError::with_kind(
+        ErrorKind::TypeMismatch,
+        format!(
+            "{} does not satisfy interface {}: {}",
+            value.describe_type(),
+            interface.name,
+            problems.join("; ")
+        ),
+    ))
+}
+
+pub(crate) fn variant_index(enum_type: &EnumType, variant: &str) -> Result<usize, RuntimeError> {
+    enum_type
+        .variants
+        .iter()
+        .position(|(name, _)| name == variant)
+        .ok_or_else(|| {
+            RuntimeError::new(format!(
+                "enum {} has no variant `{}`",
+                enum_type.name, variant
+            ))
+        })
+}
+
+pub(crate) fn eval_prefix_expression(operator: &str, right: Value) -> EvalResult {
+    match (operator, &right) {
+        ("!", _) => Ok(Value::Bool(!right.is_truthy())),
+        ("-", Value::Int(i)) => i
+            .checked_neg()
+            .map(Value::Int)
+            .ok_or_else(|| RuntimeError::with_kind(ErrorKind::Overflow, "integer overflow")),
+        ("-", Value::Float(x)) => Ok(Value::Float(-x)),
+        _ => Err(RuntimeError::with_kind(
+            ErrorKind::TypeMismatch,
+            format!("unknown operator: {}{}", operator, right.type_name()),
+        )),
+    }
+}
+
+pub(crate) fn eval_infix_expression(operator: &str, left: Value, right: Value) -> EvalResult {
+    match (&left, &right) {
+        // only reached when the left operand did not short-circuit
+        _ if operator == "&&" || operator == "||" => Ok(Value::Bool(right.is_truthy())),
+        (Value::Int(l), Value::Int(r)) => eval_integer_infix_expression(operator, *l, *r),
+        // an integer meeting a float is converted to a float
+        (Value::Float(l), Value::Float(r)) => eval_float_infix_expression(operator, *l, *r),
+        (Value::Int(l), Value::Float(r)) => eval_float_infix_expression(operator, *l as f64, *r),
+        (Value::Float(l), Value::Int(r)) => eval_float_infix_expression(operator, *l, *r as f64),
+        (Value::Str(l), Value::Str(r)) => match operator {
+            "+" => Ok(Value::Str(format!("{}{}", l, r))),
+            "==" => Ok(Value::Bool(l == r)),
+            "!=" => Ok(Value::Bool(l != r)),
+            "<" => Ok(Value::Bool(l < r)),
+            ">" => Ok(Value::Bool(l > r)),
+            "<=" => Ok(Value::Bool(l <= r)),
+            ">=" => Ok(Value::Bool(l >= r)),
+            _ => Err(unknown_infix_operator(operator, &left, &right)),
+        },
+        _ if operator == "==" => Ok(Value::Bool(left == right)),
+        _ if operator == "!=" => Ok(Value::Bool(left != right)),
+        _ if left.type_name() != right.type_name() => Err(RuntimeError::with_kind(
+            ErrorKind::TypeMismatch,
+            format!(
+                "type mismatch: {} {} {}",
+                left.type_name(),
+                operator,
+                right.type_name()
+            ),
+        )),
+        _ => Err(unknown_infix_operator(operator, &left, &right)),
+    }
+}
+
+fn eval_integer_infix_expression(operator: &str, left: i64, right: i64) -> EvalResult {
+    let overflow = || RuntimeError::with_kind(ErrorKind::Overflow, "integer overflow");
+    match operator {
+        "+" => left.checked_add(right).map(Value::Int).ok_or_else(overflow),
+        "-" => left.checked_sub(right).map(Value::Int).ok_or_else(overflow),
+        "*" => left.checked_mul(right).map(Value::Int).ok_or_else(overflow),
+        "/" => {
+            if right == 0 {
+                return Err(RuntimeError::with_kind(
+                    ErrorKind::DivisionByZero,
+                    "division by zero",
+                ));
+            }
+            left.checked_div(right).map(Value::Int).ok_or_else(overflow)
+        }
+        "%" => {
+            if right == 0 {
+                return Err(RuntimeError::with_kind(
+                    ErrorKind::DivisionByZero,
+                    "division by zero",
+                ));
+            }
+            left.checked_rem(right).map(Value::Int).ok_or_else(overflow)
+        }
+        "<" => Ok(Value::Bool(left < right)),
+        ">" => Ok(Value::Bool(left > right)),
+        "<=" => Ok(Value::Bool(left <= right)),
+        ">=" => Ok(Value::Bool(left >= right)),
+        "==" => Ok(Value::Bool(left == right)),
+        "!=" => Ok(Value::Bool(left != right)),
+        _ => Err(unknown_infix_operator(
+            operator,
+            &Value::Int(left),
+            &Value::Int(right),
+        )),
+    }
+}
+
+fn eval_float_infix_expression(operator: &str, left: f64, right: f64) -> EvalResult {
+    let arithmetic = |result: f64| {
+        if right == 0.0 && (operator == "/" || operator == "%") {
+            return Err(RuntimeError::with_kind(
+                ErrorKind::DivisionByZero,
+                "division by zero",
+            ));
+        }
+        float(result)
+    };
+    match operator {
+        "+" => arithmetic(left + right),
+        "-" => arithmetic(left - right),
+        "*" => arithmetic(left * right),
+        "/" => arithmetic(left / right),
+        "%" => arithmetic(left % right),
+        "<" => Ok(Value::Bool(left < right)),
+        ">" => Ok(Value::Bool(left > right)),
+        "<=" => Ok(Value::Bool(left <= right)),
+        ">=" => Ok(Value::Bool(left >= right)),
+        "==" => Ok(Value::Bool(left == right)),
+        "!=" => Ok(Value::Bool(left != right)),
+        _ => Err(unknown_infix_operator(
+            operator,
+            &Value::Float(left),
+            &Value::Float(right),
+        )),
+    }
+}
+
+/// A float computed from finite ones, which overflowed if it is not finite.
+pub(crate) fn float(x: f64) -> EvalResult {
+    if x.is_finite() {
+        Ok(Value::Float(x))
+    } else {
+        Err(RuntimeError::with_kind(
+            ErrorKind::Overflow,
+            "float overflow",
+        ))
+    }
+}
+
+fn unknown_infix_operator(operator: &str, left: &Value, right: &Value) -> RuntimeError {
+    RuntimeError::with_kind(
+        ErrorKind::TypeMismatch,
+        format!(
+            "unknown operator: {} {} {}",
+            left.type_name(),
+            operator,
+            right.type_name()
+        ),
+    )
+}
+
+pub(crate) fn eval_index_expression(left: Value, index: Value) -> EvalResult {
+    match (&left, &index) {
+        (Value::Array(elements), Value::Int(i)) => {
+            let elements = elements.borrow();
+            usize::try_from(*i)
+                .ok()
+                .and_then(|i| elements.get(i).cloned())
+                .ok_or_else(|| {
+                    RuntimeError::with_kind(
+                        ErrorKind::IndexOutOfBounds,
+                        format!(
+                            "index out of bounds: the length is {} but the index is {}",
+                            elements.len(),
+                            i
+                        ),
+                    )
+                })
+        }
+        (Value::Str(s), Value::Int(i)) => usize::try_from(*i)
+            .ok()
+            .and_then(|i| s.chars().nth(i))
+            .map(|c| Value::Str(c.to_string()))
+            .ok_or_else(|| {
+                RuntimeError::with_kind(
+                    ErrorKind::IndexOutOfBounds,
+                    format!(
+                        "index out of bounds: the length is {} but the index is {}",
+                        s.chars().count(),
+                        i
+                    ),
+                )
+            }),
+        (Value::Hash(pairs), _) => {
+            let key = index.hash_key().ok_or_else(|| {
+                RuntimeError::with_kind(
+                    ErrorKind::TypeMismatch,
+                    format!("unusable as hash key: {}", index.type_name()),
+                )
+            })?;
+            Ok(pairs.borrow().get(&key).cloned().unwrap_or(Value::Null))
+        }
+        _ => Err(RuntimeError::with_kind(
+            ErrorKind::TypeMismatch,
+            format!(
+                "index operator not supported: {}[{}]",
+                left.type_name(),
+                index.type_name()
+            ),
+        )),
+    }
+}
+
+pub(crate) fn assign_index(left: &Value, index: Value, value: Value) -> Result<(), RuntimeError> {
+    match (left, &index) {
+        (Value::Array(elements), Value::Int(i)) => {
+            let mut elements = elements.borrow_mut();
+            let len = elements.len();
+            let slot = usize::try_from(*i)
+                .ok()
+                .and_then(|i| elements.get_mut(i))
+                .ok_or_else(|| {
+                    RuntimeError::with_kind(
+                        ErrorKind::IndexOutOfBounds,
+                        format!(
+                            "index out of bounds: the length is {} but the index is {}",
+                            len, i
+                        ),
+                    )
+                })?;
+            *slot = value;
+            Ok(())
+        }
+        (Value::Hash(pairs), _) => {
+            let key = index.hash_key().ok_or_else(|| {
+                RuntimeError::with_kind(
+                    ErrorKind::TypeMismatch,
+                    format!("unusable as hash key: {}", index.type_name()),
+                )
+            })?;
+            pairs.borrow_mut().insert(key, value);
+            Ok(())
+        }
+        _ => Err(RuntimeError::with_kind(
+            ErrorKind::TypeMismatch,
+            format!(
+                "index assignment not supported: {}[{}]",
+                left.type_name(),
+                index.type_name()
+            ),
+        )),
+    }
+}
+
+pub(crate) fn assign_member(
+    object: &Value,
+    property: &str,
+    value: Value,
+) -> Result<(), RuntimeError> {
+    match object {
+        Value::Struct(instance) => {
+            let mut instance = instance.borrow_mut();
+            let name = instance.struct_type.name.clone();
+            match instance.fields.iter_mut().find(|(k, _)| k == property) {
+                Some((_, slot)) => {
+                    *slot = value;
+                    Ok(())
+                }
+                None => Err(RuntimeError::new(format!(
+                    "struct {} has no field `{}`",
+                    name, property
+                ))),
+            }
+        }
+        _ => Err(RuntimeError::with_kind(
+            ErrorKind::TypeMismatch,
+            format!(
+                "cannot assign to property `{}` of {}",
+                property,
+                object.type_name()
+            ),
+        )),
+    }
+}
+
+pub(crate) fn eval_member_expression(object: Value, property: &str) -> EvalResult {
+    match &object {
+        Value::Enum(enum_type) => {
+            let index = variant_index(enum_type, property)?;
+            if enum_type.variants[index].1 == 0 {
+                return Ok(Value::EnumVariant(gc::track(Rc::new(EnumValue {
+                    enum_type: Rc::clone(enum_type),
+                    variant: index,
+                    payload: vec![],
+                }))));
+            }
+            Ok(Value::VariantConstructor(Rc::clone(enum_type), index))
+        }
+        Value::Struct(value) => {
+            let instance = value.borrow();
+            if let Some(field) = instance.get(property) {
+                return Ok(field.clone());
+            }
+            let method = instance.struct_type.methods.borrow().get(property).cloned();
+            match method {
+                Some(function) => Ok(Value::BoundMethod(gc::track(Rc::new(BoundMethod {
+                    receiver: object.clone(),
+                    name: property.to_owned(),
+                    function,
+                })))),
+                None => Err(RuntimeError::new(format!(
+                    "struct {} has no field or method `{}`",
+                    instance.struct_type.name, property
+                ))),
+            }
+        }
+        Value::Host(host) if host.has_method(property) => {
+            let method = {
+                let host = Rc::clone(host);
+                let name = property.to_owned();
+                // the receiver comes first, as for methods of structs
+                move |args: &[Value]| host.call_method(&name, &args[1..])
+            };
+            let function = Value::Native(Rc::new(NativeFunction {
+                name: format!("{}.{}", host.type_name(), property),
+                func: Box::new(method),
+            }));
+            Ok(Value::BoundMethod(gc::track(Rc::new(BoundMethod {
+                receiver: object.clone(),
+                name: property.to_owned(),
+                function,
+            }))))
+        }
+        Value::Host(host) => Err(RuntimeError::new(format!(
+            "{} has no method `{}`",
+            host.type_name(),
+            property
+        ))),
+        Value::Module(module) => module.exports.get(property).cloned().ok_or_else(|| {
+            RuntimeError::with_kind(
+                ErrorKind::UnknownIdentifier,
+                format!("module {} has no export `{}`", module.name, property),
+            )
+        }),
+        Value::Error(err) => err
+            .property(property)
+            .ok_or_else(|| RuntimeError::new(format!("error has no property `{}`", property))),
+        _ => Err(RuntimeError::new(format!(
+            "type {} has no property `{}`",
+            object.type_name(),
+            property
+        ))),
+    }
+}
@@ -0,0 +1,47 @@
+use std::{any::Any, fmt, rc::Rc};
+
+use super::value::{RuntimeError, Value};
+
+/// A Rust object the host hands to scripts as it is, such as a database
+/// cursor, for them to call methods on with `obj.method(args)`.
+///
+/// Methods take `&self`, since scripts may share the object; one that
+/// changes it keeps its state in a `Cell` or `RefCell`. The object is
+/// dropped once no value refers to it, or once the collector frees the
+/// values that did. Values the object holds itself are not visible to the
+/// collector, so they stay alive while it does.
+pub trait HostObject: Any {
+    /// The name of the type scripts see, in errors and by default when the
+    /// object is printed.
+    fn type_name(&self) -> &str;
+
+    /// Whether `obj.name` is a method of the object.
+    fn has_method(&self, name: &str) -> bool;
+
+    /// Calls the method `name`, which `has_method` accepted, with `args`.
+    fn call_method(&self, name: &str, args: &[Value]) -> Result<Value, RuntimeError>;
+
+    /// Whether the object equals `other`, which is never the same object.
+    fn equals(&self, _other: &dyn HostObject) -> bool {
+        false
+    }
+
+    /// Writes the object the way `print` shows it.
+    fn display(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {{}}", self.type_name())
+    }
+}
+
+impl dyn HostObject {
+    /// The object as the Rust type it is, if it is a `T`.
+    pub fn downcast_ref<T: HostObject>(&self) -> Option<&T> {
+        (self as &dyn Any).downcast_ref()
+    }
+}
+
+impl Value {
+    /// Wraps a Rust object for scripts.
+    pub fn host(object: impl HostObject) -> Value {
+        Value::Host(Rc::new(object))
+    }
+}
@@ -0,0 +1,115 @@
+use std::time::{Duration, Instant};
+
+use crate::gc::{allocator, gc};
+
+use super::value::{ErrorKind, RuntimeError};
+
+/// Calls a program may nest before failing with a `StackOverflow` error.
+pub const DEFAULT_MAX_DEPTH: usize = 10_000;
+
+/// Steps between checks of the deadline, which reads the clock.
+const DEADLINE_INTERVAL: u64 = 1024;
+
+/// Bounds on the resources a program may use, enforced alike by the
+/// interpreter and the vm.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    /// Function calls that may be running at once. Calls in tail position
+    /// replace the call making them, so they do not count.
+    pub max_depth: usize,
+    /// Steps the program may run: nodes evaluated by the interpreter, or
+    /// instructions run by the vm.
+    pub max_steps: Option<u64>,
+    /// How long the program may run.
+    pub timeout: Option<Duration>,
+    /// Bytes the program may allocate on top of what was allocated when it
+    /// started. Only enforced with `CountingAllocator` as the global
+    /// allocator.
+    pub max_memory: Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_steps: None,
+            timeout: None,
+            max_memory: None,
+        }
+    }
+}
+
+impl Limits {
+    /// Fails a call made while `depth` calls are running if there is no
+    /// room for another one.
+    pub fn check_depth(&self, depth: usize) -> Result<(), RuntimeError> {
+        if depth >= self.max_depth {
+            return Err(RuntimeError::with_kind(
+                ErrorKind::StackOverflow,
+                format!("maximum call depth of {} exceeded", self.max_depth),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// What a run of a program has used of its limits.
+#[derive(Default, Clone)]
+pub struct Budget {
+    limits: Limits,
+    steps: u64,
+    deadline: Option<Instant>,
+    /// Bytes allocated by the thread when the run started.
+    allocated: isize,
+}
+
+impl Budget {
+    pub fn start(limits: Limits) -> Self {
+        Self {
+            limits,
+            steps: 0,
+            // a timeout too long to add to the clock never runs out
+            deadline: limits
+                .timeout
+                .and_then(|timeout| Instant::now().checked_add(timeout)),
+            allocated: allocator::allocated(),
+        }
+    }
+
+    /// Counts a step of the program, failing once it is over budget.
+    pub fn step(&mut self) -> Result<(), RuntimeError> {
+        self.steps += 1;
+        if let Some(max_steps) = self.limits.max_steps {
+            if self.steps > max_steps {
+                return Err(RuntimeError::with_kind(
+                    ErrorKind::StepLimit,
+                    format!("step limit of {} exceeded", max_steps),
+                ));
+            }
+        }
+        if let (Some(deadline), Some(timeout)) = (self.deadline, self.limits.timeout) {
+            if self.steps.is_multiple_of(DEADLINE_INTERVAL) && Instant::now() >= deadline {
+                return Err(RuntimeError::with_kind(
+                    ErrorKind::Timeout,
+                    format!("time limit of {:?} exceeded", timeout),
+                ));
+            }
+        }
+        if let Some(max_memory) = self.limits.max_memory {
+            // garbage is not held against the program
+            if self.used_memory() > max_memory
+                && (gc::collect() == 0 || self.used_memory() > max_memory)
+            {
+                return Err(RuntimeError::with_kind(
+                    ErrorKind::MemoryLimit,
+                    format!("memory limit of {} bytes exceeded", max_memory),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn used_memory(&self) -> usize {
+        (allocator::allocated() - self.allocated).max(0) as usize
+    }
+}
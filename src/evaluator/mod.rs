@@ -0,0 +1,12 @@
+pub mod builtins;
+pub mod capabilities;
+pub mod environment;
+#[allow(clippy::module_inception)]
+pub mod evaluator;
+pub mod host;
+pub mod limits;
+pub mod stdlib;
+pub mod value;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,394 @@
+//! `std/math`: numeric functions. Functions that only move a number around,
+//! such as `abs`, `min` and `clamp`, keep integers integers; the ones that
+//! compute with fractions, such as `sqrt` and `sin`, take either and return
+//! floats. Integer results that do not fit raise `Overflow` errors, and so
+//! do floats that would overflow to infinity.
+
+use std::cmp::Ordering;
+
+use crate::evaluator::{
+    builtins::{check_arity, check_arity_between, unsupported, Builtin},
+    capabilities::Capabilities,
+    evaluator::float,
+    value::{ErrorKind, RuntimeError, Value},
+};
+
+use super::int_arg;
+
+pub static BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "abs",
+        func: abs,
+    },
+    Builtin {
+        name: "min",
+        func: min,
+    },
+    Builtin {
+        name: "max",
+        func: max,
+    },
+    Builtin {
+        name: "clamp",
+        func: clamp,
+    },
+    Builtin {
+        name: "pow",
+        func: pow,
+    },
+    Builtin {
+        name: "sqrt",
+        func: sqrt,
+    },
+    Builtin {
+        name: "floor",
+        func: floor,
+    },
+    Builtin {
+        name: "ceil",
+        func: ceil,
+    },
+    Builtin {
+        name: "round",
+        func: round,
+    },
+    Builtin {
+        name: "sin",
+        func: sin,
+    },
+    Builtin {
+        name: "cos",
+        func: cos,
+    },
+    Builtin {
+        name: "tan",
+        func: tan,
+    },
+    Builtin {
+        name: "asin",
+        func: asin,
+    },
+    Builtin {
+        name: "acos",
+        func: acos,
+    },
+    Builtin {
+        name: "atan",
+        func: atan,
+    },
+    Builtin {
+        name: "atan2",
+        func: atan2,
+    },
+    Builtin {
+        name: "exp",
+        func: exp,
+    },
+    Builtin {
+        name: "log",
+        func: log,
+    },
+    Builtin {
+        name: "gcd",
+        func: gcd,
+    },
+    Builtin {
+        name: "lcm",
+        func: lcm,
+    },
+];
+
+pub fn constants() -> Vec<(&'static str, Value)> {
+    vec![
+        ("PI", Value::Float(std::f64::consts::PI)),
+        ("E", Value::Float(std::f64::consts::E)),
+    ]
+}
+
+fn overflow() -> RuntimeError {
+    RuntimeError::with_kind(ErrorKind::Overflow, "integer overflow")
+}
+
+fn invalid(name: &str, message: impl std::fmt::Display) -> RuntimeError {
+    RuntimeError::with_kind(
+        ErrorKind::WrongArguments,
+        format!("argument to `{}` {}", name, message),
+    )
+}
+
+/// Reads a number of either kind as a float.
+fn number_arg(name: &str, arg: &Value) -> Result<f64, RuntimeError> {
+    match arg {
+        Value::Int(i) => Ok(*i as f64),
+        Value::Float(x) => Ok(*x),
+        other => Err(unsupported(name, other)),
+    }
+}
+
+/// Orders two numbers, exactly when both are integers.
+fn compare(name: &str, a: &Value, b: &Value) -> Result<Ordering, RuntimeError> {
+    if let (Value::Int(a), Value::Int(b)) = (a, b) {
+        return Ok(a.cmp(b));
+    }
+    let (a, b) = (number_arg(name, a)?, number_arg(name, b)?);
+    Ok(a.partial_cmp(&b).unwrap_or(Ordering::Equal))
+}
+
+fn abs(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("abs", args, 1)?;
+    match &args[0] {
+        Value::Int(i) => i.checked_abs().map(Value::Int).ok_or_else(overflow),
+        Value::Float(x) => Ok(Value::Float(x.abs())),
+        other => Err(unsupported("abs", other)),
+    }
+}
+
+fn min(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    extreme("min", args, Ordering::Less)
+}
+
+fn max(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    extreme("max", args, Ordering::Greater)
+}
+
+/// The smallest or largest of the numbers `min` or `max` was given, either
+/// as its arguments or as one array. Ties go to the first.
+fn extreme(name: &str, args: &[Value], wanted: Ordering) -> Result<Value, RuntimeError> {
+    let numbers = match args {
+        [Value::Array(elements)] => elements.borrow().clone(),
+        _ => args.to_vec(),
+    };
+    let Some((first, rest)) = numbers.split_first() else {
+        return Err(invalid(name, "must be at least one number"));
+    };
+    // a lone number is still checked to be one
+    number_arg(name, first)?;
+    let mut best = first;
+    for number in rest {
+        if compare(name, number, best)? == wanted {
+            best = number;
+        }
+    }
+    Ok(best.clone())
+}
+
+fn clamp(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("clamp", args, 3)?;
+    let [x, low, high] = args else {
+        unreachable!("the arity was checked")
+    };
+    number_arg("clamp", x)?;
+    if compare("clamp", low, high)? == Ordering::Greater {
+        return Err(invalid(
+            "clamp",
+            format!(
+                "needs a lower bound no greater than its upper bound, got {} and {}",
+                low, high
+            ),
+        ));
+    }
+    Ok(if compare("clamp", x, low)? == Ordering::Less {
+        low.clone()
+    } else if compare("clamp", x, high)? == Ordering::Greater {
+        high.clone()
+    } else {
+        x.clone()
+    })
+}
+
+/// An integer to a non-negative integer power is an integer; any other
+/// power is a float.
+fn pow(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("pow", args, 2)?;
+    if let (Value::Int(base), Value::Int(exponent)) = (&args[0], &args[1]) {
+        if *exponent >= 0 {
+            return int_pow(*base, *exponent);
+        }
+    }
+    let base = number_arg("pow", &args[0])?;
+    let exponent = number_arg("pow", &args[1])?;
+    if base == 0.0 && exponent < 0.0 {
+        return Err(RuntimeError::with_kind(
+            ErrorKind::DivisionByZero,
+            "division by zero",
+        ));
+    }
+    let result = base.powf(exponent);
+    if result.is_nan() {
+        return Err(invalid(
+            "pow",
+            "cannot raise a negative number to a fractional power",
+        ));
+    }
+    float(result)
+}
+
+fn int_pow(base: i64, exponent: i64) -> Result<Value, RuntimeError> {
+    match (base, u32::try_from(exponent)) {
+        (_, Ok(exponent)) => base
+            .checked_pow(exponent)
+            .map(Value::Int)
+            .ok_or_else(overflow),
+        // exponents too large for `checked_pow` only fit for these bases
+        (0 | 1, Err(_)) => Ok(Value::Int(base)),
+        (-1, Err(_)) => Ok(Value::Int(if exponent % 2 == 0 { 1 } else { -1 })),
+        (_, Err(_)) => Err(overflow()),
+    }
+}
+
+fn sqrt(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("sqrt", args, 1)?;
+    let x = number_arg("sqrt", &args[0])?;
+    if x < 0.0 {
+        return Err(invalid(
+            "sqrt",
+            format!("must not be negative, got {}", args[0]),
+        ));
+    }
+    float(x.sqrt())
+}
+
+fn floor(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    rounded("floor", args, f64::floor)
+}
+
+fn ceil(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    rounded("ceil", args, f64::ceil)
+}
+
+/// Rounds halfway cases away from zero.
+fn round(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    rounded("round", args, f64::round)
+}
+
+/// Rounds a float to an integer, which integers already are.
+fn rounded(name: &str, args: &[Value], round: fn(f64) -> f64) -> Result<Value, RuntimeError> {
+    check_arity(name, args, 1)?;
+    match &args[0] {
+        Value::Int(i) => Ok(Value::Int(*i)),
+        Value::Float(x) => {
+            let x = round(*x);
+            // i64::MAX is not a float, so the upper bound is exclusive
+            if (-9_223_372_036_854_775_808.0..9_223_372_036_854_775_808.0).contains(&x) {
+                Ok(Value::Int(x as i64))
+            } else {
+                Err(RuntimeError::with_kind(
+                    ErrorKind::Overflow,
+                    format!("{:?} is too large for an integer", x),
+                ))
+            }
+        }
+        other => Err(unsupported(name, other)),
+    }
+}
+
+/// Applies a float function that is defined for every number.
+fn unary(name: &str, args: &[Value], f: fn(f64) -> f64) -> Result<Value, RuntimeError> {
+    check_arity(name, args, 1)?;
+    float(f(number_arg(name, &args[0])?))
+}
+
+fn sin(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    unary("sin", args, f64::sin)
+}
+
+fn cos(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    unary("cos", args, f64::cos)
+}
+
+fn tan(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    unary("tan", args, f64::tan)
+}
+
+fn atan(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    unary("atan", args, f64::atan)
+}
+
+fn exp(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    unary("exp", args, f64::exp)
+}
+
+fn asin(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    inverse("asin", args, f64::asin)
+}
+
+fn acos(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    inverse("acos", args, f64::acos)
+}
+
+/// Applies `asin` or `acos`, which are only defined from -1 to 1.
+fn inverse(name: &str, args: &[Value], f: fn(f64) -> f64) -> Result<Value, RuntimeError> {
+    check_arity(name, args, 1)?;
+    let x = number_arg(name, &args[0])?;
+    if !(-1.0..=1.0).contains(&x) {
+        return Err(invalid(
+            name,
+            format!("must be between -1 and 1, got {}", args[0]),
+        ));
+    }
+    float(f(x))
+}
+
+/// The angle of the point (x, y), taking y first like most languages do.
+fn atan2(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("atan2", args, 2)?;
+    let y = number_arg("atan2", &args[0])?;
+    let x = number_arg("atan2", &args[1])?;
+    float(y.atan2(x))
+}
+
+/// The natural logarithm, or the logarithm to the base given second.
+fn log(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity_between("log", args, 1, 2)?;
+    let x = number_arg("log", &args[0])?;
+    if x <= 0.0 {
+        return Err(invalid("log", format!("must be positive, got {}", args[0])));
+    }
+    match args.get(1) {
+        Some(base) => {
+            let b = number_arg("log", base)?;
+            if b <= 0.0 || b == 1.0 {
+                return Err(invalid(
+                    "log",
+                    format!("must have a positive base other than 1, got {}", base),
+                ));
+            }
+            float(x.log(b))
+        }
+        None => float(x.ln()),
+    }
+}
+
+/// The greatest common divisor, which is never negative.
+fn gcd(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("gcd", args, 2)?;
+    let a = int_arg("gcd", &args[0])?;
+    let b = int_arg("gcd", &args[1])?;
+    i64::try_from(unsigned_gcd(a, b))
+        .map(Value::Int)
+        .map_err(|_| overflow())
+}
+
+/// The least common multiple, which is never negative, and 0 if either
+/// argument is.
+fn lcm(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("lcm", args, 2)?;
+    let a = int_arg("lcm", &args[0])?;
+    let b = int_arg("lcm", &args[1])?;
+    if a == 0 || b == 0 {
+        return Ok(Value::Int(0));
+    }
+    (a.unsigned_abs() / unsigned_gcd(a, b))
+        .checked_mul(b.unsigned_abs())
+        .and_then(|lcm| i64::try_from(lcm).ok())
+        .map(Value::Int)
+        .ok_or_else(overflow)
+}
+
+fn unsigned_gcd(a: i64, b: i64) -> u64 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
@@ -0,0 +1,78 @@
+use super::{
+    builtins::{unsupported, Builtin},
+    value::{RuntimeError, Value},
+};
+
+pub mod math;
+pub mod string;
+
+#[cfg(test)]
+mod test;
+
+/// What imports of the standard library start with, as in `import
+/// "std/string" as string;`. Files cannot be imported by such paths.
+pub const PREFIX: &str = "std/";
+
+/// A module of the standard library, which exports builtins and constants.
+pub struct StdModule {
+    pub name: &'static str,
+    pub builtins: &'static [Builtin],
+    pub constants: fn() -> Vec<(&'static str, Value)>,
+}
+
+pub static MODULES: &[StdModule] = &[
+    StdModule {
+        name: "math",
+        builtins: math::BUILTINS,
+        constants: math::constants,
+    },
+    StdModule {
+        name: "string",
+        builtins: string::BUILTINS,
+        constants: Vec::new,
+    },
+];
+
+/// The module of the standard library an import names, or `None` if it
+/// does not import from the standard library.
+pub fn lookup(import: &str) -> Option<Result<&'static StdModule, String>> {
+    let name = import.strip_prefix(PREFIX)?;
+    Some(
+        MODULES
+            .iter()
+            .find(|module| module.name == name)
+            .ok_or_else(|| format!("no module {:?} in the standard library", import)),
+    )
+}
+
+impl StdModule {
+    /// The module as `import` binds it.
+    pub fn to_value(&'static self) -> Value {
+        let exports = self
+            .builtins
+            .iter()
+            .map(|builtin| (builtin.name.to_owned(), Value::Builtin(builtin)))
+            .chain(
+                (self.constants)()
+                    .into_iter()
+                    .map(|(name, value)| (name.to_owned(), value)),
+            )
+            .collect();
+
+        Value::module(self.name.to_owned(), exports)
+    }
+}
+
+pub(crate) fn string_arg<'a>(name: &str, arg: &'a Value) -> Result<&'a str, RuntimeError> {
+    match arg {
+        Value::Str(s) => Ok(s),
+        other => Err(unsupported(name, other)),
+    }
+}
+
+pub(crate) fn int_arg(name: &str, arg: &Value) -> Result<i64, RuntimeError> {
+    match arg {
+        Value::Int(i) => Ok(*i),
+        other => Err(unsupported(name, other)),
+    }
+}
@@ -0,0 +1,278 @@
+//! `std/string`: functions over strings. Indices, lengths and widths all
+//! count characters, the way `len` and indexing do, never bytes.
+
+use crate::evaluator::{
+    builtins::{check_arity, check_arity_between, unsupported, Builtin},
+    capabilities::Capabilities,
+    value::{ErrorKind, RuntimeError, Value},
+};
+
+use super::{int_arg, string_arg};
+
+pub static BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "split",
+        func: split,
+    },
+    Builtin {
+        name: "join",
+        func: join,
+    },
+    Builtin {
+        name: "trim",
+        func: trim,
+    },
+    Builtin {
+        name: "replace",
+        func: replace,
+    },
+    Builtin {
+        name: "contains",
+        func: contains,
+    },
+    Builtin {
+        name: "starts_with",
+        func: starts_with,
+    },
+    Builtin {
+        name: "ends_with",
+        func: ends_with,
+    },
+    Builtin {
+        name: "to_upper",
+        func: to_upper,
+    },
+    Builtin {
+        name: "to_lower",
+        func: to_lower,
+    },
+    Builtin {
+        name: "chars",
+        func: chars,
+    },
+    Builtin {
+        name: "repeat",
+        func: repeat,
+    },
+    Builtin {
+        name: "pad_left",
+        func: pad_left,
+    },
+    Builtin {
+        name: "pad_right",
+        func: pad_right,
+    },
+    Builtin {
+        name: "index_of",
+        func: index_of,
+    },
+    Builtin {
+        name: "substring",
+        func: substring,
+    },
+];
+
+fn invalid(name: &str, message: impl std::fmt::Display) -> RuntimeError {
+    RuntimeError::with_kind(
+        ErrorKind::WrongArguments,
+        format!("argument to `{}` {}", name, message),
+    )
+}
+
+fn strings<'a>(iter: impl Iterator<Item = &'a str>) -> Value {
+    Value::array(iter.map(|s| Value::Str(s.to_owned())).collect())
+}
+
+/// The pieces of a string between a separator; an empty separator splits
+/// it into characters.
+fn split(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("split", args, 2)?;
+    let s = string_arg("split", &args[0])?;
+    let separator = string_arg("split", &args[1])?;
+    if separator.is_empty() {
+        return Ok(characters(s));
+    }
+    Ok(strings(s.split(separator)))
+}
+
+fn join(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("join", args, 2)?;
+    let separator = string_arg("join", &args[1])?;
+    match &args[0] {
+        Value::Array(elements) => {
+            let elements = elements.borrow();
+            let parts = elements
+                .iter()
+                .map(|element| string_arg("join", element))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Value::Str(parts.join(separator)))
+        }
+        other => Err(unsupported("join", other)),
+    }
+}
+
+fn trim(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("trim", args, 1)?;
+    Ok(Value::Str(string_arg("trim", &args[0])?.trim().to_owned()))
+}
+
+/// Replaces every occurrence of a non-empty pattern.
+fn replace(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("replace", args, 3)?;
+    let s = string_arg("replace", &args[0])?;
+    let from = string_arg("replace", &args[1])?;
+    let to = string_arg("replace", &args[2])?;
+    if from.is_empty() {
+        return Err(invalid("replace", "must not be an empty pattern"));
+    }
+    Ok(Value::Str(s.replace(from, to)))
+}
+
+fn contains(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("contains", args, 2)?;
+    let s = string_arg("contains", &args[0])?;
+    Ok(Value::Bool(s.contains(string_arg("contains", &args[1])?)))
+}
+
+fn starts_with(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("starts_with", args, 2)?;
+    let s = string_arg("starts_with", &args[0])?;
+    Ok(Value::Bool(
+        s.starts_with(string_arg("starts_with", &args[1])?),
+    ))
+}
+
+fn ends_with(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("ends_with", args, 2)?;
+    let s = string_arg("ends_with", &args[0])?;
+    Ok(Value::Bool(s.ends_with(string_arg("ends_with", &args[1])?)))
+}
+
+fn to_upper(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("to_upper", args, 1)?;
+    Ok(Value::Str(string_arg("to_upper", &args[0])?.to_uppercase()))
+}
+
+fn to_lower(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("to_lower", args, 1)?;
+    Ok(Value::Str(string_arg("to_lower", &args[0])?.to_lowercase()))
+}
+
+fn chars(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("chars", args, 1)?;
+    Ok(characters(string_arg("chars", &args[0])?))
+}
+
+fn characters(s: &str) -> Value {
+    Value::array(s.chars().map(|c| Value::Str(c.to_string())).collect())
+}
+
+fn repeat(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("repeat", args, 2)?;
+    let s = string_arg("repeat", &args[0])?;
+    let count = count_arg("repeat", &args[1])?;
+    let too_long = || RuntimeError::with_kind(ErrorKind::Overflow, "string too long to repeat");
+    let len = s.len().checked_mul(count).ok_or_else(too_long)?;
+    let mut repeated = String::new();
+    repeated.try_reserve_exact(len).map_err(|_| too_long())?;
+    for _ in 0..count {
+        repeated.push_str(s);
+    }
+    Ok(Value::Str(repeated))
+}
+
+fn pad_left(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    let (s, pad, missing) = padding("pad_left", args)?;
+    let mut padded = reserve_padded(s, pad, missing)?;
+    padded.extend(std::iter::repeat_n(pad, missing));
+    padded.push_str(s);
+    Ok(Value::Str(padded))
+}
+
+fn pad_right(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    let (s, pad, missing) = padding("pad_right", args)?;
+    let mut padded = reserve_padded(s, pad, missing)?;
+    padded.push_str(s);
+    padded.extend(std::iter::repeat_n(pad, missing));
+    Ok(Value::Str(padded))
+}
+
+/// The string `pad_left` or `pad_right` was given, the character to pad it
+/// with, spaces unless one was passed, and how many of them pad it out to
+/// its width.
+fn padding<'a>(name: &str, args: &'a [Value]) -> Result<(&'a str, char, usize), RuntimeError> {
+    check_arity_between(name, args, 2, 3)?;
+    let s = string_arg(name, &args[0])?;
+    let width = count_arg(name, &args[1])?;
+    let pad = match args.get(2) {
+        Some(pad) => {
+            let mut chars = string_arg(name, pad)?.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => return Err(invalid(name, "must pad with a single character")),
+            }
+        }
+        None => ' ',
+    };
+    let missing = width.saturating_sub(s.chars().count());
+    Ok((s, pad, missing))
+}
+
+/// An empty string with room for `s` padded with `missing` characters `pad`,
+/// failing rather than aborting when there is no memory for it.
+fn reserve_padded(s: &str, pad: char, missing: usize) -> Result<String, RuntimeError> {
+    let too_long = || RuntimeError::with_kind(ErrorKind::Overflow, "string too long to pad");
+    let len = pad
+        .len_utf8()
+        .checked_mul(missing)
+        .and_then(|len| len.checked_add(s.len()))
+        .ok_or_else(too_long)?;
+    let mut padded = String::new();
+    padded.try_reserve_exact(len).map_err(|_| too_long())?;
+    Ok(padded)
+}
+
+/// Where a substring first starts, or null if it does not occur.
+fn index_of(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity("index_of", args, 2)?;
+    let s = string_arg("index_of", &args[0])?;
+    let needle = string_arg("index_of", &args[1])?;
+    Ok(match s.find(needle) {
+        Some(byte) => Value::Int(s[..byte].chars().count() as i64),
+        None => Value::Null,
+    })
+}
+
+/// The characters from a start index up to, but not including, an end
+/// index, which defaults to the length of the string.
+fn substring(_: &Capabilities, args: &[Value]) -> Result<Value, RuntimeError> {
+    check_arity_between("substring", args, 2, 3)?;
+    let s = string_arg("substring", &args[0])?;
+    let len = s.chars().count() as i64;
+    let start = int_arg("substring", &args[1])?;
+    let end = match args.get(2) {
+        Some(end) => int_arg("substring", end)?,
+        None => len,
+    };
+    if start < 0 || end < start || end > len {
+        return Err(RuntimeError::with_kind(
+            ErrorKind::IndexOutOfBounds,
+            format!(
+                "substring out of bounds: the length is {} but the range is {}..{}",
+                len, start, end
+            ),
+        ));
+    }
+    Ok(Value::Str(
+        s.chars()
+            .skip(start as usize)
+            .take((end - start) as usize)
+            .collect(),
+    ))
+}
+
+fn count_arg(name: &str, arg: &Value) -> Result<usize, RuntimeError> {
+    let count = int_arg(name, arg)?;
+    usize::try_from(count)
+        .map_err(|_| invalid(name, format!("must not be negative, got {}", count)))
+}
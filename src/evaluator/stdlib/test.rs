@@ -0,0 +1,195 @@
+use crate::test_utils::{parse, run_both};
+
+fn check(tests: &[(&str, Result<&str, &str>)], prelude: &str) {
+    for (input, expected) in tests {
+        let input = format!("{}\n{}", prelude, input);
+        for (engine, result) in run_both(&parse(&input), None) {
+            assert_eq!(
+                result.as_deref().map_err(String::as_str),
+                *expected,
+                "{} ran {:?} differently",
+                engine,
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn test_string_module() {
+    let tests = [
+        (r#"s.split("a,b,,c", ",")"#, Ok(r#"["a", "b", "", "c"]"#)),
+        (
+            r#"s.split("héllo", "")"#,
+            Ok(r#"["h", "é", "l", "l", "o"]"#),
+        ),
+        (r#"s.join(["a", "b", "c"], ", ")"#, Ok("a, b, c")),
+        (r#"s.join([], "-")"#, Ok("")),
+        (r#"s.trim("  padded \n")"#, Ok("padded")),
+        (r#"s.replace("a-b-c", "-", "+")"#, Ok("a+b+c")),
+        (r#"s.contains("haystack", "st")"#, Ok("true")),
+        (r#"s.contains("haystack", "needle")"#, Ok("false")),
+        (r#"s.starts_with("prolang", "pro")"#, Ok("true")),
+        (r#"s.ends_with("prolang", "pro")"#, Ok("false")),
+        (r#"s.to_upper("straße")"#, Ok("STRASSE")),
+        (r#"s.to_lower("ÀÉÎ")"#, Ok("àéî")),
+        (r#"s.chars("añb")"#, Ok(r#"["a", "ñ", "b"]"#)),
+        (r#"s.repeat("ab", 3)"#, Ok("ababab")),
+        (r#"s.repeat("ab", 0)"#, Ok("")),
+        (r#"s.pad_left("7", 3, "0")"#, Ok("007")),
+        (r#"s.pad_right("é", 3)"#, Ok("é  ")),
+        (r#"s.pad_left("long", 2)"#, Ok("long")),
+        (r#"s.index_of("añbñ", "b")"#, Ok("2")),
+        (r#"s.index_of("abc", "z")"#, Ok("null")),
+        (r#"s.substring("héllo", 1, 3)"#, Ok("él")),
+        (r#"s.substring("héllo", 2)"#, Ok("llo")),
+        (r#"s.substring("héllo", 5)"#, Ok("")),
+        (
+            "s.split(1, \",\")",
+            Err("argument to `split` not supported, got INT"),
+        ),
+        (
+            r#"s.join(["a", 1], "")"#,
+            Err("argument to `join` not supported, got INT"),
+        ),
+        (
+            r#"s.trim("a", "b")"#,
+            Err("wrong number of arguments to `trim`. got=2, want=1"),
+        ),
+        (
+            r#"s.pad_left("a")"#,
+            Err("wrong number of arguments to `pad_left`. got=1, want=2 to 3"),
+        ),
+        (
+            r#"s.pad_left("a", 3, "ab")"#,
+            Err("argument to `pad_left` must pad with a single character"),
+        ),
+        (
+            r#"s.repeat("a", -1)"#,
+            Err("argument to `repeat` must not be negative, got -1"),
+        ),
+        (
+            r#"s.replace("abc", "", "x")"#,
+            Err("argument to `replace` must not be an empty pattern"),
+        ),
+        (
+            r#"s.substring("abc", 2, 4)"#,
+            Err("substring out of bounds: the length is 3 but the range is 2..4"),
+        ),
+        (
+            r#"try { s.substring("abc", 2, 1) } catch (e) { e.kind }"#,
+            Ok("IndexOutOfBounds"),
+        ),
+        (
+            r#"try { s.repeat("abc", 9223372036854775807) } catch (e) { e.kind }"#,
+            Ok("Overflow"),
+        ),
+        (
+            r#"s.repeat("a", 4611686018427387904)"#,
+            Err("string too long to repeat"),
+        ),
+        (
+            r#"try { s.pad_left("x", 9223372036854775807) } catch (e) { e.kind }"#,
+            Ok("Overflow"),
+        ),
+        (
+            r#"s.pad_right("x", 4611686018427387904, "é")"#,
+            Err("string too long to pad"),
+        ),
+        ("s.missing", Err("module string has no export `missing`")),
+    ];
+    check(&tests, r#"import "std/string" as s;"#);
+}
+
+#[test]
+fn test_math_module() {
+    let tests = [
+        ("m.PI", Ok("3.141592653589793")),
+        ("m.E", Ok("2.718281828459045")),
+        ("[m.abs(-3), m.abs(-2.5)]", Ok("[3, 2.5]")),
+        ("[m.min(3, 1, 2), m.max(3, 1.5, 2)]", Ok("[1, 3]")),
+        ("[m.min([4, 2.5]), m.max([7])]", Ok("[2.5, 7]")),
+        ("[m.clamp(5, 0, 3), m.clamp(-1, 0.5, 3), m.clamp(2, 0, 3)]", Ok("[3, 0.5, 2]")),
+        ("[m.pow(2, 10), m.pow(2, -1), m.pow(4, 0.5)]", Ok("[1024, 0.5, 2.0]")),
+        ("[m.pow(1, 9999999999), m.pow(-1, 9999999999)]", Ok("[1, -1]")),
+        ("[m.sqrt(16), m.sqrt(2.25)]", Ok("[4.0, 1.5]")),
+        ("[m.floor(2.7), m.ceil(2.1), m.round(2.5), m.round(-2.5)]", Ok("[2, 3, 3, -3]")),
+        ("[m.floor(-0.5), m.round(7)]", Ok("[-1, 7]")),
+        ("[m.sin(0), m.cos(0), m.tan(0)]", Ok("[0.0, 1.0, 0.0]")),
+        ("m.round(m.asin(1) * 2 / m.PI)", Ok("1")),
+        ("[m.acos(1), m.atan(0), m.atan2(1, 1) * 4 == m.PI]", Ok("[0.0, 0.0, true]")),
+        ("[m.exp(0), m.log(m.E), m.log(8, 2), m.log(100, 10)]", Ok("[1.0, 1.0, 3.0, 2.0]")),
+        ("[m.gcd(12, 18), m.gcd(-12, 18), m.gcd(0, 0)]", Ok("[6, 6, 0]")),
+        ("[m.lcm(4, 6), m.lcm(-4, 6), m.lcm(0, 5)]", Ok("[12, 12, 0]")),
+        (
+            "m.abs(-9223372036854775807 - 1)",
+            Err("integer overflow"),
+        ),
+        ("m.pow(2, 63)", Err("integer overflow")),
+        ("m.pow(10.0, 400)", Err("float overflow")),
+        ("m.exp(1000)", Err("float overflow")),
+        ("m.pow(0, -1)", Err("division by zero")),
+        (
+            "m.pow(-8, 0.5)",
+            Err("argument to `pow` cannot raise a negative number to a fractional power"),
+        ),
+        (
+            "m.lcm(9223372036854775807, 2)",
+            Err("integer overflow"),
+        ),
+        (
+            "m.gcd(-9223372036854775807 - 1, 0)",
+            Err("integer overflow"),
+        ),
+        (
+            "m.round(9223372036854775807.0 * 2)",
+            Err("1.8446744073709552e19 is too large for an integer"),
+        ),
+        (
+            "m.sqrt(-1)",
+            Err("argument to `sqrt` must not be negative, got -1"),
+        ),
+        ("m.log(0)", Err("argument to `log` must be positive, got 0")),
+        (
+            "m.log(8, 1)",
+            Err("argument to `log` must have a positive base other than 1, got 1"),
+        ),
+        (
+            "m.asin(1.5)",
+            Err("argument to `asin` must be between -1 and 1, got 1.5"),
+        ),
+        (
+            "m.clamp(1, 3, 0)",
+            Err("argument to `clamp` needs a lower bound no greater than its upper bound, got 3 and 0"),
+        ),
+        ("m.min([])", Err("argument to `min` must be at least one number")),
+        ("m.max(1, \"2\")", Err("argument to `max` not supported, got STRING")),
+        ("m.gcd(1.5, 2)", Err("argument to `gcd` not supported, got FLOAT")),
+        ("m.sqrt(true)", Err("argument to `sqrt` not supported, got BOOL")),
+        (
+            "m.log()",
+            Err("wrong number of arguments to `log`. got=0, want=1 to 2"),
+        ),
+        (
+            "try { m.pow(3, 99) } catch (e) { e.kind }",
+            Ok("Overflow"),
+        ),
+    ];
+    check(&tests, r#"import "std/math" as m;"#);
+}
+
+#[test]
+fn test_std_imports() {
+    let tests = [
+        (
+            r#"import "std/string" as a; import "std/string" as b; a == b"#,
+            Ok("true"),
+        ),
+        (r#"import "std/string" as s; s"#, Ok("module string")),
+        (
+            r#"import "std/nothing" as n;"#,
+            Err(r#"no module "std/nothing" in the standard library"#),
+        ),
+    ];
+    check(&tests, "");
+}
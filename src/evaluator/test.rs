@@ -0,0 +1,726 @@
+use crate::{
+    checker::checker::Checker,
+    evaluator::{
+        environment::Environment,
+        evaluator::Evaluator,
+        limits::Limits,
+        value::{ErrorKind, RuntimeError, Value},
+    },
+    parser::{ast::Node, parser::MAX_NESTING},
+    test_utils::{parse, run_both},
+};
+
+#[test]
+fn test_eval_integer_and_boolean_expression() {
+    let tests = [
+        ("5", "5"),
+        ("-10", "-10"),
+        ("5 + 5 + 5 + 5 - 10", "10"),
+        ("2 * (5 + 10) / 3", "10"),
+        ("1 < 2", "true"),
+        ("1 > 2 == false", "true"),
+        ("!true", "false"),
+        ("!!5", "true"),
+        ("\"foo\" + \"bar\"", "foobar"),
+        ("\"a\" == \"a\"", "true"),
+        ("[1, 2] == [1, 2]", "true"),
+        ("7 % 3", "1"),
+        ("-7 % 3", "-1"),
+        ("1 + 10 % 4 * 2", "5"),
+        ("2 <= 2", "true"),
+        ("3 >= 4", "false"),
+        ("\"a\" <= \"b\"", "true"),
+        ("true && false", "false"),
+        ("false || 1 > 0", "true"),
+        ("1 < 2 && 2 < 3 || false", "true"),
+        ("false && missing", "false"),
+        ("true || 1 / 0", "true"),
+        (
+            "let n = 0; let bump = fn() { n += 1; true }; false && bump(); true || bump(); n",
+            "0",
+        ),
+        ("[] && 0", "true"),
+    ];
+
+    for (input, expected) in tests {
+        test_eval_output(input, expected);
+    }
+}
+
+#[test]
+fn test_eval_float_expression() {
+    let tests = [
+        ("3.5", "3.5"),
+        ("2.0", "2.0"),
+        ("-0.25", "-0.25"),
+        ("0.1 + 0.2", "0.30000000000000004"),
+        ("1.5 * 2", "3.0"),
+        ("7 / 2.0", "3.5"),
+        ("7 / 2", "3"),
+        ("5.5 % 2", "1.5"),
+        ("1 == 1.0", "true"),
+        ("[1, 2] == [1.0, 2.0]", "true"),
+        ("2 < 2.5", "true"),
+        ("-1.5 >= -1", "false"),
+        ("let x = 1; x += 0.5; x", "1.5"),
+        ("match 2.0 { 2 => \"two\", _ => \"other\" }", "two"),
+        ("try { 1.0 / 0 } catch (e) { e.kind }", "DivisionByZero"),
+    ];
+
+    for (input, expected) in tests {
+        test_eval_output(input, expected);
+    }
+}
+
+#[test]
+fn test_eval_functions_and_closures() {
+    let tests = [
+        ("let identity = fn(x) { x; }; identity(5);", "5"),
+        ("let double = fn(x) { return x * 2; 0 }; double(5);", "10"),
+        (
+            "let adder = fn(x) { fn(y) { x + y } }; let addTwo = adder(2); addTwo(3);",
+            "5",
+        ),
+        ("fn(x) { x; }(5)", "5"),
+        (
+            "let fib = fn(n) { if (n < 2) { return n; } fib(n - 1) + fib(n - 2) }; fib(10)",
+            "55",
+        ),
+        (
+            "let map = fn(arr, f) {
+                let iter = fn(arr, accumulated) {
+                    if (len(arr) == 0) { accumulated } else { iter(rest(arr), push(accumulated, f(arr[0]))) }
+                };
+                iter(arr, []);
+            };
+            map([1, 2, 3, 4], fn(x) { x * 2 })",
+            "[2, 4, 6, 8]",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        test_eval_output(input, expected);
+    }
+}
+
+#[test]
+fn test_eval_data_structures() {
+    let tests = [
+        ("[1, 2 * 2, 3 + 3][1]", "4"),
+        (
+            "let h = {\"name\": \"Jimmy\", 72: true}; h[\"name\"]",
+            "Jimmy",
+        ),
+        ("{\"name\": \"Jimmy\"}[\"missing\"]", "null"),
+        ("\"héllo\"[1]", "é"),
+        (
+            "let Point = struct { x, y }; Point { y: 2, x: 1 }",
+            "Point { x: 1, y: 2 }",
+        ),
+        ("let Point = struct { x, y }; Point { x: 1, y: 2 }.y", "2"),
+        (
+            "let Event = enum(add, key_press(str)); Event.add",
+            "Event.add",
+        ),
+        (
+            "let Event = enum(add, key_press(str)); Event.key_press(\"k\")",
+            "Event.key_press(\"k\")",
+        ),
+        ("let x = 0; for (i in 0..4) { x + i }; len(\"abc\")", "3"),
+    ];
+
+    for (input, expected) in tests {
+        test_eval_output(input, expected);
+    }
+}
+
+#[test]
+fn test_eval_match_expression() {
+    let prelude = "
+        let Option = enum(some(int), none);
+        let Point = struct { x, y };
+    ";
+    let tests = [
+        ("match 2 { 1 => \"one\", 2 => \"two\", _ => \"many\" }", "two"),
+        ("match 9 { 1 => \"one\", n => n * 2 }", "18"),
+        ("match Option.some(5) { Option.some(v) => v, Option.none => 0 }", "5"),
+        ("match Option.none { Option.some(v) => v, Option.none => 0 }", "0"),
+        (
+            "match Option.some(50) { Option.some(v) if v > 10 => \"big\", Option.some(v) => \"small\", _ => \"none\" }",
+            "big",
+        ),
+        (
+            "match Option.some(5) { Option.some(v) if v > 10 => \"big\", Option.some(v) => \"small\", _ => \"none\" }",
+            "small",
+        ),
+        ("match [1, 2, 3] { [] => 0, [a] => a, [a, b, ..rest] => rest }", "[3]"),
+        ("match [1, 2] { [1, x] => x, _ => 0 }", "2"),
+        ("match [4, 5] { [a, ..] => a }", "4"),
+        ("match (Point { x: 0, y: 7 }) { Point { x: 0, y } => y, _ => 0 }", "7"),
+        ("match (Point { x: 1, y: 7 }) { Point { x: 0, y } => y, _ => 0 }", "0"),
+        ("match true { false => 0, true => { let a = 1; a + 1 } }", "2"),
+        (
+            "let f = fn(o) { match o { Option.some(v) => { return v; }, _ => 0 }; 99 }; f(Option.some(3))",
+            "3",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        test_eval_output(&format!("{}{}", prelude, input), expected);
+    }
+}
+
+#[test]
+fn test_eval_interfaces_and_methods() {
+    let prelude = "
+        let Shape = interface { area(): int; name(): str };
+        let Rect = struct { w, h };
+        let Square = struct { side };
+        impl Rect {
+            fn area(self) { self.w * self.h }
+            fn name(self) { \"rect\" }
+            fn scale(self, k) { Rect { w: self.w * k, h: self.h * k } }
+        }
+        impl Square {
+            fn area(self) { self.side * self.side }
+            fn name(self) { \"square\" }
+        }
+    ";
+    let tests = [
+        ("Rect { w: 2, h: 3 }.area()", "6"),
+        ("Rect { w: 2, h: 3 }.scale(2).area()", "24"),
+        ("let r = Rect { w: 2, h: 3 }; let area = r.area; area()", "6"),
+        ("let s: Shape = Square { side: 4 }; s.area()", "16"),
+        (
+            "let describe = fn(s: Shape) { s.name() + \":\" };
+            describe(Rect { w: 1, h: 1 }) + describe(Square { side: 1 })",
+            "rect:square:",
+        ),
+        (
+            "let total = fn(shapes) { let sum = 0; for (s in shapes) { sum + s.area() }; shapes[1].area() };
+            total([Rect { w: 1, h: 2 }, Square { side: 3 }])",
+            "9",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        test_eval_output(&format!("{}{}", prelude, input), expected);
+    }
+}
+
+#[test]
+fn test_eval_interface_conformance_errors() {
+    let prelude = "
+        let Runner = interface { run(); stop(force); reset() };
+        let Car = struct { speed };
+        impl Car {
+            fn run(self) { self.speed }
+            fn stop(self) { 0 }
+        }
+    ";
+    let tests = [
+        (
+            "let r: Runner = Car { speed: 1 };",
+            "Car does not satisfy interface Runner: missing method(s) `reset`; \
+             method `stop` takes 0 argument(s) but the interface expects 1",
+        ),
+        (
+            "let r: Runner = 5;",
+            "INT does not satisfy interface Runner: missing method(s) `run`, `stop`, `reset`",
+        ),
+        (
+            "let go = fn(r: Runner) { r.run() }; go(Car { speed: 1 })",
+            "Car does not satisfy interface Runner: missing method(s) `reset`; \
+             method `stop` takes 0 argument(s) but the interface expects 1",
+        ),
+        (
+            "Car { speed: 1 }.fly()",
+            "struct Car has no field or method `fly`",
+        ),
+        (
+            "impl Runner { fn run(self) { 1 } }",
+            "cannot impl methods on `Runner`: expected a struct, got INTERFACE",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        match test_eval(&format!("{}{}", prelude, input)) {
+            Err(err) => assert_eq!(
+                err.message, expected,
+                "wrong error message. expected={}, got={}",
+                expected, err.message
+            ),
+            Ok(value) => panic!("no error returned for {}. got={}", input, value),
+        }
+    }
+}
+
+#[test]
+fn test_annotations_checked_on_assignment() {
+    let prelude = "
+        let Runner = interface { run() };
+        let Dog = struct { name };
+        impl Dog { fn run(self) { 1 } }
+        let Cat = struct { name };
+    ";
+    let cat = "Cat does not satisfy interface Runner: missing method(s) `run`";
+    let tests = [
+        (
+            "let d: Runner = Dog { name: 1 }; d = Cat { name: 2 };",
+            Err(cat),
+        ),
+        (
+            "let d: Runner = Dog { name: 1 }; if (true) { d = Cat { name: 2 } }",
+            Err(cat),
+        ),
+        (
+            "fn() { let d: Runner = Dog { name: 1 }; d = Cat { name: 2 }; }()",
+            Err(cat),
+        ),
+        (
+            "let f = fn() { let d: Runner = Dog { name: 1 }; fn() { d = Cat { name: 2 } } }; f()()",
+            Err(cat),
+        ),
+        (
+            "let go = fn(r: Runner) { r = Cat { name: 2 }; }; go(Dog { name: 1 })",
+            Err(cat),
+        ),
+        (
+            "let swap = fn() { d = Cat { name: 2 } }; let d: Runner = Dog { name: 1 }; swap()",
+            Err(cat),
+        ),
+        (
+            "let d: Runner = Dog { name: 1 }; d = Dog { name: 2 }; d.run() + d.name",
+            Ok("3"),
+        ),
+        // a `let` without an annotation drops the binding's annotation
+        (
+            "let d: Runner = Dog { name: 1 }; let d = 5; d = Cat { name: 2 }; d.name",
+            Ok("2"),
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let input = format!("{}{}", prelude, input);
+        for (engine, result) in run_both(&parse(&input), None) {
+            assert_eq!(
+                result.as_deref().map_err(String::as_str),
+                expected,
+                "{} ran {:?} differently",
+                engine,
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn test_eval_assignment() {
+    let tests = [
+        ("let x = 1; x = 2; x", "2"),
+        ("let x = 1; let y = 0; x = y = 5; x + y", "10"),
+        ("let x = 10; x += 5; x -= 3; x *= 2; x /= 4; x %= 4; x", "2"),
+        ("let s = \"a\"; s += \"b\"; s", "ab"),
+        ("let x = 0; for (x < 10) { x = x + 1; } x", "10"),
+        (
+            "let arr = []; for (i in 0..3) { arr = push(arr, i); } arr",
+            "[0, 1, 2]",
+        ),
+        (
+            "let counter = fn() { let n = 0; fn() { n += 1 } }; let next = counter(); next(); next()",
+            "2",
+        ),
+        ("let x = 1; if (true) { let x = 2; x = 3; } x", "1"),
+        ("let a = [1, 2, 3]; a[1] = 5; a[2] *= 10; a", "[1, 5, 30]"),
+        (
+            "let h = {\"a\": 1}; h[\"a\"] += 1; h[\"b\"] = 0; h",
+            "{\"a\": 2, \"b\": 0}",
+        ),
+        (
+            "let P = struct { x }; let p = P { x: 1 }; p.x += 41; p.x",
+            "42",
+        ),
+        ("let x = 0; x += 2", "2"),
+    ];
+
+    for (input, expected) in tests {
+        test_eval_output(input, expected);
+    }
+}
+
+#[test]
+fn test_eval_conditions() {
+    let tests = [
+        ("if 1 < 2 { 10 } else { 20 }", "10"),
+        ("if 1 > 2 { 10 }", "null"),
+        (
+            "let grade = fn(n) { if n >= 90 { \"a\" } else if n >= 80 { \"b\" } else if n >= 70 { \"c\" } else { \"f\" } }; [grade(95), grade(85), grade(75), grade(10)]",
+            "[\"a\", \"b\", \"c\", \"f\"]",
+        ),
+        ("if false { 1 } else if false { 2 }", "null"),
+        (
+            "let calls = 0; let t = fn() { calls += 1; true }; if t() { 1 } else if t() { 2 }; calls",
+            "1",
+        ),
+        ("let x = 0; for x < 5 { x += 1; } x", "5"),
+        (
+            "let even = []; for i in 0..10 { if i % 2 == 0 { even = push(even, i); } } even",
+            "[0, 2, 4, 6, 8]",
+        ),
+        (
+            "let P = struct { x }; let p = P { x: 1 }; if p == (P { x: 1 }) { \"same\" }",
+            "same",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        test_eval_output(input, expected);
+    }
+}
+
+#[test]
+fn test_eval_errors() {
+    let tests = [
+        ("5 + true;", "type mismatch: INT + BOOL"),
+        ("-true", "unknown operator: -BOOL"),
+        ("true + false;", "unknown operator: BOOL + BOOL"),
+        ("foobar", "identifier not found: foobar"),
+        ("10 / 0", "division by zero"),
+        ("10 % 0", "division by zero"),
+        ("1.5 / 0.0", "division by zero"),
+        ("1.5 % 0", "division by zero"),
+        ("1.5 + \"a\"", "type mismatch: FLOAT + STRING"),
+        (
+            "let big = 9007199254740992.0 * 9007199254740992.0; let huge = big * big * big * big * big; huge * huge",
+            "float overflow",
+        ),
+        ("{1.5: 2}", "unusable as hash key: FLOAT"),
+        ("true && missing", "identifier not found: missing"),
+        ("true >= false", "unknown operator: BOOL >= BOOL"),
+        (
+            "[1, 2][2]",
+            "index out of bounds: the length is 2 but the index is 2",
+        ),
+        ("{[1]: 2}", "unusable as hash key: ARRAY"),
+        ("match 3 { 1 => 1 }", "no match arm matched value: 3"),
+        (
+            "let Point = struct { x, y }; Point { x: 1 }",
+            "missing field `y` in initializer of struct Point",
+        ),
+        (
+            "let Option = enum(some(int), none); Option.other",
+            "enum Option has no variant `other`",
+        ),
+        (
+            "fn(x) { x }(1, 2)",
+            "wrong number of arguments. got=2, want=1",
+        ),
+        ("len(1)", "argument to `len` not supported, got INT"),
+        ("y = 1", "cannot assign to undeclared variable `y`"),
+        ("z += 1", "cannot assign to undeclared variable `z`"),
+        ("let x = 1; x %= 0", "division by zero"),
+        (
+            "let a = [1]; a[1] = 2",
+            "index out of bounds: the length is 1 but the index is 1",
+        ),
+        (
+            "let P = struct { x }; let p = P { x: 1 }; p.y = 2",
+            "struct P has no field `y`",
+        ),
+        ("let x = true; x += 1", "type mismatch: BOOL + INT"),
+        ("throw \"boom\";", "boom"),
+        (
+            "try { 1 / 0 } catch (e) { e.stack }",
+            "error has no property `stack`",
+        ),
+        ("try { 1 / 0 } catch (e) { throw e; }", "division by zero"),
+    ];
+
+    for (input, expected) in tests {
+        match test_eval(input) {
+            Err(err) => assert_eq!(
+                err.message, expected,
+                "wrong error message. expected={}, got={}",
+                expected, err.message
+            ),
+            Ok(value) => panic!("no error returned for {}. got={}", input, value),
+        }
+    }
+}
+
+#[test]
+fn test_eval_try_catch() {
+    let tests = [
+        ("try { 1 / 0 } catch (e) { e.kind }", "DivisionByZero"),
+        ("try { 1 / 0 } catch e { e.message }", "division by zero"),
+        ("try { 1 / 2 } catch { 0 }", "0"),
+        ("try { 5 } catch { 0 }", "5"),
+        ("try { [1][3] } catch (e) { e.kind }", "IndexOutOfBounds"),
+        ("try { missing } catch (e) { e.kind }", "UnknownIdentifier"),
+        ("try { 1 + \"a\" } catch (e) { e.kind }", "TypeMismatch"),
+        (
+            "try { fn(x) { x }() } catch (e) { e.kind }",
+            "WrongArguments",
+        ),
+        ("try { match 1 { 2 => 2 } } catch (e) { e.kind }", "NoMatch"),
+        ("try { throw \"boom\"; } catch (e) { e }", "Error: boom"),
+        ("try { throw 42; } catch (e) { e.message }", "42"),
+        (
+            "try {\n  1 / 0\n} catch (e) { [e.line, e.column] }",
+            "[2, 5]",
+        ),
+        (
+            "let f = fn() { 1 / 0 }; try { f() } catch (e) { [e.line, e.column] }",
+            "[1, 18]",
+        ),
+        (
+            "try { try { 1 / 0 } catch (e) { throw e; } } catch (e) { [e.kind, e.column] }",
+            "[\"DivisionByZero\", 15]",
+        ),
+        (
+            "let f = fn() { try { return 1; } catch { 2 }; 3 }; f()",
+            "1",
+        ),
+        ("let e = 1; try { 1 / 0 } catch (e) { e }; e", "1"),
+    ];
+
+    for (input, expected) in tests {
+        test_eval_output(input, expected);
+    }
+}
+
+#[test]
+fn test_eval_stack_trace() {
+    let tests = [
+        (
+            "let inner = fn(x) {\n  x / 0\n};\nlet applyFunc = fn(f, x) {\n  f(x) + 1\n};\napplyFunc(inner, 1)",
+            "DivisionByZero: division by zero\n    at main.pr:2:5\n    at inner (main.pr:5:4)\n    at applyFunc (main.pr:7:10)",
+        ),
+        (
+            "let apply = fn(f) { f() + 1 };\napply(fn() { missing })",
+            "UnknownIdentifier: identifier not found: missing\n    at main.pr:2:20\n    at <anonymous fn> (main.pr:1:22)\n    at apply (main.pr:2:6)",
+        ),
+        // errors raised by the call itself did not enter the callee
+        (
+            "let f = fn(x) { x };\nf()",
+            "WrongArguments: wrong number of arguments. got=0, want=1\n    at main.pr:2:2",
+        ),
+        // errors caught inside a function only unwind the calls made in the `try`
+        (
+            "let f = fn() { 1 / 0 };\nlet g = fn() { try { f() } catch (e) { throw e; } };\ng()",
+            "DivisionByZero: division by zero\n    at main.pr:1:18\n    at f (main.pr:2:23)\n    at g (main.pr:3:2)",
+        ),
+        // a call in tail position takes over the frame of the function making it
+        (
+            "let f = fn(x) { x / 0 };\nlet g = fn(x) { f(x) };\ng(1)",
+            "DivisionByZero: division by zero\n    at main.pr:1:19\n    at f (main.pr:3:2)",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        match test_eval(input) {
+            Err(err) => assert_eq!(err.report(), expected, "wrong report for {}", input),
+            Ok(value) => panic!("no error returned for {}. got={}", input, value),
+        }
+    }
+
+    let value = test_eval(
+        "let f = fn() { 1 / 0 }; try { f() } catch (e) { let frame = e.trace[0]; [len(e.trace), frame[\"function\"], frame[\"line\"], frame[\"column\"]] }",
+    )
+    .expect("error is caught");
+    assert_eq!(value.to_string(), "[1, \"f\", 1, 32]");
+}
+
+#[test]
+fn test_eval_deep_recursion() {
+    // far deeper than the stack of the test thread holds without growing it
+    let input = "let deep = fn(n) { if n == 0 { 0 } else { 1 + deep(n - 1) } }; deep(";
+    let mut evaluator = Evaluator::new();
+    evaluator.set_limits(Limits {
+        max_depth: 5000,
+        ..Limits::default()
+    });
+    let env = Environment::new();
+
+    let program = parse(&format!("{}4000)", input));
+    let value = evaluator
+        .eval_program(&program, &env)
+        .expect("recursion within the limit");
+    assert_eq!(value.to_string(), "4000");
+
+    let program = parse(&format!("{}6000)", input));
+    let err = evaluator
+        .eval_program(&program, &env)
+        .expect_err("recursion past the limit");
+    assert_eq!(err.kind, ErrorKind::StackOverflow);
+    assert_eq!(err.trace.len(), 5000);
+}
+
+#[test]
+fn test_eval_tail_calls() {
+    // calls in tail position reuse the frame of the caller, so they never
+    // reach a call depth limit far shallower than the recursion
+    let tests = [
+        (
+            "let count = fn(n, acc) { if n == 0 { acc } else { count(n - 1, acc + 1) } }; count(1000000, 0)",
+            "1000000",
+        ),
+        (
+            "let even = fn(n) { if n == 0 { return true; } odd(n - 1) }; let odd = fn(n) { if n == 0 { return false; } return even(n - 1); }; even(100001)",
+            "false",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let mut evaluator = Evaluator::new();
+        evaluator.set_limits(Limits {
+            max_depth: 100,
+            ..Limits::default()
+        });
+        let value = evaluator
+            .eval_program(&parse(input), &Environment::new())
+            .unwrap_or_else(|err| panic!("{} failed: {}", input, err.report()));
+        assert_eq!(value.to_string(), expected, "{}", input);
+    }
+}
+
+#[test]
+fn test_return_inside_expressions() {
+    // a `return` leaves the whole function, whatever it is nested in
+    let tests = [
+        (
+            "let f = fn() { 1 + if (true) { return 5 } else { 2 } }; f()",
+            "5",
+        ),
+        ("let f = fn() { [1, match 2 { _ => { return 3 } }] }; f()", "3"),
+        (
+            "let f = fn(n) { for (i in 0..n) { if (i == 2) { return i * 10 } } -1 }; [f(5), f(1)]",
+            "[20, -1]",
+        ),
+        (
+            "let g = fn() { throw \"no\" }; let f = fn() { try { return g() } catch (e) { 7 } }; f()",
+            "7",
+        ),
+        ("let f = fn() { try { return 4 } catch (e) { 7 } + 1 }; f()", "4"),
+    ];
+
+    for (input, expected) in tests {
+        for (engine, result) in run_both(&parse(input), None) {
+            assert_eq!(
+                result.as_deref().map_err(String::as_str),
+                Ok(expected),
+                "{} ran {:?} differently",
+                engine,
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn test_deeply_nested_values() {
+    let tests = [
+        (
+            "let a = [1]; let b = [1]; let c = [2];
+            for (i in 0..300000) { a = [a]; b = [b]; c = [c]; }
+            [a == b, a == c]",
+            "[true, false]",
+        ),
+        (
+            "let a = 1; for (i in 0..3) { a = [a, \"x\"]; } a",
+            "[[[1, \"x\"], \"x\"], \"x\"]",
+        ),
+        (
+            "let h = {\"k\": 1}; let a = [h]; h[\"self\"] = a; a",
+            "[{\"k\": 1, \"self\": [...]}]",
+        ),
+        (
+            "let h = {}; let a = [h]; h[\"a\"] = a; a == [{\"a\": a}]",
+            "true",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        for (engine, result) in run_both(&parse(input), None) {
+            assert_eq!(
+                result.as_deref().map_err(String::as_str),
+                Ok(expected),
+                "{} ran {:?} differently",
+                engine,
+                input
+            );
+        }
+    }
+}
+
+/// Programs nested as deeply as the parser allows are checked, compiled,
+/// evaluated and printed without overflowing the stack of a test thread.
+#[test]
+fn test_deeply_nested_source() {
+    let depth = MAX_NESTING - 8;
+    let tests = [
+        (
+            format!("len({}1{})", "[".repeat(depth), "]".repeat(depth)),
+            "1",
+        ),
+        (
+            format!("1{}", " + 1".repeat(depth / 2)),
+            &*(depth / 2 + 1).to_string(),
+        ),
+        (format!("{}1{}", "(".repeat(depth), ")".repeat(depth)), "1"),
+        (format!("{}1", "-".repeat(depth)), "1"),
+        (format!("let x: {}int = []; 1", "[]".repeat(depth)), "1"),
+        (
+            format!(
+                "{}1{}",
+                "for (false) { ".repeat(depth / 3),
+                " }".repeat(depth / 3)
+            ),
+            "null",
+        ),
+        (
+            format!(
+                "let v = 1; for (i in 0..{}) {{ v = [v]; }} match v {{ {}x{} => x, _ => 0 }}",
+                depth,
+                "[".repeat(depth),
+                "]".repeat(depth)
+            ),
+            "1",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let program = parse(&input);
+        let mut checker = Checker::new();
+        checker.check_program(&program);
+        assert!(!program.string().is_empty());
+        for (engine, result) in run_both(&program, None) {
+            assert_eq!(
+                result.as_deref().map_err(String::as_str),
+                Ok(expected),
+                "{} ran {:.40}... differently",
+                engine,
+                input
+            );
+        }
+    }
+}
+
+//utils
+fn test_eval(input: &str) -> Result<Value, RuntimeError> {
+    Evaluator::new().eval_program(&parse(input), &Environment::new())
+}
+
+fn test_eval_output(input: &str, expected: &str) {
+    match test_eval(input) {
+        Ok(value) => assert_eq!(
+            value.to_string(),
+            expected,
+            "wrong result for {}. expected={}, got={}",
+            input,
+            expected,
+            value
+        ),
+        Err(err) => panic!("runtime error for {}: {}", input, err),
+    }
+}
@@ -0,0 +1,828 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    rc::Rc,
+};
+
+use crate::{
+    gc::gc::{self, HeapId, Trace},
+    parser::ast::{BlockStatement, Identifier, TypeExpr},
+    vm::closure::Closure,
+};
+
+use super::{builtins::Builtin, environment::Env, host::HostObject};
+
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum Value {
+    Null,
+    Int(i64),
+    /// Scripts only make finite floats: arithmetic that would overflow to
+    /// infinity raises an error instead.
+    Float(f64),
+    Bool(bool),
+    Str(String),
+    Range(i64, i64),
+    Array(Rc<RefCell<Vec<Value>>>),
+    Hash(Rc<RefCell<BTreeMap<HashKey, Value>>>),
+    Function(Rc<Function>),
+    /// A function compiled to bytecode, created by the vm.
+    Closure(Rc<Closure>),
+    Builtin(&'static Builtin),
+    /// A function registered by the host, which scripts see as a builtin.
+    Native(Rc<NativeFunction>),
+    Enum(Rc<EnumType>),
+    EnumVariant(Rc<EnumValue>),
+    VariantConstructor(Rc<EnumType>, usize),
+    StructType(Rc<StructType>),
+    Struct(Rc<RefCell<StructValue>>),
+    Interface(Rc<InterfaceType>),
+    BoundMethod(Rc<BoundMethod>),
+    /// An object of the host's, which scripts can only call methods on.
+    Host(Rc<dyn HostObject>),
+    /// What `import` binds: the bindings a module exported.
+    Module(Rc<Module>),
+    /// A runtime error caught by `try`/`catch`.
+    Error(Rc<RuntimeError>),
+}
+
+/// The subset of values that can be used as hash keys.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HashKey {
+    Int(i64),
+    Bool(bool),
+    Str(String),
+}
+
+pub struct Function {
+    /// The name the function was declared with, for stack traces.
+    pub name: Option<String>,
+    pub parameters: Vec<Identifier>,
+    pub parameter_types: Vec<Option<TypeExpr>>,
+    pub body: Rc<BlockStatement>,
+    pub env: Env,
+    /// The file the function was declared in, as errors name it.
+    pub file: Rc<str>,
+}
+
+#[derive(Clone)]
+pub struct EnumType {
+    pub name: String,
+    pub variants: Vec<(String, usize)>, // (name, payload arity)
+}
+
+pub struct EnumValue {
+    pub enum_type: Rc<EnumType>,
+    pub variant: usize,
+    pub payload: Vec<Value>,
+}
+
+pub struct StructType {
+    pub name: String,
+    pub fields: Vec<String>,
+    /// Attached by `impl` blocks, which may run after the struct is declared.
+    /// Holds a `Function` or a `Closure`, depending on the engine.
+    pub methods: RefCell<HashMap<String, Value>>,
+}
+
+#[derive(Clone)]
+pub struct InterfaceType {
+    pub name: String,
+    pub methods: Vec<(String, usize)>, // (name, arity without the receiver)
+}
+
+pub type NativeFn = dyn Fn(&[Value]) -> Result<Value, RuntimeError>;
+
+pub struct NativeFunction {
+    pub name: String,
+    /// Checks and converts the arguments itself, failing like builtins do.
+    pub func: Box<NativeFn>,
+}
+
+pub struct Module {
+    pub name: String,
+    pub exports: BTreeMap<String, Value>,
+}
+
+/// A method looked up on a receiver, called with the receiver as first argument.
+pub struct BoundMethod {
+    pub receiver: Value,
+    pub name: String,
+    pub function: Value,
+}
+
+pub struct StructValue {
+    pub struct_type: Rc<StructType>,
+    pub fields: Vec<(String, Value)>,
+}
+
+impl StructValue {
+    pub fn get(&self, field: &str) -> Option<&Value> {
+        self.fields.iter().find(|(k, _)| k == field).map(|(_, v)| v)
+    }
+}
+
+impl Value {
+    pub fn array(elements: Vec<Value>) -> Value {
+        Value::Array(gc::track(Rc::new(RefCell::new(elements))))
+    }
+
+    pub fn hash(pairs: BTreeMap<HashKey, Value>) -> Value {
+        Value::Hash(gc::track(Rc::new(RefCell::new(pairs))))
+    }
+
+    pub fn module(name: String, exports: BTreeMap<String, Value>) -> Value {
+        Value::Module(gc::track(Rc::new(Module { name, exports })))
+    }
+
+    /// Calls `visit` with the heap objects the value refers to directly.
+    pub fn trace(&self, visit: &mut dyn FnMut(HeapId)) {
+        match self {
+            Value::Array(elements) => visit(gc::id(elements)),
+            Value::Hash(pairs) => visit(gc::id(pairs)),
+            Value::Function(func) => visit(gc::id(func)),
+            Value::Closure(closure) => visit(gc::id(closure)),
+            Value::EnumVariant(value) => visit(gc::id(value)),
+            Value::StructType(struct_type) => visit(gc::id(struct_type)),
+            Value::Struct(value) => visit(gc::id(value)),
+            Value::BoundMethod(method) => visit(gc::id(method)),
+            Value::Module(module) => visit(gc::id(module)),
+            _ => {}
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "NULL",
+            Value::Int(_) => "INT",
+            Value::Float(_) => "FLOAT",
+            Value::Bool(_) => "BOOL",
+            Value::Str(_) => "STRING",
+            Value::Range(..) => "RANGE",
+            Value::Array(_) => "ARRAY",
+            Value::Hash(_) => "HASH",
+            Value::Function(_) | Value::Closure(_) => "FUNCTION",
+            Value::Builtin(_) | Value::Native(_) => "BUILTIN",
+            Value::Enum(_) => "ENUM",
+            Value::EnumVariant(_) => "ENUM_VARIANT",
+            Value::VariantConstructor(..) => "ENUM_VARIANT",
+            Value::StructType(_) => "STRUCT",
+            Value::Struct(_) => "STRUCT_INSTANCE",
+            Value::Interface(_) => "INTERFACE",
+            Value::BoundMethod(_) => "METHOD",
+            Value::Host(_) => "HOST_OBJECT",
+            Value::Module(_) => "MODULE",
+            Value::Error(_) => "ERROR",
+        }
+    }
+
+    /// The user facing type of the value: the declared name for struct
+    /// instances and enum variants, the type name otherwise.
+    pub fn describe_type(&self) -> String {
+        match self {
+            Value::Struct(value) => value.borrow().struct_type.name.clone(),
+            Value::EnumVariant(value) => value.enum_type.name.clone(),
+            Value::Host(object) => object.type_name().to_owned(),
+            _ => self.type_name().to_owned(),
+        }
+    }
+
+    /// The number of parameters of a user defined function.
+    pub fn arity(&self) -> Option<usize> {
+        match self {
+            Value::Function(func) => Some(func.parameters.len()),
+            Value::Closure(closure) => Some(closure.function.parameters.len()),
+            _ => None,
+        }
+    }
+
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Value::Null | Value::Bool(false))
+    }
+
+    pub fn hash_key(&self) -> Option<HashKey> {
+        match self {
+            Value::Int(i) => Some(HashKey::Int(*i)),
+            Value::Bool(b) => Some(HashKey::Bool(*b)),
+            Value::Str(s) => Some(HashKey::Str(s.clone())),
+            _ => None,
+        }
+    }
+
+    /// Moves the value out, leaving null in its place. Values cannot be
+    /// taken apart by moving out of them, as they have their own `Drop`.
+    pub fn take(&mut self) -> Value {
+        std::mem::replace(self, Value::Null)
+    }
+
+    /// Renders the value the way it appears nested inside another value,
+    /// which differs from `Display` only by quoting strings.
+    pub fn inspect(&self) -> String {
+        match self {
+            Value::Str(s) => format!("{:?}", s),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl HashKey {
+    pub fn to_value(&self) -> Value {
+        match self {
+            HashKey::Int(i) => Value::Int(*i),
+            HashKey::Bool(b) => Value::Bool(*b),
+            HashKey::Str(s) => Value::Str(s.clone()),
+        }
+    }
+}
+
+impl PartialEq for Value {
+    /// Compares nested values from a stack rather than by recursion, so
+    /// deeply nested values do not overflow it. Containers already being
+    /// compared with each other are taken to be equal, so cycles end.
+    fn eq(&self, other: &Self) -> bool {
+        let mut pending = vec![];
+        let mut comparing = HashSet::new();
+        if !shallow_eq(self, other, &mut pending, &mut comparing) {
+            return false;
+        }
+        while let Some((a, b)) = pending.pop() {
+            if !shallow_eq(&a, &b, &mut pending, &mut comparing) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Compares two values without looking into the values nested in them,
+/// which are pushed onto `pending` to be compared in turn.
+fn shallow_eq(
+    a: &Value,
+    b: &Value,
+    pending: &mut Vec<(Value, Value)>,
+    comparing: &mut HashSet<(HeapId, HeapId)>,
+) -> bool {
+    match (a, b) {
+        (Value::Null, Value::Null) => true,
+        (Value::Int(a), Value::Int(b)) => a == b,
+        (Value::Float(a), Value::Float(b)) => a == b,
+        (Value::Int(a), Value::Float(b)) | (Value::Float(b), Value::Int(a)) => *a as f64 == *b,
+        (Value::Bool(a), Value::Bool(b)) => a == b,
+        (Value::Str(a), Value::Str(b)) => a == b,
+        (Value::Range(a, b), Value::Range(c, d)) => a == c && b == d,
+        (Value::Array(a), Value::Array(b)) => {
+            if !comparing.insert((gc::id(a), gc::id(b))) {
+                return true;
+            }
+            let (a, b) = (a.borrow(), b.borrow());
+            a.len() == b.len() && {
+                pending.extend(a.iter().cloned().zip(b.iter().cloned()));
+                true
+            }
+        }
+        (Value::Hash(a), Value::Hash(b)) => {
+            if !comparing.insert((gc::id(a), gc::id(b))) {
+                return true;
+            }
+            let (a, b) = (a.borrow(), b.borrow());
+            a.len() == b.len() && a.keys().eq(b.keys()) && {
+                pending.extend(a.values().cloned().zip(b.values().cloned()));
+                true
+            }
+        }
+        (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+        (Value::Closure(a), Value::Closure(b)) => Rc::ptr_eq(a, b),
+        (Value::Builtin(a), Value::Builtin(b)) => a.name == b.name,
+        (Value::Native(a), Value::Native(b)) => Rc::ptr_eq(a, b),
+        (Value::Enum(a), Value::Enum(b)) => Rc::ptr_eq(a, b),
+        (Value::EnumVariant(a), Value::EnumVariant(b)) => {
+            Rc::ptr_eq(&a.enum_type, &b.enum_type)
+                && a.variant == b.variant
+                && a.payload.len() == b.payload.len()
+                && {
+                    pending.extend(a.payload.iter().cloned().zip(b.payload.iter().cloned()));
+                    true
+                }
+        }
+        (Value::VariantConstructor(a, i), Value::VariantConstructor(b, j)) => {
+            Rc::ptr_eq(a, b) && i == j
+        }
+        (Value::StructType(a), Value::StructType(b)) => Rc::ptr_eq(a, b),
+        (Value::Interface(a), Value::Interface(b)) => Rc::ptr_eq(a, b),
+        (Value::BoundMethod(a), Value::BoundMethod(b)) => {
+            pending.push((a.receiver.clone(), b.receiver.clone()));
+            pending.push((a.function.clone(), b.function.clone()));
+            true
+        }
+        (Value::Struct(a), Value::Struct(b)) => {
+            if !comparing.insert((gc::id(a), gc::id(b))) {
+                return true;
+            }
+            let (a, b) = (a.borrow(), b.borrow());
+            Rc::ptr_eq(&a.struct_type, &b.struct_type)
+                && a.fields.len() == b.fields.len()
+                && a.fields
+                    .iter()
+                    .zip(&b.fields)
+                    .all(|((a, _), (b, _))| a == b)
+                && {
+                    pending.extend(
+                        (a.fields.iter().zip(&b.fields))
+                            .map(|((_, a), (_, b))| (a.clone(), b.clone())),
+                    );
+                    true
+                }
+        }
+        (Value::Error(a), Value::Error(b)) => a == b,
+        (Value::Host(a), Value::Host(b)) => Rc::ptr_eq(a, b) || a.equals(b.as_ref()),
+        (Value::Module(a), Value::Module(b)) => Rc::ptr_eq(a, b),
+        _ => false,
+    }
+}
+
+impl fmt::Display for Value {
+    /// Writes nested values from a stack rather than by recursion, so
+    /// deeply nested values do not overflow it. A container nested in
+    /// itself is written as `[...]`, `{...}` or `Name { ... }`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Value::Str(s) = self {
+            return write!(f, "{}", s);
+        }
+        let mut pending = vec![Pending::Value(self.clone())];
+        let mut open = HashSet::new();
+        while let Some(item) = pending.pop() {
+            let value = match item {
+                Pending::Value(value) => value,
+                Pending::Text(text) => {
+                    f.write_str(&text)?;
+                    continue;
+                }
+                Pending::Close(id) => {
+                    open.remove(&id);
+                    continue;
+                }
+            };
+            match &value {
+                Value::Array(elements) => {
+                    if !open.insert(gc::id(elements)) {
+                        f.write_str("[...]")?;
+                        continue;
+                    }
+                    f.write_str("[")?;
+                    pending.push(Pending::Close(gc::id(elements)));
+                    pending.push(Pending::Text("]".into()));
+                    let elements = elements.borrow();
+                    push_nested(&mut pending, elements.iter().map(|e| (String::new(), e)));
+                }
+                Value::Hash(pairs) => {
+                    if !open.insert(gc::id(pairs)) {
+                        f.write_str("{...}")?;
+                        continue;
+                    }
+                    f.write_str("{")?;
+                    pending.push(Pending::Close(gc::id(pairs)));
+                    pending.push(Pending::Text("}".into()));
+                    let pairs = pairs.borrow();
+                    let pairs = pairs
+                        .iter()
+                        .map(|(k, v)| (format!("{}: ", k.to_value().inspect()), v));
+                    push_nested(&mut pending, pairs);
+                }
+                Value::EnumVariant(value) => {
+                    let (name, _) = &value.enum_type.variants[value.variant];
+                    write!(f, "{}.{}", value.enum_type.name, name)?;
+                    if !value.payload.is_empty() {
+                        f.write_str("(")?;
+                        pending.push(Pending::Text(")".into()));
+                        push_nested(
+                            &mut pending,
+                            value.payload.iter().map(|v| (String::new(), v)),
+                        );
+                    }
+                }
+                Value::Struct(value) => {
+                    let id = gc::id(value);
+                    let value = value.borrow();
+                    if !open.insert(id) {
+                        write!(f, "{} {{ ... }}", value.struct_type.name)?;
+                        continue;
+                    }
+                    write!(f, "{} {{ ", value.struct_type.name)?;
+                    pending.push(Pending::Close(id));
+                    pending.push(Pending::Text(" }".into()));
+                    let fields = value.fields.iter().map(|(k, v)| (format!("{}: ", k), v));
+                    push_nested(&mut pending, fields);
+                }
+                // nested strings are quoted, as `inspect` does
+                Value::Str(s) => write!(f, "{:?}", s)?,
+                other => write_leaf(f, other)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What is left to write of a value: the values nested in it, the text
+/// around them, and the end of a container, which may be written again
+/// once it is no longer nested in itself.
+enum Pending {
+    Value(Value),
+    Text(String),
+    Close(HeapId),
+}
+
+/// Queues the values nested in a container, each after its prefix, to be
+/// written in order and separated by commas.
+fn push_nested<'a>(pending: &mut Vec<Pending>, nested: impl Iterator<Item = (String, &'a Value)>) {
+    let nested: Vec<_> = nested.collect();
+    for (i, (prefix, value)) in nested.into_iter().enumerate().rev() {
+        pending.push(Pending::Value(value.clone()));
+        if !prefix.is_empty() {
+            pending.push(Pending::Text(prefix));
+        }
+        if i > 0 {
+            pending.push(Pending::Text(", ".into()));
+        }
+    }
+}
+
+/// Writes a value that has no values nested in it to print.
+fn write_leaf(f: &mut fmt::Formatter<'_>, value: &Value) -> fmt::Result {
+    match value {
+        Value::Null => write!(f, "null"),
+        Value::Int(i) => write!(f, "{}", i),
+        // `{:?}` keeps the `.0` of whole numbers, so 1.0 does not print as 1
+        Value::Float(x) => write!(f, "{:?}", x),
+        Value::Bool(b) => write!(f, "{}", b),
+        Value::Str(s) => write!(f, "{}", s),
+        Value::Range(start, end) => write!(f, "{}..{}", start, end),
+        Value::Function(func) => {
+            let params: Vec<String> = func.parameters.iter().map(|p| p.value.clone()).collect();
+            write!(f, "fn({})", params.join(", "))
+        }
+        Value::Closure(closure) => {
+            write!(f, "fn({})", closure.function.parameters.join(", "))
+        }
+        Value::Builtin(builtin) => write!(f, "builtin {}", builtin.name),
+        Value::Native(native) => write!(f, "builtin {}", native.name),
+        Value::Enum(enum_type) => write!(f, "enum {}", enum_type.name),
+        Value::VariantConstructor(enum_type, variant) => {
+            write!(f, "{}.{}", enum_type.name, enum_type.variants[*variant].0)
+        }
+        Value::StructType(struct_type) => write!(f, "struct {}", struct_type.name),
+        Value::Interface(interface) => write!(f, "interface {}", interface.name),
+        Value::BoundMethod(method) => {
+            write!(f, "{}.{}", method.receiver.describe_type(), method.name)
+        }
+        Value::Host(object) => object.display(f),
+        Value::Module(module) => write!(f, "module {}", module.name),
+        Value::Error(err) => write!(f, "{}: {}", err.kind, err.message),
+        Value::Array(_) | Value::Hash(_) | Value::EnumVariant(_) | Value::Struct(_) => {
+            unreachable!("{} has nested values", value.type_name())
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Dropping {
+    No,
+    Yes,
+    /// The next value dropped is the one taken off the queue, whose nested
+    /// values are the ones to queue.
+    Next,
+}
+
+thread_local! {
+    static DROPPING: Cell<Dropping> = const { Cell::new(Dropping::No) };
+    /// Values whose drop was put off while another value was dropped.
+    static DROP_QUEUE: RefCell<Vec<Value>> = const { RefCell::new(vec![]) };
+}
+
+impl Drop for Value {
+    /// Drops the values nested in this one from a queue rather than by
+    /// recursion, so deeply nested values do not overflow the stack.
+    fn drop(&mut self) {
+        if matches!(
+            self,
+            Value::Null
+                | Value::Int(_)
+                | Value::Float(_)
+                | Value::Bool(_)
+                | Value::Str(_)
+                | Value::Range(..)
+                | Value::Builtin(_)
+        ) {
+            return;
+        }
+        match DROPPING.get() {
+            Dropping::Next => DROPPING.set(Dropping::Yes),
+            Dropping::Yes => {
+                let value = std::mem::replace(self, Value::Null);
+                DROP_QUEUE.with_borrow_mut(|queue| queue.push(value));
+            }
+            Dropping::No => {
+                let mut next = Some(std::mem::replace(self, Value::Null));
+                while let Some(value) = next {
+                    DROPPING.set(Dropping::Next);
+                    drop(value);
+                    next = DROP_QUEUE.with_borrow_mut(Vec::pop);
+                }
+                DROPPING.set(Dropping::No);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl Trace for RefCell<Vec<Value>> {
+    fn trace(&self, visit: &mut dyn FnMut(HeapId)) {
+        if let Ok(elements) = self.try_borrow() {
+            elements.iter().for_each(|element| element.trace(visit));
+        }
+    }
+
+    fn clear(&self) {
+        let elements = self
+            .try_borrow_mut()
+            .map(|mut elements| std::mem::take(&mut *elements));
+        drop(elements);
+    }
+}
+
+impl Trace for RefCell<BTreeMap<HashKey, Value>> {
+    fn trace(&self, visit: &mut dyn FnMut(HeapId)) {
+        if let Ok(pairs) = self.try_borrow() {
+            pairs.values().for_each(|value| value.trace(visit));
+        }
+    }
+
+    fn clear(&self) {
+        let pairs = self
+            .try_borrow_mut()
+            .map(|mut pairs| std::mem::take(&mut *pairs));
+        drop(pairs);
+    }
+}
+
+impl Trace for Function {
+    fn trace(&self, visit: &mut dyn FnMut(HeapId)) {
+        visit(gc::id(&self.env));
+    }
+}
+
+impl Trace for EnumValue {
+    fn trace(&self, visit: &mut dyn FnMut(HeapId)) {
+        self.payload.iter().for_each(|value| value.trace(visit));
+    }
+}
+
+impl Trace for StructType {
+    fn trace(&self, visit: &mut dyn FnMut(HeapId)) {
+        if let Ok(methods) = self.methods.try_borrow() {
+            methods.values().for_each(|method| method.trace(visit));
+        }
+    }
+
+    fn clear(&self) {
+        let methods = self
+            .methods
+            .try_borrow_mut()
+            .map(|mut methods| std::mem::take(&mut *methods));
+        drop(methods);
+    }
+}
+
+impl Trace for RefCell<StructValue> {
+    fn trace(&self, visit: &mut dyn FnMut(HeapId)) {
+        if let Ok(value) = self.try_borrow() {
+            visit(gc::id(&value.struct_type));
+            value
+                .fields
+                .iter()
+                .for_each(|(_, field)| field.trace(visit));
+        }
+    }
+
+    fn clear(&self) {
+        let fields = self
+            .try_borrow_mut()
+            .map(|mut value| std::mem::take(&mut value.fields));
+        drop(fields);
+    }
+}
+
+impl Trace for Module {
+    fn trace(&self, visit: &mut dyn FnMut(HeapId)) {
+        self.exports.values().for_each(|value| value.trace(visit));
+    }
+}
+
+impl Trace for BoundMethod {
+    fn trace(&self, visit: &mut dyn FnMut(HeapId)) {
+        self.receiver.trace(visit);
+        self.function.trace(visit);
+    }
+}
+
+/// What went wrong, so `catch` blocks can tell errors apart without
+/// parsing their messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Raised by `throw`, and by failures with no more specific kind.
+    Error,
+    TypeMismatch,
+    IndexOutOfBounds,
+    DivisionByZero,
+    UnknownIdentifier,
+    WrongArguments,
+    Overflow,
+    NoMatch,
+    StackOverflow,
+    /// A builtin needed a capability the program was not granted.
+    PermissionDenied,
+    /// The program ran more steps than `Limits::max_steps` allows.
+    StepLimit,
+    /// The program ran past `Limits::timeout`.
+    Timeout,
+    /// The program allocated more than `Limits::max_memory` allows.
+    MemoryLimit,
+}
+
+impl ErrorKind {
+    /// Whether `catch` blocks handle the error. Programs cannot catch running
+    /// out of their budget, which stops them for the host to handle.
+    pub fn is_catchable(&self) -> bool {
+        !matches!(
+            self,
+            ErrorKind::StepLimit | ErrorKind::Timeout | ErrorKind::MemoryLimit
+        )
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    pub kind: ErrorKind,
+    pub message: String,
+    /// `(line, column)` of the innermost expression that failed.
+    pub position: Option<(usize, usize)>,
+    /// The file `position` is in, as errors name it.
+    pub file: Option<String>,
+    /// The calls the error unwound, innermost first.
+    pub trace: Vec<TraceFrame>,
+}
+
+/// A call an error unwound: the function called, and where.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceFrame {
+    pub function: Option<String>,
+    /// The file the call was made from.
+    pub file: String,
+    pub position: (usize, usize),
+}
+
+impl TraceFrame {
+    pub fn function_name(&self) -> &str {
+        self.function.as_deref().unwrap_or("<anonymous fn>")
+    }
+
+    /// The frame as the hash scripts read from `trace`.
+    fn to_value(&self) -> Value {
+        let (line, column) = self.position;
+        let pairs: BTreeMap<HashKey, Value> = [
+            ("function", Value::Str(self.function_name().to_owned())),
+            ("file", Value::Str(self.file.clone())),
+            ("line", Value::Int(line as i64)),
+            ("column", Value::Int(column as i64)),
+        ]
+        .into_iter()
+        .map(|(key, value)| (HashKey::Str(key.to_owned()), value))
+        .collect();
+
+        Value::hash(pairs)
+    }
+}
+
+impl fmt::Display for TraceFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (line, column) = self.position;
+        write!(
+            f,
+            "{} ({}:{}:{})",
+            self.function_name(),
+            self.file,
+            line,
+            column
+        )
+    }
+}
+
+impl RuntimeError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self::with_kind(ErrorKind::Error, message)
+    }
+
+    pub fn with_kind(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            position: None,
+            file: None,
+            trace: vec![],
+        }
+    }
+
+    /// Records where in `file` the error was raised, unless a nested
+    /// expression already did.
+    pub fn at(mut self, file: &str, position: (usize, usize)) -> Self {
+        if self.position.is_none() {
+            self.position = Some(position);
+            self.file = Some(file.to_owned());
+        }
+        self
+    }
+
+    /// Records that the error unwound a call to `function` made at
+    /// `position` in `file`.
+    pub fn unwound(mut self, function: Option<&str>, file: &str, position: (usize, usize)) -> Self {
+        self.trace.push(TraceFrame {
+            function: function.map(str::to_owned),
+            file: file.to_owned(),
+            position,
+        });
+        self
+    }
+
+    /// The fields scripts read from a caught error.
+    pub fn property(&self, name: &str) -> Option<Value> {
+        let line_or_column = |pick: fn((usize, usize)) -> usize| {
+            self.position
+                .map_or(Value::Null, |position| Value::Int(pick(position) as i64))
+        };
+        match name {
+            "kind" => Some(Value::Str(self.kind.to_string())),
+            "message" => Some(Value::Str(self.message.clone())),
+            "file" => Some(self.file.clone().map_or(Value::Null, Value::Str)),
+            "line" => Some(line_or_column(|(line, _)| line)),
+            "column" => Some(line_or_column(|(_, column)| column)),
+            "trace" => Some(Value::array(
+                self.trace.iter().map(TraceFrame::to_value).collect(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// The error as a value bound by `catch`.
+    pub fn to_value(&self) -> Value {
+        Value::Error(Rc::new(self.clone()))
+    }
+
+    /// Renders an uncaught error with its kind and where it was raised.
+    pub fn report(&self) -> String {
+        let mut out = format!("{}: {}", self.kind, self.message);
+        if let (Some((line, column)), Some(file)) = (self.position, &self.file) {
+            out.push_str(&format!("\n    at {}:{}:{}", file, line, column));
+        }
+        // deep recursion repeats the same call, which is shown once
+        let mut frames = self.trace.iter().peekable();
+        while let Some(frame) = frames.next() {
+            let mut count = 1;
+            while frames.next_if_eq(&frame).is_some() {
+                count += 1;
+            }
+            match count {
+                1 => out.push_str(&format!("\n    at {}", frame)),
+                _ => {
+                    let (line, column) = frame.position;
+                    out.push_str(&format!(
+                        "\n    at {} × {} ({}:{}:{})",
+                        frame.function_name(),
+                        count,
+                        frame.file,
+                        line,
+                        column
+                    ));
+                }
+            }
+        }
+
+        out
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
@@ -0,0 +1,361 @@
+use std::{
+    cell::RefCell,
+    ffi::{c_char, c_int, c_void, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use crate::{
+    engine::engine::{Engine, Error},
+    evaluator::{
+        builtins::check_arity,
+        value::{NativeFunction, RuntimeError, Value},
+    },
+};
+
+/// An engine along with the outcome of the last program it ran, which C
+/// reads through the `prolang_result_` functions.
+pub struct ProlangEngine {
+    pub(super) engine: Engine,
+    result: Result<Value, Error>,
+    /// The result rendered for `prolang_result_string`.
+    output: CString,
+}
+
+/// A value passed to or returned from a C callback. A callback may instead
+/// return an error made by `prolang_value_error`, failing the call.
+pub struct ProlangValue(Result<Value, RuntimeError>);
+
+/// Called with the `user_data` it was registered with and the arguments of
+/// the call, which it borrows. It returns a value it made, which prolang
+/// takes over.
+pub type ProlangCallback = unsafe extern "C" fn(
+    user_data: *mut c_void,
+    args: *const *const ProlangValue,
+    argc: usize,
+) -> *mut ProlangValue;
+
+thread_local! {
+    /// The message of the last call on this thread that failed, for
+    /// `prolang_last_error`.
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Records `message` as the last error and returns `failed`.
+fn fail<T>(failed: T, message: impl Into<String>) -> T {
+    let message = c_string(message.into());
+    LAST_ERROR.with_borrow_mut(|last| *last = Some(message));
+    failed
+}
+
+/// Runs `body`, returning the message it panicked with if it did.
+fn catch<T>(body: impl FnOnce() -> T) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(body)).map_err(|payload| {
+        let message = match payload.downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => match payload.downcast_ref::<String>() {
+                Some(message) => message.clone(),
+                None => "unknown error".to_string(),
+            },
+        };
+        format!("prolang panicked: {}", message)
+    })
+}
+
+/// Runs the body of a C function, which fails with `failed` if it panics,
+/// since a panic unwinding into C is undefined behavior.
+fn guard<T>(failed: T, body: impl FnOnce() -> T) -> T {
+    catch(body).unwrap_or_else(|message| fail(failed, message))
+}
+
+/// Renders `text` for C, escaping the nul bytes it cannot hold.
+fn c_string(text: String) -> CString {
+    CString::new(text.replace('\0', "\\0")).expect("nul bytes are escaped")
+}
+
+/// # Safety
+///
+/// `text` is null or points to a nul-terminated string.
+unsafe fn read_str<'a>(text: *const c_char) -> Option<&'a str> {
+    if text.is_null() {
+        return None;
+    }
+    CStr::from_ptr(text).to_str().ok()
+}
+
+/// Creates an engine, which grants programs no capabilities. Free it with
+/// `prolang_engine_free`.
+#[no_mangle]
+pub extern "C" fn prolang_engine_new() -> *mut ProlangEngine {
+    guard(ptr::null_mut(), || {
+        Box::into_raw(Box::new(ProlangEngine {
+            engine: Engine::new(),
+            result: Ok(Value::Null),
+            output: CString::default(),
+        }))
+    })
+}
+
+/// Frees an engine and the strings it returned.
+///
+/// # Safety
+///
+/// `engine` is null or came from `prolang_engine_new`, and is not used
+/// after.
+#[no_mangle]
+pub unsafe extern "C" fn prolang_engine_free(engine: *mut ProlangEngine) {
+    guard((), || {
+        if !engine.is_null() {
+            drop(Box::from_raw(engine));
+        }
+    })
+}
+
+/// Runs `source` on the engine, returning 0 if it succeeded and -1 if it
+/// failed, including when `source` is not valid UTF-8 or prolang panicked.
+/// The error is then also the last error.
+///
+/// # Safety
+///
+/// `engine` came from `prolang_engine_new`, and `source` points to a
+/// nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn prolang_eval(engine: *mut ProlangEngine, source: *const c_char) -> c_int {
+    guard(-1, || {
+        let Some(engine) = engine.as_mut() else {
+            return fail(-1, "engine is null");
+        };
+        let result = catch(|| match read_str(source) {
+            Some(source) => engine.engine.eval(source),
+            None => Err(RuntimeError::new("source is not valid UTF-8").into()),
+        });
+        engine.result = result.unwrap_or_else(|message| Err(RuntimeError::new(message).into()));
+        match &engine.result {
+            Ok(value) => {
+                engine.output = c_string(value.to_string());
+                0
+            }
+            Err(err) => {
+                engine.output = c_string(err.to_string());
+                fail(-1, err.to_string())
+            }
+        }
+    })
+}
+
+/// The value the last program returned as it prints, or the error that
+/// stopped it. The string lives until the next `prolang_eval`. Returns
+/// null if `engine` is null.
+///
+/// # Safety
+///
+/// `engine` came from `prolang_engine_new`.
+#[no_mangle]
+pub unsafe extern "C" fn prolang_result_string(engine: *const ProlangEngine) -> *const c_char {
+    guard(ptr::null(), || match engine.as_ref() {
+        Some(engine) => engine.output.as_ptr(),
+        None => fail(ptr::null(), "engine is null"),
+    })
+}
+
+/// Stores the integer the last program returned in `out`, returning 0, or
+/// returns -1 if it returned something else or failed.
+///
+/// # Safety
+///
+/// `engine` came from `prolang_engine_new`, and `out` points to an
+/// `int64_t`.
+#[no_mangle]
+pub unsafe extern "C" fn prolang_result_int(engine: *const ProlangEngine, out: *mut i64) -> c_int {
+    guard(-1, || {
+        match (engine.as_ref().map(|engine| &engine.result), out.as_mut()) {
+            (Some(Ok(Value::Int(i))), Some(out)) => {
+                *out = *i;
+                0
+            }
+            (None, _) => fail(-1, "engine is null"),
+            (_, None) => fail(-1, "out is null"),
+            (Some(Ok(value)), _) => fail(
+                -1,
+                format!(
+                    "the last program returned {}, not an integer",
+                    value.type_name()
+                ),
+            ),
+            (Some(Err(err)), _) => fail(-1, err.to_string()),
+        }
+    })
+}
+
+/// Makes `callback` callable from programs as `name` with `arity`
+/// arguments, like a builtin. Returns 0, or -1 if `name` is not valid
+/// UTF-8 or `callback` is null.
+///
+/// # Safety
+///
+/// `engine` came from `prolang_engine_new`, `name` points to a
+/// nul-terminated string, and `callback` may be called with `user_data`
+/// for as long as the engine lives. The callback must not use the engine.
+#[no_mangle]
+pub unsafe extern "C" fn prolang_register_fn(
+    engine: *mut ProlangEngine,
+    name: *const c_char,
+    arity: usize,
+    callback: Option<ProlangCallback>,
+    user_data: *mut c_void,
+) -> c_int {
+    guard(-1, || {
+        let Some(engine) = engine.as_mut() else {
+            return fail(-1, "engine is null");
+        };
+        let Some(name) = read_str(name) else {
+            return fail(-1, "name is not valid UTF-8");
+        };
+        let Some(callback) = callback else {
+            return fail(-1, "callback is null");
+        };
+        let func = {
+            let name = name.to_owned();
+            move |args: &[Value]| {
+                check_arity(&name, args, arity)?;
+                let args: Vec<ProlangValue> = args
+                    .iter()
+                    .cloned()
+                    .map(|arg| ProlangValue(Ok(arg)))
+                    .collect();
+                let pointers: Vec<*const ProlangValue> = args.iter().map(ptr::from_ref).collect();
+                let result = callback(user_data, pointers.as_ptr(), pointers.len());
+                if result.is_null() {
+                    return Err(RuntimeError::new(format!("`{}` returned no value", name)));
+                }
+                Box::from_raw(result).0
+            }
+        };
+        engine.engine.register_native(NativeFunction {
+            name: name.to_owned(),
+            func: Box::new(func),
+        });
+        0
+    })
+}
+
+/// Makes an integer value.
+#[no_mangle]
+pub extern "C" fn prolang_value_int(value: i64) -> *mut ProlangValue {
+    guard(ptr::null_mut(), || {
+        Box::into_raw(Box::new(ProlangValue(Ok(Value::Int(value)))))
+    })
+}
+
+/// Makes a string value, copying `value`. Returns null if it is not valid
+/// UTF-8.
+///
+/// # Safety
+///
+/// `value` points to a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn prolang_value_string(value: *const c_char) -> *mut ProlangValue {
+    guard(ptr::null_mut(), || match read_str(value) {
+        Some(value) => Box::into_raw(Box::new(ProlangValue(Ok(Value::Str(value.to_owned()))))),
+        None => fail(ptr::null_mut(), "value is not valid UTF-8"),
+    })
+}
+
+/// Makes `null`.
+#[no_mangle]
+pub extern "C" fn prolang_value_null() -> *mut ProlangValue {
+    guard(ptr::null_mut(), || {
+        Box::into_raw(Box::new(ProlangValue(Ok(Value::Null))))
+    })
+}
+
+/// Makes an error for a callback to return, which fails the call with
+/// `message`.
+///
+/// # Safety
+///
+/// `message` points to a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn prolang_value_error(message: *const c_char) -> *mut ProlangValue {
+    guard(ptr::null_mut(), || {
+        let message = read_str(message).unwrap_or("callback failed");
+        Box::into_raw(Box::new(ProlangValue(Err(RuntimeError::new(message)))))
+    })
+}
+
+/// Stores the integer `value` holds in `out`, returning 0, or returns -1
+/// if it holds something else.
+///
+/// # Safety
+///
+/// `value` came from prolang, and `out` points to an `int64_t`.
+#[no_mangle]
+pub unsafe extern "C" fn prolang_value_as_int(value: *const ProlangValue, out: *mut i64) -> c_int {
+    guard(-1, || match (value.as_ref(), out.as_mut()) {
+        (Some(ProlangValue(Ok(Value::Int(i)))), Some(out)) => {
+            *out = *i;
+            0
+        }
+        (None, _) => fail(-1, "value is null"),
+        (_, None) => fail(-1, "out is null"),
+        (Some(ProlangValue(Ok(value))), _) => fail(
+            -1,
+            format!("expected an integer, got {}", value.type_name()),
+        ),
+        (Some(ProlangValue(Err(_))), _) => fail(-1, "expected an integer, got an error"),
+    })
+}
+
+/// Renders `value` as it prints. Free the string with
+/// `prolang_string_free`. Returns null if `value` is null.
+///
+/// # Safety
+///
+/// `value` came from prolang.
+#[no_mangle]
+pub unsafe extern "C" fn prolang_value_to_string(value: *const ProlangValue) -> *mut c_char {
+    guard(ptr::null_mut(), || match value.as_ref() {
+        Some(ProlangValue(Ok(value))) => c_string(value.to_string()).into_raw(),
+        Some(ProlangValue(Err(err))) => c_string(err.to_string()).into_raw(),
+        None => fail(ptr::null_mut(), "value is null"),
+    })
+}
+
+/// Frees a value made and not handed back to prolang.
+///
+/// # Safety
+///
+/// `value` is null or was made by a `prolang_value_` function, and is not
+/// used after.
+#[no_mangle]
+pub unsafe extern "C" fn prolang_value_free(value: *mut ProlangValue) {
+    guard((), || {
+        if !value.is_null() {
+            drop(Box::from_raw(value));
+        }
+    })
+}
+
+/// Frees a string returned by `prolang_value_to_string`.
+///
+/// # Safety
+///
+/// `string` is null or came from `prolang_value_to_string`, and is not used
+/// after.
+#[no_mangle]
+pub unsafe extern "C" fn prolang_string_free(string: *mut c_char) {
+    guard((), || {
+        if !string.is_null() {
+            drop(CString::from_raw(string));
+        }
+    })
+}
+
+/// The message of the last call on this thread that failed, or null if
+/// none has. The string lives until the next call on the thread fails.
+#[no_mangle]
+pub extern "C" fn prolang_last_error() -> *const c_char {
+    guard(ptr::null(), || {
+        LAST_ERROR.with_borrow(|last| last.as_ref().map_or(ptr::null(), |last| last.as_ptr()))
+    })
+}
@@ -0,0 +1,7 @@
+//! The C API, declared in `include/prolang.h`.
+
+#[allow(clippy::module_inception)]
+pub mod ffi;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,47 @@
+use std::{ffi::CStr, ptr};
+
+use crate::evaluator::value::NativeFunction;
+
+use super::ffi::{
+    prolang_engine_free, prolang_engine_new, prolang_eval, prolang_last_error, prolang_register_fn,
+    prolang_result_int, prolang_result_string,
+};
+
+#[test]
+fn test_panics_fail_the_call() {
+    unsafe {
+        let engine = prolang_engine_new();
+        (*engine).engine.register_native(NativeFunction {
+            name: "explode".to_string(),
+            func: Box::new(|_| panic!("boom")),
+        });
+
+        assert_eq!(prolang_eval(engine, c"explode()".as_ptr()), -1);
+        let expected = "runtime error: Error: prolang panicked: boom";
+        let output = CStr::from_ptr(prolang_result_string(engine));
+        assert_eq!(output.to_str(), Ok(expected));
+        let last_error = CStr::from_ptr(prolang_last_error());
+        assert_eq!(last_error.to_str(), Ok(expected));
+        let mut result = 0;
+        assert_eq!(prolang_result_int(engine, &mut result), -1);
+
+        assert_eq!(prolang_eval(engine, c"1 + 2".as_ptr()), 0);
+        assert_eq!(prolang_result_int(engine, &mut result), 0);
+        assert_eq!(result, 3);
+        prolang_engine_free(engine);
+    }
+}
+
+#[test]
+fn test_null_callback_is_not_registered() {
+    unsafe {
+        let engine = prolang_engine_new();
+        let registered = prolang_register_fn(engine, c"f".as_ptr(), 0, None, ptr::null_mut());
+        assert_eq!(registered, -1);
+        let last_error = CStr::from_ptr(prolang_last_error());
+        assert_eq!(last_error.to_str(), Ok("callback is null"));
+
+        assert_eq!(prolang_eval(engine, c"f()".as_ptr()), -1);
+        prolang_engine_free(engine);
+    }
+}
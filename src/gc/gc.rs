@@ -0,0 +1,163 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::{Rc, Weak},
+};
+
+/// Identifies a heap object by the address of its allocation.
+pub type HeapId = usize;
+
+/// A heap object that may take part in a reference cycle: arrays, hashes,
+/// struct instances, struct types, functions and closures, environments and
+/// upvalues, modules, and the values wrapping them.
+pub trait Trace {
+    /// Calls `visit` with every heap object this one references.
+    fn trace(&self, visit: &mut dyn FnMut(HeapId));
+
+    /// Drops the references this object holds, once it is known to be
+    /// unreachable, so the cycles it is part of are freed. Objects that
+    /// cannot change after they are created cannot close a cycle on their
+    /// own and need not do anything.
+    fn clear(&self) {}
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GcStats {
+    pub collections: usize,
+    /// Objects tracked since the program started.
+    pub allocated: usize,
+    /// Objects the collector freed by breaking cycles. Objects outside of
+    /// cycles are freed by reference counting as soon as they are dropped,
+    /// and are not counted here.
+    pub freed: usize,
+    /// Objects alive after the last collection.
+    pub live: usize,
+}
+
+/// Allocations between collections, at least.
+const MIN_THRESHOLD: usize = 1024;
+
+struct Heap {
+    objects: Vec<Weak<dyn Trace>>,
+    since_collection: usize,
+    threshold: usize,
+    stress: bool,
+    stats: GcStats,
+}
+
+thread_local! {
+    static HEAP: RefCell<Heap> = RefCell::new(Heap {
+        objects: vec![],
+        since_collection: 0,
+        threshold: MIN_THRESHOLD,
+        stress: false,
+        stats: GcStats::default(),
+    });
+}
+
+pub fn id<T: ?Sized>(object: &Rc<T>) -> HeapId {
+    Rc::as_ptr(object) as *const () as HeapId
+}
+
+/// Registers a new heap object with the collector, collecting first if
+/// enough objects were allocated since the last collection.
+pub fn track<T: Trace + 'static>(object: Rc<T>) -> Rc<T> {
+    let weak: Weak<dyn Trace> = Rc::downgrade(&object) as Weak<dyn Trace>;
+    let due = HEAP.with(|heap| {
+        let mut heap = heap.borrow_mut();
+        heap.objects.push(weak);
+        heap.stats.allocated += 1;
+        heap.since_collection += 1;
+        heap.stress || heap.since_collection >= heap.threshold
+    });
+    if due {
+        collect();
+    }
+
+    object
+}
+
+/// Collects every reference cycle no longer reachable from the program,
+/// returning the number of objects freed.
+///
+/// Reference counting already frees everything else, so only objects kept
+/// alive by references from other heap objects are candidates. The
+/// collector subtracts those internal references from each object's count:
+/// what remains are references from outside the heap, like locals of the
+/// interpreter or the vm stack. Everything reachable from such an object is
+/// live, and the rest is garbage whose references are cleared.
+pub fn collect() -> usize {
+    let objects: Vec<Rc<dyn Trace>> = HEAP
+        .with(|heap| {
+            let mut heap = heap.borrow_mut();
+            heap.since_collection = 0;
+            std::mem::take(&mut heap.objects)
+        })
+        .iter()
+        .filter_map(Weak::upgrade)
+        .collect();
+
+    let index: HashMap<HeapId, usize> = objects
+        .iter()
+        .enumerate()
+        .map(|(i, object)| (id(object), i))
+        .collect();
+    // the upgrade above holds one reference to each object
+    let mut external: Vec<usize> = objects
+        .iter()
+        .map(|object| Rc::strong_count(object) - 1)
+        .collect();
+    for object in &objects {
+        object.trace(&mut |child| {
+            if let Some(&i) = index.get(&child) {
+                external[i] = external[i].saturating_sub(1);
+            }
+        });
+    }
+
+    let mut reachable: Vec<bool> = external.iter().map(|&count| count > 0).collect();
+    let mut pending: Vec<usize> = (0..objects.len()).filter(|&i| reachable[i]).collect();
+    while let Some(i) = pending.pop() {
+        objects[i].trace(&mut |child| {
+            if let Some(&j) = index.get(&child) {
+                if !reachable[j] {
+                    reachable[j] = true;
+                    pending.push(j);
+                }
+            }
+        });
+    }
+
+    let mut freed = 0;
+    let mut survivors: Vec<Weak<dyn Trace>> = vec![];
+    for (object, reachable) in objects.iter().zip(reachable) {
+        if reachable {
+            survivors.push(Rc::downgrade(object));
+        } else {
+            object.clear();
+            freed += 1;
+        }
+    }
+
+    HEAP.with(|heap| {
+        let mut heap = heap.borrow_mut();
+        let live = survivors.len();
+        heap.objects = survivors;
+        heap.threshold = MIN_THRESHOLD.max(live);
+        heap.stats.collections += 1;
+        heap.stats.freed += freed;
+        heap.stats.live = live;
+    });
+
+    freed
+}
+
+pub fn stats() -> GcStats {
+    HEAP.with(|heap| heap.borrow().stats)
+}
+
+/// Makes every allocation collect, to surface objects the collector frees
+/// while they are still in use.
+pub fn set_stress(stress: bool) {
+    HEAP.with(|heap| heap.borrow_mut().stress = stress);
+}
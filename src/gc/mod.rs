@@ -0,0 +1,6 @@
+pub mod allocator;
+#[allow(clippy::module_inception)]
+pub mod gc;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,167 @@
+use std::fs;
+
+use crate::{
+    evaluator::{environment::Environment, evaluator::Evaluator},
+    gc::gc,
+    test_utils::{interpret, parse, run, run_both},
+};
+
+#[test]
+fn test_collect_frees_cycles() {
+    let tests = [
+        // the inner function's environment holds the function itself
+        "let walk = fn(n) { let iter = fn(n) { if (n == 0) { return 0; } iter(n - 1) }; iter(n) }; walk(3)",
+        // an array holding itself
+        "let a = [1, 2]; a[0] = a; len(a)",
+        // a hash holding itself through a nested array
+        "let h = {\"self\": []}; h[\"self\"] = [h]; len(h)",
+        // a struct instance holding itself
+        "let Node = struct { next }; let n = Node { next: 0 }; n.next = n; 0",
+    ];
+
+    for input in tests {
+        let program = parse(input);
+
+        gc::collect();
+        interpret(&program).unwrap_or_else(|err| panic!("runtime error for {}: {}", input, err));
+        assert!(gc::collect() > 0, "interpreter leaked a cycle in {}", input);
+        assert_eq!(
+            gc::collect(),
+            0,
+            "second collection freed more for {}",
+            input
+        );
+
+        run(&program).unwrap_or_else(|err| panic!("runtime error for {}: {}", input, err));
+        assert!(gc::collect() > 0, "vm leaked a cycle in {}", input);
+        assert_eq!(
+            gc::collect(),
+            0,
+            "second collection freed more for {}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_collect_keeps_reachable_values() {
+    let env = Environment::new();
+    let mut evaluator = Evaluator::new();
+    let setup = parse(
+        "let counter = fn() { let n = 0; let next = fn() { n += 1 }; next }; let tick = counter(); let a = [1]; a[0] = a;",
+    );
+    evaluator
+        .eval_program(&setup, &env)
+        .expect("setup evaluates");
+
+    gc::collect();
+    let after = parse("tick(); tick(); [tick(), len(a), len(a[0][0])]");
+    let value = evaluator
+        .eval_program(&after, &env)
+        .expect("values survive a collection");
+    assert_eq!(value.to_string(), "[3, 1, 1]");
+
+    drop(env);
+    assert!(gc::collect() > 0, "dropped environment was not collected");
+}
+
+#[test]
+fn test_stress_mode() {
+    let tests = [
+        (
+            "let fib = fn(n) { if (n < 2) { return n; } fib(n - 1) + fib(n - 2) }; fib(10)",
+            "55",
+        ),
+        (
+            "let map = fn(arr, f) { let iter = fn(arr, acc) { if (len(arr) == 0) { return acc; } iter(rest(arr), push(acc, f(first(arr)))) }; iter(arr, []) }; map([1, 2, 3], fn(x) { x * 2 })",
+            "[2, 4, 6]",
+        ),
+        (
+            "let counter = fn() { let n = 0; fn() { n += 1 } }; let a = counter(); a(); a(); {\"n\": a(), \"xs\": [[1], [2]]}",
+            "{\"n\": 3, \"xs\": [[1], [2]]}",
+        ),
+        (
+            "let Point = struct { x, y }; impl Point { fn sum(self) { self.x + self.y } }; let ps = [Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]; ps[0].sum() + ps[1].sum()",
+            "10",
+        ),
+    ];
+
+    gc::set_stress(true);
+    for (input, expected) in tests {
+        let program = parse(input);
+        let before = gc::stats().collections;
+
+        for (engine, result) in [("interpreter", interpret(&program)), ("vm", run(&program))] {
+            let value = result
+                .unwrap_or_else(|err| panic!("{} runtime error for {}: {}", engine, input, err));
+            assert_eq!(
+                value.to_string(),
+                expected,
+                "{} gave the wrong result under stress for {}",
+                engine,
+                input
+            );
+        }
+        assert!(
+            gc::stats().collections > before,
+            "stress mode did not collect for {}",
+            input
+        );
+    }
+    gc::set_stress(false);
+}
+
+#[test]
+fn test_stress_mode_keeps_imported_modules() {
+    let dir = std::env::temp_dir().join(format!("prolang-gc-modules-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let files = [
+        (
+            "adder.pr",
+            "let base = 10; export let add = fn(x) { x + base };",
+        ),
+        // once imported, this module's environment, which holds `adder`, is
+        // garbage kept alive by the cycle through `forget`
+        (
+            "user.pr",
+            "import \"adder\" as adder; let forget = fn() { forget }; export let n = 1;",
+        ),
+    ];
+    for (name, source) in files {
+        fs::write(dir.join(name), source).unwrap();
+    }
+    // the second import of `adder` is served from the cache of loaded
+    // modules, which the collector must not have cleared
+    let program = parse(
+        "import \"user\" as user; let n = [user.n]; import \"adder\" as adder; [n, adder.add(1)]",
+    );
+
+    gc::set_stress(true);
+    let results = run_both(&program, Some(&dir.join("main.pr")));
+    gc::set_stress(false);
+    fs::remove_dir_all(&dir).unwrap();
+
+    for (engine, result) in results {
+        assert_eq!(
+            result,
+            Ok("[[1], 11]".to_owned()),
+            "{} lost an imported module under stress",
+            engine
+        );
+    }
+}
+
+#[test]
+fn test_gc_stats() {
+    let program = parse("let a = [1]; a[0] = a; gc_stats()[\"allocated\"] > 0");
+    assert_eq!(interpret(&program).expect("evaluates").to_string(), "true");
+
+    gc::collect();
+    let stats = gc::stats();
+    assert!(stats.collections > 0);
+    assert!(stats.freed > 0);
+    assert!(stats.allocated >= stats.freed + stats.live);
+
+    let program = parse("let s = gc_stats(); [s[\"collections\"] > 0, len(s)]");
+    assert_eq!(run(&program).expect("runs").to_string(), "[true, 4]");
+}
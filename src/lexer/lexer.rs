@@ -0,0 +1,298 @@
+use super::token::{lookup_ident, Token, TokenType};
+
+pub struct Lexer {
+    input: Vec<char>,
+    line_column: (usize, usize),
+    position: usize,
+    read_position: usize,
+    ch: char,
+}
+
+impl Lexer {
+    pub fn new(input: String) -> Self {
+        let mut l = Self {
+            input: input.chars().collect(),
+            line_column: (1, 0),
+            position: 0,
+            read_position: 0,
+            ch: '\0',
+        };
+
+        l.read_char();
+
+        l
+    }
+
+    fn read_char(&mut self) {
+        self.ch = self.input.get(self.read_position).copied().unwrap_or('\0');
+        self.position = self.read_position;
+        self.read_position += 1;
+        self.line_column.1 += 1;
+    }
+
+    fn peek_char(&self) -> char {
+        self.input.get(self.read_position).copied().unwrap_or('\0')
+    }
+
+    fn reset_line(&mut self) {
+        self.line_column.0 += 1;
+        self.line_column.1 = 0;
+    }
+
+    pub fn next_token(&mut self) -> Token {
+        let mut tok = Token::default();
+
+        self.skip_whitespace();
+
+        match self.ch {
+            '\n' => {
+                self.reset_line();
+                self.read_char();
+                return self.next_token();
+            }
+            '=' => {
+                if self.peek_char() == '=' {
+                    let ch = self.ch;
+                    self.read_char();
+                    let mut literal = String::from(ch);
+                    literal.push(self.ch);
+                    tok = Token::new(TokenType::EQ, literal, self.line_column);
+                } else if self.peek_char() == '>' {
+                    let ch = self.ch;
+                    self.read_char();
+                    let mut literal = String::from(ch);
+                    literal.push(self.ch);
+                    tok = Token::new(TokenType::FatArrow, literal, self.line_column);
+                } else {
+                    tok = Token::new(TokenType::ASSIGN, self.ch.to_string(), self.line_column);
+                }
+            }
+            '+' => {
+                if self.peek_char() == '=' {
+                    tok = self.two_char_token(TokenType::PlusAssign);
+                } else {
+                    tok = Token::new(TokenType::PLUS, self.ch.to_string(), self.line_column)
+                }
+            }
+            '-' => {
+                if self.peek_char() == '=' {
+                    tok = self.two_char_token(TokenType::MinusAssign);
+                } else {
+                    tok = Token::new(TokenType::MINUS, self.ch.to_string(), self.line_column)
+                }
+            }
+            '%' => {
+                if self.peek_char() == '=' {
+                    tok = self.two_char_token(TokenType::PercentAssign);
+                } else {
+                    tok = Token::new(TokenType::PERCENT, self.ch.to_string(), self.line_column)
+                }
+            }
+            '&' => {
+                if self.peek_char() == '&' {
+                    tok = self.two_char_token(TokenType::AND);
+                } else {
+                    tok = Token::new(TokenType::ILLEGAL, self.ch.to_string(), self.line_column)
+                }
+            }
+            '|' => {
+                if self.peek_char() == '|' {
+                    tok = self.two_char_token(TokenType::OR);
+                } else {
+                    tok = Token::new(TokenType::ILLEGAL, self.ch.to_string(), self.line_column)
+                }
+            }
+            '!' => {
+                if self.peek_char() == '=' {
+                    let ch = self.ch;
+                    self.read_char();
+                    let mut literal = String::from(ch);
+                    literal.push(self.ch);
+                    tok = Token::new(TokenType::NotEq, literal, self.line_column);
+                } else {
+                    tok = Token::new(TokenType::BANG, self.ch.to_string(), self.line_column);
+                }
+            }
+            '/' => {
+                if self.peek_char() == '/' {
+                    self.read_char();
+                    self.skip_comment();
+                    return self.next_token();
+                } else if self.peek_char() == '*' {
+                    self.read_char();
+                    self.skip_comment();
+                    // Advance past the closing "*/"
+                    self.read_char();
+                    self.read_char();
+                    return self.next_token();
+                } else if self.peek_char() == '=' {
+                    tok = self.two_char_token(TokenType::SlashAssign);
+                } else {
+                    tok = Token::new(TokenType::SLASH, self.ch.to_string(), self.line_column)
+                }
+            }
+            '*' => {
+                if self.peek_char() == '=' {
+                    tok = self.two_char_token(TokenType::AsteriskAssign);
+                } else {
+                    tok = Token::new(TokenType::ASTERISK, self.ch.to_string(), self.line_column)
+                }
+            }
+            '<' => {
+                if self.peek_char() == '=' {
+                    tok = self.two_char_token(TokenType::LtEq);
+                } else {
+                    tok = Token::new(TokenType::LT, self.ch.to_string(), self.line_column)
+                }
+            }
+            '>' => {
+                if self.peek_char() == '=' {
+                    tok = self.two_char_token(TokenType::GtEq);
+                } else {
+                    tok = Token::new(TokenType::GT, self.ch.to_string(), self.line_column)
+                }
+            }
+            ';' => tok = Token::new(TokenType::SEMICOLON, self.ch.to_string(), self.line_column),
+            ':' => tok = Token::new(TokenType::COLON, self.ch.to_string(), self.line_column),
+            ',' => tok = Token::new(TokenType::COMMA, self.ch.to_string(), self.line_column),
+            '(' => tok = Token::new(TokenType::LPAREN, self.ch.to_string(), self.line_column),
+            ')' => tok = Token::new(TokenType::RPAREN, self.ch.to_string(), self.line_column),
+            '{' => tok = Token::new(TokenType::LBRACE, self.ch.to_string(), self.line_column),
+            '}' => tok = Token::new(TokenType::RBRACE, self.ch.to_string(), self.line_column),
+            '[' => tok = Token::new(TokenType::LBRACKET, self.ch.to_string(), self.line_column),
+            ']' => tok = Token::new(TokenType::RBRACKET, self.ch.to_string(), self.line_column),
+            '"' => {
+                let position = self.line_column;
+                tok = Token::new(TokenType::STRING, self.read_string(), position)
+            }
+            '.' => {
+                if self.peek_char() == '.' {
+                    let ch = self.ch;
+                    self.read_char();
+                    let mut literal = String::from(ch);
+                    literal.push(ch);
+                    tok = Token::new(TokenType::Spreed, literal, self.line_column)
+                } else {
+                    tok = Token::new(TokenType::Dot, self.ch.to_string(), self.line_column)
+                }
+            }
+            '\0' => tok.token_type = TokenType::EOF,
+            _ => {
+                if is_letter(self.ch) {
+                    tok.literal = self.read_indentifier();
+                    tok.token_type = lookup_ident(&tok.literal);
+                    tok.position = (self.line_column.0, self.line_column.1 - 1);
+                    return tok;
+                } else if is_digit(self.ch) {
+                    (tok.literal, tok.token_type) = self.read_number();
+                    tok.position = (self.line_column.0, self.line_column.1 - 1);
+                    return tok;
+                } else {
+                    tok = Token::new(TokenType::ILLEGAL, self.ch.to_string(), self.line_column)
+                }
+            }
+        }
+
+        self.read_char();
+        tok
+    }
+
+    /// Builds a token from the current and next character, consuming both.
+    fn two_char_token(&mut self, token_type: TokenType) -> Token {
+        let mut literal = String::from(self.ch);
+        self.read_char();
+        literal.push(self.ch);
+        Token::new(token_type, literal, self.line_column)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while ['\t', '\r', ' '].contains(&self.ch) {
+            self.read_char()
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.ch == '/' {
+            while self.ch != '\n' && self.ch != '\0' {
+                self.read_char()
+            }
+        } else {
+            // step past the opening '*' so "/*/" does not close the comment
+            self.read_char();
+            while !(self.ch == '*' && self.peek_char() == '/') && self.ch != '\0' {
+                if self.ch == '\n' {
+                    self.reset_line()
+                }
+                self.read_char()
+            }
+        }
+    }
+
+    /// Reads an identifier, which may contain digits after its first letter,
+    /// as in `atan2`.
+    fn read_indentifier(&mut self) -> String {
+        let position = self.position;
+        while is_letter(self.ch) || is_digit(self.ch) {
+            self.read_char();
+        }
+
+        self.input[position..self.position].iter().collect()
+    }
+
+    /// Reads an integer, or a float if a `.` and more digits follow, so
+    /// `1..5` still lexes as a range.
+    fn read_number(&mut self) -> (String, TokenType) {
+        let position = self.position;
+        while is_digit(self.ch) {
+            self.read_char();
+        }
+        let mut token_type = TokenType::INT;
+        if self.ch == '.' && is_digit(self.peek_char()) {
+            token_type = TokenType::FLOAT;
+            self.read_char();
+            while is_digit(self.ch) {
+                self.read_char();
+            }
+        }
+
+        (
+            self.input[position..self.position].iter().collect(),
+            token_type,
+        )
+    }
+
+    /// Reads a double quoted string, leaving `self.ch` on the closing quote.
+    fn read_string(&mut self) -> String {
+        let mut out = String::new();
+        loop {
+            self.read_char();
+            match self.ch {
+                '"' | '\0' => break,
+                '\\' => {
+                    self.read_char();
+                    match self.ch {
+                        'n' => out.push('\n'),
+                        't' => out.push('\t'),
+                        'r' => out.push('\r'),
+                        '0' => out.push('\0'),
+                        ch => out.push(ch),
+                    }
+                }
+                '\n' => {
+                    out.push('\n');
+                    self.reset_line();
+                }
+                ch => out.push(ch),
+            }
+        }
+        out
+    }
+}
+
+fn is_letter(ch: char) -> bool {
+    ch.is_ascii_lowercase() || ch.is_ascii_uppercase() || ch == '_'
+}
+
+fn is_digit(ch: char) -> bool {
+    ch.is_ascii_digit()
+}
@@ -1,4 +1,4 @@
-use crate::core::lexer::{lexer::Lexer, token::TokenType};
+use crate::lexer::{lexer::Lexer, token::TokenType};
 
 #[test]
 fn test_next_token() {
@@ -463,3 +463,237 @@ let a = 10;
         );
     }
 }
+
+#[test]
+fn test_next_token_match_and_literals() {
+    let input = r#"
+        match x { Option.some(v) => "a\"b", [h, ..t] => {} }
+        enum struct "héllo" : [ ]
+        "#
+    .to_string();
+
+    let tests = [
+        (TokenType::MATCH, "match"),
+        (TokenType::IDENT, "x"),
+        (TokenType::LBRACE, "{"),
+        (TokenType::IDENT, "Option"),
+        (TokenType::Dot, "."),
+        (TokenType::IDENT, "some"),
+        (TokenType::LPAREN, "("),
+        (TokenType::IDENT, "v"),
+        (TokenType::RPAREN, ")"),
+        (TokenType::FatArrow, "=>"),
+        (TokenType::STRING, "a\"b"),
+        (TokenType::COMMA, ","),
+        (TokenType::LBRACKET, "["),
+        (TokenType::IDENT, "h"),
+        (TokenType::COMMA, ","),
+        (TokenType::Spreed, ".."),
+        (TokenType::IDENT, "t"),
+        (TokenType::RBRACKET, "]"),
+        (TokenType::FatArrow, "=>"),
+        (TokenType::LBRACE, "{"),
+        (TokenType::RBRACE, "}"),
+        (TokenType::RBRACE, "}"),
+        (TokenType::ENUM, "enum"),
+        (TokenType::STRUCT, "struct"),
+        (TokenType::STRING, "héllo"),
+        (TokenType::COLON, ":"),
+        (TokenType::LBRACKET, "["),
+        (TokenType::RBRACKET, "]"),
+        (TokenType::EOF, ""),
+    ];
+
+    let mut l = Lexer::new(input);
+    for (expected_type, expected_literal) in tests {
+        let tok = l.next_token();
+        assert_eq!(
+            tok.token_type, expected_type,
+            "test - tokentype wrong. extected={:?}, got={:?} - literal_e: {}",
+            expected_type, tok.token_type, tok.literal,
+        );
+        assert_eq!(
+            tok.literal, expected_literal,
+            "test - literal wrong. extected={}, got={}",
+            expected_literal, tok.literal,
+        )
+    }
+}
+
+#[test]
+fn test_next_token_assignment_operators() {
+    let input = "x = 1; x += 2; x -= 3; x *= 4; x /= 5; x %= 6; x / 7".to_string();
+
+    let tests = [
+        (TokenType::IDENT, "x"),
+        (TokenType::ASSIGN, "="),
+        (TokenType::INT, "1"),
+        (TokenType::SEMICOLON, ";"),
+        (TokenType::IDENT, "x"),
+        (TokenType::PlusAssign, "+="),
+        (TokenType::INT, "2"),
+        (TokenType::SEMICOLON, ";"),
+        (TokenType::IDENT, "x"),
+        (TokenType::MinusAssign, "-="),
+        (TokenType::INT, "3"),
+        (TokenType::SEMICOLON, ";"),
+        (TokenType::IDENT, "x"),
+        (TokenType::AsteriskAssign, "*="),
+        (TokenType::INT, "4"),
+        (TokenType::SEMICOLON, ";"),
+        (TokenType::IDENT, "x"),
+        (TokenType::SlashAssign, "/="),
+        (TokenType::INT, "5"),
+        (TokenType::SEMICOLON, ";"),
+        (TokenType::IDENT, "x"),
+        (TokenType::PercentAssign, "%="),
+        (TokenType::INT, "6"),
+        (TokenType::SEMICOLON, ";"),
+        (TokenType::IDENT, "x"),
+        (TokenType::SLASH, "/"),
+        (TokenType::INT, "7"),
+        (TokenType::EOF, ""),
+    ];
+
+    let mut l = Lexer::new(input);
+    for (expected_type, expected_literal) in tests {
+        let tok = l.next_token();
+        assert_eq!(
+            tok.token_type, expected_type,
+            "test - tokentype wrong. extected={:?}, got={:?} - literal_e: {}",
+            expected_type, tok.token_type, tok.literal,
+        );
+        assert_eq!(
+            tok.literal, expected_literal,
+            "test - literal wrong. extected={}, got={}",
+            expected_literal, tok.literal,
+        )
+    }
+}
+
+#[test]
+fn test_next_token_comparison_and_logical_operators() {
+    let input = "a % 2 <= b >= c < d > e && f || !g & |".to_string();
+
+    let tests = [
+        (TokenType::IDENT, "a"),
+        (TokenType::PERCENT, "%"),
+        (TokenType::INT, "2"),
+        (TokenType::LtEq, "<="),
+        (TokenType::IDENT, "b"),
+        (TokenType::GtEq, ">="),
+        (TokenType::IDENT, "c"),
+        (TokenType::LT, "<"),
+        (TokenType::IDENT, "d"),
+        (TokenType::GT, ">"),
+        (TokenType::IDENT, "e"),
+        (TokenType::AND, "&&"),
+        (TokenType::IDENT, "f"),
+        (TokenType::OR, "||"),
+        (TokenType::BANG, "!"),
+        (TokenType::IDENT, "g"),
+        (TokenType::ILLEGAL, "&"),
+        (TokenType::ILLEGAL, "|"),
+        (TokenType::EOF, ""),
+    ];
+
+    let mut l = Lexer::new(input);
+    for (expected_type, expected_literal) in tests {
+        let tok = l.next_token();
+        assert_eq!(
+            tok.token_type, expected_type,
+            "test - tokentype wrong. extected={:?}, got={:?} - literal_e: {}",
+            expected_type, tok.token_type, tok.literal,
+        );
+        assert_eq!(
+            tok.literal, expected_literal,
+            "test - literal wrong. extected={}, got={}",
+            expected_literal, tok.literal,
+        )
+    }
+}
+
+#[test]
+fn test_next_token_error_handling_keywords() {
+    let input = "try { throw e; } catch (e) {}".to_string();
+
+    let tests = [
+        (TokenType::TRY, "try"),
+        (TokenType::LBRACE, "{"),
+        (TokenType::THROW, "throw"),
+        (TokenType::IDENT, "e"),
+        (TokenType::SEMICOLON, ";"),
+        (TokenType::RBRACE, "}"),
+        (TokenType::CATCH, "catch"),
+        (TokenType::LPAREN, "("),
+        (TokenType::IDENT, "e"),
+        (TokenType::RPAREN, ")"),
+        (TokenType::LBRACE, "{"),
+        (TokenType::RBRACE, "}"),
+        (TokenType::EOF, ""),
+    ];
+
+    let mut l = Lexer::new(input);
+    for (expected_type, expected_literal) in tests {
+        let tok = l.next_token();
+        assert_eq!(
+            tok.token_type, expected_type,
+            "test - tokentype wrong. extected={:?}, got={:?} - literal_e: {}",
+            expected_type, tok.token_type, tok.literal,
+        );
+        assert_eq!(
+            tok.literal, expected_literal,
+            "test - literal wrong. extected={}, got={}",
+            expected_literal, tok.literal,
+        )
+    }
+}
+
+#[test]
+fn test_next_token_floats() {
+    let input = "3.14 + 2 * 0.5; 1..10; x.y2".to_string();
+
+    let tests = [
+        (TokenType::FLOAT, "3.14"),
+        (TokenType::PLUS, "+"),
+        (TokenType::INT, "2"),
+        (TokenType::ASTERISK, "*"),
+        (TokenType::FLOAT, "0.5"),
+        (TokenType::SEMICOLON, ";"),
+        (TokenType::INT, "1"),
+        (TokenType::Spreed, ".."),
+        (TokenType::INT, "10"),
+        (TokenType::SEMICOLON, ";"),
+        (TokenType::IDENT, "x"),
+        (TokenType::Dot, "."),
+        (TokenType::IDENT, "y2"),
+        (TokenType::EOF, ""),
+    ];
+
+    let mut l = Lexer::new(input);
+    for (expected_type, expected_literal) in tests {
+        let tok = l.next_token();
+        assert_eq!(
+            tok.token_type, expected_type,
+            "test - tokentype wrong. extected={:?}, got={:?} - literal_e: {}",
+            expected_type, tok.token_type, tok.literal,
+        );
+        assert_eq!(
+            tok.literal, expected_literal,
+            "test - literal wrong. extected={}, got={}",
+            expected_literal, tok.literal,
+        )
+    }
+}
+
+#[test]
+fn test_multi_line_comment() {
+    let input = "/* one\n two */ let /*/ still comment */ x".to_string();
+
+    let mut l = Lexer::new(input);
+    let tok = l.next_token();
+    assert_eq!(tok.token_type, TokenType::LET);
+    assert_eq!(tok.position.0, 2, "line not advanced inside comment");
+    assert_eq!(l.next_token().token_type, TokenType::IDENT);
+    assert_eq!(l.next_token().token_type, TokenType::EOF);
+}
@@ -1,3 +1,4 @@
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Default, PartialEq, Eq, Clone, Hash)]
 pub enum TokenType {
     #[default]
@@ -8,6 +9,8 @@ pub enum TokenType {
     // Identifiers + literals
     IDENT,
     INT,
+    FLOAT,
+    STRING,
     // Operators
     ASSIGN,
     PLUS,
@@ -15,22 +18,36 @@ pub enum TokenType {
     BANG,
     ASTERISK,
     SLASH,
+    PERCENT,
     EQ,
     NotEq,
     Spreed,
+    FatArrow,
+    PlusAssign,
+    MinusAssign,
+    AsteriskAssign,
+    SlashAssign,
+    PercentAssign,
 
     Dot,
 
     LT,
     GT,
+    LtEq,
+    GtEq,
+    AND,
+    OR,
     // Delimiters
     COMMA,
     SEMICOLON,
+    COLON,
 
     LPAREN,
     RPAREN,
     LBRACE,
     RBRACE,
+    LBRACKET,
+    RBRACKET,
 
     // Keywords
     FUNCTION,
@@ -42,6 +59,17 @@ pub enum TokenType {
     RETURN,
     FOR,
     IN,
+    MATCH,
+    ENUM,
+    STRUCT,
+    INTERFACE,
+    IMPL,
+    TRY,
+    CATCH,
+    THROW,
+    IMPORT,
+    EXPORT,
+    AS,
 }
 
 #[derive(Default, Debug, Clone)]
@@ -62,6 +90,17 @@ pub fn lookup_ident(ident: &str) -> TokenType {
         "return" => TokenType::RETURN,
         "for" => TokenType::FOR,
         "in" => TokenType::IN,
+        "match" => TokenType::MATCH,
+        "enum" => TokenType::ENUM,
+        "struct" => TokenType::STRUCT,
+        "interface" => TokenType::INTERFACE,
+        "impl" => TokenType::IMPL,
+        "try" => TokenType::TRY,
+        "catch" => TokenType::CATCH,
+        "throw" => TokenType::THROW,
+        "import" => TokenType::IMPORT,
+        "export" => TokenType::EXPORT,
+        "as" => TokenType::AS,
         _ => TokenType::IDENT,
     }
 }
@@ -0,0 +1,75 @@
+//! Prolang, an interpreted language for prototyping, as a library.
+//!
+//! [`Engine`] runs programs for a host, keeping what they define between
+//! runs:
+//!
+//! ```
+//! use prolang::{Engine, Value};
+//!
+//! let mut engine = Engine::new();
+//! engine.eval("let double = fn(x) { x * 2 };").unwrap();
+//! assert_eq!(engine.eval("double(21)").unwrap(), Value::Int(42));
+//! ```
+//!
+//! The stages it is built from are public too: the [`Lexer`], the
+//! [`Parser`] and the [`ast`] it builds, the [`Checker`], the tree-walking
+//! [`Evaluator`], and the [`Compiler`] and [`Vm`] running bytecode, with the
+//! [`Loader`] finding the modules a program imports. The items re-exported
+//! here are the whole API, which follows semantic versioning; the modules
+//! they come from are private.
+
+mod checker;
+mod compiler;
+mod engine;
+mod evaluator;
+mod ffi;
+mod gc;
+mod lexer;
+mod module;
+mod parser;
+#[cfg(test)]
+mod test_utils;
+mod vm;
+
+pub use checker::checker::Checker;
+pub use compiler::{
+    bytecode::{Bytecode, CompileError, CompiledFunction, Constant, UpvalueRef},
+    cache,
+    compiler::Compiler,
+    disassembler::Disassembler,
+};
+pub use engine::{
+    convert::{FromValue, IntoResult, IntoValue, NativeFn},
+    engine::{Callable, Engine, Error},
+};
+pub use evaluator::{
+    builtins::Builtin,
+    capabilities::{Capabilities, Paths},
+    environment::{Env, Environment},
+    evaluator::Evaluator,
+    host::HostObject,
+    limits::{Limits, DEFAULT_MAX_DEPTH},
+    value::{
+        BoundMethod, EnumType, EnumValue, ErrorKind, Function, HashKey, InterfaceType, Module,
+        NativeFunction, RuntimeError, StructType, StructValue, TraceFrame, Value,
+    },
+};
+pub use gc::{allocator::CountingAllocator, gc::set_stress as set_gc_stress};
+pub use lexer::{
+    lexer::Lexer,
+    token::{Token, TokenType},
+};
+pub use module::{
+    loader::Loader,
+    project::{Graph, Project},
+};
+pub use parser::{ast, parser::Parser};
+pub use vm::{
+    closure::{Closure, Upvalue},
+    vm::Vm,
+};
+
+// the memory limit is only enforced with the allocator counting
+#[cfg(test)]
+#[global_allocator]
+static ALLOCATOR: gc::allocator::CountingAllocator = gc::allocator::CountingAllocator;
@@ -1,7 +1,69 @@
-use crate::core::runner::{file_runner, repl};
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use prolang::{Capabilities, CountingAllocator, Limits, Paths, DEFAULT_MAX_DEPTH};
+use runner::{file_runner, repl, Emit, EngineKind};
+use std::{path::PathBuf, process::ExitCode, time::Duration};
 
-mod core;
+mod runner;
+mod utils;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// What a program run from the command line may do. It may do anything,
+/// unless it is sandboxed by `--sandbox` or any of the `--allow` flags, which
+/// then grant all it may do.
+#[derive(Debug, Args)]
+struct Sandbox {
+    /// Denies the program everything the `--allow` flags do not grant.
+    #[clap(long)]
+    sandbox: bool,
+    /// Allows writing to the standard output.
+    #[clap(long)]
+    allow_stdout: bool,
+    /// Allows reading the standard input.
+    #[clap(long)]
+    allow_stdin: bool,
+    /// Allows reading the files under a directory.
+    #[clap(long, value_name = "DIR")]
+    allow_read: Vec<PathBuf>,
+    /// Allows writing the files under a directory.
+    #[clap(long, value_name = "DIR")]
+    allow_write: Vec<PathBuf>,
+    /// Allows reading environment variables.
+    #[clap(long)]
+    allow_env: bool,
+    /// Allows reading the time.
+    #[clap(long)]
+    allow_clock: bool,
+    /// Allows drawing random numbers.
+    #[clap(long)]
+    allow_random: bool,
+}
+
+impl Sandbox {
+    fn capabilities(self) -> Capabilities {
+        let sandboxed = self.sandbox
+            || self.allow_stdout
+            || self.allow_stdin
+            || !self.allow_read.is_empty()
+            || !self.allow_write.is_empty()
+            || self.allow_env
+            || self.allow_clock
+            || self.allow_random;
+        if !sandboxed {
+            return Capabilities::all();
+        }
+        Capabilities {
+            stdout: self.allow_stdout,
+            stdin: self.allow_stdin,
+            read: Paths::Under(self.allow_read),
+            write: Paths::Under(self.allow_write),
+            env: self.allow_env,
+            clock: self.allow_clock,
+            random: self.allow_random,
+        }
+    }
+}
 
 /// The `Prolang` Interpreter CLI.
 #[derive(Debug, Parser)]
@@ -14,17 +76,90 @@ struct ProlangCLI {
 
 #[derive(Debug, Subcommand)]
 enum Command {
-    /// Runs the `Prolang` file provided.
-    Run { file_path: String },
+    /// Runs the `Prolang` file provided, or a `.prc` file built from one, or
+    /// else the entry point of the project in the working directory.
+    Run {
+        file_path: Option<String>,
+        /// The backend to run the program with.
+        #[clap(long, value_enum, default_value_t = EngineKind::Interpreter)]
+        engine: EngineKind,
+        /// Fails if the dependencies do not match `prolang.lock`, instead of
+        /// updating it.
+        #[clap(long)]
+        locked: bool,
+        /// Runs the garbage collector on every allocation.
+        #[clap(long)]
+        gc_stress: bool,
+        /// The number of function calls that may be running at once.
+        #[clap(long, default_value_t = DEFAULT_MAX_DEPTH)]
+        max_depth: usize,
+        /// The number of steps the program may run.
+        #[clap(long)]
+        max_steps: Option<u64>,
+        /// How long the program may run, such as `500ms` or `2s`.
+        #[clap(long, value_parser = runner::parse_duration)]
+        timeout: Option<Duration>,
+        /// The memory the program may allocate, such as `64M`.
+        #[clap(long, value_parser = runner::parse_size)]
+        max_memory: Option<usize>,
+        #[clap(flatten)]
+        sandbox: Sandbox,
+    },
+    /// Compiles the `Prolang` file provided, or else the entry point of the
+    /// project in the working directory, without running it.
+    Build {
+        file_path: Option<String>,
+        /// The output to produce.
+        #[clap(long, value_enum, default_value_t = Emit::Prc)]
+        emit: Emit,
+        /// Fails if the dependencies do not match `prolang.lock`, instead of
+        /// updating it.
+        #[clap(long)]
+        locked: bool,
+    },
+    /// Creates a project: a directory with a `prolang.toml`, a
+    /// `src/main.pr` and a `tests` directory.
+    New {
+        /// The directory to create, whose name the project is given.
+        name: String,
+    },
     /// Init an interactive repl session
     Repl,
 }
 
-fn main() {
+fn main() -> ExitCode {
     let prolang_cli = ProlangCLI::parse();
 
     match prolang_cli.command {
-        Command::Run { file_path } => file_runner::run_file(file_path),
-        Command::Repl => repl::start(),
+        Command::Run {
+            file_path,
+            engine,
+            locked,
+            gc_stress,
+            max_depth,
+            max_steps,
+            timeout,
+            max_memory,
+            sandbox,
+        } => {
+            prolang::set_gc_stress(gc_stress);
+            let limits = Limits {
+                max_depth,
+                max_steps,
+                timeout,
+                max_memory,
+            };
+            file_runner::run_file(file_path, engine, locked, limits, &sandbox.capabilities())
+        }
+        Command::Build {
+            file_path,
+            emit,
+            locked,
+        } => file_runner::build_file(file_path, emit, locked),
+        Command::New { name } => file_runner::new_project(name),
+        Command::Repl => {
+            repl::start();
+            ExitCode::SUCCESS
+        }
     }
 }
@@ -0,0 +1,222 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{
+    evaluator::{
+        capabilities::Capabilities,
+        value::{ErrorKind, RuntimeError},
+    },
+    lexer::lexer::Lexer,
+    parser::{ast::Program, parser::Parser},
+};
+
+/// The extension of source files, which imports may leave out.
+pub const SOURCE_EXTENSION: &str = "pr";
+
+/// How errors name the file of a program read from no file, such as one
+/// typed into the REPL.
+pub const MAIN_FILE: &str = "main.pr";
+
+/// A module read for an `import`.
+pub(crate) struct Source {
+    /// The file, canonicalized, which identifies the module.
+    pub path: PathBuf,
+    /// The name importers see the module by: its file name without extension.
+    pub name: String,
+    pub text: String,
+    pub program: Program,
+}
+
+/// A project as the loader sees it: the directories its files import from.
+#[derive(Debug, Clone)]
+pub(crate) struct Package {
+    /// The project directory, under which the package's files are.
+    pub root: PathBuf,
+    /// Directories searched for imports of the package's files that are not
+    /// found next to the importing file.
+    pub sources: Vec<PathBuf>,
+    /// The packages whose sources imports starting with their name are
+    /// searched in, as indexes of the loader's packages.
+    pub dependencies: BTreeMap<String, usize>,
+}
+
+/// Finds, reads and parses the files `import` names for both engines, which
+/// keep what they built of each module themselves. Tracks the modules being
+/// loaded, so that a module importing itself, directly or not, is an error
+/// rather than endless recursion.
+#[derive(Default, Clone)]
+pub struct Loader {
+    /// The file the program was read from, which relative imports of the
+    /// program resolve against. Imports of a program without one resolve
+    /// against the working directory.
+    main: Option<PathBuf>,
+    packages: Vec<Package>,
+    /// The modules being loaded, outermost first.
+    loading: Vec<PathBuf>,
+}
+
+impl Loader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves the imports of the program against the file at `path`.
+    pub fn set_main(&mut self, path: &Path) {
+        self.main = Some(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+    }
+
+    /// Resolves the imports of files under the package's root through its
+    /// sources and dependencies, returning its index for the dependencies of
+    /// packages added later.
+    pub(crate) fn add_package(&mut self, package: Package) -> usize {
+        self.packages.push(package);
+        self.packages.len() - 1
+    }
+
+    /// Finds the file `import` names: relative to the directory of the file
+    /// importing it, or else to the sources of the package that file is in,
+    /// or else, if the import starts with the name of one of the package's
+    /// dependencies, to the sources of that dependency.
+    pub(crate) fn resolve(&self, import: &str) -> Result<PathBuf, String> {
+        let mut relative = PathBuf::from(import);
+        if relative.extension().is_none() {
+            relative.set_extension(SOURCE_EXTENSION);
+        }
+        let importer = self.loading.last().or(self.main.as_ref());
+        let dir = importer
+            .and_then(|file| file.parent())
+            .unwrap_or(Path::new(""));
+        let mut candidates = vec![dir.join(&relative)];
+        if let Some(package) = importer.and_then(|file| self.package_of(file)) {
+            candidates.extend(package.sources.iter().map(|dir| dir.join(&relative)));
+
+            let mut components = relative.components();
+            let dependency = match components.next() {
+                Some(Component::Normal(name)) => name
+                    .to_str()
+                    .and_then(|name| package.dependencies.get(name)),
+                _ => None,
+            };
+            if let Some(dependency) = dependency {
+                let rest = components.as_path();
+                let sources = &self.packages[*dependency].sources;
+                candidates.extend(sources.iter().map(|dir| dir.join(rest)));
+            }
+        }
+
+        candidates
+            .into_iter()
+            .find_map(|path| path.canonicalize().ok().filter(|path| path.is_file()))
+            .ok_or_else(|| format!("cannot find module \"{}\"", import))
+    }
+
+    /// Reads and parses the module at `path`, a path returned by
+    /// [`resolve`](Self::resolve), which is being loaded until
+    /// [`leave`](Self::leave) is called. Fails if `capabilities` do not
+    /// grant reading it, or if it is being loaded already, naming the
+    /// imports that lead back to it.
+    pub(crate) fn enter(
+        &mut self,
+        path: PathBuf,
+        capabilities: &Capabilities,
+    ) -> Result<Source, RuntimeError> {
+        capabilities.check_read("import", &path.to_string_lossy())?;
+
+        let start = self
+            .main
+            .iter()
+            .chain(&self.loading)
+            .position(|p| *p == path);
+        if let Some(start) = start {
+            let chain: Vec<String> = self
+                .main
+                .iter()
+                .chain(&self.loading)
+                .skip(start)
+                .chain([&path])
+                .map(|p| self.display(p))
+                .collect();
+            return Err(RuntimeError::new(format!(
+                "import cycle: {}",
+                chain.join(" -> ")
+            )));
+        }
+
+        let text = fs::read_to_string(&path).map_err(|err| {
+            RuntimeError::new(format!(
+                "cannot read module {}: {}",
+                self.display(&path),
+                err
+            ))
+        })?;
+        let mut parser = Parser::new(Lexer::new(text.trim_end().to_string()));
+        parser.set_file(self.display(&path));
+        let program = parser
+            .parse_program()
+            .unwrap_or(Program { statements: vec![] });
+        if !parser.errors().is_empty() {
+            return Err(RuntimeError::new(format!(
+                "module {} does not parse: {}",
+                self.display(&path),
+                parser.errors().join("; ")
+            )));
+        }
+
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.loading.push(path.clone());
+        Ok(Source {
+            path,
+            name,
+            text,
+            program,
+        })
+    }
+
+    /// Finishes loading the module entered last.
+    pub(crate) fn leave(&mut self) {
+        self.loading.pop();
+    }
+
+    /// The package with the innermost root above `file`.
+    fn package_of(&self, file: &Path) -> Option<&Package> {
+        self.packages
+            .iter()
+            .filter(|package| file.starts_with(&package.root))
+            .max_by_key(|package| package.root.components().count())
+    }
+
+    /// How errors name the main file: by its file name.
+    pub fn main_file(&self) -> String {
+        self.main
+            .as_deref()
+            .map_or(MAIN_FILE.to_string(), |main| self.display(main))
+    }
+
+    /// `path` relative to the directory of the main file, when it is under
+    /// it, which is how errors name the file.
+    pub fn display(&self, path: &Path) -> String {
+        let dir = self.main.as_deref().and_then(Path::parent);
+        let relative = dir.and_then(|dir| path.strip_prefix(dir).ok());
+        relative.unwrap_or(path).display().to_string()
+    }
+}
+
+/// How errors name the main file of a program run without a loader.
+pub fn main_file(loader: Option<&Loader>) -> String {
+    loader.map_or(MAIN_FILE.to_string(), Loader::main_file)
+}
+
+/// The error of an `import` of a file by a program run without a loader,
+/// which may import the standard library only.
+pub fn no_loader(import: &str) -> RuntimeError {
+    RuntimeError::with_kind(
+        ErrorKind::PermissionDenied,
+        format!("cannot import \"{}\": no loader was set", import),
+    )
+}
@@ -0,0 +1,154 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use toml::{Table, Value};
+
+use super::manifest::quote;
+
+/// The file recording how the dependencies of a project were resolved, next
+/// to its manifest.
+pub const LOCK_FILE: &str = "prolang.lock";
+
+const HEADER: &str = "# Written by prolang when the dependencies change. Do not edit it.\n";
+
+/// What `prolang.lock` records: every project the locking project depends
+/// on, directly or not.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Lock {
+    /// Sorted by name, which is unique.
+    pub packages: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    /// The project directory, relative to the locking project's.
+    pub path: PathBuf,
+    /// The hash of the project's manifest and source files, which changes
+    /// with any of them.
+    pub hash: u64,
+    /// The names of the packages it depends on.
+    pub dependencies: Vec<String>,
+}
+
+impl Lock {
+    /// Reads the lock of the project in `root`, if it has one.
+    pub fn read(root: &Path) -> Result<Option<Self>, String> {
+        let path = root.join(LOCK_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text = fs::read_to_string(&path)
+            .map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+        Self::parse(&text)
+            .map(Some)
+            .map_err(|err| format!("invalid {}: {}", path.display(), err))
+    }
+
+    /// Writes the lock of the project in `root`.
+    pub fn write(&self, root: &Path) -> Result<(), String> {
+        let path = root.join(LOCK_FILE);
+        fs::write(&path, self.to_toml())
+            .map_err(|err| format!("cannot write {}: {}", path.display(), err))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let table: Table = text
+            .parse()
+            .map_err(|err: toml::de::Error| err.message().to_owned())?;
+        let packages = match table.get("package") {
+            Some(Value::Array(packages)) => packages,
+            Some(_) => return Err("`package` must be an array of tables".to_owned()),
+            None => return Ok(Self::default()),
+        };
+
+        let packages = packages
+            .iter()
+            .map(|package| {
+                let Value::Table(package) = package else {
+                    return Err("`package` must be an array of tables".to_owned());
+                };
+                let string = |key: &str| match package.get(key) {
+                    Some(Value::String(s)) => Ok(s.clone()),
+                    _ => Err(format!("`package.{}` must be a string", key)),
+                };
+                let hash = string("hash")?;
+                let hash = hash
+                    .strip_prefix("fnv1a:")
+                    .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| format!("invalid hash `{}`", hash))?;
+                let dependencies = match package.get("dependencies") {
+                    Some(Value::Array(names)) => names
+                        .iter()
+                        .map(|name| match name {
+                            Value::String(name) => Ok(name.clone()),
+                            _ => Err("`package.dependencies` must be an array of strings"),
+                        })
+                        .collect::<Result<_, _>>()?,
+                    None => vec![],
+                    Some(_) => {
+                        return Err("`package.dependencies` must be an array of strings".to_owned())
+                    }
+                };
+
+                Ok(LockedPackage {
+                    name: string("name")?,
+                    version: string("version")?,
+                    path: PathBuf::from(string("path")?),
+                    hash,
+                    dependencies,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { packages })
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut out = HEADER.to_owned();
+        for package in &self.packages {
+            let dependencies: Vec<String> = package.dependencies.iter().map(|d| quote(d)).collect();
+            out.push_str(&format!(
+                "\n[[package]]\nname = {}\nversion = {}\npath = {}\nhash = \"fnv1a:{:016x}\"\ndependencies = [{}]\n",
+                quote(&package.name),
+                quote(&package.version),
+                quote(&package.path.to_string_lossy()),
+                package.hash,
+                dependencies.join(", ")
+            ));
+        }
+
+        out
+    }
+
+    /// Why `self` does not lock the same dependencies as `other`, if it
+    /// does not.
+    pub fn difference(&self, other: &Lock) -> Option<String> {
+        let find = |lock: &Lock, name: &str| lock.packages.iter().find(|p| p.name == name).cloned();
+        for package in &self.packages {
+            match find(other, &package.name) {
+                None => return Some(format!("`{}` is not locked", package.name)),
+                Some(locked) if locked.path != package.path => {
+                    return Some(format!(
+                        "`{}` is at {}, but locked at {}",
+                        package.name,
+                        package.path.display(),
+                        locked.path.display()
+                    ))
+                }
+                Some(locked) if locked != *package => {
+                    return Some(format!("`{}` changed since it was locked", package.name))
+                }
+                _ => {}
+            }
+        }
+        other
+            .packages
+            .iter()
+            .find(|locked| find(self, &locked.name).is_none())
+            .map(|locked| format!("`{}` is locked but no longer depended on", locked.name))
+    }
+}
@@ -0,0 +1,176 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use toml::{Table, Value};
+
+use super::version::{Version, VersionReq};
+
+/// The file describing a project, in its root directory.
+pub const MANIFEST_FILE: &str = "prolang.toml";
+
+/// What `prolang.toml` says about a project.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub name: String,
+    pub version: String,
+    /// The file `prolang run` runs, relative to the project directory.
+    pub entry: PathBuf,
+    /// The directories imports are searched in when they are not found next
+    /// to the importing file, relative to the project directory.
+    pub sources: Vec<PathBuf>,
+    pub dependencies: BTreeMap<String, Dependency>,
+}
+
+/// A project depended on, from the `[dependencies]` table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dependency {
+    /// The directory of the project, relative to the one depending on it.
+    pub path: PathBuf,
+    /// The versions of the project accepted, any if `None`.
+    pub version: Option<VersionReq>,
+}
+
+impl Manifest {
+    /// The manifest `prolang new` writes for a project called `name`.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            version: "0.1.0".to_owned(),
+            entry: PathBuf::from("src/main.pr"),
+            sources: vec![PathBuf::from("src")],
+            dependencies: BTreeMap::new(),
+        }
+    }
+
+    /// Reads a manifest. The `[package]` table must name the project and its
+    /// version; the entry point and source directories default to those of
+    /// [`new`](Self::new).
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let table: Table = text
+            .parse()
+            .map_err(|err: toml::de::Error| err.message().to_owned())?;
+        let package = match table.get("package") {
+            Some(Value::Table(package)) => package,
+            Some(_) => return Err("`package` must be a table".to_owned()),
+            None => return Err("missing the `[package]` table".to_owned()),
+        };
+
+        let name = string(package, "package.name")?.ok_or("missing `package.name`")?;
+        if !is_valid_name(&name) {
+            return Err(format!(
+                "invalid package name `{}`: use letters, digits, `_` and `-`",
+                name
+            ));
+        }
+        let version = string(package, "package.version")?.ok_or("missing `package.version`")?;
+        Version::parse(&version).map_err(|err| format!("`package.version`: {}", err))?;
+        let defaults = Self::new(&name);
+        let entry = string(package, "package.entry")?
+            .map(PathBuf::from)
+            .unwrap_or(defaults.entry);
+        let sources = match package.get("sources") {
+            Some(Value::Array(sources)) => sources
+                .iter()
+                .map(|source| match source {
+                    Value::String(source) => Ok(PathBuf::from(source)),
+                    _ => Err("`package.sources` must be an array of strings".to_owned()),
+                })
+                .collect::<Result<_, _>>()?,
+            Some(_) => return Err("`package.sources` must be an array of strings".to_owned()),
+            None => defaults.sources,
+        };
+
+        let mut dependencies = BTreeMap::new();
+        match table.get("dependencies") {
+            Some(Value::Table(table)) => {
+                for (name, dependency) in table {
+                    dependencies.insert(name.clone(), Dependency::parse(name, dependency)?);
+                }
+            }
+            Some(_) => return Err("`dependencies` must be a table".to_owned()),
+            None => {}
+        }
+
+        Ok(Self {
+            name,
+            version,
+            entry,
+            sources,
+            dependencies,
+        })
+    }
+
+    /// The manifest as `prolang.toml` holds it.
+    pub fn to_toml(&self) -> String {
+        let sources: Vec<String> = self
+            .sources
+            .iter()
+            .map(|source| quote(&source.to_string_lossy()))
+            .collect();
+        let mut out = format!(
+            "[package]\nname = {}\nversion = {}\nentry = {}\nsources = [{}]\n\n[dependencies]\n",
+            quote(&self.name),
+            quote(&self.version),
+            quote(&self.entry.to_string_lossy()),
+            sources.join(", ")
+        );
+        for (name, dependency) in &self.dependencies {
+            out.push_str(&format!(
+                "{} = {{ path = {}",
+                name,
+                quote(&dependency.path.to_string_lossy())
+            ));
+            if let Some(version) = &dependency.version {
+                out.push_str(&format!(", version = {}", quote(&version.to_string())));
+            }
+            out.push_str(" }\n");
+        }
+
+        out
+    }
+}
+
+impl Dependency {
+    fn parse(name: &str, dependency: &Value) -> Result<Self, String> {
+        let key = format!("dependencies.{}", name);
+        let Value::Table(dependency) = dependency else {
+            return Err(format!(
+                "`{}` must be a table like {{ path = \"../{}\" }}",
+                key, name
+            ));
+        };
+        let path = string(dependency, &format!("{}.path", key))?
+            .ok_or_else(|| format!("missing `{}.path`", key))?;
+        let version = string(dependency, &format!("{}.version", key))?
+            .map(|version| VersionReq::parse(&version))
+            .transpose()
+            .map_err(|err| format!("`{}.version`: {}", key, err))?;
+
+        Ok(Self {
+            path: PathBuf::from(path),
+            version,
+        })
+    }
+}
+
+/// Whether `name` can name a package: what imports of it start with.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// The string at the last part of `key` in `table`, if it is there.
+fn string(table: &Table, key: &str) -> Result<Option<String>, String> {
+    let field = key.rsplit('.').next().unwrap_or(key);
+    match table.get(field) {
+        Some(Value::String(s)) => Ok(Some(s.clone())),
+        Some(_) => Err(format!("`{}` must be a string", key)),
+        None => Ok(None),
+    }
+}
+
+/// `s` as a TOML string.
+pub(crate) fn quote(s: &str) -> String {
+    Value::String(s.to_owned()).to_string()
+}
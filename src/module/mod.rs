@@ -0,0 +1,8 @@
+pub mod loader;
+pub mod lock;
+pub mod manifest;
+pub mod project;
+pub mod version;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,358 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::compiler::cache::source_hash;
+
+use super::{
+    loader::{Loader, Package, SOURCE_EXTENSION},
+    lock::{Lock, LockedPackage},
+    manifest::{is_valid_name, Dependency, Manifest, MANIFEST_FILE},
+    version::Version,
+};
+
+/// A directory with a `prolang.toml`.
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub(crate) root: PathBuf,
+    pub(crate) manifest: Manifest,
+}
+
+impl Project {
+    /// Reads the project whose manifest is in `root`.
+    pub fn open(root: &Path) -> Result<Self, String> {
+        let path = root.join(MANIFEST_FILE);
+        let text = fs::read_to_string(&path)
+            .map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+        let manifest =
+            Manifest::parse(&text).map_err(|err| format!("invalid {}: {}", path.display(), err))?;
+
+        Ok(Self {
+            root: root.canonicalize().unwrap_or_else(|_| root.to_path_buf()),
+            manifest,
+        })
+    }
+
+    /// The project `path` belongs to: the closest directory from it upwards
+    /// with a manifest, if there is one.
+    pub fn find(path: &Path) -> Result<Option<Self>, String> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        path.ancestors()
+            .find(|dir| dir.join(MANIFEST_FILE).is_file())
+            .map(Self::open)
+            .transpose()
+    }
+
+    /// Creates a project in the new directory `root`, named after it: the
+    /// manifest, a `src/main.pr` to run and an empty `tests` directory.
+    pub fn create(root: &Path) -> Result<Self, String> {
+        let name = root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if !is_valid_name(&name) {
+            return Err(format!(
+                "invalid project name `{}`: use letters, digits, `_` and `-`",
+                name
+            ));
+        }
+        if root.exists() {
+            return Err(format!("{} already exists", root.display()));
+        }
+
+        let manifest = Manifest::new(&name);
+        let create = |path: &Path, contents: Option<String>| {
+            let created = match contents {
+                Some(contents) => fs::write(path, contents),
+                None => fs::create_dir_all(path),
+            };
+            created.map_err(|err| format!("cannot create {}: {}", path.display(), err))
+        };
+        let entry = root.join(&manifest.entry);
+        create(entry.parent().unwrap_or(root), None)?;
+        create(&root.join("tests"), None)?;
+        create(&root.join(MANIFEST_FILE), Some(manifest.to_toml()))?;
+        create(
+            &entry,
+            Some(format!("println(\"Hello from {}!\");\n", name)),
+        )?;
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            manifest,
+        })
+    }
+
+    /// The directory the manifest is in.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The name the manifest gives the project.
+    pub fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    /// The file `prolang run` runs.
+    pub fn entry(&self) -> PathBuf {
+        self.root.join(&self.manifest.entry)
+    }
+
+    /// The project with the projects it depends on, directly or not, each
+    /// found once and checked against the versions required of it.
+    pub fn resolve(self) -> Result<Graph, String> {
+        let mut graph = Graph {
+            projects: vec![],
+            dependencies: vec![],
+        };
+        graph.add(self, &mut vec![])?;
+
+        Ok(graph)
+    }
+
+    /// The hash of the manifest and the source files of the project, which
+    /// changes with any of them.
+    pub fn content_hash(&self) -> Result<u64, String> {
+        let mut files = vec![self.root.join(MANIFEST_FILE)];
+        for source in &self.manifest.sources {
+            source_files(&self.root.join(source), &mut files)?;
+        }
+        files.sort();
+        files.dedup();
+
+        let mut contents = String::new();
+        for file in files {
+            let text = fs::read_to_string(&file)
+                .map_err(|err| format!("cannot read {}: {}", file.display(), err))?;
+            let name = relative_path(&file, &self.root);
+            contents.push_str(&format!("{}\0{}\0", name.display(), text));
+        }
+
+        Ok(source_hash(&contents))
+    }
+}
+
+/// A project and the projects it depends on.
+#[derive(Debug)]
+pub struct Graph {
+    /// The project depending on the others first, then each dependency once.
+    pub(crate) projects: Vec<Project>,
+    /// The dependencies of each project by name, as indexes of `projects`.
+    pub(crate) dependencies: Vec<BTreeMap<String, usize>>,
+}
+
+impl Graph {
+    /// The project depending on the others.
+    pub fn root(&self) -> &Project {
+        &self.projects[0]
+    }
+
+    /// A loader for a program of the root project read from `main`, which
+    /// resolves imports through the source directories and dependencies of
+    /// the project the importing file is in.
+    pub fn loader(&self, main: &Path) -> Loader {
+        let mut loader = Loader::new();
+        loader.set_main(main);
+        for (project, dependencies) in self.projects.iter().zip(&self.dependencies) {
+            loader.add_package(Package {
+                root: project.root.clone(),
+                sources: project
+                    .manifest
+                    .sources
+                    .iter()
+                    .map(|source| project.root.join(source))
+                    .collect(),
+                dependencies: dependencies.clone(),
+            });
+        }
+
+        loader
+    }
+
+    /// What `prolang.lock` records of the dependencies as they are now.
+    pub(crate) fn lock(&self) -> Result<Lock, String> {
+        let mut packages = self.projects[1..]
+            .iter()
+            .zip(&self.dependencies[1..])
+            .map(|(project, dependencies)| {
+                Ok(LockedPackage {
+                    name: project.manifest.name.clone(),
+                    version: project.manifest.version.clone(),
+                    path: relative_path(&project.root, &self.root().root),
+                    hash: project.content_hash()?,
+                    dependencies: dependencies
+                        .values()
+                        .map(|i| self.projects[*i].manifest.name.clone())
+                        .collect(),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Lock { packages })
+    }
+
+    /// Checks `prolang.lock` against the dependencies as they are now, and
+    /// rewrites it if they changed, unless `locked`, when that is an error.
+    pub fn sync_lock(&self, locked: bool) -> Result<(), String> {
+        let root = &self.root().root;
+        let lock = self.lock()?;
+        let existing = match Lock::read(root) {
+            Ok(existing) => existing,
+            Err(err) if locked => return Err(err),
+            Err(_) => None,
+        };
+        let difference = match &existing {
+            Some(existing) => lock.difference(existing),
+            // projects without dependencies need no lock
+            None if lock.packages.is_empty() => None,
+            None => Some("there is no prolang.lock".to_owned()),
+        };
+        match difference {
+            None => Ok(()),
+            Some(difference) if locked => Err(format!(
+                "the dependencies do not match prolang.lock: {}",
+                difference
+            )),
+            Some(_) => lock.write(root),
+        }
+    }
+
+    /// Adds `project` and, first, what it depends on, returning its index.
+    /// `path` holds the projects depending on it, to reject cycles.
+    fn add(&mut self, project: Project, path: &mut Vec<usize>) -> Result<usize, String> {
+        let index = self.projects.len();
+        let dependencies = project.manifest.dependencies.clone();
+        self.projects.push(project);
+        self.dependencies.push(BTreeMap::new());
+        path.push(index);
+
+        for (name, dependency) in dependencies {
+            let dependent = &self.projects[index];
+            let dir = dependent.root.join(&dependency.path);
+            let root = dir.canonicalize().map_err(|_| {
+                format!(
+                    "`{}` depends on `{}` at {}, which does not exist",
+                    dependent.manifest.name,
+                    name,
+                    dir.display()
+                )
+            })?;
+
+            let found = match self.projects.iter().position(|p| p.root == root) {
+                Some(found) if path.contains(&found) => {
+                    let start = path.iter().position(|i| *i == found).unwrap_or(0);
+                    let cycle: Vec<&str> = path[start..]
+                        .iter()
+                        .chain([&found])
+                        .map(|i| self.projects[*i].manifest.name.as_str())
+                        .collect();
+                    return Err(format!("dependency cycle: {}", cycle.join(" -> ")));
+                }
+                Some(found) => {
+                    check_dependency(dependent, &name, &dependency, &self.projects[found])?;
+                    found
+                }
+                None => {
+                    let project = Project::open(&root).map_err(|err| {
+                        format!(
+                            "dependency `{}` of `{}`: {}",
+                            name, dependent.manifest.name, err
+                        )
+                    })?;
+                    check_dependency(dependent, &name, &dependency, &project)?;
+                    if let Some(other) = self.projects.iter().find(|p| p.manifest.name == name) {
+                        return Err(format!(
+                            "two packages are named `{}`: {} and {}",
+                            name,
+                            other.root.display(),
+                            project.root.display()
+                        ));
+                    }
+                    self.add(project, path)?
+                }
+            };
+            self.dependencies[index].insert(name, found);
+        }
+
+        path.pop();
+        Ok(index)
+    }
+}
+
+/// Checks that `project` is the package `dependent` depends on by `name`,
+/// in a version it accepts.
+fn check_dependency(
+    dependent: &Project,
+    name: &str,
+    dependency: &Dependency,
+    project: &Project,
+) -> Result<(), String> {
+    if project.manifest.name != name {
+        return Err(format!(
+            "`{}` depends on `{}` at {}, which is the package `{}`",
+            dependent.manifest.name,
+            name,
+            project.root.display(),
+            project.manifest.name
+        ));
+    }
+    if let Some(required) = &dependency.version {
+        let version = Version::parse(&project.manifest.version)?;
+        if !required.matches(&version) {
+            return Err(format!(
+                "`{}` requires `{}` {}, but {} has version {}",
+                dependent.manifest.name,
+                name,
+                required,
+                project.root.display(),
+                version
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds the source files under `dir` to `files`.
+fn source_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        // a source directory that does not exist holds no files
+        Err(_) if !dir.exists() => return Ok(()),
+        Err(err) => return Err(format!("cannot read {}: {}", dir.display(), err)),
+    };
+    for entry in entries {
+        let path = entry
+            .map_err(|err| format!("cannot read {}: {}", dir.display(), err))?
+            .path();
+        if path.is_dir() {
+            source_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == SOURCE_EXTENSION) {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// `path` relative to `base`, both absolute.
+fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let common = path
+        .components()
+        .zip(base.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut relative: PathBuf = base
+        .components()
+        .skip(common)
+        .map(|_| Component::ParentDir)
+        .collect();
+    relative.extend(path.components().skip(common));
+    match relative.as_os_str().is_empty() {
+        true => PathBuf::from("."),
+        false => relative,
+    }
+}
@@ -0,0 +1,612 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    compiler::compiler::Compiler,
+    evaluator::{
+        capabilities::{Capabilities, Paths},
+        environment::Environment,
+        evaluator::Evaluator,
+        value::ErrorKind,
+    },
+    module::{
+        loader::Loader,
+        lock::{Lock, LockedPackage},
+        manifest::{Dependency, Manifest},
+        project::Project,
+        version::{Version, VersionReq},
+    },
+    test_utils::{parse, reading, run_both},
+    vm::vm::Vm,
+};
+
+/// Writes `input` to `main.pr` in `dir` and runs it with both engines.
+fn run(dir: &Path, input: &str) -> [(&'static str, Result<String, String>); 2] {
+    let main = dir.join("main.pr");
+    fs::write(&main, input).unwrap();
+    run_both(&parse(input), Some(&main))
+}
+
+#[test]
+fn test_imports() {
+    let dir = std::env::temp_dir().join(format!("prolang-modules-{}", std::process::id()));
+    fs::create_dir_all(dir.join("lib")).unwrap();
+    let files = [
+        (
+            "lib/util.pr",
+            "import \"counter\" as counter;
+            let hidden = 10;
+            export let helper = fn(x) { x * counter.step + hidden };
+            export let name = \"util\";",
+        ),
+        ("lib/counter.pr", "export let step = 2;"),
+        ("a.pr", "import \"b\" as b; export let x = 1;"),
+        ("b.pr", "import \"a.pr\" as a; export let y = 2;"),
+        ("back.pr", "import \"main\" as main;"),
+        ("leaky.pr", "export let get = fn() { secret };"),
+        ("early.pr", "return 1;"),
+    ];
+    for (name, source) in files {
+        fs::write(dir.join(name), source).unwrap();
+    }
+
+    let tests = [
+        ("import \"lib/util\" as util; util.helper(16)", Ok("42")),
+        ("import \"lib/util.pr\" as util; util.name", Ok("util")),
+        ("import \"lib/util\" as util; util", Ok("module util")),
+        (
+            "import \"lib/util\" as a; import \"./lib/util.pr\" as b; a == b",
+            Ok("true"),
+        ),
+        (
+            "import \"lib/util\" as util; util.hidden",
+            Err("module util has no export `hidden`"),
+        ),
+        (
+            "import \"lib/util\" as util; util.step",
+            Err("module util has no export `step`"),
+        ),
+        (
+            "import \"missing\" as m; 1",
+            Err("cannot find module \"missing\""),
+        ),
+        (
+            "import \"a\" as a; a.x",
+            Err("import cycle: a.pr -> b.pr -> a.pr"),
+        ),
+        (
+            "import \"back\" as back; 1",
+            Err("import cycle: main.pr -> back.pr -> main.pr"),
+        ),
+        (
+            "let secret = 1; import \"leaky\" as leaky; leaky.get()",
+            Err("identifier not found: secret"),
+        ),
+        (
+            "import \"early\" as early; 1",
+            Err("module early returns outside of a function"),
+        ),
+    ];
+
+    for (input, expected) in tests {
+        for (engine, result) in run(&dir, input) {
+            let result = result.as_ref().map(String::as_str).map_err(String::as_str);
+            assert_eq!(
+                result, expected,
+                "wrong result from the {} for {}",
+                engine, input
+            );
+        }
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Errors raised in a module name its file, relative to the directory of
+/// the main file, both where they were raised and in the calls they unwound.
+#[test]
+fn test_errors_in_modules_name_their_file() {
+    let dir = std::env::temp_dir().join(format!("prolang-module-errors-{}", std::process::id()));
+    fs::create_dir_all(dir.join("lib")).unwrap();
+    fs::write(
+        dir.join("lib/calc.pr"),
+        "let divide = fn(a, b) { a / b };
+export let ratio = fn(a, b) {
+    1 + divide(a, b)
+};",
+    )
+    .unwrap();
+    let main = dir.join("app.pr");
+    let input = "import \"lib/calc\" as calc;
+let run = fn() { 1 + calc.ratio(1, 0) };
+let caught = try { run() } catch (e) { [e.file, e.trace[0][\"file\"], e.trace[1][\"file\"]] };
+run();";
+    fs::write(&main, input).unwrap();
+    let program = parse(input);
+
+    let mut evaluator = Evaluator::new();
+    evaluator.set_path(&main);
+    evaluator.set_capabilities(reading());
+    let env = Environment::new();
+    let interpreted = evaluator.eval_program(&program, &env).unwrap_err();
+    let mut compiler = Compiler::new();
+    compiler.set_path(&main);
+    compiler.set_capabilities(reading());
+    let compiled = Vm::new(compiler.compile(&program).unwrap())
+        .run()
+        .unwrap_err();
+
+    for (engine, err) in [("interpreter", interpreted), ("vm", compiled)] {
+        assert_eq!(
+            err.report(),
+            "DivisionByZero: division by zero
+    at lib/calc.pr:1:27
+    at divide (lib/calc.pr:3:15)
+    at ratio (app.pr:2:32)
+    at run (app.pr:4:4)",
+            "wrong report from the {}",
+            engine
+        );
+    }
+    assert_eq!(
+        env.borrow().get("caught").map(|caught| caught.to_string()),
+        Some("[\"lib/calc.pr\", \"lib/calc.pr\", \"app.pr\"]".to_string())
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_import_from_working_directory() {
+    let program = parse("import \"no/such/module\" as m;");
+    let mut evaluator = Evaluator::new();
+    evaluator.set_loader(Loader::new());
+    let err = evaluator
+        .eval_program(&program, &Environment::new())
+        .unwrap_err();
+    assert_eq!(err.message, "cannot find module \"no/such/module\"");
+}
+
+/// Imports read files only where reading them is granted, so a sandboxed
+/// program sees neither what a module exports nor what a file that does not
+/// parse holds, and without a loader imports no files at all.
+#[test]
+fn test_imports_need_read_capability() {
+    let dir = std::env::temp_dir().join(format!("prolang-import-caps-{}", std::process::id()));
+    fs::create_dir_all(dir.join("app")).unwrap();
+    fs::create_dir_all(dir.join("secret")).unwrap();
+    fs::write(dir.join("app/util.pr"), "export let one = 1;").unwrap();
+    fs::write(dir.join("secret/keys.pr"), "export let token = \"s3cr3t\";").unwrap();
+    fs::write(dir.join("secret/cfg.pr"), "password = DB_PASSWORD").unwrap();
+    let main = dir.join("app/main.pr");
+    let denied = |file: &str| {
+        let path = dir.join(file).canonicalize().unwrap();
+        Err(format!("`import` may not read {}", path.display()))
+    };
+    let keys = dir.join("secret/keys").display().to_string();
+    let cfg = dir.join("secret/cfg").display().to_string();
+
+    let tests = [
+        (
+            Paths::All,
+            "import \"util\" as u; u.one",
+            Ok("1".to_string()),
+        ),
+        (
+            Paths::All,
+            &*format!("import {:?} as k; k.token", keys),
+            Ok("s3cr3t".to_string()),
+        ),
+        (
+            Paths::Under(vec![dir.join("app")]),
+            "import \"util\" as u; u.one",
+            Ok("1".to_string()),
+        ),
+        (
+            Paths::Under(vec![dir.join("app")]),
+            &*format!("import {:?} as k; k.token", keys),
+            denied("secret/keys.pr"),
+        ),
+        (
+            Paths::Under(vec![dir.join("app")]),
+            &*format!("import {:?} as c; 1", cfg),
+            denied("secret/cfg.pr"),
+        ),
+        (
+            Paths::Under(vec![]),
+            "import \"util\" as u; u.one",
+            denied("app/util.pr"),
+        ),
+        (
+            Paths::Under(vec![]),
+            "import \"std/math\" as m; m.abs(-1)",
+            Ok("1".to_string()),
+        ),
+    ];
+    for (read, input, expected) in tests {
+        let capabilities = Capabilities {
+            read,
+            ..Capabilities::default()
+        };
+        let program = parse(input);
+
+        let mut evaluator = Evaluator::new();
+        evaluator.set_path(&main);
+        evaluator.set_capabilities(capabilities.clone());
+        let interpreted = evaluator.eval_program(&program, &Environment::new());
+        if expected.is_err() {
+            let kind = interpreted.as_ref().map_err(|err| err.kind);
+            assert_eq!(kind.unwrap_err(), ErrorKind::PermissionDenied, "{}", input);
+        }
+        let interpreted = interpreted
+            .map(|v| v.to_string())
+            .map_err(|err| err.message);
+        assert_eq!(interpreted, expected, "interpreter: {}", input);
+
+        let mut compiler = Compiler::new();
+        compiler.set_path(&main);
+        compiler.set_capabilities(capabilities);
+        let compiled = compiler
+            .compile(&program)
+            .map_err(|err| err.message)
+            .and_then(|bytecode| Vm::new(bytecode).run().map_err(|err| err.message))
+            .map(|v| v.to_string());
+        assert_eq!(compiled, expected, "vm: {}", input);
+    }
+
+    // a program run without a loader may still import the standard library
+    let input = format!("import {:?} as k; k.token", keys);
+    let err = Evaluator::new()
+        .eval_program(&parse(&input), &Environment::new())
+        .unwrap_err();
+    assert_eq!(err.kind, ErrorKind::PermissionDenied);
+    assert_eq!(
+        err.message,
+        format!("cannot import {:?}: no loader was set", keys)
+    );
+    let err = Compiler::new().compile(&parse(&input)).err().unwrap();
+    assert_eq!(
+        err.message,
+        format!("cannot import {:?}: no loader was set", keys)
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_manifest_parsing() {
+    let manifest = Manifest::parse(
+        "[package]
+        name = \"app\"
+        version = \"1.2.0\"
+        sources = [\"src\", \"lib\"]
+
+        [dependencies]
+        utils = { path = \"../utils\" }",
+    )
+    .unwrap();
+    assert_eq!(
+        manifest,
+        Manifest {
+            name: "app".to_owned(),
+            version: "1.2.0".to_owned(),
+            entry: PathBuf::from("src/main.pr"),
+            sources: vec![PathBuf::from("src"), PathBuf::from("lib")],
+            dependencies: BTreeMap::from([(
+                "utils".to_owned(),
+                Dependency {
+                    path: PathBuf::from("../utils"),
+                    version: None,
+                },
+            )]),
+        }
+    );
+    assert_eq!(Manifest::parse(&manifest.to_toml()), Ok(manifest));
+
+    let tests = [
+        ("", "missing the `[package]` table"),
+        ("[package]\nversion = \"1.0.0\"", "missing `package.name`"),
+        ("[package]\nname = \"app\"", "missing `package.version`"),
+        (
+            "[package]\nname = \"my app\"\nversion = \"1.0.0\"",
+            "invalid package name `my app`: use letters, digits, `_` and `-`",
+        ),
+        (
+            "[package]\nname = \"app\"\nversion = 1",
+            "`package.version` must be a string",
+        ),
+        (
+            "[package]\nname = \"app\"\nversion = \"1.0\"",
+            "`package.version`: invalid version `1.0`, expected major.minor.patch",
+        ),
+        (
+            "[package]\nname = \"app\"\nversion = \"1.0.0\"\n[dependencies]\nutils = { path = \"u\", version = \"^x\" }",
+            "`dependencies.utils.version`: invalid version requirement `^x`: `x` is not a version number",
+        ),
+        (
+            "[package]\nname = \"app\"\nversion = \"1.0.0\"\nsources = \"src\"",
+            "`package.sources` must be an array of strings",
+        ),
+        (
+            "[package]\nname = \"app\"\nversion = \"1.0.0\"\n[dependencies]\nutils = {}",
+            "missing `dependencies.utils.path`",
+        ),
+    ];
+    for (input, expected) in tests {
+        assert_eq!(
+            Manifest::parse(input),
+            Err(expected.to_owned()),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_projects() {
+    let dir = std::env::temp_dir().join(format!("prolang-projects-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let root = dir.join("app");
+
+    let project = Project::create(&root).unwrap();
+    assert_eq!(project.manifest, Manifest::new("app"));
+    assert!(root.join("tests").is_dir());
+    assert_eq!(
+        fs::read_to_string(project.entry()).unwrap(),
+        "println(\"Hello from app!\");\n"
+    );
+    assert_eq!(
+        Project::create(&root).unwrap_err(),
+        format!("{} already exists", root.display())
+    );
+
+    // the source directories are searched from anywhere in the project
+    fs::create_dir_all(root.join("src/text")).unwrap();
+    fs::write(
+        root.join("src/text/shout.pr"),
+        "export let shout = fn(s) { s + \"!\" };",
+    )
+    .unwrap();
+    let test = root.join("tests/shout_test.pr");
+    let found = Project::find(test.parent().unwrap()).unwrap().unwrap();
+    assert_eq!(found.manifest.name, "app");
+    let mut evaluator = Evaluator::new();
+    evaluator.set_loader(found.resolve().unwrap().loader(&test));
+    evaluator.set_capabilities(reading());
+    let result = evaluator.eval_program(
+        &parse("import \"text/shout\" as text; text.shout(\"hi\")"),
+        &Environment::new(),
+    );
+    assert_eq!(result.unwrap().to_string(), "hi!");
+
+    assert!(Project::find(Path::new("/")).unwrap().is_none());
+    fs::write(root.join("prolang.toml"), "[package]").unwrap();
+    assert_eq!(
+        Project::find(&root).unwrap_err(),
+        format!(
+            "invalid {}: missing `package.name`",
+            root.join("prolang.toml").display()
+        )
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_version_requirements() {
+    let tests = [
+        ("1.2.3", "1.2.3", true),
+        ("1.2.3", "1.9.0", true),
+        ("1.2.3", "2.0.0", false),
+        ("1.2.3", "1.2.2", false),
+        ("^0.2.3", "0.2.9", true),
+        ("^0.2.3", "0.3.0", false),
+        ("^0.0.3", "0.0.4", false),
+        ("^0", "0.9.9", true),
+        ("~1.2", "1.2.7", true),
+        ("~1.2", "1.3.0", false),
+        ("~1", "1.9.0", true),
+        ("=1.2", "1.2.5", true),
+        ("=1.2.3", "1.2.4", false),
+        (">=1.2, <1.5", "1.4.9", true),
+        (">=1.2, <1.5", "1.5.0", false),
+        ("> 1.2.3", "1.2.3", false),
+        ("<= 2", "2.0.0", true),
+        ("*", "7.0.0", true),
+    ];
+    for (requirement, version, expected) in tests {
+        let parsed = VersionReq::parse(requirement).unwrap();
+        assert_eq!(
+            parsed.matches(&Version::parse(version).unwrap()),
+            expected,
+            "{} matching {}",
+            requirement,
+            version
+        );
+        assert_eq!(VersionReq::parse(&parsed.to_string()), Ok(parsed));
+    }
+
+    assert_eq!(
+        VersionReq::parse("1.2.3.4"),
+        Err("invalid version requirement `1.2.3.4`: `1.2.3.4` is not a version number".to_owned())
+    );
+}
+
+#[test]
+fn test_lock_files() {
+    let lock = Lock {
+        packages: vec![
+            LockedPackage {
+                name: "strings".to_owned(),
+                version: "1.0.0".to_owned(),
+                path: PathBuf::from("../strings"),
+                hash: 0xdeadbeef,
+                dependencies: vec![],
+            },
+            LockedPackage {
+                name: "utils".to_owned(),
+                version: "0.3.1".to_owned(),
+                path: PathBuf::from("vendor/utils"),
+                hash: 42,
+                dependencies: vec!["strings".to_owned()],
+            },
+        ],
+    };
+    assert_eq!(Lock::parse(&lock.to_toml()), Ok(lock.clone()));
+    assert_eq!(lock.difference(&lock), None);
+
+    let mut changed = lock.clone();
+    changed.packages[1].hash = 43;
+    assert_eq!(
+        changed.difference(&lock),
+        Some("`utils` changed since it was locked".to_owned())
+    );
+    changed.packages[1].path = PathBuf::from("../utils");
+    assert_eq!(
+        changed.difference(&lock),
+        Some("`utils` is at ../utils, but locked at vendor/utils".to_owned())
+    );
+    changed.packages.remove(1);
+    assert_eq!(
+        changed.difference(&lock),
+        Some("`utils` is locked but no longer depended on".to_owned())
+    );
+    assert_eq!(
+        lock.difference(&changed),
+        Some("`utils` is not locked".to_owned())
+    );
+}
+
+#[test]
+fn test_dependencies() {
+    let dir = std::env::temp_dir().join(format!("prolang-dependencies-{}", std::process::id()));
+    let project = |name: &str, version: &str, dependencies: &str| {
+        let root = dir.join(name);
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(
+            root.join("prolang.toml"),
+            format!(
+                "[package]\nname = \"{}\"\nversion = \"{}\"\n[dependencies]\n{}",
+                name, version, dependencies
+            ),
+        )
+        .unwrap();
+        root
+    };
+    let app = project(
+        "app",
+        "0.1.0",
+        "utils = { path = \"../utils\", version = \"^1.2\" }",
+    );
+    let utils = project("utils", "1.4.0", "strings = { path = \"../strings\" }");
+    let strings = project("strings", "0.2.0", "");
+    fs::write(
+        utils.join("src/text.pr"),
+        "import \"strings/case\" as case; export let title = fn(s) { case.upper + s };",
+    )
+    .unwrap();
+    fs::write(strings.join("src/case.pr"), "export let upper = \"T:\";").unwrap();
+
+    let graph = Project::open(&app).unwrap().resolve().unwrap();
+    let names: Vec<&str> = graph
+        .projects
+        .iter()
+        .map(|p| p.manifest.name.as_str())
+        .collect();
+    assert_eq!(names, ["app", "utils", "strings"]);
+
+    let lock = graph.lock().unwrap();
+    assert_eq!(lock.packages[0].name, "strings");
+    assert_eq!(lock.packages[0].path, PathBuf::from("../strings"));
+    assert_eq!(lock.packages[1].dependencies, ["strings"]);
+    assert!(graph
+        .sync_lock(true)
+        .unwrap_err()
+        .contains("there is no prolang.lock"));
+    graph.sync_lock(false).unwrap();
+    graph.sync_lock(true).unwrap();
+    fs::write(strings.join("src/case.pr"), "export let upper = \"U:\";").unwrap();
+    assert_eq!(
+        graph.sync_lock(true),
+        Err(
+            "the dependencies do not match prolang.lock: `strings` changed since it was locked"
+                .to_owned()
+        )
+    );
+
+    // a package imports from its own dependencies only
+    let main = app.join("src/main.pr");
+    let tests = [
+        (
+            "import \"utils/text\" as text; text.title(\"x\")",
+            Ok("U:x"),
+        ),
+        (
+            "import \"strings/case\" as case; 1",
+            Err("cannot find module \"strings/case\""),
+        ),
+    ];
+    for (input, expected) in tests {
+        let mut evaluator = Evaluator::new();
+        evaluator.set_loader(graph.loader(&main));
+        evaluator.set_capabilities(reading());
+        let result = evaluator.eval_program(&parse(input), &Environment::new());
+        let result = result.map(|v| v.to_string()).map_err(|err| err.message);
+        let result = result.as_ref().map(String::as_str).map_err(String::as_str);
+        assert_eq!(result, expected, "{}", input);
+
+        let mut compiler = Compiler::new();
+        compiler.set_loader(graph.loader(&main));
+        compiler.set_capabilities(reading());
+        let result = compiler
+            .compile(&parse(input))
+            .map_err(|err| err.message)
+            .and_then(|bytecode| Vm::new(bytecode).run().map_err(|err| err.message))
+            .map(|v| v.to_string());
+        let result = result.as_ref().map(String::as_str).map_err(String::as_str);
+        assert_eq!(result, expected, "{}", input);
+    }
+
+    let tests = [
+        (
+            "utils = { path = \"../utils\", version = \"^2\" }",
+            format!(
+                "`app` requires `utils` ^2, but {} has version 1.4.0",
+                utils.display()
+            ),
+        ),
+        (
+            "tools = { path = \"../utils\" }",
+            format!(
+                "`app` depends on `tools` at {}, which is the package `utils`",
+                utils.display()
+            ),
+        ),
+        (
+            "utils = { path = \"../nowhere\" }",
+            format!(
+                "`app` depends on `utils` at {}, which does not exist",
+                app.join("../nowhere").display()
+            ),
+        ),
+    ];
+    for (dependencies, expected) in tests {
+        project("app", "0.1.0", dependencies);
+        assert_eq!(
+            Project::open(&app).unwrap().resolve().unwrap_err(),
+            expected
+        );
+    }
+
+    project("app", "0.1.0", "utils = { path = \"../utils\" }");
+    project("strings", "0.2.0", "app = { path = \"../app\" }");
+    assert_eq!(
+        Project::open(&app).unwrap().resolve().unwrap_err(),
+        "dependency cycle: app -> utils -> strings -> app"
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,209 @@
+use std::{cmp::Ordering, fmt};
+
+/// A `major.minor.patch` package version.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+/// What versions of a dependency a package accepts: comparators separated
+/// by commas, which all have to match, as in `>=1.2, <1.5`.
+///
+/// A comparator without an operator is a caret requirement, as in Cargo:
+/// `1.2` and `^1.2` accept the versions from 1.2.0 up to, but excluding,
+/// 2.0.0, and `^0.2` those up to 0.3.0. `~1.2` accepts 1.2.x, `=1.2` 1.2.x
+/// too and `=1.2.3` just 1.2.3, and `*` any version. Parts left out of a
+/// version compared with `<`, `<=`, `>` or `>=` count as 0.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionReq {
+    comparators: Vec<Comparator>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Caret,
+    Tilde,
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Any,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Comparator {
+    op: Op,
+    version: Version,
+    /// How many of the version's parts were written.
+    parts: usize,
+}
+
+impl Version {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        match parse_parts(input)? {
+            (version, 3) => Ok(version),
+            _ => Err(format!(
+                "invalid version `{}`, expected major.minor.patch",
+                input
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl VersionReq {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let comparators = input
+            .split(',')
+            .map(|comparator| Comparator::parse(comparator.trim()))
+            .collect::<Result<_, _>>()
+            .map_err(|err| format!("invalid version requirement `{}`: {}", input, err))?;
+
+        Ok(Self { comparators })
+    }
+
+    pub fn matches(&self, version: &Version) -> bool {
+        self.comparators.iter().all(|c| c.matches(version))
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let comparators: Vec<String> = self.comparators.iter().map(|c| c.to_string()).collect();
+        write!(f, "{}", comparators.join(", "))
+    }
+}
+
+impl Comparator {
+    fn parse(input: &str) -> Result<Self, String> {
+        if input == "*" {
+            return Ok(Self {
+                op: Op::Any,
+                version: Version::default(),
+                parts: 0,
+            });
+        }
+        let (op, rest) = [
+            (">=", Op::GreaterEq),
+            ("<=", Op::LessEq),
+            (">", Op::Greater),
+            ("<", Op::Less),
+            ("=", Op::Exact),
+            ("^", Op::Caret),
+            ("~", Op::Tilde),
+        ]
+        .into_iter()
+        .find_map(|(prefix, op)| input.strip_prefix(prefix).map(|rest| (op, rest)))
+        .unwrap_or((Op::Caret, input));
+        let (version, parts) = parse_parts(rest.trim())?;
+
+        Ok(Self { op, version, parts })
+    }
+
+    fn matches(&self, version: &Version) -> bool {
+        let ordering = version.cmp(&self.version);
+        match self.op {
+            Op::Any => true,
+            Op::Greater => ordering == Ordering::Greater,
+            Op::GreaterEq => ordering != Ordering::Less,
+            Op::Less => ordering == Ordering::Less,
+            Op::LessEq => ordering != Ordering::Greater,
+            Op::Exact | Op::Tilde | Op::Caret => {
+                ordering != Ordering::Less && *version < self.upper_bound()
+            }
+        }
+    }
+
+    /// The first version past the ones a caret, tilde or exact comparator
+    /// accepts.
+    fn upper_bound(&self) -> Version {
+        let Version {
+            major,
+            minor,
+            patch,
+        } = self.version;
+        // how many leading parts must stay the same
+        let fixed = match self.op {
+            Op::Exact => self.parts,
+            Op::Tilde => self.parts.clamp(1, 2),
+            _ => match (major, minor) {
+                (0, 0) => self.parts,
+                (0, _) => self.parts.min(2),
+                _ => 1,
+            },
+        };
+        match fixed {
+            0 => Version {
+                major: u64::MAX,
+                minor: u64::MAX,
+                patch: u64::MAX,
+            },
+            1 => Version {
+                major: major + 1,
+                minor: 0,
+                patch: 0,
+            },
+            2 => Version {
+                major,
+                minor: minor + 1,
+                patch: 0,
+            },
+            _ => Version {
+                major,
+                minor,
+                patch: patch + 1,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self.op {
+            Op::Any => return write!(f, "*"),
+            Op::Caret => "^",
+            Op::Tilde => "~",
+            Op::Exact => "=",
+            Op::Greater => ">",
+            Op::GreaterEq => ">=",
+            Op::Less => "<",
+            Op::LessEq => "<=",
+        };
+        let parts = [self.version.major, self.version.minor, self.version.patch];
+        let parts: Vec<String> = parts[..self.parts].iter().map(u64::to_string).collect();
+        write!(f, "{}{}", op, parts.join("."))
+    }
+}
+
+/// Parses one to three dot separated numbers, returning them as a version
+/// with the parts left out as 0, and how many there were.
+fn parse_parts(input: &str) -> Result<(Version, usize), String> {
+    let parts = input
+        .split('.')
+        .map(|part| {
+            part.parse::<u64>()
+                .map_err(|_| format!("`{}` is not a version number", input))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if parts.is_empty() || parts.len() > 3 {
+        return Err(format!("`{}` is not a version number", input));
+    }
+    let part = |i: usize| parts.get(i).copied().unwrap_or(0);
+
+    Ok((
+        Version {
+            major: part(0),
+            minor: part(1),
+            patch: part(2),
+        },
+        parts.len(),
+    ))
+}
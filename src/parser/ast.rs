@@ -0,0 +1,1200 @@
+use std::{any::Any, rc::Rc};
+
+use crate::lexer::token::Token;
+
+pub trait Node {
+    fn token_literal(&self) -> &str;
+    /// The (line, column) of the node's token.
+    fn position(&self) -> (usize, usize);
+    fn string(&self) -> String;
+    fn get_as_any(&self) -> &dyn Any;
+}
+
+pub trait Statement: Node {}
+
+pub trait Expression: Node {}
+
+#[allow(dead_code)]
+pub trait Iterators: Node {}
+
+pub struct Program {
+    pub statements: Vec<Box<dyn Statement>>,
+}
+
+impl Node for Program {
+    fn token_literal(&self) -> &str {
+        if !self.statements.is_empty() {
+            return self.statements.first().unwrap().token_literal();
+        }
+        ""
+    }
+    fn position(&self) -> (usize, usize) {
+        self.statements
+            .first()
+            .map_or((1, 1), |statement| statement.position())
+    }
+    fn string(&self) -> String {
+        let mut out = String::new();
+        for s in &self.statements {
+            out.push_str(&s.string());
+        }
+        out
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct LetStatement {
+    pub token: Token, //LET token
+    pub name: Identifier,
+    pub type_annotation: Option<TypeExpr>,
+    pub value: Option<Box<dyn Expression>>,
+    /// Written `export let`, which makes the binding visible to importers.
+    pub exported: bool,
+}
+
+impl Statement for LetStatement {}
+
+impl Node for LetStatement {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        let mut out = String::new();
+        if self.exported {
+            out.push_str("export ");
+        }
+        out.push_str(self.token_literal());
+        out.push(' ');
+        out.push_str(&self.name.string());
+        if let Some(type_annotation) = &self.type_annotation {
+            out.push_str(": ");
+            out.push_str(&type_annotation.string());
+        }
+        out.push_str(" = ");
+        if let Some(value) = &self.value {
+            out.push_str(&value.string());
+        }
+        out.push(';');
+
+        out
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct Identifier {
+    pub token: Token, //IDENT token
+    pub value: String,
+}
+
+impl Expression for Identifier {}
+
+impl Node for Identifier {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        self.value.to_owned()
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct ExpressionStatement {
+    pub token: Token, //first token of the expression
+    pub expression: Option<Box<dyn Expression>>,
+}
+
+impl Statement for ExpressionStatement {}
+
+impl Node for ExpressionStatement {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        if let Some(value) = &self.expression {
+            return value.string();
+        }
+
+        "".to_owned()
+    }
+
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct ReturnStatemnt {
+    pub token: Token, //return
+    pub return_value: Option<Box<dyn Expression>>,
+}
+
+impl Statement for ReturnStatemnt {}
+
+impl Node for ReturnStatemnt {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push_str(self.token_literal());
+        out.push(' ');
+        if let Some(return_value) = &self.return_value {
+            out.push_str(&return_value.string());
+        }
+        out.push(';');
+
+        out
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct ThrowStatement {
+    pub token: Token, //throw
+    pub value: Option<Box<dyn Expression>>,
+}
+
+impl Statement for ThrowStatement {}
+
+impl Node for ThrowStatement {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push_str(self.token_literal());
+        out.push(' ');
+        if let Some(value) = &self.value {
+            out.push_str(&value.string());
+        }
+        out.push(';');
+
+        out
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// `import "path" as alias;`, which binds the module loaded from `path`.
+pub struct ImportStatement {
+    pub token: Token, //import
+    pub path: String,
+    pub alias: Identifier,
+}
+
+impl Statement for ImportStatement {}
+
+impl Node for ImportStatement {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        format!(
+            "{} {:?} as {};",
+            self.token_literal(),
+            self.path,
+            self.alias.string()
+        )
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct IntegerLiteral {
+    pub token: Token,
+    pub value: i64,
+}
+
+impl Expression for IntegerLiteral {}
+
+impl Node for IntegerLiteral {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+
+    fn string(&self) -> String {
+        self.token.literal.to_owned()
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct FloatLiteral {
+    pub token: Token,
+    pub value: f64,
+}
+
+impl Expression for FloatLiteral {}
+
+impl Node for FloatLiteral {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+
+    fn string(&self) -> String {
+        self.token.literal.to_owned()
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct BooleanLiteral {
+    pub token: Token,
+    pub value: bool,
+}
+
+impl Node for BooleanLiteral {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        self.token.literal.to_owned()
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Expression for BooleanLiteral {}
+
+pub struct PrefixExpression {
+    pub token: Token, //prefix token
+    pub operator: String,
+    pub right: Option<Box<dyn Expression>>,
+}
+
+impl Expression for PrefixExpression {}
+
+impl Node for PrefixExpression {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push('(');
+        out.push_str(&self.operator);
+        out.push_str(&self.right.as_ref().unwrap().string());
+        out.push(')');
+
+        out
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct InfixExpression {
+    pub token: Token, //infix token: '-', '+'
+    pub left: Box<dyn Expression>,
+    pub operator: String,
+    pub right: Option<Box<dyn Expression>>,
+}
+
+impl Expression for InfixExpression {}
+
+impl Node for InfixExpression {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push('(');
+        out.push_str(&self.left.string());
+        out.push(' ');
+        out.push_str(&self.operator);
+        out.push(' ');
+        out.push_str(&self.right.as_ref().unwrap().string());
+        out.push(')');
+
+        out
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct AssignExpression {
+    pub token: Token, // = or a compound operator such as +=
+    pub target: Box<dyn Expression>,
+    pub operator: String,
+    pub value: Option<Box<dyn Expression>>,
+}
+
+impl AssignExpression {
+    /// The arithmetic operator of a compound assignment: "+" for "+=".
+    pub fn binary_operator(&self) -> Option<&str> {
+        self.operator.strip_suffix('=').filter(|op| !op.is_empty())
+    }
+}
+
+impl Expression for AssignExpression {}
+
+impl Node for AssignExpression {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&self.target.string());
+        out.push(' ');
+        out.push_str(&self.operator);
+        out.push(' ');
+        if let Some(value) = &self.value {
+            out.push_str(&value.string());
+        }
+
+        out
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct IfExpression {
+    pub token: Token, //IF
+    pub condition: Option<Box<dyn Expression>>,
+    pub consequence: Option<BlockStatement>,
+    /// `else if` branches, tried in order before the final `else`.
+    pub else_ifs: Vec<(Box<dyn Expression>, BlockStatement)>,
+    pub alternative: Option<BlockStatement>,
+}
+
+impl Expression for IfExpression {}
+
+impl Node for IfExpression {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push_str(self.token_literal());
+        out.push_str(&self.condition.as_ref().unwrap().string());
+        out.push(' ');
+        out.push_str(&self.consequence.as_ref().unwrap().string());
+        for (condition, consequence) in &self.else_ifs {
+            out.push_str(" else if");
+            out.push_str(&condition.string());
+            out.push(' ');
+            out.push_str(&consequence.string());
+        }
+        if let Some(alt) = &self.alternative {
+            out.push_str(" else ");
+            out.push_str(&alt.string())
+        }
+
+        out
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// `try { .. } catch (e) { .. }`: evaluates to the value of the `try` block,
+/// or to the value of the `catch` block when a runtime error was raised in it.
+pub struct TryExpression {
+    pub token: Token, //TRY
+    pub body: BlockStatement,
+    /// The name the caught error is bound to, if any.
+    pub parameter: Option<Identifier>,
+    pub handler: BlockStatement,
+}
+
+impl Expression for TryExpression {}
+
+impl Node for TryExpression {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push_str(self.token_literal());
+        out.push(' ');
+        out.push_str(&self.body.string());
+        out.push_str(" catch ");
+        if let Some(parameter) = &self.parameter {
+            out.push_str(&format!("({}) ", parameter.value));
+        }
+        out.push_str(&self.handler.string());
+
+        out
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct BlockStatement {
+    pub token: Token, //{
+    pub statements: Vec<Box<dyn Statement>>,
+}
+
+impl Node for BlockStatement {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+
+    fn string(&self) -> String {
+        let mut out = String::new();
+
+        for s in &self.statements {
+            out.push_str(&s.string());
+        }
+
+        out
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct FunctionLiteral {
+    pub token: Token, //FN
+    pub parameters: Vec<Identifier>,
+    pub parameter_types: Vec<Option<TypeExpr>>, // one entry per parameter
+    pub return_type: Option<TypeExpr>,
+    pub body: Option<Rc<BlockStatement>>,
+}
+
+impl Expression for FunctionLiteral {}
+
+impl Node for FunctionLiteral {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+
+    fn string(&self) -> String {
+        let mut out = String::new();
+        let mut params = vec![];
+
+        for (p, t) in self.parameters.iter().zip(&self.parameter_types) {
+            match t {
+                Some(t) => params.push(format!("{}: {}", p.string(), t.string())),
+                None => params.push(p.string()),
+            }
+        }
+        out.push_str(self.token_literal());
+        out.push('(');
+        out.push_str(params.join(", ").as_str());
+        out.push(')');
+        if let Some(return_type) = &self.return_type {
+            out.push_str(": ");
+            out.push_str(&return_type.string());
+        }
+        out.push(' ');
+        if let Some(body) = &self.body {
+            out.push_str(&body.string());
+        }
+
+        out
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct CallExpression {
+    pub token: Token, //IDENT
+    pub function: Box<dyn Expression>,
+    pub arguments: Vec<Box<dyn Expression>>,
+}
+
+impl Expression for CallExpression {}
+
+impl Node for CallExpression {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+
+    fn string(&self) -> String {
+        let mut out = String::new();
+        let mut args = vec![];
+        for a in &self.arguments {
+            args.push(a.string());
+        }
+        out.push_str(&self.function.string());
+        out.push('(');
+        out.push_str(&args.join(", "));
+        out.push(')');
+
+        out
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct ForLoopExpression {
+    pub token: Token, //FOR
+    pub condition: Option<ForLoopCondition>,
+    pub body: Option<BlockStatement>,
+}
+
+pub enum ForLoopCondition {
+    Loop,
+    ForIn(Box<dyn Expression>),
+    For(Box<dyn Expression>),
+}
+
+impl ForLoopCondition {
+    fn string(&self) -> String {
+        match self {
+            ForLoopCondition::Loop => "".to_owned(),
+            ForLoopCondition::ForIn(exp) | ForLoopCondition::For(exp) => exp.string(),
+        }
+    }
+}
+
+impl Statement for ForLoopExpression {}
+
+impl Node for ForLoopExpression {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+
+    fn string(&self) -> String {
+        let mut out: String = String::new();
+        out.push_str(self.token_literal());
+        out.push(' ');
+        if let Some(cond) = &self.condition {
+            out.push_str(&cond.string())
+        }
+        if let Some(body) = &self.body {
+            out.push_str(&body.string());
+        }
+
+        out
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct ConditionalIteratorExpression {
+    pub token: Token, // IN
+    pub variable: Identifier,
+    pub r#in: Option<Box<dyn Expression>>,
+}
+
+impl Expression for ConditionalIteratorExpression {}
+
+impl Node for ConditionalIteratorExpression {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push('(');
+        out.push_str(&self.variable.string());
+        out.push(' ');
+        out.push_str(self.token_literal());
+        out.push(' ');
+        if let Some(r#in) = &self.r#in {
+            out.push_str(&r#in.string())
+        }
+        out.push(')');
+
+        out
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct IteratorLiteral {
+    pub token: Token, // .. SPREED
+    pub start: Box<dyn Expression>,
+    pub end: Option<Box<dyn Expression>>,
+}
+
+impl Iterators for IteratorLiteral {}
+
+impl Expression for IteratorLiteral {}
+
+impl Node for IteratorLiteral {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&self.start.string());
+        out.push_str(self.token_literal());
+        out.push_str(&self.end.as_ref().unwrap().string());
+
+        out
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct StringLiteral {
+    pub token: Token,
+    pub value: String,
+}
+
+impl Expression for StringLiteral {}
+
+impl Node for StringLiteral {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        format!("{:?}", self.value)
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct ArrayLiteral {
+    pub token: Token, // [
+    pub elements: Vec<Box<dyn Expression>>,
+}
+
+impl Expression for ArrayLiteral {}
+
+impl Node for ArrayLiteral {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        let elements: Vec<String> = self.elements.iter().map(|e| e.string()).collect();
+        format!("[{}]", elements.join(", "))
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct HashLiteral {
+    pub token: Token, // {
+    pub pairs: Vec<(Box<dyn Expression>, Box<dyn Expression>)>,
+}
+
+impl Expression for HashLiteral {}
+
+impl Node for HashLiteral {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        let pairs: Vec<String> = self
+            .pairs
+            .iter()
+            .map(|(k, v)| format!("{}: {}", k.string(), v.string()))
+            .collect();
+        format!("{{{}}}", pairs.join(", "))
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct IndexExpression {
+    pub token: Token, // [
+    pub left: Box<dyn Expression>,
+    pub index: Option<Box<dyn Expression>>,
+}
+
+impl Expression for IndexExpression {}
+
+impl Node for IndexExpression {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push('(');
+        out.push_str(&self.left.string());
+        out.push('[');
+        if let Some(index) = &self.index {
+            out.push_str(&index.string());
+        }
+        out.push_str("])");
+
+        out
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct MemberExpression {
+    pub token: Token, // .
+    pub object: Box<dyn Expression>,
+    pub property: Identifier,
+}
+
+impl Expression for MemberExpression {}
+
+impl Node for MemberExpression {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        format!("{}.{}", self.object.string(), self.property.string())
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct EnumVariant {
+    pub name: Identifier,
+    pub fields: Vec<Identifier>, // payload type names
+}
+
+pub struct EnumLiteral {
+    pub token: Token, // ENUM
+    pub variants: Vec<EnumVariant>,
+}
+
+impl Expression for EnumLiteral {}
+
+impl Node for EnumLiteral {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        let variants: Vec<String> = self
+            .variants
+            .iter()
+            .map(|v| {
+                if v.fields.is_empty() {
+                    return v.name.string();
+                }
+                let fields: Vec<String> = v.fields.iter().map(|f| f.string()).collect();
+                format!("{}({})", v.name.string(), fields.join(", "))
+            })
+            .collect();
+        format!("{}({})", self.token_literal(), variants.join(", "))
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct StructLiteral {
+    pub token: Token, // STRUCT
+    pub fields: Vec<Identifier>,
+}
+
+impl Expression for StructLiteral {}
+
+impl Node for StructLiteral {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        let fields: Vec<String> = self.fields.iter().map(|f| f.string()).collect();
+        format!("{} {{ {} }}", self.token_literal(), fields.join(", "))
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct StructExpression {
+    pub token: Token, // {
+    pub name: Identifier,
+    pub fields: Vec<(Identifier, Box<dyn Expression>)>,
+}
+
+impl Expression for StructExpression {}
+
+impl Node for StructExpression {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|(k, v)| format!("{}: {}", k.string(), v.string()))
+            .collect();
+        format!("{} {{ {} }}", self.name.string(), fields.join(", "))
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub enum Pattern {
+    Wildcard(Token),
+    Literal {
+        token: Token, // first token of the literal
+        value: Box<dyn Expression>,
+    },
+    Binding(Identifier),
+    EnumVariant {
+        enum_name: Identifier,
+        variant: Identifier,
+        payload: Option<Vec<Pattern>>,
+    },
+    Array {
+        token: Token, // [
+        elements: Vec<Pattern>,
+        rest: Option<Box<Pattern>>,
+    },
+    Struct {
+        name: Identifier,
+        fields: Vec<(Identifier, Pattern)>,
+    },
+}
+
+impl Pattern {
+    pub fn token(&self) -> &Token {
+        match self {
+            Pattern::Wildcard(token) => token,
+            Pattern::Literal { token, .. } => token,
+            Pattern::Binding(ident) => &ident.token,
+            Pattern::EnumVariant { enum_name, .. } => &enum_name.token,
+            Pattern::Array { token, .. } => token,
+            Pattern::Struct { name, .. } => &name.token,
+        }
+    }
+
+    /// Whether the pattern matches every value, binding at most a name.
+    pub fn is_irrefutable(&self) -> bool {
+        matches!(self, Pattern::Wildcard(_) | Pattern::Binding(_))
+    }
+
+    pub fn string(&self) -> String {
+        match self {
+            Pattern::Wildcard(_) => "_".to_owned(),
+            Pattern::Literal { value, .. } => value.string(),
+            Pattern::Binding(ident) => ident.string(),
+            Pattern::EnumVariant {
+                enum_name,
+                variant,
+                payload,
+                ..
+            } => {
+                let mut out = format!("{}.{}", enum_name.string(), variant.string());
+                if let Some(payload) = payload {
+                    let payload: Vec<String> = payload.iter().map(|p| p.string()).collect();
+                    out.push_str(&format!("({})", payload.join(", ")));
+                }
+                out
+            }
+            Pattern::Array { elements, rest, .. } => {
+                let mut elements: Vec<String> = elements.iter().map(|p| p.string()).collect();
+                if let Some(rest) = rest {
+                    elements.push(format!("..{}", rest.string()));
+                }
+                format!("[{}]", elements.join(", "))
+            }
+            Pattern::Struct { name, fields, .. } => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", k.string(), v.string()))
+                    .collect();
+                format!("{} {{ {} }}", name.string(), fields.join(", "))
+            }
+        }
+    }
+}
+
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Box<dyn Expression>>,
+    pub body: BlockStatement,
+}
+
+impl MatchArm {
+    fn string(&self) -> String {
+        let mut out = self.pattern.string();
+        if let Some(guard) = &self.guard {
+            out.push_str(" if ");
+            out.push_str(&guard.string());
+        }
+        out.push_str(" => ");
+        out.push_str(&self.body.string());
+
+        out
+    }
+}
+
+pub struct MatchExpression {
+    pub token: Token, // MATCH
+    pub subject: Option<Box<dyn Expression>>,
+    pub arms: Vec<MatchArm>,
+}
+
+impl Expression for MatchExpression {}
+
+impl Node for MatchExpression {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        let arms: Vec<String> = self.arms.iter().map(|a| a.string()).collect();
+        let mut out = String::new();
+        out.push_str(self.token_literal());
+        out.push(' ');
+        if let Some(subject) = &self.subject {
+            out.push_str(&subject.string());
+        }
+        out.push_str(" { ");
+        out.push_str(&arms.join(", "));
+        out.push_str(" }");
+
+        out
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// A type annotation such as `int`, `[]int` or `fn(int, int): int`.
+#[derive(Clone)]
+pub enum TypeExpr {
+    Named(Identifier),
+    Array(Box<TypeExpr>),
+    Function {
+        parameters: Vec<TypeExpr>,
+        return_type: Option<Box<TypeExpr>>,
+    },
+}
+
+impl TypeExpr {
+    pub fn string(&self) -> String {
+        match self {
+            TypeExpr::Named(ident) => ident.string(),
+            TypeExpr::Array(element) => format!("[]{}", element.string()),
+            TypeExpr::Function {
+                parameters,
+                return_type,
+            } => {
+                let parameters: Vec<String> = parameters.iter().map(|p| p.string()).collect();
+                let mut out = format!("fn({})", parameters.join(", "));
+                if let Some(return_type) = return_type {
+                    out.push_str(": ");
+                    out.push_str(&return_type.string());
+                }
+                out
+            }
+        }
+    }
+}
+
+pub struct MethodSignature {
+    pub name: Identifier,
+    pub parameters: Vec<Identifier>,
+    pub return_type: Option<TypeExpr>,
+}
+
+pub struct InterfaceLiteral {
+    pub token: Token, // INTERFACE
+    pub methods: Vec<MethodSignature>,
+}
+
+impl Expression for InterfaceLiteral {}
+
+impl Node for InterfaceLiteral {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        let methods: Vec<String> = self
+            .methods
+            .iter()
+            .map(|m| {
+                let params: Vec<String> = m.parameters.iter().map(|p| p.string()).collect();
+                let mut out = format!("{}({})", m.name.string(), params.join(", "));
+                if let Some(return_type) = &m.return_type {
+                    out.push_str(": ");
+                    out.push_str(&return_type.string());
+                }
+                out.push(';');
+                out
+            })
+            .collect();
+        format!("{} {{ {} }}", self.token_literal(), methods.join(" "))
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct ImplStatement {
+    pub token: Token, // IMPL
+    pub target: Identifier,
+    pub methods: Vec<(Identifier, FunctionLiteral)>,
+}
+
+impl Statement for ImplStatement {}
+
+impl Node for ImplStatement {
+    fn token_literal(&self) -> &str {
+        &self.token.literal
+    }
+    fn position(&self) -> (usize, usize) {
+        self.token.position
+    }
+    fn string(&self) -> String {
+        let methods: Vec<String> = self
+            .methods
+            .iter()
+            .map(|(name, func)| format!("{}: {}", name.string(), func.string()))
+            .collect();
+        format!(
+            "{} {} {{ {} }}",
+            self.token_literal(),
+            self.target.string(),
+            methods.join(", ")
+        )
+    }
+    fn get_as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lexer::token::{Token, TokenType};
+
+    use super::{Identifier, LetStatement, Node, Program};
+
+    #[test]
+    fn test_string() {
+        let program = Program {
+            statements: vec![Box::new(LetStatement {
+                token: Token {
+                    token_type: TokenType::LET,
+                    literal: "let".to_string(),
+                    position: (0, 0),
+                },
+                name: Identifier {
+                    token: Token {
+                        token_type: TokenType::IDENT,
+                        literal: "myVar".to_string(),
+                        position: (0, 0),
+                    },
+                    value: "myVar".to_string(),
+                },
+                type_annotation: None,
+                value: Some(Box::new(Identifier {
+                    token: Token {
+                        token_type: TokenType::IDENT,
+                        literal: "anotherVar".to_string(),
+                        position: (0, 0),
+                    },
+                    value: "anotherVar".to_string(),
+                })),
+                exported: false,
+            })],
+        };
+        assert_eq!(
+            program.string(),
+            "let myVar = anotherVar;",
+            "program.string() wrong, got={}",
+            program.string()
+        )
+    }
+}
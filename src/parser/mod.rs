@@ -0,0 +1,29 @@
+use std::any::Any;
+
+pub mod ast;
+mod parse_func;
+#[allow(clippy::module_inception)]
+pub mod parser;
+#[cfg(test)]
+mod test;
+
+pub fn is_of_type<T: 'static>(x: &dyn Any) -> bool {
+    x.is::<T>()
+}
+
+pub fn get_of_type<T: 'static>(x: &dyn Any) -> Option<&T> {
+    x.downcast_ref::<T>()
+}
+
+/// Stack left when a pass over the syntax tree grows the Rust stack, which
+/// has to hold what the pass does for a node before it reaches the next.
+const STACK_RED_ZONE: usize = 128 * 1024;
+/// Stack allocated each time a pass grows the Rust stack.
+const STACK_GROWTH: usize = 1024 * 1024;
+
+/// Runs a pass over a node of the syntax tree on a Rust stack grown as
+/// needed, so that the passes recursing as deeply as the program nests do
+/// not overflow the stack of whichever thread runs them.
+pub(crate) fn grow_stack<R>(pass: impl FnOnce() -> R) -> R {
+    stacker::maybe_grow(STACK_RED_ZONE, STACK_GROWTH, pass)
+}
@@ -0,0 +1,658 @@
+use std::rc::Rc;
+
+use crate::lexer::token::{Token, TokenType};
+use crate::parser::ast::ConditionalIteratorExpression;
+
+use super::{
+    ast::{
+        ArrayLiteral, AssignExpression, BlockStatement, BooleanLiteral, CallExpression,
+        EnumLiteral, EnumVariant, Expression, ExpressionStatement, FloatLiteral, FunctionLiteral,
+        HashLiteral, Identifier, IfExpression, IndexExpression, InfixExpression, IntegerLiteral,
+        InterfaceLiteral, IteratorLiteral, MatchArm, MatchExpression, MemberExpression,
+        MethodSignature, PrefixExpression, StringLiteral, StructExpression, StructLiteral,
+        TryExpression,
+    },
+    get_of_type, is_of_type,
+    parser::{Parser, ASSIGN, LOWEST, PREFIX},
+};
+
+pub(crate) fn parse_infix_func(
+    p: &mut Parser,
+    left: Box<dyn Expression>,
+) -> Option<Box<dyn Expression>> {
+    match p.cur_token.token_type {
+        TokenType::PLUS
+        | TokenType::MINUS
+        | TokenType::SLASH
+        | TokenType::ASTERISK
+        | TokenType::PERCENT
+        | TokenType::EQ
+        | TokenType::NotEq
+        | TokenType::LT
+        | TokenType::GT
+        | TokenType::LtEq
+        | TokenType::GtEq
+        | TokenType::AND
+        | TokenType::OR => Some(parse_infix_expression(p, left)),
+        TokenType::ASSIGN
+        | TokenType::PlusAssign
+        | TokenType::MinusAssign
+        | TokenType::AsteriskAssign
+        | TokenType::SlashAssign
+        | TokenType::PercentAssign => parse_assign_expression(p, left),
+        TokenType::LPAREN => Some(parse_call_epression(p, left)),
+        TokenType::LBRACKET => parse_index_expression(p, left),
+        TokenType::Dot => parse_member_expression(p, left),
+        TokenType::IN => parse_conditional_iter_expression(p, left),
+        TokenType::Spreed => Some(parse_spreed_epression(p, left)),
+        _ => None,
+    }
+}
+
+fn parse_infix_expression(p: &mut Parser, left: Box<dyn Expression>) -> Box<dyn Expression> {
+    let mut expression = Box::new(InfixExpression {
+        token: p.cur_token.clone(),
+        left,
+        operator: p.cur_token.literal.clone(),
+        right: None,
+    });
+    let precedence = p.cur_precedence();
+    p.next_token();
+    expression.right = p.parse_expression(precedence);
+    expression
+}
+
+fn parse_assign_expression(
+    p: &mut Parser,
+    target: Box<dyn Expression>,
+) -> Option<Box<dyn Expression>> {
+    let any = target.get_as_any();
+    if !is_of_type::<Identifier>(any)
+        && !is_of_type::<IndexExpression>(any)
+        && !is_of_type::<MemberExpression>(any)
+    {
+        p.invalid_assignment_target_error(&target.string());
+        return None;
+    }
+
+    let mut exp = AssignExpression {
+        token: p.cur_token.clone(),
+        target,
+        operator: p.cur_token.literal.clone(),
+        value: None,
+    };
+    p.next_token();
+    // right associative: `a = b = c` assigns `c` to `b` first
+    exp.value = p.parse_expression(ASSIGN - 1);
+
+    Some(Box::new(exp))
+}
+
+fn parse_index_expression(
+    p: &mut Parser,
+    left: Box<dyn Expression>,
+) -> Option<Box<dyn Expression>> {
+    let mut exp = IndexExpression {
+        token: p.cur_token.clone(),
+        left,
+        index: None,
+    };
+    p.next_token();
+    exp.index = p.parse_expression(LOWEST);
+    if !p.expect_peek(TokenType::RBRACKET) {
+        return None;
+    }
+
+    Some(Box::new(exp))
+}
+
+fn parse_member_expression(
+    p: &mut Parser,
+    object: Box<dyn Expression>,
+) -> Option<Box<dyn Expression>> {
+    let token = p.cur_token.clone();
+    if !p.expect_peek(TokenType::IDENT) {
+        return None;
+    }
+
+    Some(Box::new(MemberExpression {
+        token,
+        object,
+        property: Identifier {
+            token: p.cur_token.clone(),
+            value: p.cur_token.literal.clone(),
+        },
+    }))
+}
+
+fn parse_call_epression(p: &mut Parser, function: Box<dyn Expression>) -> Box<dyn Expression> {
+    let exp = CallExpression {
+        token: p.cur_token.clone(),
+        function,
+        arguments: p.parse_call_argument(),
+    };
+
+    Box::new(exp)
+}
+
+pub fn parse_conditional_iter_expression(
+    p: &mut Parser,
+    variable: Box<dyn Expression>,
+) -> Option<Box<dyn Expression>> {
+    let ident = get_of_type::<Identifier>(variable.get_as_any())?;
+    let mut expression = ConditionalIteratorExpression {
+        token: p.cur_token.clone(),
+        variable: Identifier {
+            token: ident.token.clone(),
+            value: ident.value.clone(),
+        },
+        r#in: None,
+    };
+    p.next_token();
+
+    expression.r#in = p.parse_expression(LOWEST);
+
+    Some(Box::new(expression))
+}
+
+fn parse_spreed_epression(p: &mut Parser, int: Box<dyn Expression>) -> Box<dyn Expression> {
+    let mut expression = Box::new(IteratorLiteral {
+        token: p.cur_token.clone(),
+        start: int,
+        end: None,
+    });
+
+    let precedence = p.cur_precedence();
+    p.next_token();
+    expression.end = p.parse_expression(precedence);
+
+    expression
+}
+
+pub(crate) fn parse_prefix_func(p: &mut Parser) -> Option<Box<dyn Expression>> {
+    match p.cur_token.token_type {
+        TokenType::IDENT => parse_identifier(p),
+        TokenType::INT => parse_integer_literal(p),
+        TokenType::FLOAT => parse_float_literal(p),
+        TokenType::STRING => Some(parse_string_literal(p)),
+        TokenType::BANG => parse_prefix_expression(p),
+        TokenType::MINUS => parse_prefix_expression(p),
+        TokenType::TRUE | TokenType::FALSE => Some(parse_boolean(p)),
+        TokenType::LPAREN => parse_grouped_expression(p),
+        TokenType::IF => parse_if_expression(p),
+        TokenType::TRY => parse_try_expression(p),
+        TokenType::FUNCTION => parse_fn_literal(p),
+        TokenType::LBRACKET => Some(parse_array_literal(p)),
+        TokenType::LBRACE => parse_hash_literal(p),
+        TokenType::ENUM => parse_enum_literal(p),
+        TokenType::STRUCT => parse_struct_literal(p),
+        TokenType::INTERFACE => parse_interface_literal(p),
+        TokenType::MATCH => parse_match_expression(p),
+        _ => None,
+    }
+}
+
+fn parse_prefix_expression(p: &mut Parser) -> Option<Box<dyn Expression>> {
+    let mut expression = Box::new(PrefixExpression {
+        token: p.cur_token.clone(),
+        operator: p.cur_token.literal.clone(),
+        right: None,
+    });
+    p.next_token();
+    if let Some(right_exp) = p.parse_expression(PREFIX) {
+        expression.right = Some(right_exp);
+        return Some(expression);
+    }
+
+    None
+}
+
+fn parse_identifier(p: &mut Parser) -> Option<Box<dyn Expression>> {
+    let expression = Identifier {
+        token: p.cur_token.clone(),
+        value: p.cur_token.literal.clone(),
+    };
+
+    if p.peek_token_is(&TokenType::LBRACE) && !p.no_struct_literal {
+        p.next_token();
+        return parse_struct_expression(p, expression);
+    }
+
+    Some(Box::new(expression))
+}
+
+fn parse_string_literal(p: &Parser) -> Box<dyn Expression> {
+    Box::new(StringLiteral {
+        token: p.cur_token.clone(),
+        value: p.cur_token.literal.clone(),
+    })
+}
+
+fn parse_array_literal(p: &mut Parser) -> Box<dyn Expression> {
+    Box::new(ArrayLiteral {
+        token: p.cur_token.clone(),
+        elements: p.parse_expression_list(TokenType::RBRACKET),
+    })
+}
+
+fn parse_hash_literal(p: &mut Parser) -> Option<Box<dyn Expression>> {
+    let mut hash = HashLiteral {
+        token: p.cur_token.clone(),
+        pairs: vec![],
+    };
+
+    // a hash literal nested in a restricted position is unambiguous again
+    let no_struct_literal = std::mem::replace(&mut p.no_struct_literal, false);
+    while !p.peek_token_is(&TokenType::RBRACE) {
+        p.next_token();
+        let key = p.parse_expression(LOWEST)?;
+        if !p.expect_peek(TokenType::COLON) {
+            return None;
+        }
+        p.next_token();
+        let value = p.parse_expression(LOWEST)?;
+        hash.pairs.push((key, value));
+
+        if !p.peek_token_is(&TokenType::RBRACE) && !p.expect_peek(TokenType::COMMA) {
+            return None;
+        }
+    }
+    p.no_struct_literal = no_struct_literal;
+
+    if !p.expect_peek(TokenType::RBRACE) {
+        return None;
+    }
+
+    Some(Box::new(hash))
+}
+
+fn parse_struct_expression(p: &mut Parser, name: Identifier) -> Option<Box<dyn Expression>> {
+    let mut exp = StructExpression {
+        token: p.cur_token.clone(),
+        name,
+        fields: vec![],
+    };
+
+    while !p.peek_token_is(&TokenType::RBRACE) {
+        if !p.expect_peek(TokenType::IDENT) {
+            return None;
+        }
+        let field = Identifier {
+            token: p.cur_token.clone(),
+            value: p.cur_token.literal.clone(),
+        };
+        if !p.expect_peek(TokenType::COLON) {
+            return None;
+        }
+        p.next_token();
+        let value = p.parse_expression(LOWEST)?;
+        exp.fields.push((field, value));
+
+        if !p.peek_token_is(&TokenType::RBRACE) && !p.expect_peek(TokenType::COMMA) {
+            return None;
+        }
+    }
+
+    if !p.expect_peek(TokenType::RBRACE) {
+        return None;
+    }
+
+    Some(Box::new(exp))
+}
+
+fn parse_enum_literal(p: &mut Parser) -> Option<Box<dyn Expression>> {
+    let mut lit = EnumLiteral {
+        token: p.cur_token.clone(),
+        variants: vec![],
+    };
+    if !p.expect_peek(TokenType::LPAREN) {
+        return None;
+    }
+
+    while !p.peek_token_is(&TokenType::RPAREN) {
+        if !p.expect_peek(TokenType::IDENT) {
+            return None;
+        }
+        let mut variant = EnumVariant {
+            name: Identifier {
+                token: p.cur_token.clone(),
+                value: p.cur_token.literal.clone(),
+            },
+            fields: vec![],
+        };
+        if p.peek_token_is(&TokenType::LPAREN) {
+            p.next_token();
+            variant.fields = p
+                .parse_fn_parameters()?
+                .into_iter()
+                .map(|(field, _)| field)
+                .collect();
+        }
+        lit.variants.push(variant);
+
+        if !p.peek_token_is(&TokenType::RPAREN) && !p.expect_peek(TokenType::COMMA) {
+            return None;
+        }
+    }
+
+    if !p.expect_peek(TokenType::RPAREN) {
+        return None;
+    }
+
+    Some(Box::new(lit))
+}
+
+fn parse_struct_literal(p: &mut Parser) -> Option<Box<dyn Expression>> {
+    let mut lit = StructLiteral {
+        token: p.cur_token.clone(),
+        fields: vec![],
+    };
+    if !p.expect_peek(TokenType::LBRACE) {
+        return None;
+    }
+
+    while !p.peek_token_is(&TokenType::RBRACE) {
+        if !p.expect_peek(TokenType::IDENT) {
+            return None;
+        }
+        lit.fields.push(Identifier {
+            token: p.cur_token.clone(),
+            value: p.cur_token.literal.clone(),
+        });
+        // field types are accepted but not checked yet
+        if p.peek_token_is(&TokenType::COLON) {
+            p.next_token();
+            if !p.expect_peek(TokenType::IDENT) {
+                return None;
+            }
+        }
+
+        if !p.peek_token_is(&TokenType::RBRACE) && !p.expect_peek(TokenType::COMMA) {
+            return None;
+        }
+    }
+
+    if !p.expect_peek(TokenType::RBRACE) {
+        return None;
+    }
+
+    Some(Box::new(lit))
+}
+
+fn parse_match_expression(p: &mut Parser) -> Option<Box<dyn Expression>> {
+    let mut expression = MatchExpression {
+        token: p.cur_token.clone(),
+        subject: None,
+        arms: vec![],
+    };
+
+    p.next_token();
+    let no_struct_literal = std::mem::replace(&mut p.no_struct_literal, true);
+    expression.subject = p.parse_expression(LOWEST);
+    p.no_struct_literal = no_struct_literal;
+
+    if !p.expect_peek(TokenType::LBRACE) {
+        return None;
+    }
+
+    while !p.peek_token_is(&TokenType::RBRACE) {
+        p.next_token();
+        let pattern = p.parse_pattern()?;
+
+        let mut guard = None;
+        if p.peek_token_is(&TokenType::IF) {
+            p.next_token();
+            p.next_token();
+            guard = Some(p.parse_expression(LOWEST)?);
+        }
+
+        if !p.expect_peek(TokenType::FatArrow) {
+            return None;
+        }
+        p.next_token();
+
+        let body = if p.cur_token_is(TokenType::LBRACE) {
+            p.parse_block_statement()?
+        } else {
+            let token = p.cur_token.clone();
+            let expression = p.parse_expression(LOWEST)?;
+            BlockStatement {
+                token: token.clone(),
+                statements: vec![Box::new(ExpressionStatement {
+                    token,
+                    expression: Some(expression),
+                })],
+            }
+        };
+        expression.arms.push(MatchArm {
+            pattern,
+            guard,
+            body,
+        });
+
+        if p.peek_token_is(&TokenType::COMMA) {
+            p.next_token();
+        } else if !p.peek_token_is(&TokenType::RBRACE) && !p.cur_token_is(TokenType::RBRACE) {
+            p.expect_peek(TokenType::COMMA);
+            return None;
+        }
+    }
+
+    if !p.expect_peek(TokenType::RBRACE) {
+        return None;
+    }
+
+    Some(Box::new(expression))
+}
+
+fn parse_integer_literal(p: &mut Parser) -> Option<Box<dyn Expression>> {
+    let Ok(value) = p.cur_token.literal.parse() else {
+        p.number_error("an integer");
+        return None;
+    };
+    Some(Box::new(IntegerLiteral {
+        token: p.cur_token.clone(),
+        value,
+    }))
+}
+
+fn parse_float_literal(p: &mut Parser) -> Option<Box<dyn Expression>> {
+    let value = p
+        .cur_token
+        .literal
+        .parse()
+        .ok()
+        .filter(|f: &f64| f.is_finite());
+    let Some(value) = value else {
+        p.number_error("a float");
+        return None;
+    };
+    Some(Box::new(FloatLiteral {
+        token: p.cur_token.clone(),
+        value,
+    }))
+}
+
+fn parse_boolean(p: &Parser) -> Box<dyn Expression> {
+    Box::new(BooleanLiteral {
+        token: p.cur_token.clone(),
+        value: p.cur_token_is(TokenType::TRUE),
+    })
+}
+
+fn parse_grouped_expression(p: &mut Parser) -> Option<Box<dyn Expression>> {
+    p.next_token();
+    let no_struct_literal = std::mem::replace(&mut p.no_struct_literal, false);
+    let exp = p.parse_expression(LOWEST);
+    p.no_struct_literal = no_struct_literal;
+    if !p.expect_peek(TokenType::RPAREN) {
+        return None;
+    }
+    exp
+}
+
+fn parse_if_expression(p: &mut Parser) -> Option<Box<dyn Expression>> {
+    let mut expression = IfExpression {
+        token: p.cur_token.clone(),
+        condition: None,
+        consequence: None,
+        else_ifs: vec![],
+        alternative: None,
+    };
+
+    p.next_token();
+    expression.condition = Some(p.parse_condition()?);
+
+    if !p.expect_peek(TokenType::LBRACE) {
+        return None;
+    }
+
+    expression.consequence = p.parse_block_statement();
+
+    while p.peek_token_is(&TokenType::ELSE) {
+        p.next_token();
+        if p.peek_token_is(&TokenType::IF) {
+            p.next_token();
+            p.next_token();
+            let condition = p.parse_condition()?;
+            if !p.expect_peek(TokenType::LBRACE) {
+                return None;
+            }
+            let consequence = p.parse_block_statement()?;
+            expression.else_ifs.push((condition, consequence));
+            continue;
+        }
+        if !p.expect_peek(TokenType::LBRACE) {
+            return None;
+        }
+        expression.alternative = p.parse_block_statement();
+        break;
+    }
+
+    Some(Box::new(expression))
+}
+
+fn parse_try_expression(p: &mut Parser) -> Option<Box<dyn Expression>> {
+    let token = p.cur_token.clone();
+    if !p.expect_peek(TokenType::LBRACE) {
+        return None;
+    }
+    let body = p.parse_block_statement()?;
+
+    if !p.expect_peek(TokenType::CATCH) {
+        return None;
+    }
+    // the parameter is optional, with or without parentheses
+    let parenthesized = p.peek_token_is(&TokenType::LPAREN);
+    if parenthesized {
+        p.next_token();
+    }
+    let mut parameter = None;
+    if parenthesized || p.peek_token_is(&TokenType::IDENT) {
+        if !p.expect_peek(TokenType::IDENT) {
+            return None;
+        }
+        parameter = Some(Identifier {
+            token: p.cur_token.clone(),
+            value: p.cur_token.literal.clone(),
+        });
+    }
+    if parenthesized && !p.expect_peek(TokenType::RPAREN) {
+        return None;
+    }
+
+    if !p.expect_peek(TokenType::LBRACE) {
+        return None;
+    }
+    let handler = p.parse_block_statement()?;
+
+    Some(Box::new(TryExpression {
+        token,
+        body,
+        parameter,
+        handler,
+    }))
+}
+
+fn parse_fn_literal(p: &mut Parser) -> Option<Box<dyn Expression>> {
+    let token = p.cur_token.clone();
+    Some(Box::new(parse_function(p, token)?))
+}
+
+/// Parses a parameter list, optional return type and body, starting with the
+/// current token just before the opening parenthesis.
+pub(crate) fn parse_function(p: &mut Parser, token: Token) -> Option<FunctionLiteral> {
+    let mut lit = FunctionLiteral {
+        token,
+        parameters: vec![],
+        parameter_types: vec![],
+        return_type: None,
+        body: None,
+    };
+    if !p.expect_peek(TokenType::LPAREN) {
+        return None;
+    }
+
+    if let Some(params) = p.parse_fn_parameters() {
+        (lit.parameters, lit.parameter_types) = params.into_iter().unzip();
+    }
+
+    if p.peek_token_is(&TokenType::COLON) {
+        p.next_token();
+        p.next_token();
+        lit.return_type = Some(p.parse_type()?);
+    }
+
+    if !p.expect_peek(TokenType::LBRACE) {
+        return None;
+    }
+
+    lit.body = p.parse_block_statement().map(Rc::new);
+
+    Some(lit)
+}
+
+fn parse_interface_literal(p: &mut Parser) -> Option<Box<dyn Expression>> {
+    let mut lit = InterfaceLiteral {
+        token: p.cur_token.clone(),
+        methods: vec![],
+    };
+    if !p.expect_peek(TokenType::LBRACE) {
+        return None;
+    }
+
+    while !p.peek_token_is(&TokenType::RBRACE) {
+        if !p.expect_peek(TokenType::IDENT) {
+            return None;
+        }
+        let mut method = MethodSignature {
+            name: Identifier {
+                token: p.cur_token.clone(),
+                value: p.cur_token.literal.clone(),
+            },
+            parameters: vec![],
+            return_type: None,
+        };
+        if !p.expect_peek(TokenType::LPAREN) {
+            return None;
+        }
+        method.parameters = p
+            .parse_fn_parameters()?
+            .into_iter()
+            .map(|(param, _)| param)
+            .collect();
+        if p.peek_token_is(&TokenType::COLON) {
+            p.next_token();
+            p.next_token();
+            method.return_type = Some(p.parse_type()?);
+        }
+        lit.methods.push(method);
+
+        if p.peek_token_is(&TokenType::SEMICOLON) || p.peek_token_is(&TokenType::COMMA) {
+            p.next_token();
+        }
+    }
+    p.next_token();
+
+    Some(Box::new(lit))
+}
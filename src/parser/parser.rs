@@ -0,0 +1,808 @@
+use std::collections::HashMap;
+
+use crate::lexer::lexer::Lexer;
+use crate::lexer::token::{Token, TokenType};
+use crate::module::loader::MAIN_FILE;
+
+use super::ast::{
+    BlockStatement, ConditionalIteratorExpression, Expression, ExpressionStatement,
+    ForLoopCondition, ForLoopExpression, Identifier, ImplStatement, ImportStatement, LetStatement,
+    Pattern, Program, ReturnStatemnt, Statement, ThrowStatement, TypeExpr,
+};
+use super::parse_func::{parse_function, parse_infix_func, parse_prefix_func};
+use super::{grow_stack, is_of_type};
+
+pub(crate) const LOWEST: i32 = 1;
+pub(crate) const ASSIGN: i32 = 2; // = or +=
+const LOGICAL_OR: i32 = 3; // ||
+const LOGICAL_AND: i32 = 4; // &&
+const EQUALS: i32 = 5; // ==
+const LESSGREATER: i32 = 6; // > or <
+const SPREED: i32 = 7;
+const IN: i32 = 8;
+const SUM: i32 = 9; // +
+const PRODUCT: i32 = 10; // *
+pub(crate) const PREFIX: i32 = 11; // -X or !X
+const CALL: i32 = 12; // fn(x)
+const INDEX: i32 = 13; // array[index] or object.property
+
+/// How deeply expressions, blocks, patterns and types may nest, counting
+/// each operator applied to an expression as one level. The passes over
+/// the syntax tree recurse as deeply, so deeper programs are rejected before
+/// they could overflow the stack.
+pub const MAX_NESTING: usize = 512;
+
+pub struct Parser {
+    l: Lexer,
+    errors: Vec<String>,
+    pub(crate) cur_token: Token,
+    peek_token: Token,
+    precedences: HashMap<TokenType, i32>,
+    /// Set while parsing a position followed by a block (e.g. a `match` subject),
+    /// where `Name {` must not be read as a struct expression.
+    pub(crate) no_struct_literal: bool,
+    /// How many blocks enclose the current token; imports and exports are
+    /// only allowed outside of all of them.
+    block_depth: usize,
+    /// How deeply the node being parsed is nested, up to `MAX_NESTING`.
+    depth: usize,
+    /// How many errors there were when nesting went too deep, after which
+    /// the rest of the input is skipped and the errors of the nodes left
+    /// unfinished are dropped.
+    too_deep: Option<usize>,
+    /// The file being parsed, as errors name it.
+    file: String,
+}
+
+impl Parser {
+    pub fn new(l: Lexer) -> Self {
+        let mut p = Self {
+            l,
+            errors: vec![],
+            cur_token: Token::default(),
+            peek_token: Token::default(),
+            precedences: HashMap::default(),
+            no_struct_literal: false,
+            block_depth: 0,
+            depth: 0,
+            too_deep: None,
+            file: MAIN_FILE.to_string(),
+        };
+        p.register_precedences();
+        // clear default tokens
+        p.next_token();
+        p.next_token();
+
+        p
+    }
+
+    /// Names the file being parsed in errors, in place of `main.pr`.
+    pub fn set_file(&mut self, file: impl Into<String>) {
+        self.file = file.into();
+    }
+
+    pub fn parse_program(&mut self) -> Option<Program> {
+        let mut program = Program { statements: vec![] };
+        while !self.cur_token_is(TokenType::EOF) {
+            if let Some(stmt) = self.parse_statement() {
+                program.statements.push(stmt);
+            }
+            self.next_token()
+        }
+        if let Some(errors) = self.too_deep {
+            self.errors.truncate(errors);
+        }
+
+        Some(program)
+    }
+
+    fn parse_statement(&mut self) -> Option<Box<dyn Statement>> {
+        match self.cur_token.token_type {
+            TokenType::LET => self.parse_let_statement(),
+            TokenType::RETURN => self.parse_return_statement(),
+            TokenType::THROW => self.parse_throw_statement(),
+            TokenType::FOR => self.parse_for_expression(),
+            TokenType::IMPL => self.parse_impl_statement(),
+            TokenType::IMPORT => self.parse_import_statement(),
+            TokenType::EXPORT => self.parse_export_statement(),
+            TokenType::None => None,
+            _ => self.parse_expression_statment(),
+        }
+    }
+
+    fn parse_let_statement(&mut self) -> Option<Box<dyn Statement>> {
+        Some(Box::new(self.parse_let()?))
+    }
+
+    fn parse_let(&mut self) -> Option<LetStatement> {
+        let mut stmt = LetStatement {
+            token: self.cur_token.clone(),
+            value: None,
+            type_annotation: None,
+            name: Identifier::default(),
+            exported: false,
+        };
+
+        if !self.expect_peek(TokenType::IDENT) {
+            return None;
+        }
+
+        stmt.name = Identifier {
+            token: self.cur_token.clone(),
+            value: String::new(),
+        };
+        stmt.name.value.push_str(&self.cur_token.literal);
+
+        if self.peek_token_is(&TokenType::COLON) {
+            self.next_token();
+            self.next_token();
+            stmt.type_annotation = Some(self.parse_type()?);
+        }
+
+        if !self.expect_peek(TokenType::ASSIGN) {
+            return None;
+        }
+
+        self.next_token();
+        stmt.value = self.parse_expression(LOWEST);
+
+        if self.peek_token_is(&TokenType::SEMICOLON) {
+            self.next_token();
+        }
+
+        Some(stmt)
+    }
+
+    fn parse_import_statement(&mut self) -> Option<Box<dyn Statement>> {
+        if self.block_depth > 0 {
+            self.top_level_error("import");
+            return None;
+        }
+        let token = self.cur_token.clone();
+        if !self.expect_peek(TokenType::STRING) {
+            return None;
+        }
+        let path = self.cur_token.literal.clone();
+        if !self.expect_peek(TokenType::AS) || !self.expect_peek(TokenType::IDENT) {
+            return None;
+        }
+        let alias = Identifier {
+            token: self.cur_token.clone(),
+            value: self.cur_token.literal.clone(),
+        };
+
+        if self.peek_token_is(&TokenType::SEMICOLON) {
+            self.next_token();
+        }
+
+        Some(Box::new(ImportStatement { token, path, alias }))
+    }
+
+    /// `export let ...`: only bindings can be exported.
+    fn parse_export_statement(&mut self) -> Option<Box<dyn Statement>> {
+        if self.block_depth > 0 {
+            self.top_level_error("export");
+            return None;
+        }
+        if !self.expect_peek(TokenType::LET) {
+            return None;
+        }
+        let mut stmt = self.parse_let()?;
+        stmt.exported = true;
+
+        Some(Box::new(stmt))
+    }
+
+    fn parse_return_statement(&mut self) -> Option<Box<dyn Statement>> {
+        let mut stmt = ReturnStatemnt {
+            token: self.cur_token.clone(),
+            return_value: None,
+        };
+
+        self.next_token();
+
+        stmt.return_value = self.parse_expression(LOWEST);
+
+        if self.peek_token_is(&TokenType::SEMICOLON) {
+            self.next_token();
+        }
+
+        Some(Box::new(stmt))
+    }
+
+    fn parse_throw_statement(&mut self) -> Option<Box<dyn Statement>> {
+        let mut stmt = ThrowStatement {
+            token: self.cur_token.clone(),
+            value: None,
+        };
+
+        self.next_token();
+
+        stmt.value = self.parse_expression(LOWEST);
+
+        if self.peek_token_is(&TokenType::SEMICOLON) {
+            self.next_token();
+        }
+
+        Some(Box::new(stmt))
+    }
+
+    fn parse_expression_statment(&mut self) -> Option<Box<dyn Statement>> {
+        let stmt = ExpressionStatement {
+            token: self.cur_token.clone(),
+            expression: self.parse_expression(LOWEST),
+        };
+
+        if self.peek_token_is(&TokenType::SEMICOLON) {
+            self.next_token()
+        }
+
+        Some(Box::new(stmt))
+    }
+
+    pub(crate) fn parse_expression(&mut self, precedence: i32) -> Option<Box<dyn Expression>> {
+        self.nested(|p| p.parse_nested_expression(precedence))
+    }
+
+    fn parse_nested_expression(&mut self, precedence: i32) -> Option<Box<dyn Expression>> {
+        match parse_prefix_func(self) {
+            Some(mut left_exp) => {
+                while !self.peek_token_is(&TokenType::SEMICOLON)
+                    && precedence < self.peek_precedence()
+                {
+                    // the expression so far becomes an operand, one level deeper
+                    self.nest()?;
+                    // TODO: think about this implementation
+                    self.next_token();
+                    left_exp = parse_infix_func(self, left_exp)?;
+                }
+                Some(left_exp)
+            }
+            _ => {
+                self.no_prefix_parse_fn_error();
+                None
+            }
+        }
+    }
+
+    /// Parses a node nested one level deeper than the one around it with
+    /// `parse`, which may nest the node deeper still.
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Option<T>) -> Option<T> {
+        let depth = self.depth;
+        let node = self.nest().and_then(|_| grow_stack(|| parse(self)));
+        self.depth = depth;
+        node
+    }
+
+    /// Goes one level deeper, failing past `MAX_NESTING`, when the rest of
+    /// the input is skipped.
+    fn nest(&mut self) -> Option<()> {
+        if self.depth == MAX_NESTING {
+            if self.too_deep.is_none() {
+                self.nesting_error();
+                self.too_deep = Some(self.errors.len());
+            }
+            while !self.cur_token_is(TokenType::EOF) {
+                self.next_token();
+            }
+            return None;
+        }
+        self.depth += 1;
+        Some(())
+    }
+
+    pub(crate) fn parse_block_statement(&mut self) -> Option<BlockStatement> {
+        self.nested(Self::parse_block)
+    }
+
+    fn parse_block(&mut self) -> Option<BlockStatement> {
+        let mut block = BlockStatement {
+            token: self.cur_token.clone(),
+            statements: vec![],
+        };
+
+        self.next_token();
+
+        self.block_depth += 1;
+        while !self.cur_token_is(TokenType::RBRACE) && !self.cur_token_is(TokenType::EOF) {
+            if let Some(stmt) = self.parse_statement() {
+                block.statements.push(stmt);
+            }
+            self.next_token()
+        }
+        self.block_depth -= 1;
+
+        Some(block)
+    }
+
+    pub(crate) fn parse_fn_parameters(&mut self) -> Option<Vec<(Identifier, Option<TypeExpr>)>> {
+        let mut identifiers = vec![];
+        if self.peek_token_is(&TokenType::RPAREN) {
+            self.next_token();
+            return Some(identifiers);
+        }
+
+        self.next_token();
+
+        identifiers.push(self.parse_fn_parameter()?);
+
+        while self.peek_token_is(&TokenType::COMMA) {
+            self.next_token();
+            self.next_token();
+            identifiers.push(self.parse_fn_parameter()?)
+        }
+
+        if !self.expect_peek(TokenType::RPAREN) {
+            return None;
+        }
+
+        Some(identifiers)
+    }
+
+    fn parse_fn_parameter(&mut self) -> Option<(Identifier, Option<TypeExpr>)> {
+        let ident = Identifier {
+            token: self.cur_token.clone(),
+            value: self.cur_token.literal.to_owned(),
+        };
+        if !self.peek_token_is(&TokenType::COLON) {
+            return Some((ident, None));
+        }
+        self.next_token();
+        self.next_token();
+
+        Some((ident, Some(self.parse_type()?)))
+    }
+
+    pub(crate) fn parse_type(&mut self) -> Option<TypeExpr> {
+        self.nested(Self::parse_nested_type)
+    }
+
+    fn parse_nested_type(&mut self) -> Option<TypeExpr> {
+        match self.cur_token.token_type {
+            TokenType::IDENT => Some(TypeExpr::Named(Identifier {
+                token: self.cur_token.clone(),
+                value: self.cur_token.literal.clone(),
+            })),
+            TokenType::LBRACKET => {
+                if !self.expect_peek(TokenType::RBRACKET) {
+                    return None;
+                }
+                self.next_token();
+                Some(TypeExpr::Array(Box::new(self.parse_type()?)))
+            }
+            TokenType::FUNCTION => {
+                if !self.expect_peek(TokenType::LPAREN) {
+                    return None;
+                }
+                let mut parameters = vec![];
+                while !self.peek_token_is(&TokenType::RPAREN) {
+                    self.next_token();
+                    parameters.push(self.parse_type()?);
+                    if !self.peek_token_is(&TokenType::RPAREN)
+                        && !self.expect_peek(TokenType::COMMA)
+                    {
+                        return None;
+                    }
+                }
+                self.next_token();
+                let mut return_type = None;
+                if self.peek_token_is(&TokenType::COLON) {
+                    self.next_token();
+                    self.next_token();
+                    return_type = Some(Box::new(self.parse_type()?));
+                }
+                Some(TypeExpr::Function {
+                    parameters,
+                    return_type,
+                })
+            }
+            _ => {
+                let msg = format!(
+                    "{}:{}:{} \n invalid type -> unexpected token {:?}",
+                    self.file,
+                    self.cur_token.position.0,
+                    self.cur_token.position.1,
+                    self.cur_token.literal,
+                );
+                self.errors.push(msg);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn parse_call_argument(&mut self) -> Vec<Box<dyn Expression>> {
+        self.parse_expression_list(TokenType::RPAREN)
+    }
+
+    pub(crate) fn parse_expression_list(&mut self, end: TokenType) -> Vec<Box<dyn Expression>> {
+        let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, false);
+        let list = self.parse_delimited_expressions(end);
+        self.no_struct_literal = no_struct_literal;
+        list
+    }
+
+    fn parse_delimited_expressions(&mut self, end: TokenType) -> Vec<Box<dyn Expression>> {
+        let mut list = vec![];
+
+        if self.peek_token_is(&end) {
+            self.next_token();
+            return list;
+        }
+
+        self.next_token();
+        if let Some(exp) = self.parse_expression(LOWEST) {
+            list.push(exp);
+        }
+
+        while self.peek_token_is(&TokenType::COMMA) {
+            self.next_token();
+            if self.peek_token_is(&end) {
+                break;
+            }
+            self.next_token();
+            if let Some(exp) = self.parse_expression(LOWEST) {
+                list.push(exp);
+            }
+        }
+
+        if !self.expect_peek(end) {
+            return vec![];
+        }
+
+        list
+    }
+
+    pub(crate) fn parse_pattern(&mut self) -> Option<Pattern> {
+        self.nested(Self::parse_nested_pattern)
+    }
+
+    fn parse_nested_pattern(&mut self) -> Option<Pattern> {
+        match self.cur_token.token_type {
+            TokenType::IDENT if self.cur_token.literal == "_" => {
+                Some(Pattern::Wildcard(self.cur_token.clone()))
+            }
+            TokenType::IDENT => {
+                let name = Identifier {
+                    token: self.cur_token.clone(),
+                    value: self.cur_token.literal.clone(),
+                };
+                if self.peek_token_is(&TokenType::Dot) {
+                    self.next_token();
+                    if !self.expect_peek(TokenType::IDENT) {
+                        return None;
+                    }
+                    let variant = Identifier {
+                        token: self.cur_token.clone(),
+                        value: self.cur_token.literal.clone(),
+                    };
+                    let mut payload = None;
+                    if self.peek_token_is(&TokenType::LPAREN) {
+                        self.next_token();
+                        let (patterns, rest) = self.parse_pattern_list(TokenType::RPAREN)?;
+                        if rest.is_some() {
+                            self.pattern_error("rest patterns are only allowed in arrays");
+                            return None;
+                        }
+                        payload = Some(patterns);
+                    }
+                    return Some(Pattern::EnumVariant {
+                        enum_name: name,
+                        variant,
+                        payload,
+                    });
+                }
+                if self.peek_token_is(&TokenType::LBRACE) {
+                    self.next_token();
+                    let fields = self.parse_struct_pattern_fields()?;
+                    return Some(Pattern::Struct { name, fields });
+                }
+                Some(Pattern::Binding(name))
+            }
+            TokenType::INT
+            | TokenType::FLOAT
+            | TokenType::STRING
+            | TokenType::TRUE
+            | TokenType::FALSE
+            | TokenType::MINUS => {
+                let token = self.cur_token.clone();
+                let value = self.parse_expression(PREFIX)?;
+                Some(Pattern::Literal { token, value })
+            }
+            TokenType::LBRACKET => {
+                let token = self.cur_token.clone();
+                let (elements, rest) = self.parse_pattern_list(TokenType::RBRACKET)?;
+                Some(Pattern::Array {
+                    token,
+                    elements,
+                    rest,
+                })
+            }
+            _ => {
+                self.pattern_error(&format!(
+                    "unexpected token {:?} in pattern",
+                    self.cur_token.literal
+                ));
+                None
+            }
+        }
+    }
+
+    /// Parses comma separated patterns up to `end`, including an optional `..rest`.
+    fn parse_pattern_list(
+        &mut self,
+        end: TokenType,
+    ) -> Option<(Vec<Pattern>, Option<Box<Pattern>>)> {
+        let mut patterns = vec![];
+        let mut rest = None;
+
+        while !self.peek_token_is(&end) {
+            self.next_token();
+            if self.cur_token_is(TokenType::Spreed) {
+                if self.peek_token_is(&TokenType::IDENT) {
+                    self.next_token();
+                    rest = Some(Box::new(self.parse_pattern()?));
+                } else {
+                    rest = Some(Box::new(Pattern::Wildcard(self.cur_token.clone())));
+                }
+                break;
+            }
+            patterns.push(self.parse_pattern()?);
+            if !self.peek_token_is(&TokenType::COMMA) {
+                break;
+            }
+            self.next_token();
+        }
+
+        if !self.expect_peek(end) {
+            return None;
+        }
+
+        Some((patterns, rest))
+    }
+
+    fn parse_struct_pattern_fields(&mut self) -> Option<Vec<(Identifier, Pattern)>> {
+        let mut fields = vec![];
+
+        while !self.peek_token_is(&TokenType::RBRACE) {
+            if !self.expect_peek(TokenType::IDENT) {
+                return None;
+            }
+            let field = Identifier {
+                token: self.cur_token.clone(),
+                value: self.cur_token.literal.clone(),
+            };
+            let pattern = if self.peek_token_is(&TokenType::COLON) {
+                self.next_token();
+                self.next_token();
+                self.parse_pattern()?
+            } else {
+                Pattern::Binding(field.clone())
+            };
+            fields.push((field, pattern));
+            if !self.peek_token_is(&TokenType::COMMA) {
+                break;
+            }
+            self.next_token();
+        }
+
+        if !self.expect_peek(TokenType::RBRACE) {
+            return None;
+        }
+
+        Some(fields)
+    }
+
+    fn parse_impl_statement(&mut self) -> Option<Box<dyn Statement>> {
+        let mut stmt = ImplStatement {
+            token: self.cur_token.clone(),
+            target: Identifier::default(),
+            methods: vec![],
+        };
+
+        if !self.expect_peek(TokenType::IDENT) {
+            return None;
+        }
+        stmt.target = Identifier {
+            token: self.cur_token.clone(),
+            value: self.cur_token.literal.clone(),
+        };
+
+        if !self.expect_peek(TokenType::LBRACE) {
+            return None;
+        }
+
+        while !self.peek_token_is(&TokenType::RBRACE) {
+            if !self.expect_peek(TokenType::FUNCTION) {
+                return None;
+            }
+            let token = self.cur_token.clone();
+            if !self.expect_peek(TokenType::IDENT) {
+                return None;
+            }
+            let name = Identifier {
+                token: self.cur_token.clone(),
+                value: self.cur_token.literal.clone(),
+            };
+            stmt.methods.push((name, parse_function(self, token)?));
+
+            if self.peek_token_is(&TokenType::SEMICOLON) || self.peek_token_is(&TokenType::COMMA) {
+                self.next_token();
+            }
+        }
+        self.next_token();
+
+        if self.peek_token_is(&TokenType::SEMICOLON) {
+            self.next_token();
+        }
+
+        Some(Box::new(stmt))
+    }
+
+    fn parse_for_expression(&mut self) -> Option<Box<dyn Statement>> {
+        let mut expression = ForLoopExpression {
+            token: self.cur_token.clone(),
+            condition: None,
+            body: None,
+        };
+
+        if self.peek_token_is(&TokenType::LBRACE) {
+            self.next_token();
+            expression.condition = Some(ForLoopCondition::Loop);
+        } else {
+            self.next_token();
+            let condition = self.parse_condition()?;
+            if is_of_type::<ConditionalIteratorExpression>(condition.get_as_any()) {
+                expression.condition = Some(ForLoopCondition::ForIn(condition))
+            } else {
+                expression.condition = Some(ForLoopCondition::For(condition));
+            }
+            if !self.expect_peek(TokenType::LBRACE) {
+                return None;
+            }
+        }
+
+        expression.body = self.parse_block_statement();
+
+        if self.peek_token_is(&TokenType::SEMICOLON) {
+            self.next_token();
+        }
+
+        Some(Box::new(expression))
+    }
+}
+
+// util functions
+impl Parser {
+    fn register_precedences(&mut self) {
+        self.precedences.insert(TokenType::ASSIGN, ASSIGN);
+        self.precedences.insert(TokenType::PlusAssign, ASSIGN);
+        self.precedences.insert(TokenType::MinusAssign, ASSIGN);
+        self.precedences.insert(TokenType::AsteriskAssign, ASSIGN);
+        self.precedences.insert(TokenType::SlashAssign, ASSIGN);
+        self.precedences.insert(TokenType::PercentAssign, ASSIGN);
+        self.precedences.insert(TokenType::EQ, EQUALS);
+        self.precedences.insert(TokenType::NotEq, EQUALS);
+        self.precedences.insert(TokenType::LT, LESSGREATER);
+        self.precedences.insert(TokenType::GT, LESSGREATER);
+        self.precedences.insert(TokenType::LtEq, LESSGREATER);
+        self.precedences.insert(TokenType::GtEq, LESSGREATER);
+        self.precedences.insert(TokenType::AND, LOGICAL_AND);
+        self.precedences.insert(TokenType::OR, LOGICAL_OR);
+        self.precedences.insert(TokenType::PLUS, SUM);
+        self.precedences.insert(TokenType::MINUS, SUM);
+        self.precedences.insert(TokenType::SLASH, PRODUCT);
+        self.precedences.insert(TokenType::ASTERISK, PRODUCT);
+        self.precedences.insert(TokenType::PERCENT, PRODUCT);
+        self.precedences.insert(TokenType::LPAREN, CALL);
+        self.precedences.insert(TokenType::LBRACKET, INDEX);
+        self.precedences.insert(TokenType::Dot, INDEX);
+        self.precedences.insert(TokenType::Spreed, SPREED);
+        self.precedences.insert(TokenType::IN, IN);
+    }
+
+    fn peek_precedence(&self) -> i32 {
+        if let Some(&p) = self.precedences.get(&self.peek_token.token_type) {
+            return p;
+        }
+        LOWEST
+    }
+
+    pub(crate) fn cur_precedence(&self) -> i32 {
+        if let Some(&p) = self.precedences.get(&self.cur_token.token_type) {
+            return p;
+        }
+        LOWEST
+    }
+
+    pub(crate) fn next_token(&mut self) {
+        self.cur_token = self.peek_token.take();
+        self.peek_token = self.l.next_token();
+    }
+
+    pub(crate) fn cur_token_is(&self, t: TokenType) -> bool {
+        self.cur_token.token_type == t
+    }
+    pub(crate) fn peek_token_is(&self, t: &TokenType) -> bool {
+        self.peek_token.token_type == *t
+    }
+    pub(crate) fn expect_peek(&mut self, t: TokenType) -> bool {
+        if self.peek_token_is(&t) {
+            self.next_token();
+            return true;
+        }
+        self.peek_error(t);
+        false
+    }
+
+    pub fn errors(&self) -> &Vec<String> {
+        &self.errors
+    }
+    fn peek_error(&mut self, t: TokenType) {
+        let msg = format!(
+            "{}:{}:{} \n unexpected character -> expected next token to be {:?}, got {:?} instead",
+            self.file,
+            self.cur_token.position.0,
+            self.cur_token.position.1,
+            t,
+            self.peek_token.token_type,
+        );
+        self.errors.push(msg)
+    }
+    /// Parses the condition of an `if` or `for`, which may be written with
+    /// or without parentheses. A `{` after a bare identifier opens the body,
+    /// so struct expressions need parentheses here: `if p == (P { x: 1 }) {`.
+    pub(crate) fn parse_condition(&mut self) -> Option<Box<dyn Expression>> {
+        let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, true);
+        let condition = self.parse_expression(LOWEST);
+        self.no_struct_literal = no_struct_literal;
+
+        condition
+    }
+
+    pub(crate) fn invalid_assignment_target_error(&mut self, target: &str) {
+        let msg = format!(
+            "{}:{}:{} \n invalid assignment -> cannot assign to `{}`",
+            self.file, self.cur_token.position.0, self.cur_token.position.1, target,
+        );
+        self.errors.push(msg)
+    }
+    fn top_level_error(&mut self, keyword: &str) {
+        let msg = format!(
+            "{}:{}:{} \n misplaced {} -> only allowed at the top level of a file",
+            self.file, self.cur_token.position.0, self.cur_token.position.1, keyword,
+        );
+        self.errors.push(msg)
+    }
+    pub(crate) fn number_error(&mut self, kind: &str) {
+        let msg = format!(
+            "{}:{}:{} \n invalid number -> {} is too large for {}",
+            self.file,
+            self.cur_token.position.0,
+            self.cur_token.position.1,
+            self.cur_token.literal,
+            kind,
+        );
+        self.errors.push(msg)
+    }
+    fn pattern_error(&mut self, reason: &str) {
+        let msg = format!(
+            "{}:{}:{} \n invalid pattern -> {}",
+            self.file, self.cur_token.position.0, self.cur_token.position.1, reason,
+        );
+        self.errors.push(msg)
+    }
+    fn nesting_error(&mut self) {
+        let msg = format!(
+            "{}:{}:{} \n nested too deeply -> more than {} levels of expressions, blocks, patterns or types",
+            self.file, self.cur_token.position.0, self.cur_token.position.1, MAX_NESTING,
+        );
+        self.errors.push(msg)
+    }
+    fn no_prefix_parse_fn_error(&mut self) {
+        let msg = format!(
+            "no prefix parse function for {:?} found",
+            self.cur_token.literal
+        );
+        self.errors.push(msg);
+    }
+}
@@ -1,16 +1,26 @@
+// lints the older tests trip, which are kept as they were written
+#![allow(
+    clippy::bool_assert_comparison,
+    clippy::borrowed_box,
+    clippy::collapsible_match,
+    clippy::explicit_counter_loop,
+    clippy::get_first,
+    clippy::len_zero
+)]
+
 use std::any::Any;
 
-use crate::core::{
+use crate::{
     lexer::lexer::Lexer,
     parser::{
         ast::{
             BooleanLiteral, CallExpression, ConditionalIteratorExpression, Expression,
             ExpressionStatement, ForLoopCondition, ForLoopExpression, FunctionLiteral, Identifier,
-            IfExpression, InfixExpression, IntegerLiteral, IteratorLiteral, LetStatement, Node,
-            PrefixExpression, Statement,
+            IfExpression, InfixExpression, IntegerLiteral, IteratorLiteral, LetStatement,
+            MatchExpression, Node, Pattern, PrefixExpression, Statement, StructExpression,
         },
         get_of_type,
-        parser::Parser,
+        parser::{Parser, MAX_NESTING},
     },
 };
 
@@ -300,6 +310,107 @@ fn test_if_expression() {
     )
 }
 
+#[test]
+fn test_condition_parsing() {
+    let tests = [
+        ("if x < y { x }", "if(x < y) x"),
+        ("if (x < y) { x }", "if(x < y) x"),
+        (
+            "if a { 1 } else if b { 2 } else if (c) { 3 } else { 4 }",
+            "ifa 1 else ifb 2 else ifc 3 else 4",
+        ),
+        ("if a { 1 } else if b { 2 }", "ifa 1 else ifb 2"),
+        ("for x < 10 { x += 1; }", "for (x < 10)x += 1"),
+        ("for (x < 10) { x += 1; }", "for (x < 10)x += 1"),
+        ("for i in 0..10 { i; }", "for (i in 0..10)i"),
+        ("for (i in 0..10) { i; }", "for (i in 0..10)i"),
+        ("for ok { 1; }", "for ok1"),
+    ];
+
+    for (input, expected) in tests {
+        let l = Lexer::new(input.to_string());
+        let mut p = Parser::new(l);
+        let program = p.parse_program().expect("parse_program() return none");
+
+        assert!(!chack_parser_errors(&p), "input: {}", input);
+        let actual = program.string();
+        assert_eq!(actual, expected, "expected={}, got={}", expected, actual)
+    }
+}
+
+#[test]
+fn test_try_expression_parsing() {
+    let tests = [
+        (
+            "try { a / b } catch (e) { e.message }",
+            "try (a / b) catch (e) e.message",
+        ),
+        ("try { a } catch e { 0 }", "try a catch (e) 0"),
+        ("try { a } catch { 0 }", "try a catch 0"),
+        (
+            "let x = try { f() } catch { throw \"again\"; };",
+            "let x = try f() catch throw \"again\";;",
+        ),
+        ("throw e;", "throw e;"),
+    ];
+
+    for (input, expected) in tests {
+        let l = Lexer::new(input.to_string());
+        let mut p = Parser::new(l);
+        let program = p.parse_program().expect("parse_program() return none");
+
+        assert!(!chack_parser_errors(&p), "input: {}", input);
+        let actual = program.string();
+        assert_eq!(actual, expected, "expected={}, got={}", expected, actual)
+    }
+
+    for input in ["try { a }", "try { a } catch (e { 0 }", "try a catch { 0 }"] {
+        let l = Lexer::new(input.to_string());
+        let mut p = Parser::new(l);
+        p.parse_program();
+        assert!(!p.errors().is_empty(), "no parser errors for {}", input);
+    }
+}
+
+#[test]
+fn test_condition_struct_and_hash_literals_are_not_ambiguous() {
+    // a `{` after a bare identifier in a condition opens the body
+    let l = Lexer::new(String::from("if p == Point { p } else { q }"));
+    let mut p = Parser::new(l);
+    let program = p.parse_program().expect("parse_program() return none");
+    assert!(!chack_parser_errors(&p));
+    assert_eq!(program.string(), "if(p == Point) p else q");
+
+    let tests = [
+        (
+            "if p == (Point { x: 1 }) { p }",
+            "if(p == Point { x: 1 }) p",
+        ),
+        (
+            "if {\"a\": 1}[\"a\"] == 1 { 2 }",
+            "if(({\"a\": 1}[\"a\"]) == 1) 2",
+        ),
+        (
+            "for x in [Point { x: 1 }] { x; }",
+            "for (x in [Point { x: 1 }])x",
+        ),
+        (
+            "if eq(p, Point { x: 1 }) { p }",
+            "ifeq(p, Point { x: 1 }) p",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let l = Lexer::new(input.to_string());
+        let mut p = Parser::new(l);
+        let program = p.parse_program().expect("parse_program() return none");
+
+        assert!(!chack_parser_errors(&p), "input: {}", input);
+        let actual = program.string();
+        assert_eq!(actual, expected, "expected={}, got={}", expected, actual)
+    }
+}
+
 #[test]
 fn test_for_expression_type_loop_parsing() {
     let input = String::from("for { x + y; }");
@@ -947,6 +1058,58 @@ fn test_operator_precedence_parsing() {
             input: String::from("add(a + b + c * d / f + g)"),
             expected: String::from("add((((a + b) + ((c * d) / f)) + g))"),
         },
+        TestCase {
+            input: String::from("a * [1, 2, 3, 4][b * c] * d"),
+            expected: String::from("((a * ([1, 2, 3, 4][(b * c)])) * d)"),
+        },
+        TestCase {
+            input: String::from("add(a * b[2], b[1], 2 * [1, 2][1])"),
+            expected: String::from("add((a * (b[2])), (b[1]), (2 * ([1, 2][1])))"),
+        },
+        TestCase {
+            input: String::from("-Option.some(1 + 2).x[0]"),
+            expected: String::from("(-(Option.some((1 + 2)).x[0]))"),
+        },
+        TestCase {
+            input: String::from("a + b % c * d"),
+            expected: String::from("(a + ((b % c) * d))"),
+        },
+        TestCase {
+            input: String::from("a <= b == c >= d"),
+            expected: String::from("((a <= b) == (c >= d))"),
+        },
+        TestCase {
+            input: String::from("a || b && c"),
+            expected: String::from("(a || (b && c))"),
+        },
+        TestCase {
+            input: String::from("a && b || c && d"),
+            expected: String::from("((a && b) || (c && d))"),
+        },
+        TestCase {
+            input: String::from("a == b && c != d || !e"),
+            expected: String::from("(((a == b) && (c != d)) || (!e))"),
+        },
+        TestCase {
+            input: String::from("i % 2 == 0 && n <= 1"),
+            expected: String::from("(((i % 2) == 0) && (n <= 1))"),
+        },
+        TestCase {
+            input: String::from("x = a || b"),
+            expected: String::from("x = (a || b)"),
+        },
+        TestCase {
+            input: String::from("x = y = 1 + 2"),
+            expected: String::from("x = y = (1 + 2)"),
+        },
+        TestCase {
+            input: String::from("a[i] += b * c == d"),
+            expected: String::from("(a[i]) += ((b * c) == d)"),
+        },
+        TestCase {
+            input: String::from("p.x %= add(1, 2)"),
+            expected: String::from("p.x %= add(1, 2)"),
+        },
     ];
 
     for tt in tests {
@@ -964,6 +1127,295 @@ fn test_operator_precedence_parsing() {
     }
 }
 
+#[test]
+fn test_literal_string_parsing() {
+    struct TestCase {
+        input: String,
+        expected: String,
+    }
+
+    let tests = [
+        TestCase {
+            input: String::from(r#"let s = "hello world";"#),
+            expected: String::from(r#"let s = "hello world";"#),
+        },
+        TestCase {
+            input: String::from(r#"{"one": 1, two: 1 + 1}"#),
+            expected: String::from(r#"{"one": 1, two: (1 + 1)}"#),
+        },
+        TestCase {
+            input: String::from("{}"),
+            expected: String::from("{}"),
+        },
+        TestCase {
+            input: String::from("let Event = enum(add, remove, key_press(str));"),
+            expected: String::from("let Event = enum(add, remove, key_press(str));"),
+        },
+        TestCase {
+            input: String::from("let Point = struct { x: int, y };"),
+            expected: String::from("let Point = struct { x, y };"),
+        },
+        TestCase {
+            input: String::from("let p = Point { x: 1, y: a * 2 };"),
+            expected: String::from("let p = Point { x: 1, y: (a * 2) };"),
+        },
+        TestCase {
+            input: String::from("let Runner = interface { run(): int; stop(force: bool) };"),
+            expected: String::from("let Runner = interface { run(): int; stop(force); };"),
+        },
+        TestCase {
+            input: String::from("impl Point { fn area(self): int { self.x * self.y } }"),
+            expected: String::from("impl Point { area: fn(self): int (self.x * self.y) }"),
+        },
+        TestCase {
+            input: String::from(
+                "let apply: fn(int, int): int = fn(a: int, f: fn(int): []int) { f(a) };",
+            ),
+            expected: String::from(
+                "let apply: fn(int, int): int = fn(a: int, f: fn(int): []int) f(a);",
+            ),
+        },
+    ];
+
+    for tt in tests {
+        let l = Lexer::new(tt.input);
+        let mut p = Parser::new(l);
+        let program = p.parse_program().expect("parse_program() return none");
+
+        assert!(!chack_parser_errors(&p));
+        let actual = program.string();
+        assert_eq!(
+            actual, tt.expected,
+            "expected={}, got={}",
+            tt.expected, actual
+        )
+    }
+}
+
+#[test]
+fn test_invalid_assignment_target() {
+    let tests = ["1 = 2", "f() += 1", "(a + b) = c"];
+
+    for input in tests {
+        let l = Lexer::new(input.to_string());
+        let mut p = Parser::new(l);
+        p.parse_program();
+
+        let errors = p.errors();
+        assert!(
+            errors.iter().any(|e| e.contains("invalid assignment")),
+            "expected an invalid assignment error for {}, got={:?}",
+            input,
+            errors
+        );
+    }
+}
+
+/// Programs nested deeper than the passes after parsing can recurse fail to
+/// parse with a single error, however deep they are or however they nest.
+#[test]
+fn test_nesting_limit() {
+    let depth = MAX_NESTING / 2;
+    let within = [
+        format!("{}1{}", "(".repeat(depth), ")".repeat(depth)),
+        format!("1{}", " + 1".repeat(depth)),
+    ];
+    for input in within {
+        let mut p = Parser::new(Lexer::new(input.clone()));
+        p.parse_program();
+        assert!(!chack_parser_errors(&p), "input: {}", input);
+    }
+
+    let depth = 20000;
+    let tests = [
+        format!("len({}1{})", "[".repeat(depth), "]".repeat(depth)),
+        format!("1{}", " + 1".repeat(depth)),
+        format!("{}1{}", "(".repeat(depth), ")".repeat(depth)),
+        format!("{}1", "-".repeat(depth)),
+        format!("{}1{}", "fn() { ".repeat(depth), " }".repeat(depth)),
+        format!("{}1{}", "for (false) { ".repeat(depth), " }".repeat(depth)),
+        format!("let a = [1]; a{}", "[0]".repeat(depth)),
+        format!(
+            "match 1 {{ {}x{} => x }}",
+            "[".repeat(depth),
+            "]".repeat(depth)
+        ),
+        format!("let x: {}int = [];", "[]".repeat(depth)),
+    ];
+    for input in tests {
+        let mut p = Parser::new(Lexer::new(input.clone()));
+        p.parse_program();
+        assert_eq!(p.errors().len(), 1, "{:?}", p.errors());
+        assert!(
+            p.errors()[0].contains(&format!(
+                "nested too deeply -> more than {} levels",
+                MAX_NESTING
+            )),
+            "got={:?}",
+            p.errors()
+        );
+    }
+}
+
+#[test]
+fn test_number_literal_parsing() {
+    let tests = [
+        ("3.25", "3.25"),
+        ("-0.5 * 2", "((-0.5) * 2)"),
+        ("1..3", "1..3"),
+    ];
+
+    for (input, expected) in tests {
+        let l = Lexer::new(input.to_string());
+        let mut p = Parser::new(l);
+        let program = p.parse_program().expect("parse_program() return none");
+
+        assert!(!chack_parser_errors(&p), "input: {}", input);
+        let actual = program.string();
+        assert_eq!(actual, expected, "expected={}, got={}", expected, actual)
+    }
+
+    let input = "9223372036854775808";
+    let mut p = Parser::new(Lexer::new(input.to_string()));
+    p.parse_program();
+    assert!(
+        p.errors()
+            .iter()
+            .any(|e| e.contains("9223372036854775808 is too large for an integer")),
+        "got={:?}",
+        p.errors()
+    );
+}
+
+#[test]
+fn test_import_and_export_parsing() {
+    let tests = [
+        (
+            r#"import "lib/util" as util; util.helper(1)"#,
+            r#"import "lib/util" as util;util.helper(1)"#,
+        ),
+        ("export let x: int = 1;", "export let x: int = 1;"),
+    ];
+
+    for (input, expected) in tests {
+        let l = Lexer::new(input.to_string());
+        let mut p = Parser::new(l);
+        let program = p.parse_program().expect("parse_program() return none");
+
+        assert!(!chack_parser_errors(&p), "input: {}", input);
+        let actual = program.string();
+        assert_eq!(actual, expected, "expected={}, got={}", expected, actual)
+    }
+
+    let tests = [
+        ("fn() { import \"util\" as util; }", "misplaced import"),
+        ("if true { export let x = 1; }", "misplaced export"),
+        ("export fn() {}", "expected next token to be LET"),
+        ("import util;", "expected next token to be STRING"),
+        ("import \"util\";", "expected next token to be AS"),
+    ];
+
+    for (input, expected) in tests {
+        let l = Lexer::new(input.to_string());
+        let mut p = Parser::new(l);
+        p.parse_program();
+
+        let errors = p.errors();
+        assert!(
+            errors.iter().any(|e| e.contains(expected)),
+            "expected {:?} for {}, got={:?}",
+            expected,
+            input,
+            errors
+        );
+    }
+}
+
+#[test]
+fn test_match_expression_parsing() {
+    let input = String::from(
+        "
+        match x {
+            Option.some(v) if v > 1 => v,
+            Option.none => { 0 }
+            [first, _, ..rest] => first,
+            Point { x, y: 0 } => x,
+            -1 => 0,
+            _ => \"other\",
+        }
+        ",
+    );
+
+    let l = Lexer::new(input);
+    let mut p = Parser::new(l);
+    let program = p.parse_program().expect("parse_program() return some");
+
+    assert!(!chack_parser_errors(&p));
+    assert_eq!(
+        program.statements.len(),
+        1,
+        "program.statements does not contain 1 statements. got={}",
+        program.statements.len()
+    );
+
+    let stmt = get_of_type::<ExpressionStatement>(program.statements[0].get_as_any())
+        .expect("program.Statements[0] is ast.ExpressionStatement");
+    let exp = get_of_type::<MatchExpression>(stmt.expression.as_ref().unwrap().get_as_any())
+        .expect("exp is not MatchExpression");
+
+    test_identifier(&Box::new(exp.subject.as_ref().unwrap().as_ref()), "x");
+
+    let expected = [
+        ("Option.some(v)", Some("(v > 1)"), "v"),
+        ("Option.none", None, "0"),
+        ("[first, _, ..rest]", None, "first"),
+        ("Point { x: x, y: 0 }", None, "x"),
+        ("(-1)", None, "0"),
+        ("_", None, "\"other\""),
+    ];
+    assert_eq!(
+        exp.arms.len(),
+        expected.len(),
+        "wrong number of arms. got={}",
+        exp.arms.len()
+    );
+
+    for (arm, (pattern, guard, body)) in exp.arms.iter().zip(expected) {
+        assert_eq!(arm.pattern.string(), pattern);
+        assert_eq!(arm.guard.as_ref().map(|g| g.string()).as_deref(), guard);
+        assert_eq!(arm.body.string(), body);
+    }
+
+    assert!(matches!(exp.arms[0].pattern, Pattern::EnumVariant { .. }));
+    assert!(matches!(exp.arms[2].pattern, Pattern::Array { .. }));
+    assert!(matches!(exp.arms[3].pattern, Pattern::Struct { .. }));
+    assert!(matches!(exp.arms[4].pattern, Pattern::Literal { .. }));
+    assert!(matches!(exp.arms[5].pattern, Pattern::Wildcard(_)));
+}
+
+#[test]
+fn test_match_subject_is_not_struct_expression() {
+    let input = String::from("match p { _ => Point { x: 1 } }");
+
+    let l = Lexer::new(input);
+    let mut p = Parser::new(l);
+    let program = p.parse_program().expect("parse_program() return some");
+
+    assert!(!chack_parser_errors(&p));
+    let stmt = get_of_type::<ExpressionStatement>(program.statements[0].get_as_any())
+        .expect("program.Statements[0] is ast.ExpressionStatement");
+    let exp = get_of_type::<MatchExpression>(stmt.expression.as_ref().unwrap().get_as_any())
+        .expect("exp is not MatchExpression");
+
+    test_identifier(&Box::new(exp.subject.as_ref().unwrap().as_ref()), "p");
+    let body = get_of_type::<ExpressionStatement>(exp.arms[0].body.statements[0].get_as_any())
+        .expect("arm body is ast.ExpressionStatement");
+    assert!(
+        get_of_type::<StructExpression>(body.expression.as_ref().unwrap().get_as_any()).is_some(),
+        "arm body is not StructExpression"
+    );
+}
+
 //utils
 fn test_infix_expression(
     exp: &'static Box<dyn Expression>,
@@ -0,0 +1,199 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use prolang::{
+    cache::{self, CacheError},
+    Bytecode, Capabilities, Environment, Limits, Loader, Project,
+};
+
+use super::{compile, emit_bytecode, exec, exec_bytecode, exec_vm, Emit, EngineKind};
+
+const CACHE_EXTENSION: &str = "prc";
+
+/// Runs a source file, or a `.prc` file, which always runs on the vm. Without
+/// a file, runs the entry point of the project in the working directory.
+///
+/// A `.prc` file is only trusted while the hash it records matches the source
+/// next to it; a stale or unreadable cache is rebuilt from that source.
+///
+/// Fails if the program does not compile or stops with an uncaught error.
+pub fn run_file(
+    file_path: Option<String>,
+    engine: EngineKind,
+    locked: bool,
+    limits: Limits,
+    caps: &Capabilities,
+) -> ExitCode {
+    let Some(path) = program_path(file_path) else {
+        return ExitCode::FAILURE;
+    };
+    if path.extension().is_some_and(|ext| ext == CACHE_EXTENSION) {
+        return exit_code(run_cache(&path, locked, limits, caps));
+    }
+
+    let file_content = read_file(&path);
+    let Some(loader) = loader(&path, locked) else {
+        return ExitCode::FAILURE;
+    };
+    let output = match engine {
+        EngineKind::Interpreter => exec(&file_content, loader, &Environment::new(), limits, caps),
+        EngineKind::Vm => {
+            match load_cache(&path.with_extension(CACHE_EXTENSION), &file_content, caps) {
+                Some(bytecode) => exec_bytecode(bytecode, limits, caps),
+                None => exec_vm(&file_content, loader, limits, caps),
+            }
+        }
+    };
+    exit_code(output)
+}
+
+pub fn build_file(file_path: Option<String>, emit: Emit, locked: bool) -> ExitCode {
+    let Some(path) = program_path(file_path) else {
+        return ExitCode::FAILURE;
+    };
+    let file_content = read_file(&path);
+
+    let built = match emit {
+        Emit::Prc => {
+            write_cache(&path, &file_content, locked, &Capabilities::all()).map(|_| String::new())
+        }
+        Emit::Bytecode => {
+            loader(&path, locked).and_then(|loader| emit_bytecode(&file_content, loader))
+        }
+    };
+    if let Some(out) = &built {
+        print!("{}", out);
+    }
+    exit_code(built)
+}
+
+/// Creates a project in the directory `name`.
+pub fn new_project(name: String) -> ExitCode {
+    match Project::create(Path::new(&name)) {
+        Ok(project) => {
+            println!(
+                "created project `{}` in {}",
+                project.name(),
+                project.root().display()
+            );
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            println!("could not create the project: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// The file given, or else the entry point of the project in the working
+/// directory.
+fn program_path(file_path: Option<String>) -> Option<PathBuf> {
+    if let Some(file_path) = file_path {
+        return Some(PathBuf::from(file_path));
+    }
+
+    let dir = env::current_dir().unwrap_or_default();
+    match Project::find(&dir) {
+        Ok(Some(project)) => Some(project.entry()),
+        Ok(None) => {
+            println!(
+                "no file given, and no prolang.toml in {} or above it",
+                dir.display()
+            );
+            None
+        }
+        Err(err) => {
+            println!("{}", err);
+            None
+        }
+    }
+}
+
+/// Loads the imports of the program at `path` through the sources and
+/// dependencies of the project it belongs to, which are checked against
+/// `prolang.lock` if `locked`, or else update it.
+fn loader(path: &Path, locked: bool) -> Option<Loader> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let loader = Project::find(dir).and_then(|project| match project {
+        Some(project) => {
+            let graph = project.resolve()?;
+            graph.sync_lock(locked)?;
+            Ok(graph.loader(path))
+        }
+        None => {
+            let mut loader = Loader::new();
+            loader.set_main(path);
+            Ok(loader)
+        }
+    });
+    match loader {
+        Ok(loader) => Some(loader),
+        Err(err) => {
+            println!("{}", err);
+            None
+        }
+    }
+}
+
+fn run_cache(path: &Path, locked: bool, limits: Limits, caps: &Capabilities) -> Option<String> {
+    let source_path = path.with_extension("pr");
+    let source = fs::read_to_string(&source_path).ok();
+    if let Some(source) = &source {
+        let bytecode = load_cache(path, source, caps)
+            .or_else(|| write_cache(&source_path, source, locked, caps));
+        return exec_bytecode(bytecode?, limits, caps);
+    }
+
+    match read_cache(path) {
+        Ok(module) => exec_bytecode(module.bytecode, limits, caps),
+        Err(err) => {
+            println!("invalid bytecode file {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+fn exit_code(output: Option<String>) -> ExitCode {
+    match output {
+        Some(_) => ExitCode::SUCCESS,
+        None => ExitCode::FAILURE,
+    }
+}
+
+/// Loads the cache at `path` if it was built from `source` and the modules
+/// it imported are unchanged, and `caps` grant reading them.
+fn load_cache(path: &Path, source: &str, caps: &Capabilities) -> Option<Bytecode> {
+    let module = read_cache(path).ok()?;
+    let fresh = module.source_hash == cache::source_hash(source)
+        && module.bytecode.imports.iter().all(|(import, hash)| {
+            caps.check_read("import", &import.to_string_lossy()).is_ok()
+                && fs::read_to_string(import).is_ok_and(|text| cache::source_hash(&text) == *hash)
+        });
+    fresh.then_some(module.bytecode)
+}
+
+fn read_cache(path: &Path) -> Result<cache::CachedModule, CacheError> {
+    let bytes = fs::read(path).map_err(|err| CacheError::new(err.to_string()))?;
+    cache::decode(&bytes)
+}
+
+/// Compiles `source`, read from the file at `path`, with imports reading
+/// the files `caps` grant, and writes it to the cache next to it, returning
+/// the bytecode unless compilation failed.
+fn write_cache(path: &Path, source: &str, locked: bool, caps: &Capabilities) -> Option<Bytecode> {
+    let bytecode = compile(source, loader(path, locked)?, caps)?;
+    let bytes = cache::encode(&bytecode, cache::source_hash(source));
+    let path = path.with_extension(CACHE_EXTENSION);
+    if let Err(err) = fs::write(&path, bytes) {
+        println!("could not write {}: {}", path.display(), err);
+    }
+
+    Some(bytecode)
+}
+
+fn read_file(path: &Path) -> String {
+    fs::read_to_string(path).expect("Should have been able to read content of file")
+}
@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use clap::ValueEnum;
+
+use prolang::{
+    ast::Program, Bytecode, Capabilities, Checker, Compiler, Disassembler, Env, Evaluator, Lexer,
+    Limits, Loader, Parser, RuntimeError, Value, Vm,
+};
+
+pub mod file_runner;
+pub mod repl;
+#[cfg(test)]
+mod test;
+
+fn print_parse_errors(errors: &Vec<String>) {
+    println!("Woops! We ran into some issue here!");
+    println!(" parser errors:");
+    for err in errors {
+        println!("\t{}", err)
+    }
+}
+
+fn print_check_warnings(warnings: &Vec<String>) {
+    if warnings.is_empty() {
+        return;
+    }
+    println!(" warnings:");
+    for warning in warnings {
+        println!("\t{}", warning)
+    }
+}
+
+/// The backend a program is run with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum EngineKind {
+    /// Walks the syntax tree directly.
+    #[default]
+    Interpreter,
+    /// Compiles to bytecode and runs it on the stack vm.
+    Vm,
+}
+
+/// What `prolang build` outputs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum Emit {
+    /// A `.prc` bytecode file next to the source, which `run` loads instead
+    /// of compiling the source again.
+    #[default]
+    Prc,
+    /// A human-readable disassembly of the compiled bytecode.
+    Bytecode,
+}
+
+/// Parses and checks a program, naming `file` in the errors and warnings.
+fn parse(input: &str, file: &str) -> Option<Program> {
+    let l = Lexer::new(input.trim_end().to_string());
+    let mut p = Parser::new(l);
+    p.set_file(file);
+    let program = p.parse_program().unwrap();
+    if !p.errors().is_empty() {
+        print_parse_errors(p.errors());
+        return None;
+    }
+
+    let mut checker = Checker::new();
+    checker.set_file(file);
+    checker.check_program(&program);
+    print_check_warnings(checker.warnings());
+
+    Some(program)
+}
+
+/// Renders the result of a program, or reports the error that stopped it
+/// and returns `None`.
+fn output(result: Result<Value, RuntimeError>) -> Option<String> {
+    match result {
+        Ok(Value::Null) => Some("".to_string()),
+        Ok(value) => Some(value.to_string()),
+        Err(err) => {
+            eprintln!("runtime error: {}", err.report());
+            None
+        }
+    }
+}
+
+/// Runs a program, loading its imports with `loader`, returning its output,
+/// or `None` if it did not parse or failed with an uncaught error.
+fn exec(
+    input: &str,
+    loader: Loader,
+    env: &Env,
+    limits: Limits,
+    caps: &Capabilities,
+) -> Option<String> {
+    let program = parse(input, &loader.main_file())?;
+    let mut evaluator = Evaluator::new();
+    evaluator.set_loader(loader);
+    evaluator.set_limits(limits);
+    evaluator.set_capabilities(caps.clone());
+    output(evaluator.eval_program(&program, env))
+}
+
+/// Compiles a program, whose imports may read the files `caps` grant.
+fn compile(input: &str, loader: Loader, caps: &Capabilities) -> Option<Bytecode> {
+    let program = parse(input, &loader.main_file())?;
+
+    let mut compiler = Compiler::new();
+    compiler.set_loader(loader);
+    compiler.set_capabilities(caps.clone());
+    match compiler.compile(&program) {
+        Ok(bytecode) => Some(bytecode),
+        Err(err) => {
+            eprintln!("compile error: {}", err);
+            None
+        }
+    }
+}
+
+fn exec_bytecode(bytecode: Bytecode, limits: Limits, caps: &Capabilities) -> Option<String> {
+    let mut vm = Vm::new(bytecode);
+    vm.set_limits(limits);
+    vm.set_capabilities(caps.clone());
+    output(vm.run())
+}
+
+fn exec_vm(input: &str, loader: Loader, limits: Limits, caps: &Capabilities) -> Option<String> {
+    exec_bytecode(compile(input, loader, caps)?, limits, caps)
+}
+
+fn emit_bytecode(input: &str, loader: Loader) -> Option<String> {
+    compile(input, loader, &Capabilities::all())
+        .map(|bytecode| Disassembler::new(&bytecode).disassemble())
+}
+
+/// Splits a number from the unit after it, as in `500ms`.
+fn split_unit(input: &str) -> Result<(u64, &str), String> {
+    let input = input.trim();
+    let end = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(end);
+    let number = number
+        .parse()
+        .map_err(|_| format!("expected a number, got `{}`", input))?;
+    Ok((number, unit))
+}
+
+/// Parses a duration in `ms`, `s` or `m`, taking a bare number as seconds.
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let (number, unit) = split_unit(input)?;
+    match unit {
+        "ms" => Ok(Duration::from_millis(number)),
+        "" | "s" => Ok(Duration::from_secs(number)),
+        "m" => number
+            .checked_mul(60)
+            .map(Duration::from_secs)
+            .ok_or_else(|| format!("duration `{}` is too long", input)),
+        _ => Err(format!("unknown time unit `{}`, expected ms, s or m", unit)),
+    }
+}
+
+/// Parses a size in bytes, with an optional `K`, `M` or `G` suffix for
+/// kibibytes, mebibytes or gibibytes.
+pub fn parse_size(input: &str) -> Result<usize, String> {
+    let (number, unit) = split_unit(input)?;
+    let scale: u64 = match unit.to_ascii_uppercase().trim_end_matches('B') {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        _ => return Err(format!("unknown size unit `{}`, expected K, M or G", unit)),
+    };
+    number
+        .checked_mul(scale)
+        .and_then(|size| usize::try_from(size).ok())
+        .ok_or_else(|| format!("size `{}` is too large", input))
+}
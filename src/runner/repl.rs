@@ -1,6 +1,8 @@
-use std::env;
+use std::{env, io};
 
-use crate::core::utils::utils::log_interactive;
+use prolang::{Capabilities, Environment, Limits, Loader};
+
+use crate::utils::utils::log_interactive;
 
 use super::exec;
 
@@ -11,13 +13,22 @@ pub fn start() {
     println!("Hello {}! This is the Prolang programing language!", usr);
     println!("Feel free to type in commands");
 
+    let env = Environment::new();
+
     loop {
         let mut input = String::new();
         log_interactive(PROMPT);
 
         match io::stdin().read_line(&mut input) {
             Ok(_) => {
-                let output = exec(&input);
+                let output = exec(
+                    &input,
+                    Loader::new(),
+                    &env,
+                    Limits::default(),
+                    &Capabilities::all(),
+                )
+                .unwrap_or_default();
                 log_interactive(&output);
             }
             Err(_) => todo!(),
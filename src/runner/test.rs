@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use super::{parse_duration, parse_size};
+
+#[test]
+fn test_parse_duration() {
+    let tests = vec![
+        ("500ms", Ok(Duration::from_millis(500))),
+        ("2s", Ok(Duration::from_secs(2))),
+        ("3", Ok(Duration::from_secs(3))),
+        ("1m", Ok(Duration::from_secs(60))),
+        (
+            "2h",
+            Err("unknown time unit `h`, expected ms, s or m".to_string()),
+        ),
+        ("ms", Err("expected a number, got `ms`".to_string())),
+        (
+            "307445734561825861m",
+            Err("duration `307445734561825861m` is too long".to_string()),
+        ),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(parse_duration(input), expected, "{}", input);
+    }
+}
+
+#[test]
+fn test_parse_size() {
+    let tests = vec![
+        ("100", Ok(100)),
+        ("4K", Ok(4096)),
+        ("64M", Ok(64 << 20)),
+        ("1gb", Ok(1 << 30)),
+        (
+            "2T",
+            Err("unknown size unit `T`, expected K, M or G".to_string()),
+        ),
+        ("", Err("expected a number, got ``".to_string())),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(parse_size(input), expected, "{}", input);
+    }
+}
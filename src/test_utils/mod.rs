@@ -0,0 +1,82 @@
+//! Helpers shared by the tests of the stages.
+
+use std::path::Path;
+
+use crate::{
+    compiler::compiler::Compiler,
+    evaluator::{
+        capabilities::{Capabilities, Paths},
+        environment::Environment,
+        evaluator::Evaluator,
+        value::{RuntimeError, Value},
+    },
+    lexer::lexer::Lexer,
+    parser::{ast::Program, parser::Parser},
+    vm::vm::Vm,
+};
+
+pub(crate) fn parse(input: &str) -> Program {
+    let l = Lexer::new(input.to_string());
+    let mut p = Parser::new(l);
+    let program = p.parse_program().expect("parse_program() return some");
+    assert!(
+        p.errors().is_empty(),
+        "parser has errors for {}: {:?}",
+        input,
+        p.errors()
+    );
+
+    program
+}
+
+/// Runs a program on the vm.
+pub(crate) fn run(program: &Program) -> Result<Value, RuntimeError> {
+    let bytecode = Compiler::new()
+        .compile(program)
+        .unwrap_or_else(|err| panic!("compile error: {}", err));
+    Vm::new(bytecode).run()
+}
+
+/// Runs a program on the tree-walking interpreter.
+pub(crate) fn interpret(program: &Program) -> Result<Value, RuntimeError> {
+    Evaluator::new().eval_program(program, &Environment::new())
+}
+
+/// Grants reading any file, which importing modules needs.
+pub(crate) fn reading() -> Capabilities {
+    Capabilities {
+        read: Paths::All,
+        ..Capabilities::default()
+    }
+}
+
+/// Runs a program with both engines, as the file at `path` if given, giving
+/// what each printed it evaluated to or the message of the error it raised.
+/// Imports may read any file if it is given.
+pub(crate) fn run_both(
+    program: &Program,
+    path: Option<&Path>,
+) -> [(&'static str, Result<String, String>); 2] {
+    let mut evaluator = Evaluator::new();
+    let mut compiler = Compiler::new();
+    if let Some(path) = path {
+        evaluator.set_path(path);
+        evaluator.set_capabilities(reading());
+        compiler.set_path(path);
+        compiler.set_capabilities(reading());
+    }
+
+    let interpreted = evaluator
+        .eval_program(program, &Environment::new())
+        .map(|value| value.to_string())
+        .map_err(|err| err.message);
+    let compiled = match compiler.compile(program) {
+        Ok(bytecode) => Vm::new(bytecode)
+            .run()
+            .map(|value| value.to_string())
+            .map_err(|err| err.message),
+        Err(err) => Err(err.message),
+    };
+
+    [("interpreter", interpreted), ("vm", compiled)]
+}
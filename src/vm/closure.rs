@@ -0,0 +1,44 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    compiler::bytecode::CompiledFunction,
+    evaluator::value::Value,
+    gc::gc::{self, HeapId, Trace},
+};
+
+pub struct Closure {
+    pub function: Rc<CompiledFunction>,
+    pub upvalues: Vec<Rc<RefCell<Upvalue>>>,
+}
+
+/// A variable captured by a closure. It points into the stack while the
+/// frame declaring it is running, and owns the value once that scope ends.
+pub enum Upvalue {
+    Open(usize),
+    Closed(Value),
+}
+
+impl Trace for Closure {
+    fn trace(&self, visit: &mut dyn FnMut(HeapId)) {
+        self.upvalues
+            .iter()
+            .for_each(|upvalue| visit(gc::id(upvalue)));
+    }
+}
+
+impl Trace for RefCell<Upvalue> {
+    fn trace(&self, visit: &mut dyn FnMut(HeapId)) {
+        if let Ok(upvalue) = self.try_borrow() {
+            if let Upvalue::Closed(value) = &*upvalue {
+                value.trace(visit);
+            }
+        }
+    }
+
+    fn clear(&self) {
+        let value = self
+            .try_borrow_mut()
+            .map(|mut upvalue| std::mem::replace(&mut *upvalue, Upvalue::Closed(Value::Null)));
+        drop(value);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod closure;
+#[allow(clippy::module_inception)]
+pub mod vm;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,683 @@
+use std::{fs, time::Duration};
+
+use crate::{
+    compiler::compiler::Compiler,
+    evaluator::{
+        capabilities::{Capabilities, Paths},
+        environment::Environment,
+        evaluator::Evaluator,
+        limits::Limits,
+        value::ErrorKind,
+    },
+    test_utils::{interpret, parse, run},
+    vm::vm::Vm,
+};
+
+#[test]
+fn test_vm_integer_and_boolean_expression() {
+    let tests = [
+        ("5", "5"),
+        ("-10", "-10"),
+        ("5 + 5 + 5 + 5 - 10", "10"),
+        ("2 * (5 + 10) / 3", "10"),
+        ("1 < 2", "true"),
+        ("1 > 2 == false", "true"),
+        ("!true", "false"),
+        ("!!5", "true"),
+        ("\"foo\" + \"bar\"", "foobar"),
+        ("[1, 2] == [1, 2]", "true"),
+        ("1 + 10 % 4 * 2", "5"),
+        ("\"a\" <= \"b\"", "true"),
+        ("1 < 2 && 2 < 3 || false", "true"),
+        ("false && missing", "false"),
+        ("true || 1 / 0", "true"),
+        ("[] && 0", "true"),
+        ("1; 2; 3", "3"),
+        ("0.1 + 0.2", "0.30000000000000004"),
+        ("-2.5 * 2", "-5.0"),
+        ("7 / 2.0 == 3.5", "true"),
+        ("", "null"),
+    ];
+
+    for (input, expected) in tests {
+        test_vm_output(input, expected);
+    }
+}
+
+#[test]
+fn test_vm_functions_and_closures() {
+    let tests = [
+        ("let identity = fn(x) { x; }; identity(5);", "5"),
+        ("let double = fn(x) { return x * 2; 0 }; double(5);", "10"),
+        ("fn(x) { x; }(5)", "5"),
+        ("fn() { }()", "null"),
+        (
+            "let fib = fn(n) { if (n < 2) { return n; } fib(n - 1) + fib(n - 2) }; fib(15)",
+            "610",
+        ),
+        (
+            "let adder = fn(a) { fn(b) { fn(c) { a + b + c } } }; adder(1)(2)(3)",
+            "6",
+        ),
+        (
+            "let counter = fn() { let n = 0; fn() { n += 1 } }; let a = counter(); let b = counter(); a(); a(); [a(), b()]",
+            "[3, 1]",
+        ),
+        (
+            "let pair = fn() { let n = 0; [fn() { n += 1 }, fn() { n }] }; let p = pair(); p[0](); p[0](); p[1]()",
+            "2",
+        ),
+        (
+            "let fns = []; for (i in 0..3) { fns = push(fns, fn() { i }); } [fns[0](), fns[1](), fns[2]()]",
+            "[0, 1, 2]",
+        ),
+        (
+            "let f = fn() { let x = 1; if true { let y = 2; return fn() { x + y }; } }; f()()",
+            "3",
+        ),
+        (
+            "let outer = fn() { let x = 1; let inner = fn() { x += 1; x }; inner(); x }; outer()",
+            "2",
+        ),
+        (
+            "let even = fn(n) { if n == 0 { true } else { odd(n - 1) } }; let odd = fn(n) { if n == 0 { false } else { even(n - 1) } }; even(10)",
+            "true",
+        ),
+        ("len(\"abc\") + len([1])", "4"),
+        ("let greet = fn(name: str): str { \"hi \" + name }; greet(\"bo\")", "hi bo"),
+    ];
+
+    for (input, expected) in tests {
+        test_vm_output(input, expected);
+    }
+}
+
+#[test]
+fn test_vm_data_structures_and_loops() {
+    let tests = [
+        ("[1, 2 * 2, 3 + 3][1]", "4"),
+        (
+            "let h = {\"name\": \"Jimmy\", 72: true}; [h[\"name\"], h[72]]",
+            "[\"Jimmy\", true]",
+        ),
+        ("{\"name\": \"Jimmy\"}[\"missing\"]", "null"),
+        (
+            "let Point = struct { x, y }; Point { y: 2, x: 1 }",
+            "Point { x: 1, y: 2 }",
+        ),
+        (
+            "let Event = enum(add, key_press(str)); [Event.add, Event.key_press(\"k\")]",
+            "[Event.add, Event.key_press(\"k\")]",
+        ),
+        ("let a = [1, 2, 3]; a[1] = 5; a[2] *= 10; a", "[1, 5, 30]"),
+        (
+            "let P = struct { x }; let p = P { x: 1 }; p.x += 41; p.x",
+            "42",
+        ),
+        ("let x = 0; for x < 5 { x += 1; } x", "5"),
+        ("let s = 0; for i in 2..5 { s += i; } s", "9"),
+        ("let s = \"\"; for c in \"abc\" { s = c + s; } s", "cba"),
+        (
+            "let a = [1, 2]; let n = 0; for x in a { a = push(a, x); n += 1; } [n, len(a)]",
+            "[2, 4]",
+        ),
+        ("let f = fn() { for i in 0..10 { if i == 3 { return i; } } }; f()", "3"),
+        ("let x = 1; if (true) { let x = 2; x = 3; } x", "1"),
+        (
+            "let grade = fn(n) { if n >= 90 { \"a\" } else if n >= 80 { \"b\" } else { \"f\" } }; [grade(95), grade(85), grade(10)]",
+            "[\"a\", \"b\", \"f\"]",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        test_vm_output(input, expected);
+    }
+}
+
+#[test]
+fn test_vm_match_interfaces_and_methods() {
+    let prelude = "
+        let Option = enum(some(int), none);
+        let Point = struct { x, y };
+        let Shape = interface { area(): int; name(): str };
+        let Rect = struct { w, h };
+        impl Rect {
+            fn area(self) { self.w * self.h }
+            fn name(self) { \"rect\" }
+            fn scale(self, k) { Rect { w: self.w * k, h: self.h * k } }
+        }
+    ";
+    let tests = [
+        ("match 9 { 1 => \"one\", n => n * 2 }", "18"),
+        (
+            "match Option.some(5) { Option.some(v) if v > 10 => \"big\", Option.some(v) => \"small\", _ => \"none\" }",
+            "small",
+        ),
+        ("match Option.none { Option.some(v) => v, Option.none => 0 }", "0"),
+        ("match [1, 2, 3] { [] => 0, [a] => a, [a, b, ..rest] => rest }", "[3]"),
+        ("match [1, 2] { [1, x] => x, _ => 0 }", "2"),
+        ("match (Point { x: 0, y: 7 }) { Point { x: 0, y } => y, _ => 0 }", "7"),
+        (
+            "let f = fn(o) { match o { Option.some(v) => { return v; }, _ => 0 }; 99 }; f(Option.some(3))",
+            "3",
+        ),
+        (
+            "let fs = match Option.some(4) { Option.some(v) => fn() { v }, _ => fn() { 0 } }; fs()",
+            "4",
+        ),
+        ("Rect { w: 2, h: 3 }.scale(2).area()", "24"),
+        ("let r = Rect { w: 2, h: 3 }; let area = r.area; area()", "6"),
+        ("let s: Shape = Rect { w: 1, h: 4 }; s.area()", "4"),
+    ];
+
+    for (input, expected) in tests {
+        test_vm_output(&format!("{}{}", prelude, input), expected);
+    }
+}
+
+#[test]
+fn test_vm_errors() {
+    let tests = [
+        ("5 + true;", "type mismatch: INT + BOOL"),
+        ("-true", "unknown operator: -BOOL"),
+        ("foobar", "identifier not found: foobar"),
+        ("10 / 0", "division by zero"),
+        ("1.5 / 0", "division by zero"),
+        (
+            "let big = 9007199254740992.0 * 9007199254740992.0; let huge = big * big * big * big * big; huge * huge",
+            "float overflow",
+        ),
+        ("{[1]: 2}", "unusable as hash key: ARRAY"),
+        ("match 3 { 1 => 1 }", "no match arm matched value: 3"),
+        (
+            "let Point = struct { x, y }; Point { x: 1 }",
+            "missing field `y` in initializer of struct Point",
+        ),
+        (
+            "fn(x) { x }(1, 2)",
+            "wrong number of arguments. got=2, want=1",
+        ),
+        ("len(1)", "argument to `len` not supported, got INT"),
+        ("5()", "not a function: INT"),
+        ("y = 1", "cannot assign to undeclared variable `y`"),
+        (
+            "fn() { z += 1 }()",
+            "cannot assign to undeclared variable `z`",
+        ),
+        ("for x in 5 { x }", "INT is not iterable"),
+        (
+            "let Runner = interface { run() }; let r: Runner = 5;",
+            "INT does not satisfy interface Runner: missing method(s) `run`",
+        ),
+        (
+            "let P = struct { x }; impl P { fn m() { 1 } }",
+            "method `m` of P must take the receiver as its first parameter",
+        ),
+        ("throw \"boom\";", "boom"),
+        (
+            "let f = fn() {\n  [1][2]\n}; f()",
+            "index out of bounds: the length is 1 but the index is 2",
+        ),
+        (
+            "let Runner = interface { run() }; let f = fn(r: Runner) { r }; f(1)",
+            "INT does not satisfy interface Runner: missing method(s) `run`",
+        ),
+        ("try { 1 / 0 } catch (e) { throw e; }", "division by zero"),
+        (
+            "let f = fn() { 1 / 0 };\nlet apply = fn(g) { g() };\napply(f)",
+            "division by zero",
+        ),
+        (
+            "let P = struct { x }; impl P { fn m(self) { fn() { self.y }() } }; P { x: 1 }.m()",
+            "struct P has no field or method `y`",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let program = parse(input);
+        let interpreted = interpret(&program);
+        match run(&program) {
+            Err(err) => {
+                assert_eq!(
+                    err.message, expected,
+                    "wrong error message. expected={}, got={}",
+                    expected, err.message
+                );
+                assert_eq!(
+                    interpreted.map_err(|e| (e.kind, e.message, e.position, e.trace)),
+                    Err((err.kind, err.message, err.position, err.trace)),
+                    "vm and interpreter disagree for {}",
+                    input
+                );
+            }
+            Ok(value) => panic!("no error returned for {}. got={}", input, value),
+        }
+    }
+}
+
+#[test]
+fn test_vm_try_catch() {
+    let tests = [
+        ("try { 1 / 0 } catch (e) { e.kind }", "DivisionByZero"),
+        ("try { 1 / 2 } catch { 0 }", "0"),
+        ("try { throw \"boom\"; } catch (e) { e }", "Error: boom"),
+        ("try {\n  1 / 0\n} catch (e) { [e.line, e.column] }", "[2, 5]"),
+        (
+            "let f = fn() { 1 / 0 }; try { f() } catch (e) { [e.line, e.column] }",
+            "[1, 18]",
+        ),
+        (
+            "try { try { 1 / 0 } catch (e) { throw e; } } catch (e) { [e.kind, e.column] }",
+            "[\"DivisionByZero\", 15]",
+        ),
+        // unwinding drops the frames and temporaries of the failed calls
+        (
+            "let deep = fn(n) { if n == 0 { missing } else { 1 + deep(n - 1) } }; [1, try { deep(5) } catch { 2 }, 3]",
+            "[1, 2, 3]",
+        ),
+        (
+            "let f = fn() { let x = 1; let g = try { fn() { x } } catch { 0 }; g() }; f()",
+            "1",
+        ),
+        (
+            "let f = fn() { let n = 0; let inc = fn() { n += 1 }; try { inc(); 1 / 0 } catch { inc() } }; f()",
+            "2",
+        ),
+        // a `return` leaves the try block, which must no longer catch
+        (
+            "let f = fn() { try { return 1; } catch { 2 } }; try { f(); 1 / 0 } catch (e) { e.kind }",
+            "DivisionByZero",
+        ),
+        (
+            "let results = []; for (d in [1, 0, 2]) { results = push(results, try { 4 / d } catch { -1 }) }; results",
+            "[4, -1, 2]",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        test_vm_output(input, expected);
+    }
+}
+
+#[test]
+fn test_vm_tail_calls() {
+    let tests = [
+        // the last expression of an if/else block
+        (
+            "let count = fn(n, acc) { if n == 0 { acc } else { count(n - 1, acc + 1) } }; count(1000000, 0)",
+            "1000000",
+        ),
+        // mutual recursion, through `return` and a last expression
+        (
+            "let even = fn(n) { if n == 0 { return true; } odd(n - 1) }; let odd = fn(n) { if n == 0 { return false; } return even(n - 1); }; even(10001)",
+            "false",
+        ),
+        // else-if branches, and closures replacing each other's frames
+        (
+            "let loop = fn(n) { let down = fn(m) { m - 1 }; if n == 0 { 0 } else if n % 2 == 0 { loop(down(n)) } else { loop(n - 1) } }; loop(10000)",
+            "0",
+        ),
+        (
+            "let Counter = struct { n }; impl Counter { fn run(self, i) { if i == 0 { self.n } else { self.n += 1; self.run(i - 1) } } }; Counter { n: 0 }.run(10000)",
+            "10000",
+        ),
+        // a `return` from a `for` loop leaves the loop as well
+        (
+            "let find = fn(n) { for (x in [1]) { if n == 0 { return x; } return find(n - 1); } }; find(10000)",
+            "1",
+        ),
+        // calls to builtins complete in place of the frame
+        (
+            "let build = fn(n, acc) { if n == 0 { len(acc) } else { build(n - 1, push(acc, n)) } }; build(1000, [])",
+            "1000",
+        ),
+        // a call in a `try` block is not in tail position, so its errors are caught
+        (
+            "let f = fn() { 1 / 0 }; let g = fn() { try { return f(); } catch (e) { return e.kind; } }; g()",
+            "DivisionByZero",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        test_vm_output(input, expected);
+    }
+}
+
+#[test]
+fn test_vm_stack_overflow() {
+    let limits = Limits {
+        max_depth: 100,
+        ..Limits::default()
+    };
+    let tests = [
+        (
+            "let f = fn(n) { 1 + f(n + 1) };\nf(0)",
+            Err("StackOverflow: maximum call depth of 100 exceeded\n    at main.pr:1:22\n    at f × 99 (main.pr:1:22)\n    at f (main.pr:2:2)"),
+        ),
+        (
+            "let a = fn(n) { 1 + b(n) }; let b = fn(n) { 1 + a(n) }; try { a(0) } catch (e) { [e.kind, len(e.trace)] }",
+            Ok("[\"StackOverflow\", 100]"),
+        ),
+        // the calls unwound by a caught overflow can be made again
+        (
+            "let g = fn(n) { if n == 0 { 0 } else { 1 + g(n - 1) } }; [try { g(100) } catch (e) { e.kind }, g(99)]",
+            Ok("[\"StackOverflow\", 99]"),
+        ),
+        ("let t = fn(n) { if n == 0 { 0 } else { t(n - 1) } }; t(1000)", Ok("0")),
+    ];
+
+    for (input, expected) in tests {
+        let program = parse(input);
+        let mut evaluator = Evaluator::new();
+        evaluator.set_limits(limits);
+        let interpreted = evaluator.eval_program(&program, &Environment::new());
+        let bytecode = Compiler::new()
+            .compile(&program)
+            .unwrap_or_else(|err| panic!("compile error: {}", err));
+        let mut vm = Vm::new(bytecode);
+        vm.set_limits(limits);
+        let compiled = vm.run();
+
+        match expected {
+            Ok(expected) => {
+                let value =
+                    compiled.unwrap_or_else(|err| panic!("runtime error for {}: {}", input, err));
+                assert_eq!(value.to_string(), expected, "wrong result for {}", input);
+                assert_eq!(
+                    interpreted.map(|value| value.to_string()),
+                    Ok(value.to_string()),
+                    "vm and interpreter disagree for {}",
+                    input
+                );
+            }
+            Err(expected) => {
+                let err = compiled.expect_err("the vm overflows");
+                assert_eq!(err.kind, ErrorKind::StackOverflow);
+                assert_eq!(err.report(), expected, "wrong report for {}", input);
+                assert_eq!(
+                    interpreted.map_err(|e| e.report()),
+                    Err(err.report()),
+                    "vm and interpreter disagree for {}",
+                    input
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_vm_budgets() {
+    let steps = Limits {
+        max_steps: Some(10_000),
+        ..Limits::default()
+    };
+    let timeout = Limits {
+        timeout: Some(Duration::from_millis(50)),
+        ..Limits::default()
+    };
+    let memory = Limits {
+        max_memory: Some(1 << 20),
+        ..Limits::default()
+    };
+    // too long to have a deadline
+    let forever = Limits {
+        timeout: Some(Duration::MAX),
+        ..Limits::default()
+    };
+    let tests = [
+        ("for { }", steps, Err(ErrorKind::StepLimit)),
+        (
+            "let f = fn() { f() }; f()",
+            steps,
+            Err(ErrorKind::StepLimit),
+        ),
+        ("let n = 0; for n < 100 { n = n + 1; }; n", steps, Ok("100")),
+        ("for { }", timeout, Err(ErrorKind::Timeout)),
+        (
+            "let n = 0; for n < 100 { n = n + 1; }; n",
+            forever,
+            Ok("100"),
+        ),
+        (
+            "let s = \"ab\"; for { s = s + s; }",
+            memory,
+            Err(ErrorKind::MemoryLimit),
+        ),
+        // garbage does not count against the limit
+        (
+            "let n = 0; for n < 1000 { let s = [n, n, n, n, n, n, n, n]; n = n + 1; }; n",
+            memory,
+            Ok("1000"),
+        ),
+        // running out of a budget cannot be caught
+        (
+            "try { for { } } catch (e) { 0 }",
+            steps,
+            Err(ErrorKind::StepLimit),
+        ),
+        (
+            "let f = fn() { try { for { } } catch (e) { 0 } }; try { f() } catch (e) { 0 }",
+            timeout,
+            Err(ErrorKind::Timeout),
+        ),
+    ];
+
+    for (input, limits, expected) in tests {
+        let program = parse(input);
+        let mut evaluator = Evaluator::new();
+        evaluator.set_limits(limits);
+        let interpreted = evaluator.eval_program(&program, &Environment::new());
+        let bytecode = Compiler::new()
+            .compile(&program)
+            .unwrap_or_else(|err| panic!("compile error: {}", err));
+        let mut vm = Vm::new(bytecode);
+        vm.set_limits(limits);
+        let compiled = vm.run();
+
+        for (engine, result) in [("interpreter", interpreted), ("vm", compiled)] {
+            assert_eq!(
+                result
+                    .map(|value| value.to_string())
+                    .map_err(|err| err.kind),
+                expected.map(str::to_string),
+                "wrong result from the {} for {}",
+                engine,
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn test_vm_capabilities() {
+    let dir = std::env::temp_dir().join(format!("prolang-capabilities-{}", std::process::id()));
+    let allowed = dir.join("allowed");
+    fs::create_dir_all(&allowed).unwrap();
+    fs::write(allowed.join("in.txt"), "hello").unwrap();
+    fs::write(dir.join("secret.txt"), "secret").unwrap();
+    let granted = Capabilities {
+        read: Paths::Under(vec![allowed.clone()]),
+        write: Paths::Under(vec![allowed.clone()]),
+        env: true,
+        clock: true,
+        random: true,
+        ..Capabilities::default()
+    };
+    let path = |name: &str| dir.join(name).display().to_string();
+
+    let tests = [
+        (
+            "print(1)".to_string(),
+            Capabilities::default(),
+            Err("`print` is not allowed"),
+        ),
+        (
+            "clock()".to_string(),
+            Capabilities::default(),
+            Err("`clock` is not allowed"),
+        ),
+        (
+            "clock() > 0".to_string(),
+            granted.clone(),
+            Ok("true".to_string()),
+        ),
+        (
+            "[random(1), random(1)]".to_string(),
+            granted.clone(),
+            Ok("[0, 0]".to_string()),
+        ),
+        (
+            "env(\"PROLANG_UNSET_VARIABLE\")".to_string(),
+            granted.clone(),
+            Ok("null".to_string()),
+        ),
+        (
+            "env(\"HOME\")".to_string(),
+            Capabilities::default(),
+            Err("`env` is not allowed"),
+        ),
+        (
+            "input()".to_string(),
+            granted.clone(),
+            Err("`input` is not allowed"),
+        ),
+        (
+            format!("read_file(\"{}\")", path("allowed/in.txt")),
+            granted.clone(),
+            Ok("hello".to_string()),
+        ),
+        (
+            format!(
+                "write_file(\"{0}\", \"out\"); read_file(\"{0}\")",
+                path("allowed/out.txt")
+            ),
+            granted.clone(),
+            Ok("out".to_string()),
+        ),
+        (
+            format!("read_file(\"{}\")", path("allowed/../secret.txt")),
+            granted.clone(),
+            Err("may not read"),
+        ),
+        (
+            format!("read_file(\"{}\")", path("secret.txt")),
+            granted.clone(),
+            Err("may not read"),
+        ),
+        (
+            format!("write_file(\"{}\", \"x\")", path("new.txt")),
+            granted.clone(),
+            Err("may not write"),
+        ),
+        (
+            format!("read_file(\"{}\")", path("allowed/in.txt")),
+            Capabilities::default(),
+            Err("may not read"),
+        ),
+        // denials can be caught
+        (
+            "try { random(2) } catch (e) { e.kind }".to_string(),
+            Capabilities::default(),
+            Ok("PermissionDenied".to_string()),
+        ),
+    ];
+
+    for (input, capabilities, expected) in tests {
+        let program = parse(&input);
+        let mut evaluator = Evaluator::new();
+        evaluator.set_capabilities(capabilities.clone());
+        let interpreted = evaluator.eval_program(&program, &Environment::new());
+        let bytecode = Compiler::new()
+            .compile(&program)
+            .unwrap_or_else(|err| panic!("compile error: {}", err));
+        let mut vm = Vm::new(bytecode);
+        vm.set_capabilities(capabilities);
+        let compiled = vm.run();
+
+        for (engine, result) in [("interpreter", interpreted), ("vm", compiled)] {
+            match &expected {
+                Ok(expected) => assert_eq!(
+                    result.map(|value| value.to_string()),
+                    Ok(expected.clone()),
+                    "wrong result from the {} for {}",
+                    engine,
+                    input
+                ),
+                Err(expected) => {
+                    let err = result.expect_err("a capability is missing");
+                    assert_eq!(err.kind, ErrorKind::PermissionDenied, "{}", input);
+                    assert!(
+                        err.message.contains(expected),
+                        "wrong message from the {} for {}: {}",
+                        engine,
+                        input,
+                        err.message
+                    );
+                }
+            }
+        }
+    }
+    assert!(!dir.join("new.txt").exists());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_vm_capabilities_reject_dangling_links() {
+    let dir = std::env::temp_dir().join(format!("prolang-links-{}", std::process::id()));
+    let allowed = dir.join("allowed");
+    fs::create_dir_all(&allowed).unwrap();
+    let link = allowed.join("link");
+    std::os::unix::fs::symlink(dir.join("escape.txt"), &link).unwrap();
+    let granted = Capabilities {
+        read: Paths::Under(vec![allowed.clone()]),
+        write: Paths::Under(vec![allowed.clone()]),
+        ..Capabilities::default()
+    };
+    let program = parse(&format!("write_file(\"{}\", \"x\")", link.display()));
+
+    let mut evaluator = Evaluator::new();
+    evaluator.set_capabilities(granted.clone());
+    let interpreted = evaluator.eval_program(&program, &Environment::new());
+    let bytecode = Compiler::new()
+        .compile(&program)
+        .unwrap_or_else(|err| panic!("compile error: {}", err));
+    let mut vm = Vm::new(bytecode);
+    vm.set_capabilities(granted);
+    let compiled = vm.run();
+
+    for (engine, result) in [("interpreter", interpreted), ("vm", compiled)] {
+        let err = result.expect_err("the link leads outside of the allowed directory");
+        assert_eq!(err.kind, ErrorKind::PermissionDenied, "{}", engine);
+    }
+    assert!(!dir.join("escape.txt").exists());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+//utils
+/// Runs the input on the vm and checks it against the expected output and
+/// against the interpreter.
+fn test_vm_output(input: &str, expected: &str) {
+    let program = parse(input);
+    let value = match run(&program) {
+        Ok(value) => value,
+        Err(err) => panic!("runtime error for {}: {}", input, err),
+    };
+    assert_eq!(
+        value.to_string(),
+        expected,
+        "wrong result for {}. expected={}, got={}",
+        input,
+        expected,
+        value
+    );
+
+    let interpreted = interpret(&program)
+        .unwrap_or_else(|err| panic!("interpreter error for {}: {}", input, err));
+    assert_eq!(
+        interpreted.to_string(),
+        value.to_string(),
+        "vm and interpreter disagree for {}",
+        input
+    );
+}
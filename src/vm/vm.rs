@@ -0,0 +1,868 @@
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use crate::{
+    compiler::{
+        bytecode::{Bytecode, Constant},
+        code::{read_u16, Opcode},
+        compiler::NO_PAYLOAD,
+    },
+    evaluator::{
+        builtins,
+        capabilities::Capabilities,
+        evaluator::{
+            assign_index, assign_member, check_type, construct_variant, eval_index_expression,
+            eval_infix_expression, eval_member_expression, eval_prefix_expression, new_struct,
+            thrown_error, variant_index,
+        },
+        limits::{Budget, Limits},
+        stdlib,
+        value::{ErrorKind, RuntimeError, StructType, Value},
+    },
+    gc::gc,
+};
+
+use super::closure::{Closure, Upvalue};
+
+type VmResult<T = ()> = Result<T, RuntimeError>;
+
+struct Frame {
+    closure: Rc<Closure>,
+    ip: usize,
+    /// Stack index of the frame's first local slot.
+    base: usize,
+}
+
+/// A `try` block being run, which errors unwind to.
+struct Handler {
+    /// The number of frames below the one running the `try` block.
+    depth: usize,
+    /// The stack height when the `try` block started.
+    stack_len: usize,
+    /// Offset of the catch block.
+    catch: usize,
+}
+
+impl Frame {
+    fn read_u8(&mut self) -> usize {
+        let byte = self.closure.function.instructions[self.ip];
+        self.ip += 1;
+        byte as usize
+    }
+
+    fn read_u16(&mut self) -> usize {
+        let operand = read_u16(&self.closure.function.instructions[self.ip..]);
+        self.ip += 2;
+        operand as usize
+    }
+
+    /// Where the instruction just read was compiled from: the failed
+    /// instruction in the frame that raised an error, the call in a caller.
+    fn position(&self) -> (usize, usize) {
+        self.closure.function.position_at(self.ip.saturating_sub(1))
+    }
+
+    /// The file the instruction just read was compiled from.
+    fn file(&self) -> &str {
+        self.closure.function.file_at(self.ip.saturating_sub(1))
+    }
+}
+
+/// Runs compiled bytecode with a value stack and call frames. Each frame
+/// reserves its local slots at the bottom of its part of the stack, and
+/// temporaries are pushed above them.
+pub struct Vm {
+    constants: Vec<Constant>,
+    /// `Constant::Int`, `Constant::Float` and `Constant::Str` pool entries,
+    /// converted once.
+    values: Vec<Value>,
+    globals: Vec<Option<Value>>,
+    global_names: Vec<String>,
+    stack: Vec<Value>,
+    frames: Vec<Frame>,
+    open_upvalues: Vec<Rc<RefCell<Upvalue>>>,
+    handlers: Vec<Handler>,
+    main: Rc<Closure>,
+    limits: Limits,
+    /// What the running program has used of `limits`.
+    budget: Budget,
+    capabilities: Capabilities,
+}
+
+impl Vm {
+    pub fn new(bytecode: Bytecode) -> Self {
+        let values = bytecode
+            .constants
+            .iter()
+            .map(|constant| match constant {
+                Constant::Int(i) => Value::Int(*i),
+                Constant::Float(x) => Value::Float(*x),
+                Constant::Str(s) => Value::Str(s.clone()),
+                _ => Value::Null,
+            })
+            .collect();
+
+        Self {
+            constants: bytecode.constants,
+            values,
+            globals: vec![None; bytecode.globals.len()],
+            global_names: bytecode.globals,
+            stack: vec![],
+            frames: vec![],
+            open_upvalues: vec![],
+            handlers: vec![],
+            main: Rc::new(Closure {
+                function: bytecode.main,
+                upvalues: vec![],
+            }),
+            limits: Limits::default(),
+            budget: Budget::default(),
+            capabilities: Capabilities::default(),
+        }
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Grants builtins the capabilities given, in place of none.
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities;
+    }
+
+    pub fn run(&mut self) -> VmResult<Value> {
+        self.stack.clear();
+        self.frames.clear();
+        self.open_upvalues.clear();
+        self.handlers.clear();
+        self.budget = Budget::start(self.limits);
+        self.stack
+            .resize(self.main.function.num_locals, Value::Null);
+        let mut frame = Frame {
+            closure: Rc::clone(&self.main),
+            ip: 0,
+            base: 0,
+        };
+
+        loop {
+            match self.execute(&mut frame) {
+                Ok(value) => return Ok(value),
+                Err(err) => {
+                    let err = err.at(frame.file(), frame.position());
+                    self.catch(&mut frame, err)?;
+                }
+            }
+        }
+    }
+
+    /// Runs instructions from `frame` until the top level code returns, or
+    /// an instruction fails.
+    fn execute(&mut self, frame: &mut Frame) -> VmResult<Value> {
+        loop {
+            let byte = frame.read_u8() as u8;
+            self.budget.step()?;
+            let op = Opcode::from_byte(byte)
+                .ok_or_else(|| RuntimeError::new(format!("unknown opcode {}", byte)))?;
+
+            match op {
+                Opcode::Constant => {
+                    let index = frame.read_u16();
+                    self.push(self.values[index].clone());
+                }
+                Opcode::Null => self.push(Value::Null),
+                Opcode::True => self.push(Value::Bool(true)),
+                Opcode::False => self.push(Value::Bool(false)),
+                Opcode::Pop => {
+                    self.pop();
+                }
+                Opcode::Dup => self.push(self.peek(0).clone()),
+                Opcode::Dup2 => {
+                    self.push(self.peek(1).clone());
+                    self.push(self.peek(1).clone());
+                }
+
+                Opcode::Add
+                | Opcode::Sub
+                | Opcode::Mul
+                | Opcode::Div
+                | Opcode::Mod
+                | Opcode::Equal
+                | Opcode::NotEqual
+                | Opcode::Less
+                | Opcode::Greater
+                | Opcode::LessEqual
+                | Opcode::GreaterEqual => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    self.push(eval_infix_expression(binary_operator(op), left, right)?);
+                }
+                Opcode::Minus => {
+                    let right = self.pop();
+                    self.push(eval_prefix_expression("-", right)?);
+                }
+                Opcode::Bang => {
+                    let right = self.pop();
+                    self.push(Value::Bool(!right.is_truthy()));
+                }
+                Opcode::ToBool => {
+                    let value = self.pop();
+                    self.push(Value::Bool(value.is_truthy()));
+                }
+
+                Opcode::Jump => frame.ip = frame.read_u16(),
+                Opcode::JumpIfFalse => {
+                    let target = frame.read_u16();
+                    if !self.pop().is_truthy() {
+                        frame.ip = target;
+                    }
+                }
+
+                Opcode::GetGlobal => {
+                    let index = frame.read_u16();
+                    let value = self.get_global(index)?;
+                    self.push(value);
+                }
+                Opcode::GetGlobalOpt => {
+                    let index = frame.read_u16();
+                    let value = self.globals[index].clone().unwrap_or(Value::Null);
+                    self.push(value);
+                }
+                Opcode::SetGlobal => {
+                    let index = frame.read_u16();
+                    self.globals[index] = Some(self.pop());
+                }
+                Opcode::AssignGlobal => {
+                    let index = frame.read_u16();
+                    self.check_global(index)?;
+                    self.globals[index] = Some(self.pop());
+                }
+                Opcode::CheckGlobal => {
+                    let index = frame.read_u16();
+                    self.check_global(index)?;
+                }
+                Opcode::GetLocal => {
+                    let slot = frame.read_u16();
+                    self.push(self.stack[frame.base + slot].clone());
+                }
+                Opcode::SetLocal => {
+                    let slot = frame.read_u16();
+                    self.stack[frame.base + slot] = self.pop();
+                }
+                Opcode::GetUpvalue => {
+                    let index = frame.read_u16();
+                    let value = match &*frame.closure.upvalues[index].borrow() {
+                        Upvalue::Open(slot) => self.stack[*slot].clone(),
+                        Upvalue::Closed(value) => value.clone(),
+                    };
+                    self.push(value);
+                }
+                Opcode::SetUpvalue => {
+                    let index = frame.read_u16();
+                    let value = self.pop();
+                    match &mut *frame.closure.upvalues[index].borrow_mut() {
+                        Upvalue::Open(slot) => self.stack[*slot] = value,
+                        Upvalue::Closed(closed) => *closed = value,
+                    }
+                }
+                Opcode::CloseUpvalues => {
+                    let slot = frame.read_u16();
+                    self.close_upvalues(frame.base + slot);
+                }
+
+                Opcode::Array => {
+                    let len = frame.read_u16();
+                    let elements = self.stack.split_off(self.stack.len() - len);
+                    self.push(Value::array(elements));
+                }
+                Opcode::Hash => {
+                    let len = frame.read_u16();
+                    let values = self.stack.split_off(self.stack.len() - len * 2);
+                    let mut pairs = BTreeMap::new();
+                    for pair in values.chunks(2) {
+                        let key = pair[0].hash_key().ok_or_else(|| {
+                            RuntimeError::with_kind(
+                                ErrorKind::TypeMismatch,
+                                format!("unusable as hash key: {}", pair[0].type_name()),
+                            )
+                        })?;
+                        pairs.insert(key, pair[1].clone());
+                    }
+                    self.push(Value::hash(pairs));
+                }
+                Opcode::Range => {
+                    let end = self.pop();
+                    let start = self.pop();
+                    match (start, end) {
+                        (Value::Int(start), Value::Int(end)) => self.push(Value::Range(start, end)),
+                        (start, end) => {
+                            return Err(RuntimeError::with_kind(
+                                ErrorKind::TypeMismatch,
+                                format!(
+                                    "range bounds must be INT, got {}..{}",
+                                    start.type_name(),
+                                    end.type_name()
+                                ),
+                            ))
+                        }
+                    }
+                }
+                Opcode::Index => {
+                    let index = self.pop();
+                    let left = self.pop();
+                    self.push(eval_index_expression(left, index)?);
+                }
+                Opcode::SetIndex => {
+                    let value = self.pop();
+                    let index = self.pop();
+                    let left = self.pop();
+                    assign_index(&left, index, value.clone())?;
+                    self.push(value);
+                }
+                Opcode::GetMember => {
+                    let name = frame.read_u16();
+                    let object = self.pop();
+                    let name = self.string_constant(name);
+                    self.push(eval_member_expression(object, name)?);
+                }
+                Opcode::SetMember => {
+                    let name = frame.read_u16();
+                    let value = self.pop();
+                    let object = self.pop();
+                    assign_member(&object, self.string_constant(name), value.clone())?;
+                    self.push(value);
+                }
+
+                Opcode::Call => {
+                    let argc = frame.read_u8();
+                    if let Some(callee) = self.call_value(argc)? {
+                        // `frames` holds the top level code and every call but the running one
+                        self.limits.check_depth(self.frames.len())?;
+                        self.frames.push(std::mem::replace(frame, callee));
+                    }
+                }
+                Opcode::TailCall => {
+                    let argc = frame.read_u8();
+                    match self.call_value(argc)? {
+                        Some(callee) => {
+                            // the callee takes over the frame, with itself and
+                            // its arguments moved down over the caller's
+                            self.close_upvalues(frame.base);
+                            self.stack.drain(frame.base - 1..callee.base - 1);
+                            *frame = Frame {
+                                base: frame.base,
+                                ..callee
+                            };
+                        }
+                        None => {
+                            if let Some(result) = self.return_from(frame) {
+                                return Ok(result);
+                            }
+                        }
+                    }
+                }
+                Opcode::Return => {
+                    if let Some(result) = self.return_from(frame) {
+                        return Ok(result);
+                    }
+                }
+                Opcode::Closure => {
+                    let index = frame.read_u16();
+                    let Constant::Function(function) = &self.constants[index] else {
+                        return Err(RuntimeError::new("closure of a non-function constant"));
+                    };
+                    let function = Rc::clone(function);
+                    let upvalues = function
+                        .upvalues
+                        .iter()
+                        .map(|upvalue| match upvalue.is_local {
+                            true => self.capture_upvalue(frame.base + upvalue.index),
+                            false => Rc::clone(&frame.closure.upvalues[upvalue.index]),
+                        })
+                        .collect();
+                    self.push(Value::Closure(gc::track(Rc::new(Closure {
+                        function,
+                        upvalues,
+                    }))));
+                }
+
+                Opcode::NewType => {
+                    let index = frame.read_u16();
+                    let Constant::Type(declaration) = &self.constants[index] else {
+                        return Err(RuntimeError::new("type of a non-type constant"));
+                    };
+                    let value = match declaration {
+                        Value::Enum(enum_type) => Value::Enum(Rc::new((**enum_type).clone())),
+                        Value::StructType(struct_type) => {
+                            Value::StructType(gc::track(Rc::new(StructType {
+                                name: struct_type.name.clone(),
+                                fields: struct_type.fields.clone(),
+                                methods: RefCell::default(),
+                            })))
+                        }
+                        Value::Interface(interface) => {
+                            Value::Interface(Rc::new((**interface).clone()))
+                        }
+                        other => other.clone(),
+                    };
+                    self.push(value);
+                }
+                Opcode::MakeStruct => {
+                    let name = frame.read_u16();
+                    let fields = frame.read_u16();
+                    let fields = self.names_constant(fields).clone();
+                    let values = self.stack.split_off(self.stack.len() - fields.len());
+                    let struct_type = match &self.pop() {
+                        Value::StructType(struct_type) => Rc::clone(struct_type),
+                        other => {
+                            return Err(RuntimeError::with_kind(
+                                ErrorKind::TypeMismatch,
+                                format!(
+                                    "`{}` is not a struct, got {}",
+                                    self.string_constant(name),
+                                    other.type_name()
+                                ),
+                            ))
+                        }
+                    };
+                    self.push(new_struct(
+                        struct_type,
+                        fields.into_iter().zip(values).collect(),
+                    )?);
+                }
+                Opcode::Module => {
+                    let name = frame.read_u16();
+                    let names = frame.read_u16();
+                    let names = self.names_constant(names).clone();
+                    let values = self.stack.split_off(self.stack.len() - names.len());
+                    let name = self.string_constant(name).to_owned();
+                    self.push(Value::module(name, names.into_iter().zip(values).collect()));
+                }
+                Opcode::StdModule => {
+                    let import = frame.read_u16();
+                    let module = stdlib::lookup(self.string_constant(import))
+                        .and_then(Result::ok)
+                        .expect("std module validated by the compiler");
+                    self.push(module.to_value());
+                }
+                Opcode::Impl => {
+                    let target = frame.read_u16();
+                    if !matches!(self.peek(0), Value::StructType(_)) {
+                        return Err(RuntimeError::with_kind(
+                            ErrorKind::TypeMismatch,
+                            format!(
+                                "cannot impl methods on `{}`: expected a struct, got {}",
+                                self.string_constant(target),
+                                self.peek(0).type_name()
+                            ),
+                        ));
+                    }
+                }
+                Opcode::Method => {
+                    let name = frame.read_u16();
+                    let method = self.pop();
+                    let Value::StructType(struct_type) = self.peek(0) else {
+                        return Err(RuntimeError::new("method outside of an impl block"));
+                    };
+                    let name = self.string_constant(name);
+                    if method.arity() == Some(0) {
+                        return Err(RuntimeError::new(format!(
+                            "method `{}` of {} must take the receiver as its first parameter",
+                            name, struct_type.name
+                        )));
+                    }
+                    struct_type
+                        .methods
+                        .borrow_mut()
+                        .insert(name.to_owned(), method);
+                }
+                Opcode::CheckType => {
+                    let type_value = self.pop();
+                    check_type(self.peek(0), &type_value)?;
+                }
+
+                Opcode::IterInit => {
+                    let slot = frame.base + frame.read_u16();
+                    let iterable = match &self.pop() {
+                        Value::Range(start, end) => Value::Range(*start, *end),
+                        // iterate over a snapshot, like the interpreter
+                        Value::Array(elements) => Value::array(elements.borrow().clone()),
+                        Value::Str(s) => {
+                            Value::array(s.chars().map(|c| Value::Str(c.to_string())).collect())
+                        }
+                        other => {
+                            return Err(RuntimeError::with_kind(
+                                ErrorKind::TypeMismatch,
+                                format!("{} is not iterable", other.type_name()),
+                            ))
+                        }
+                    };
+                    self.stack[slot] = iterable;
+                    self.stack[slot + 1] = Value::Int(0);
+                }
+                Opcode::IterNext => {
+                    let slot = frame.base + frame.read_u16();
+                    let exit = frame.read_u16();
+                    let Value::Int(position) = self.stack[slot + 1] else {
+                        return Err(RuntimeError::new("corrupt iterator position"));
+                    };
+                    let item = match &self.stack[slot] {
+                        Value::Range(start, end) => {
+                            Some(start + position).filter(|i| i < end).map(Value::Int)
+                        }
+                        Value::Array(elements) => elements.borrow().get(position as usize).cloned(),
+                        _ => None,
+                    };
+                    match item {
+                        Some(item) => {
+                            self.stack[slot + 1] = Value::Int(position + 1);
+                            self.push(item);
+                        }
+                        None => frame.ip = exit,
+                    }
+                }
+
+                Opcode::MatchVariant => {
+                    let names = frame.read_u16();
+                    let payload_len = frame.read_u8();
+                    let enum_type = self.pop();
+                    let subject = self.pop();
+                    let names = self.names_constant(names);
+                    let enum_type = match &enum_type {
+                        Value::Enum(enum_type) => Rc::clone(enum_type),
+                        other => {
+                            return Err(RuntimeError::with_kind(
+                                ErrorKind::TypeMismatch,
+                                format!("`{}` is not an enum, got {}", names[0], other.type_name()),
+                            ))
+                        }
+                    };
+                    let index = variant_index(&enum_type, &names[1])?;
+                    let matched = match &subject {
+                        Value::EnumVariant(value)
+                            if Rc::ptr_eq(&value.enum_type, &enum_type)
+                                && value.variant == index =>
+                        {
+                            if payload_len != NO_PAYLOAD && payload_len != value.payload.len() {
+                                return Err(RuntimeError::new(format!(
+                                    "pattern {} expects {} field(s), but the variant has {}",
+                                    names[2],
+                                    payload_len,
+                                    value.payload.len()
+                                )));
+                            }
+                            true
+                        }
+                        _ => false,
+                    };
+                    self.push(Value::Bool(matched));
+                }
+                Opcode::VariantField => {
+                    let index = frame.read_u8();
+                    let Value::EnumVariant(value) = &self.pop() else {
+                        return Err(RuntimeError::new("variant field of a non-variant"));
+                    };
+                    self.push(value.payload[index].clone());
+                }
+                Opcode::MatchArray => {
+                    let len = frame.read_u16();
+                    let has_rest = frame.read_u8() == 1;
+                    let matched = match &self.pop() {
+                        Value::Array(values) => {
+                            let values = values.borrow();
+                            values.len() >= len && (has_rest || values.len() == len)
+                        }
+                        _ => false,
+                    };
+                    self.push(Value::Bool(matched));
+                }
+                Opcode::ArrayRest => {
+                    let from = frame.read_u16();
+                    let Value::Array(values) = &self.pop() else {
+                        return Err(RuntimeError::new("rest pattern of a non-array"));
+                    };
+                    let rest = values.borrow()[from..].to_vec();
+                    self.push(Value::array(rest));
+                }
+                Opcode::MatchStruct => {
+                    let name = frame.read_u16();
+                    let struct_type = self.pop();
+                    let subject = self.pop();
+                    let struct_type = match &struct_type {
+                        Value::StructType(struct_type) => Rc::clone(struct_type),
+                        other => {
+                            return Err(RuntimeError::with_kind(
+                                ErrorKind::TypeMismatch,
+                                format!(
+                                    "`{}` is not a struct, got {}",
+                                    self.string_constant(name),
+                                    other.type_name()
+                                ),
+                            ))
+                        }
+                    };
+                    let matched = match &subject {
+                        Value::Struct(value) => {
+                            Rc::ptr_eq(&value.borrow().struct_type, &struct_type)
+                        }
+                        _ => false,
+                    };
+                    self.push(Value::Bool(matched));
+                }
+                Opcode::StructField => {
+                    let field = frame.read_u16();
+                    let Value::Struct(value) = &self.pop() else {
+                        return Err(RuntimeError::new("struct field of a non-struct"));
+                    };
+                    let field = self.string_constant(field);
+                    let value = value.borrow();
+                    let field_value = value.get(field).cloned().ok_or_else(|| {
+                        RuntimeError::new(format!(
+                            "struct {} has no field `{}`",
+                            value.struct_type.name, field
+                        ))
+                    })?;
+                    self.push(field_value);
+                }
+                Opcode::NoMatch => {
+                    let subject = self.pop();
+                    return Err(RuntimeError::with_kind(
+                        ErrorKind::NoMatch,
+                        format!("no match arm matched value: {}", subject.inspect()),
+                    ));
+                }
+
+                Opcode::Try => {
+                    let catch = frame.read_u16();
+                    self.handlers.push(Handler {
+                        depth: self.frames.len(),
+                        stack_len: self.stack.len(),
+                        catch,
+                    });
+                }
+                Opcode::EndTry => {
+                    self.handlers.pop();
+                }
+                Opcode::Throw => return Err(thrown_error(self.pop())),
+            }
+        }
+    }
+
+    /// Unwinds to the innermost `try` block and resumes at its catch block,
+    /// with the error on the stack. Returns the error if there is none.
+    fn catch(&mut self, frame: &mut Frame, err: RuntimeError) -> VmResult {
+        if !err.kind.is_catchable() {
+            return Err(self.trace(frame, err, 0));
+        }
+        let depth = self.handlers.last().map_or(0, |handler| handler.depth);
+        let err = self.trace(frame, err, depth);
+        let Some(handler) = self.handlers.pop() else {
+            return Err(err);
+        };
+        if handler.depth < self.frames.len() {
+            *frame = self
+                .frames
+                .drain(handler.depth..)
+                .next()
+                .expect("the handler's frame is on the frame stack");
+        }
+        self.close_upvalues(handler.stack_len);
+        self.stack.truncate(handler.stack_len);
+        self.push(err.to_value());
+        frame.ip = handler.catch;
+
+        Ok(())
+    }
+
+    /// Returns the value on top of the stack to the caller of `frame`, which
+    /// becomes the running frame, or returns it out of the top level code.
+    fn return_from(&mut self, frame: &mut Frame) -> Option<Value> {
+        let result = self.pop();
+        self.close_upvalues(frame.base);
+        let Some(caller) = self.frames.pop() else {
+            return Some(result);
+        };
+        // `return` may leave `try` blocks of the frame
+        while (self.handlers.last()).is_some_and(|h| h.depth > self.frames.len()) {
+            self.handlers.pop();
+        }
+        // drop the locals and the callee below them
+        self.stack.truncate(frame.base - 1);
+        self.push(result);
+        *frame = caller;
+
+        None
+    }
+
+    /// Records the calls above the first `depth + 1` frames in the trace of
+    /// `err`, which unwinds them.
+    fn trace(&self, frame: &Frame, mut err: RuntimeError, depth: usize) -> RuntimeError {
+        for i in (depth + 1..=self.frames.len()).rev() {
+            let callee = self.frames.get(i).unwrap_or(frame);
+            let caller = &self.frames[i - 1];
+            err = err.unwound(
+                callee.closure.function.name.as_deref(),
+                caller.file(),
+                caller.position(),
+            );
+        }
+
+        err
+    }
+
+    /// Calls the value below the `argc` arguments on top of the stack.
+    /// Returns the frame to enter for closures; other callables complete
+    /// immediately and leave their result on the stack.
+    fn call_value(&mut self, argc: usize) -> VmResult<Option<Frame>> {
+        let callee_slot = self.stack.len() - 1 - argc;
+        match &self.stack[callee_slot].clone() {
+            Value::Closure(closure) => {
+                let arity = closure.function.parameters.len();
+                if arity != argc {
+                    return Err(RuntimeError::with_kind(
+                        ErrorKind::WrongArguments,
+                        format!("wrong number of arguments. got={}, want={}", argc, arity),
+                    ));
+                }
+                let base = callee_slot + 1;
+                let num_locals = closure.function.num_locals.max(argc);
+                self.stack.resize(base + num_locals, Value::Null);
+                Ok(Some(Frame {
+                    closure: Rc::clone(closure),
+                    ip: 0,
+                    base,
+                }))
+            }
+            Value::BoundMethod(method) => {
+                self.stack[callee_slot] = method.function.clone();
+                self.stack.insert(callee_slot + 1, method.receiver.clone());
+                self.call_value(argc + 1)
+            }
+            Value::Builtin(builtin) => {
+                let args = self.stack.split_off(callee_slot + 1);
+                self.pop();
+                self.push((builtin.func)(&self.capabilities, &args)?);
+                Ok(None)
+            }
+            Value::Native(native) => {
+                let args = self.stack.split_off(callee_slot + 1);
+                self.pop();
+                self.push((native.func)(&args)?);
+                Ok(None)
+            }
+            Value::VariantConstructor(enum_type, variant) => {
+                let args = self.stack.split_off(callee_slot + 1);
+                self.pop();
+                self.push(construct_variant(Rc::clone(enum_type), *variant, args)?);
+                Ok(None)
+            }
+            other => Err(RuntimeError::with_kind(
+                ErrorKind::TypeMismatch,
+                format!("not a function: {}", other.type_name()),
+            )),
+        }
+    }
+
+    fn get_global(&self, index: usize) -> VmResult<Value> {
+        if let Some(value) = &self.globals[index] {
+            return Ok(value.clone());
+        }
+        let name = global_name(&self.global_names[index]);
+        match builtins::lookup(name) {
+            Some(builtin) => Ok(Value::Builtin(builtin)),
+            None => Err(RuntimeError::with_kind(
+                ErrorKind::UnknownIdentifier,
+                format!("identifier not found: {}", name),
+            )),
+        }
+    }
+
+    fn check_global(&self, index: usize) -> VmResult {
+        if self.globals[index].is_none() {
+            return Err(RuntimeError::with_kind(
+                ErrorKind::UnknownIdentifier,
+                format!(
+                    "cannot assign to undeclared variable `{}`",
+                    global_name(&self.global_names[index])
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    fn capture_upvalue(&mut self, slot: usize) -> Rc<RefCell<Upvalue>> {
+        let existing = self
+            .open_upvalues
+            .iter()
+            .find(|upvalue| matches!(&*upvalue.borrow(), Upvalue::Open(s) if *s == slot));
+        if let Some(upvalue) = existing {
+            return Rc::clone(upvalue);
+        }
+
+        let upvalue = gc::track(Rc::new(RefCell::new(Upvalue::Open(slot))));
+        self.open_upvalues.push(Rc::clone(&upvalue));
+        upvalue
+    }
+
+    /// Moves the values of upvalues pointing at `from` or above off the stack.
+    fn close_upvalues(&mut self, from: usize) {
+        let stack = &self.stack;
+        self.open_upvalues.retain(|upvalue| {
+            let mut upvalue = upvalue.borrow_mut();
+            match *upvalue {
+                Upvalue::Open(slot) if slot >= from => {
+                    *upvalue = Upvalue::Closed(stack[slot].clone());
+                    false
+                }
+                _ => true,
+            }
+        });
+    }
+
+    fn string_constant(&self, index: usize) -> &str {
+        match &self.constants[index] {
+            Constant::Str(s) => s,
+            _ => "",
+        }
+    }
+
+    fn names_constant(&self, index: usize) -> &Vec<String> {
+        match &self.constants[index] {
+            Constant::Names(names) => names,
+            _ => panic!("constant {} is not a list of names", index),
+        }
+    }
+
+    fn push(&mut self, value: Value) {
+        self.stack.push(value);
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().expect("stack underflow")
+    }
+
+    fn peek(&self, distance: usize) -> &Value {
+        &self.stack[self.stack.len() - 1 - distance]
+    }
+}
+
+fn binary_operator(op: Opcode) -> &'static str {
+    match op {
+        Opcode::Add => "+",
+        Opcode::Sub => "-",
+        Opcode::Mul => "*",
+        Opcode::Div => "/",
+        Opcode::Mod => "%",
+        Opcode::Equal => "==",
+        Opcode::NotEqual => "!=",
+        Opcode::Less => "<",
+        Opcode::Greater => ">",
+        Opcode::LessEqual => "<=",
+        Opcode::GreaterEqual => ">=",
+        _ => unreachable!("{} is not a binary operator", op),
+    }
+}
+
+/// The name a global was declared with, without the prefix of the module
+/// declaring it.
+fn global_name(global: &str) -> &str {
+    global.rsplit_once("::").map_or(global, |(_, name)| name)
+}
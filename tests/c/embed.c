@@ -0,0 +1,61 @@
+/* Embeds prolang through its C API; run by tests/c_api.rs. */
+#include <stdio.h>
+#include <string.h>
+
+#include "prolang.h"
+
+static int failures = 0;
+
+static void expect(int ok, const char *what) {
+    if (!ok) {
+        fprintf(stderr, "failed: %s\n", what);
+        failures++;
+    }
+}
+
+static ProlangValue *add(void *user_data, const ProlangValue *const *args, size_t argc) {
+    int64_t a, b;
+    (void)user_data;
+    (void)argc;
+    if (prolang_value_as_int(args[0], &a) != 0 || prolang_value_as_int(args[1], &b) != 0) {
+        return prolang_value_error("add takes two integers");
+    }
+    return prolang_value_int(a + b);
+}
+
+static ProlangValue *greet(void *user_data, const ProlangValue *const *args, size_t argc) {
+    char greeting[64];
+    char *name = prolang_value_to_string(args[0]);
+    (void)argc;
+    snprintf(greeting, sizeof greeting, "%s, %s", (const char *)user_data, name);
+    prolang_string_free(name);
+    return prolang_value_string(greeting);
+}
+
+static void run(ProlangEngine *engine, const char *source) {
+    int status = prolang_eval(engine, source);
+    printf("%d %s\n", status, prolang_result_string(engine));
+}
+
+int main(void) {
+    ProlangEngine *engine = prolang_engine_new();
+    int64_t result = 0;
+
+    expect(prolang_eval(engine, "let double = fn(x) { x * 2 }; double(21)") == 0, "eval");
+    expect(prolang_result_int(engine, &result) == 0 && result == 42, "result is 42");
+
+    expect(prolang_register_fn(engine, "add", 2, add, NULL) == 0, "register add");
+    expect(prolang_register_fn(engine, "greet", 1, greet, "hello") == 0, "register greet");
+
+    run(engine, "add(double(2), 3)");
+    run(engine, "greet(\"ada\")");
+    run(engine, "add(1)");
+    run(engine, "add(\"one\", 2)");
+    run(engine, "try { add(true, 1) } catch (e) { e.message }");
+    run(engine, "1 / 0");
+    run(engine, "print(1)");
+    expect(prolang_result_int(engine, &result) == -1, "no integer after a failure");
+
+    prolang_engine_free(engine);
+    return failures;
+}
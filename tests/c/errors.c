@@ -0,0 +1,56 @@
+/* Checks the errors the C API reports; run by tests/c_api.rs. */
+#include <stdio.h>
+
+#include "prolang.h"
+
+static int failures = 0;
+
+static void expect(int ok, const char *what) {
+    if (!ok) {
+        fprintf(stderr, "failed: %s\n", what);
+        failures++;
+    }
+}
+
+static ProlangValue *nothing(void *user_data, const ProlangValue *const *args, size_t argc) {
+    (void)user_data;
+    (void)args;
+    (void)argc;
+    return prolang_value_null();
+}
+
+static void report(int status) {
+    printf("%d %s\n", status, prolang_last_error());
+}
+
+int main(void) {
+    ProlangEngine *engine = prolang_engine_new();
+    ProlangValue *value = prolang_value_string("one");
+    int64_t result = 0;
+
+    expect(prolang_last_error() == NULL, "no error before a call fails");
+
+    report(prolang_eval(NULL, "1"));
+    report(prolang_eval(engine, "\xff"));
+    report(prolang_eval(engine, "1 / 0"));
+    report(prolang_result_int(engine, &result));
+    expect(prolang_eval(engine, "\"one\"") == 0, "eval");
+    report(prolang_result_int(engine, &result));
+    report(prolang_result_int(engine, NULL));
+    report(prolang_register_fn(engine, "\xff", 0, nothing, NULL));
+    report(prolang_value_as_int(value, &result));
+
+    expect(prolang_value_string("\xff") == NULL, "invalid string");
+    report(-1);
+    expect(prolang_value_to_string(NULL) == NULL, "null value");
+    report(-1);
+    expect(prolang_result_string(NULL) == NULL, "null engine");
+    report(-1);
+
+    expect(prolang_eval(engine, "1") == 0, "eval after a failure");
+    report(0);
+
+    prolang_value_free(value);
+    prolang_engine_free(engine);
+    return failures;
+}
@@ -0,0 +1,273 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Compiles `tests/c/embed.c` against the header and the shared library
+/// with the system C compiler, then checks what it prints.
+#[test]
+fn test_c_program_embeds_prolang() {
+    let program = compile("embed");
+    let output = Command::new(&program).output().expect("embed runs");
+    assert!(
+        output.status.success(),
+        "embed failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        [
+            "0 7",
+            "0 hello, ada",
+            "-1 runtime error: WrongArguments: wrong number of arguments to `add`. got=1, want=2\n    at main.pr:1:4",
+            "-1 runtime error: Error: add takes two integers\n    at main.pr:1:4",
+            "0 add takes two integers",
+            "-1 runtime error: DivisionByZero: division by zero\n    at main.pr:1:3",
+            "-1 runtime error: PermissionDenied: `print` is not allowed\n    at main.pr:1:6",
+            "",
+        ]
+        .join("\n")
+    );
+}
+
+/// Checks that the C API returns its documented error values and reports
+/// why through `prolang_last_error`.
+#[test]
+fn test_c_program_reads_errors() {
+    let program = compile("errors");
+    let output = Command::new(&program).output().expect("errors runs");
+    assert!(
+        output.status.success(),
+        "errors failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        [
+            "-1 engine is null",
+            "-1 runtime error: Error: source is not valid UTF-8",
+            "-1 runtime error: DivisionByZero: division by zero\n    at main.pr:1:3",
+            "-1 runtime error: DivisionByZero: division by zero\n    at main.pr:1:3",
+            "-1 the last program returned STRING, not an integer",
+            "-1 out is null",
+            "-1 name is not valid UTF-8",
+            "-1 expected an integer, got STRING",
+            "-1 value is not valid UTF-8",
+            "-1 value is null",
+            "-1 engine is null",
+            "0 engine is null",
+            "",
+        ]
+        .join("\n")
+    );
+}
+
+/// Compiles `tests/c/<name>.c` against the header and the shared library
+/// with the system C compiler, returning the program.
+fn compile(name: &str) -> PathBuf {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // the shared library is built next to the test binaries
+    let exe = env::current_exe().expect("test binary path");
+    let lib_dir = exe.parent().expect("the test binary is in a directory");
+    let program = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+
+    let compiled = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg(root.join(format!("tests/c/{}.c", name)))
+        .arg("-I")
+        .arg(root.join("include"))
+        .arg("-L")
+        .arg(lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-lprolang")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-o")
+        .arg(&program)
+        .status()
+        .expect("the C compiler runs");
+    assert!(compiled.success(), "{}.c does not compile", name);
+    program
+}
+
+/// Checks that `include/prolang.h` is the header `header()` generates from
+/// `src/ffi/ffi.rs`. Run with `PROLANG_BLESS=1` to write it instead.
+#[test]
+fn test_header_is_generated_from_ffi() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let source = fs::read_to_string(root.join("src/ffi/ffi.rs")).expect("ffi.rs is readable");
+    let path = root.join("include/prolang.h");
+    let generated = header(&source);
+    if env::var_os("PROLANG_BLESS").is_some() {
+        fs::write(&path, &generated).expect("the header is written");
+    }
+    let current = fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        current == generated,
+        "include/prolang.h is out of date, run the tests with PROLANG_BLESS=1\n{}",
+        generated
+    );
+}
+
+/// Renders the C declarations of the public items in `source`, with their
+/// doc comments up to the `# Safety` section.
+fn header(source: &str) -> String {
+    let mut out = String::from(
+        "/* Generated from src/ffi/ffi.rs by tests/c_api.rs; do not edit. */\n\
+         #ifndef PROLANG_H\n#define PROLANG_H\n\n\
+         #include <stddef.h>\n#include <stdint.h>\n\n\
+         #ifdef __cplusplus\nextern \"C\" {\n#endif\n",
+    );
+    let mut doc: Vec<&str> = vec![];
+    let mut lines = source.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if let Some(text) = line.strip_prefix("///") {
+            doc.push(text.strip_prefix(' ').unwrap_or(text));
+            continue;
+        }
+        if line.starts_with("#[") {
+            continue;
+        }
+        let declaration = if let Some(name) = line.strip_prefix("pub struct ") {
+            let name = name.split([' ', '(', ';']).next().unwrap();
+            Some(format!("typedef struct {0} {0};", name))
+        } else if let Some(alias) = line.strip_prefix("pub type ") {
+            let signature = read_until(line, &mut lines, ';');
+            let name = alias.split(' ').next().unwrap();
+            let (params, ret) = split_signature(&signature);
+            Some(format!(
+                "typedef {}(*{})({});",
+                c_return(&ret),
+                name,
+                c_params(&params)
+            ))
+        } else if line.starts_with("pub extern \"C\" fn")
+            || line.starts_with("pub unsafe extern \"C\" fn")
+        {
+            let signature = read_until(line, &mut lines, '{');
+            let name = signature
+                .split("fn ")
+                .nth(1)
+                .unwrap()
+                .split('(')
+                .next()
+                .unwrap();
+            let (params, ret) = split_signature(&signature);
+            Some(format!(
+                "{}{}({});",
+                c_return(&ret),
+                name,
+                c_params(&params)
+            ))
+        } else {
+            None
+        };
+        if let Some(declaration) = declaration {
+            out.push('\n');
+            let end = doc
+                .iter()
+                .position(|line| *line == "# Safety")
+                .unwrap_or(doc.len());
+            let doc: Vec<&str> = doc[..end]
+                .iter()
+                .copied()
+                .filter(|line| !line.is_empty())
+                .collect();
+            if !doc.is_empty() {
+                out.push_str("/*\n");
+                for line in doc {
+                    out.push_str(&format!(" * {}\n", line));
+                }
+                out.push_str(" */\n");
+            }
+            out.push_str(&declaration);
+            out.push('\n');
+        }
+        doc.clear();
+    }
+    out.push_str("\n#ifdef __cplusplus\n}\n#endif\n\n#endif /* PROLANG_H */\n");
+    out
+}
+
+/// Joins the lines of an item from `first` up to `end`.
+fn read_until<'a>(first: &str, lines: &mut impl Iterator<Item = &'a str>, end: char) -> String {
+    let mut signature = first.to_string();
+    while !signature.contains(end) {
+        signature.push(' ');
+        signature.push_str(lines.next().expect("the item ends").trim());
+    }
+    signature[..signature.find(end).unwrap()].to_string()
+}
+
+/// Splits a function signature into its parameter list and return type.
+fn split_signature(signature: &str) -> (String, String) {
+    let open = signature.find('(').unwrap();
+    let close = signature.rfind(')').unwrap();
+    let params = signature[open + 1..close].to_string();
+    let ret = signature[close + 1..]
+        .trim()
+        .strip_prefix("->")
+        .map_or(String::new(), |ret| ret.trim().to_string());
+    (params, ret)
+}
+
+fn c_params(params: &str) -> String {
+    let params: Vec<String> = params
+        .split(',')
+        .map(str::trim)
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            let (name, ty) = param.split_once(':').unwrap();
+            let ty = c_type(ty.trim());
+            if ty.ends_with('*') {
+                format!("{}{}", ty, name.trim())
+            } else {
+                format!("{} {}", ty, name.trim())
+            }
+        })
+        .collect();
+    if params.is_empty() {
+        return "void".to_string();
+    }
+    params.join(", ")
+}
+
+fn c_return(ty: &str) -> String {
+    let ty = c_type(ty);
+    if ty.ends_with('*') {
+        ty
+    } else {
+        format!("{} ", ty)
+    }
+}
+
+fn c_type(ty: &str) -> String {
+    // function pointers are nullable in C, which Rust spells `Option`
+    if let Some(ty) = ty
+        .strip_prefix("Option<")
+        .and_then(|ty| ty.strip_suffix('>'))
+    {
+        return c_type(ty);
+    }
+    if let Some(ty) = ty.strip_prefix("*mut ") {
+        return format!("{}*", c_return(ty));
+    }
+    if let Some(ty) = ty.strip_prefix("*const ") {
+        let ty = c_type(ty);
+        if ty.ends_with('*') {
+            return format!("{}const *", ty);
+        }
+        return format!("const {} *", ty);
+    }
+    match ty {
+        "" => "void",
+        "c_char" => "char",
+        "c_int" => "int",
+        "c_void" => "void",
+        "i64" => "int64_t",
+        "usize" => "size_t",
+        ty => ty,
+    }
+    .to_string()
+}
@@ -0,0 +1,116 @@
+use std::{fs, path::Path, process::Command};
+
+/// Runs every program in `tests/conformance` with both engines, from a built
+/// `.prc` file, and with the collector running on every allocation, and
+/// checks that they print the same output and exit with the same status.
+/// Programs may end with an uncaught error, which must be reported alike on
+/// stderr.
+#[test]
+fn test_engines_agree_on_conformance_suite() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/conformance");
+    let mut programs: Vec<_> = fs::read_dir(&dir)
+        .expect("conformance directory exists")
+        .map(|entry| entry.expect("readable entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "pr"))
+        .collect();
+    programs.sort();
+    assert!(!programs.is_empty(), "no programs in {}", dir.display());
+
+    // `build` writes the `.prc` next to the source, so build copies
+    let build_dir =
+        std::env::temp_dir().join(format!("prolang-conformance-{}", std::process::id()));
+    fs::create_dir_all(&build_dir).expect("build directory is created");
+
+    for program in programs {
+        let interpreted = run(&program, &["--engine=interpreter"]);
+        let compiled = run(&program, &["--engine=vm"]);
+        let cached = run_cached(&program, &build_dir);
+        assert!(
+            !interpreted.0.is_empty(),
+            "{} printed nothing",
+            program.display()
+        );
+        assert_eq!(
+            interpreted,
+            compiled,
+            "engines disagree on {}",
+            program.display()
+        );
+        assert_eq!(
+            interpreted,
+            cached,
+            "cached bytecode disagrees on {}",
+            program.display()
+        );
+        for engine in ["--engine=interpreter", "--engine=vm"] {
+            assert_eq!(
+                interpreted,
+                run(&program, &[engine, "--gc-stress"]),
+                "{} --gc-stress disagrees on {}",
+                engine,
+                program.display()
+            );
+        }
+    }
+
+    fs::remove_dir_all(&build_dir).expect("build directory is removed");
+}
+
+/// Errors that stop a program are reported on stderr, leaving stdout to what
+/// the program printed.
+#[test]
+fn test_uncaught_errors_are_reported_on_stderr() {
+    let dir = std::env::temp_dir().join(format!("prolang-stderr-{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("directory is created");
+    let thrown = dir.join("thrown.pr");
+    fs::write(&thrown, "print(\"before\");\n1 / 0").expect("program is written");
+    let unresolved = dir.join("unresolved.pr");
+    fs::write(&unresolved, "import \"missing\" as m;\n1").expect("program is written");
+
+    for engine in ["--engine=interpreter", "--engine=vm"] {
+        let (stdout, stderr, code) = run(&thrown, &[engine]);
+        assert_eq!(stdout, "before", "{}", engine);
+        assert_eq!(
+            stderr, "runtime error: DivisionByZero: division by zero\n    at thrown.pr:2:3\n",
+            "{}",
+            engine
+        );
+        assert_eq!(code, Some(1), "{}", engine);
+    }
+    let (stdout, stderr, code) = run(&unresolved, &["--engine=vm"]);
+    assert_eq!(stdout, "");
+    assert_eq!(stderr, "compile error: cannot find module \"missing\"\n");
+    assert_eq!(code, Some(1));
+
+    fs::remove_dir_all(&dir).expect("directory is removed");
+}
+
+fn run_cached(program: &Path, build_dir: &Path) -> (String, String, Option<i32>) {
+    let source = build_dir.join(program.file_name().expect("program has a file name"));
+    fs::copy(program, &source).expect("program is copied");
+    let status = Command::new(env!("CARGO_BIN_EXE_prolang"))
+        .arg("build")
+        .arg(&source)
+        .status()
+        .expect("prolang builds");
+    assert!(status.success(), "build failed on {}", program.display());
+
+    let cache = source.with_extension("prc");
+    assert!(cache.exists(), "build wrote no {}", cache.display());
+    run(&cache, &["--engine=vm"])
+}
+
+/// Runs a program, returning what it printed to stdout and stderr and its
+/// exit code.
+fn run(program: &Path, flags: &[&str]) -> (String, String, Option<i32>) {
+    let output = Command::new(env!("CARGO_BIN_EXE_prolang"))
+        .arg("run")
+        .args(flags)
+        .arg(program)
+        .output()
+        .expect("prolang runs");
+
+    let stdout = String::from_utf8(output.stdout).expect("output is utf-8");
+    let stderr = String::from_utf8(output.stderr).expect("errors are utf-8");
+    (stdout, stderr, output.status.code())
+}
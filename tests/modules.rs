@@ -0,0 +1,134 @@
+use std::{fs, path::Path, process::Command};
+
+/// Runs a program importing modules with both engines and from a built
+/// `.prc` file, which must be rebuilt once a module it imported changes.
+#[test]
+fn test_modules_run_alike_and_invalidate_caches() {
+    let dir = std::env::temp_dir().join(format!("prolang-modules-{}", std::process::id()));
+    fs::create_dir_all(dir.join("lib")).expect("project directory is created");
+    let main = dir.join("main.pr");
+    fs::write(
+        &main,
+        "import \"lib/greet\" as greet;\nimport \"lib/greet\" as again;\nprintln(greet.hello(\"modules\"));\nprintln(greet == again);",
+    )
+    .expect("main is written");
+    fs::write(
+        dir.join("lib/greet.pr"),
+        "println(\"loading greet\");\nexport let hello = fn(name) { \"hello \" + name };",
+    )
+    .expect("module is written");
+
+    let expected = "loading greet\nhello modules\ntrue\n";
+    assert_eq!(run(&main, &["--engine=interpreter"]), expected);
+    assert_eq!(run(&main, &["--engine=vm"]), expected);
+
+    let status = Command::new(env!("CARGO_BIN_EXE_prolang"))
+        .arg("build")
+        .arg(&main)
+        .status()
+        .expect("prolang builds");
+    assert!(status.success(), "build failed");
+    let cache = main.with_extension("prc");
+    assert_eq!(run(&cache, &[]), expected);
+
+    fs::write(
+        dir.join("lib/greet.pr"),
+        "export let hello = fn(name) { \"hi \" + name };",
+    )
+    .expect("module is rewritten");
+    assert_eq!(run(&cache, &[]), "hi modules\ntrue\n");
+
+    fs::remove_dir_all(&dir).expect("project directory is removed");
+}
+
+/// A sandboxed program imports the modules under the directories it may read
+/// only, whichever engine runs it and whether or not it was built.
+#[test]
+fn test_sandboxed_imports() {
+    let dir = std::env::temp_dir().join(format!("prolang-sandbox-{}", std::process::id()));
+    fs::create_dir_all(dir.join("app")).expect("app directory is created");
+    fs::create_dir_all(dir.join("secret")).expect("secret directory is created");
+    fs::write(dir.join("secret/keys.pr"), "export let token = \"s3cr3t\";")
+        .expect("module is written");
+    let main = dir.join("app/main.pr");
+    fs::write(&main, "import \"../secret/keys\" as k;\nprintln(k.token);")
+        .expect("main is written");
+    let status = Command::new(env!("CARGO_BIN_EXE_prolang"))
+        .arg("build")
+        .arg(&main)
+        .status()
+        .expect("prolang builds");
+    assert!(status.success(), "build failed");
+
+    let allow_app = format!("--allow-read={}", dir.join("app").display());
+    let allow_secret = format!("--allow-read={}", dir.join("secret").display());
+    for engine in ["--engine=interpreter", "--engine=vm"] {
+        for program in [main.clone(), main.with_extension("prc")] {
+            let output = Command::new(env!("CARGO_BIN_EXE_prolang"))
+                .args(["run", engine, "--sandbox", "--allow-stdout", &allow_app])
+                .arg(&program)
+                .output()
+                .expect("prolang runs");
+            assert!(!output.status.success(), "{} imported a secret", engine);
+            let out = String::from_utf8_lossy(&output.stdout);
+            let err = String::from_utf8_lossy(&output.stderr);
+            assert!(!out.contains("s3cr3t") && !err.contains("s3cr3t"));
+            assert!(
+                out.contains("`import` may not read") || err.contains("`import` may not read"),
+                "{} did not deny the import: {}{}",
+                engine,
+                out,
+                err
+            );
+        }
+        assert_eq!(
+            run(&main, &[engine, "--allow-stdout", &allow_secret]),
+            "s3cr3t\n"
+        );
+    }
+
+    fs::remove_dir_all(&dir).expect("directory is removed");
+}
+
+fn run(program: &Path, flags: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_prolang"))
+        .arg("run")
+        .args(flags)
+        .arg(program)
+        .output()
+        .expect("prolang runs");
+    assert!(output.status.success(), "{} failed", program.display());
+
+    String::from_utf8(output.stdout).expect("output is utf-8")
+}
+
+/// Creates a project with `prolang new` and runs its entry point from the
+/// project directory without naming it.
+#[test]
+fn test_new_project_runs_its_entry_point() {
+    let dir = std::env::temp_dir().join(format!("prolang-new-{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("directory is created");
+
+    let status = Command::new(env!("CARGO_BIN_EXE_prolang"))
+        .current_dir(&dir)
+        .args(["new", "hello"])
+        .status()
+        .expect("prolang runs");
+    assert!(status.success(), "new failed");
+
+    let project = dir.join("hello");
+    for engine in ["--engine=interpreter", "--engine=vm"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_prolang"))
+            .current_dir(project.join("tests"))
+            .args(["run", engine])
+            .output()
+            .expect("prolang runs");
+        assert!(output.status.success(), "run failed with {}", engine);
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "Hello from hello!\n"
+        );
+    }
+
+    fs::remove_dir_all(&dir).expect("directory is removed");
+}